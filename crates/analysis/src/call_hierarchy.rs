@@ -0,0 +1,376 @@
+//! Find the calls to and from functions and functors.
+
+use fast_hash::FxHashMap;
+use paths::{PathId, PathMap};
+use syntax::ast::{self, AstNode as _, SyntaxNodePtr};
+use syntax::{SyntaxKind as SK, SyntaxNode, SyntaxToken};
+use text_pos::Range;
+use text_size_util::TextRange;
+
+/// A binding of a function, value, structure, or functor, which may make or receive calls.
+#[derive(Debug)]
+pub struct CallHierarchyItem {
+  /// The name.
+  pub name: String,
+  /// What it is.
+  pub kind: CallHierarchyItemKind,
+  /// The path of the file containing the binding.
+  pub path: PathId,
+  /// The range of the whole binding.
+  pub range: Range,
+  /// The range of the name in the binding.
+  pub name_range: Range,
+}
+
+/// What a call hierarchy item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallHierarchyItemKind {
+  /// A function, bound with `fun` or with `val` and `fn`.
+  Fun,
+  /// Some other value.
+  Val,
+  /// A structure.
+  Structure,
+  /// A functor.
+  Functor,
+}
+
+/// The calls from one item to the item asked about.
+#[derive(Debug)]
+pub struct IncomingCall {
+  /// The item making the calls.
+  pub from: CallHierarchyItem,
+  /// The ranges of the calls, in the file of `from`.
+  pub from_ranges: Vec<Range>,
+}
+
+/// The calls to one item from the item asked about.
+#[derive(Debug)]
+pub struct OutgoingCall {
+  /// The item being called.
+  pub to: CallHierarchyItem,
+  /// The ranges of the calls, in the file of the item asked about.
+  pub from_ranges: Vec<Range>,
+}
+
+type Files = PathMap<mlb_statics::SourceFile>;
+
+/// A binding, identified by its file and its syntax.
+type BindingKey = (PathId, SyntaxNodePtr);
+
+/// A call, which is any use of a function or functor.
+struct Site {
+  node: SyntaxNode,
+  range: TextRange,
+  callee: BindingKey,
+}
+
+pub(crate) fn prepare(files: &Files, path: PathId, tok: &SyntaxToken) -> Option<CallHierarchyItem> {
+  let (path, binding) = binding_at(files, path, tok)?;
+  item(files, path, &binding)
+}
+
+pub(crate) fn incoming(
+  files: &Files,
+  path: PathId,
+  tok: &SyntaxToken,
+) -> Option<Vec<IncomingCall>> {
+  let (path, binding) = binding_at(files, path, tok)?;
+  let target = (path, SyntaxNodePtr::new(&binding));
+  let mut callers = FxHashMap::<BindingKey, (SyntaxNode, Vec<TextRange>)>::default();
+  for (&path, file) in files.iter() {
+    for site in sites(files, file) {
+      if site.callee != target {
+        continue;
+      }
+      let caller = match enclosing_caller(&site.node) {
+        Some(x) => x,
+        None => continue,
+      };
+      callers
+        .entry((path, SyntaxNodePtr::new(&caller)))
+        .or_insert_with(|| (caller, Vec::new()))
+        .1
+        .push(site.range);
+    }
+  }
+  let mut ret: Vec<_> = callers
+    .into_iter()
+    .filter_map(|((path, ptr), (caller, ranges))| {
+      let file = files.get(&path)?;
+      let call = IncomingCall {
+        from: item(files, path, &caller)?,
+        from_ranges: ranges
+          .into_iter()
+          .filter_map(|x| file.pos_db.range(x))
+          .collect(),
+      };
+      Some(((path, ptr.text_range().start()), call))
+    })
+    .collect();
+  ret.sort_unstable_by_key(|&(key, _)| key);
+  Some(ret.into_iter().map(|(_, call)| call).collect())
+}
+
+pub(crate) fn outgoing(
+  files: &Files,
+  path: PathId,
+  tok: &SyntaxToken,
+) -> Option<Vec<OutgoingCall>> {
+  let (path, binding) = binding_at(files, path, tok)?;
+  let file = files.get(&path)?;
+  let mut callees = FxHashMap::<BindingKey, Vec<TextRange>>::default();
+  for site in sites(files, file) {
+    if !binding.text_range().contains_range(site.range)
+      || enclosing_caller(&site.node).as_ref() != Some(&binding)
+    {
+      continue;
+    }
+    callees.entry(site.callee).or_default().push(site.range);
+  }
+  let mut ret: Vec<_> = callees
+    .into_iter()
+    .filter_map(|((callee_path, callee_ptr), mut ranges)| {
+      let callee_file = files.get(&callee_path)?;
+      let callee = callee_ptr.to_node(callee_file.parsed.root.syntax());
+      ranges.sort_unstable_by_key(|x| x.start());
+      let first = ranges.first()?.start();
+      let call = OutgoingCall {
+        to: item(files, callee_path, &callee)?,
+        from_ranges: ranges
+          .into_iter()
+          .filter_map(|x| file.pos_db.range(x))
+          .collect(),
+      };
+      Some((first, call))
+    })
+    .collect();
+  ret.sort_unstable_by_key(|&(first, _)| first);
+  Some(ret.into_iter().map(|(_, call)| call).collect())
+}
+
+/// returns the binding whose name is the token, or the callable binding the token refers to.
+fn binding_at(files: &Files, path: PathId, tok: &SyntaxToken) -> Option<(PathId, SyntaxNode)> {
+  let node = tok.parent()?;
+  let binding = node.ancestors().find(|x| is_binding(x.kind()));
+  if let Some(binding) = binding {
+    let on_name =
+      binding_name(&binding).map_or(false, |(_, range)| range.contains_range(tok.text_range()));
+    if on_name {
+      return Some((path, binding));
+    }
+  }
+  let file = files.get(&path)?;
+  let site = sites(files, file)
+    .into_iter()
+    .filter(|site| site.range.contains_range(tok.text_range()))
+    .min_by_key(|site| site.range.len())?;
+  let (callee_path, callee_ptr) = site.callee;
+  let callee_file = files.get(&callee_path)?;
+  Some((
+    callee_path,
+    callee_ptr.to_node(callee_file.parsed.root.syntax()),
+  ))
+}
+
+/// returns all the calls in the file.
+fn sites(files: &Files, file: &mlb_statics::SourceFile) -> Vec<Site> {
+  let root = file.parsed.root.syntax();
+  let exps = file
+    .lowered
+    .arenas
+    .exp
+    .iter()
+    .filter_map(|(idx, exp)| matches!(exp, hir::Exp::Path(_)).then_some(hir::Idx::from(idx)));
+  let str_exps = file
+    .lowered
+    .arenas
+    .str_exp
+    .iter()
+    .filter_map(|(idx, str_exp)| {
+      matches!(str_exp, hir::StrExp::App(_, _)).then_some(hir::Idx::from(idx))
+    });
+  exps
+    .chain(str_exps)
+    .filter_map(|idx| {
+      let node = file.lowered.ptrs.hir_to_ast(idx)?.to_node(root);
+      site(files, file, idx, node)
+    })
+    .collect()
+}
+
+/// returns the call for the index, whose node is given.
+fn site(
+  files: &Files,
+  file: &mlb_statics::SourceFile,
+  idx: hir::Idx,
+  node: SyntaxNode,
+) -> Option<Site> {
+  let (range, functor_name) = match (idx, node.kind()) {
+    (hir::Idx::Exp(exp), SK::PathExp) => match &file.lowered.arenas.exp[exp] {
+      hir::Exp::Path(_) => (node.text_range(), None),
+      _ => return None,
+    },
+    // the function of an infix application is lowered with the pointer to the whole application.
+    (hir::Idx::Exp(exp), SK::InfixExp) => match &file.lowered.arenas.exp[exp] {
+      hir::Exp::Path(_) => {
+        let name = ast::InfixExp::cast(node.clone())?.name_star_eq()?;
+        (name.token.text_range(), None)
+      }
+      _ => return None,
+    },
+    (hir::Idx::StrExp(str_exp), SK::AppStrExp) => match &file.lowered.arenas.str_exp[str_exp] {
+      hir::StrExp::App(name, _) => {
+        let name_tok = ast::AppStrExp::cast(node.clone())?.name()?;
+        (name_tok.text_range(), Some(name.as_str()))
+      }
+      _ => return None,
+    },
+    _ => return None,
+  };
+  let def = file.info.get_def(idx)?;
+  let callee = def_binding(files, def, functor_name)?;
+  Some(Site {
+    node,
+    range,
+    callee,
+  })
+}
+
+/// returns the callable binding that defines the def. for functors, we need the name, since the def
+/// is for the whole `functor ... and ...` declaration.
+fn def_binding(files: &Files, def: statics::Def, functor_name: Option<&str>) -> Option<BindingKey> {
+  let path = match def.path {
+    statics::DefPath::Regular(path) => path,
+    statics::DefPath::StdBasis(_) => return None,
+  };
+  let file = files.get(&path)?;
+  let node = file
+    .lowered
+    .ptrs
+    .hir_to_ast(def.idx)?
+    .to_node(file.parsed.root.syntax());
+  let binding = match node.kind() {
+    // the name of a `fun` is lowered with the pointer to the whole binding.
+    SK::FunBind => node,
+    SK::ConPat | SK::TypedNamePat => {
+      let mut node = node;
+      while matches!(
+        node.kind(),
+        SK::ConPat | SK::TypedNamePat | SK::ParenPat | SK::TypedPat
+      ) {
+        node = node.parent()?;
+      }
+      (node.kind() == SK::ValBind && is_fn_val_bind(&node)).then_some(node)?
+    }
+    SK::FunctorDec => {
+      let functor_name = functor_name?;
+      node
+        .children()
+        .filter_map(ast::FunctorBind::cast)
+        .find(|x| x.functor_name().map_or(false, |x| x.text() == functor_name))?
+        .syntax()
+        .clone()
+    }
+    _ => return None,
+  };
+  Some((path, SyntaxNodePtr::new(&binding)))
+}
+
+/// returns the binding that is making the call at the node.
+///
+/// this is the innermost enclosing function binding. but for calls that are not in a function,
+/// like in `val x = let val y = f 1 in y end` at the top level of a structure, it's the outermost
+/// value binding, or else the structure or functor binding.
+fn enclosing_caller(node: &SyntaxNode) -> Option<SyntaxNode> {
+  let mut val_bind = None::<SyntaxNode>;
+  for node in node.ancestors() {
+    match node.kind() {
+      SK::FunBind => return Some(node),
+      SK::ValBind => {
+        if is_fn_val_bind(&node) {
+          return Some(node);
+        }
+        val_bind = Some(node);
+      }
+      SK::StrBind | SK::FunctorBind => return Some(val_bind.unwrap_or(node)),
+      _ => {}
+    }
+  }
+  val_bind
+}
+
+fn is_binding(kind: SK) -> bool {
+  matches!(
+    kind,
+    SK::FunBind | SK::ValBind | SK::StrBind | SK::FunctorBind
+  )
+}
+
+fn is_fn_val_bind(node: &SyntaxNode) -> bool {
+  ast::ValBind::cast(node.clone())
+    .and_then(|x| x.exp())
+    .map_or(false, |x| matches!(x, ast::Exp::FnExp(_)))
+}
+
+fn item(files: &Files, path: PathId, binding: &SyntaxNode) -> Option<CallHierarchyItem> {
+  let file = files.get(&path)?;
+  let kind = match binding.kind() {
+    SK::FunBind => CallHierarchyItemKind::Fun,
+    SK::ValBind => {
+      if is_fn_val_bind(binding) {
+        CallHierarchyItemKind::Fun
+      } else {
+        CallHierarchyItemKind::Val
+      }
+    }
+    SK::StrBind => CallHierarchyItemKind::Structure,
+    SK::FunctorBind => CallHierarchyItemKind::Functor,
+    _ => return None,
+  };
+  let (name, name_range) = binding_name(binding)?;
+  Some(CallHierarchyItem {
+    name,
+    kind,
+    path,
+    range: file.pos_db.range(binding.text_range())?,
+    name_range: file.pos_db.range(name_range)?,
+  })
+}
+
+fn binding_name(binding: &SyntaxNode) -> Option<(String, TextRange)> {
+  let tok = match binding.kind() {
+    SK::FunBind => {
+      let case = ast::FunBind::cast(binding.clone())?
+        .fun_bind_cases()
+        .next()?;
+      let name = match case.fun_bind_case_head()? {
+        ast::FunBindCaseHead::PrefixFunBindCaseHead(head) => head.name_star_eq()?,
+        ast::FunBindCaseHead::InfixFunBindCaseHead(head) => head.name_star_eq()?,
+      };
+      name.token
+    }
+    SK::ValBind => {
+      let pat = ast::ValBind::cast(binding.clone())?.pat()?;
+      let name = match &pat {
+        ast::Pat::ConPat(pat) if pat.pat().is_none() => pat
+          .path()?
+          .name_star_eq_dots()
+          .last()?
+          .name_star_eq()
+          .map(|x| x.token),
+        ast::Pat::TypedNamePat(pat) => pat.name_star_eq().map(|x| x.token),
+        _ => None,
+      };
+      match name {
+        Some(x) => x,
+        // some other pattern, like a tuple.
+        None => return Some((pat.syntax().text().to_string(), pat.syntax().text_range())),
+      }
+    }
+    SK::StrBind => ast::StrBind::cast(binding.clone())?.name()?,
+    SK::FunctorBind => ast::FunctorBind::cast(binding.clone())?.functor_name()?,
+    _ => return None,
+  };
+  Some((tok.text().to_owned(), tok.text_range()))
+}
@@ -0,0 +1,577 @@
+//! Find top-level items and source files that are never used.
+
+use crate::doc_items::top_str_decs;
+use crate::mod_graph::indices;
+use crate::Error;
+use fast_hash::FxHashSet;
+use paths::{PathId, PathMap};
+use syntax::{SyntaxKind, SyntaxNode};
+use text_size_util::TextRange;
+
+type Files = PathMap<mlb_statics::SourceFile>;
+
+/// A top-level item of a file that may be unused.
+struct Item<'a> {
+  kind: ItemKind,
+  name: &'a hir::Name,
+  /// the whole declaration. uses inside this don't count.
+  dec_range: TextRange,
+  /// where to report the item as unused.
+  name_range: TextRange,
+  used: bool,
+}
+
+#[derive(Clone, Copy)]
+enum ItemKind {
+  /// a value, constructor, or exception, which is used when a reference to it has this as the
+  /// definition index.
+  Val(ValItemKind, hir::Idx),
+  /// a structure, signature, or functor, which is used when a reference to it, or something inside
+  /// it, has a definition index inside it.
+  Mod(statics::ModKind),
+}
+
+#[derive(Clone, Copy)]
+enum ValItemKind {
+  Value,
+  Constructor,
+  Exception,
+}
+
+impl ItemKind {
+  fn desc(self) -> &'static str {
+    match self {
+      ItemKind::Val(ValItemKind::Value, _) => "value",
+      ItemKind::Val(ValItemKind::Constructor, _) => "constructor",
+      ItemKind::Val(ValItemKind::Exception, _) => "exception",
+      ItemKind::Mod(kind) => kind.keyword(),
+    }
+  }
+}
+
+/// The items of a file, and whether the file does anything when run besides define them.
+struct FileItems<'a> {
+  items: Vec<Item<'a>>,
+  has_effect: bool,
+  used: bool,
+}
+
+/// A reference from somewhere to a definition, with the name used, for references to values.
+struct Ref<'a> {
+  path: PathId,
+  range: TextRange,
+  def: statics::Def,
+  name: Option<&'a hir::Name>,
+}
+
+pub(crate) fn get(files: &Files, input: &crate::input::Input) -> PathMap<Vec<Error>> {
+  let mut items: PathMap<FileItems<'_>> = files
+    .iter()
+    .map(|(&path, file)| (path, file_items(file)))
+    .collect();
+  for group in input.groups.values() {
+    let mut sources = Vec::<PathId>::new();
+    group_sources(
+      input,
+      &group.bas_dec,
+      &mut sources,
+      &mut FxHashSet::default(),
+    );
+    mark_exports(&mut items, files, &sources, &group.bas_dec);
+  }
+  for r in files.iter().flat_map(|(&path, file)| refs(path, file)) {
+    let (def, name) = (r.def, r.name);
+    let def_path = match def.path {
+      statics::DefPath::Regular(path) => path,
+      statics::DefPath::StdBasis(_) => continue,
+    };
+    let def_range = match files
+      .get(&def_path)
+      .and_then(|file| file.lowered.ptrs.hir_to_ast(def.idx))
+    {
+      Some(ptr) => ptr.text_range(),
+      None => continue,
+    };
+    let file_items = match items.get_mut(&def_path) {
+      Some(x) => x,
+      None => continue,
+    };
+    if r.path != def_path {
+      file_items.used = true;
+    }
+    for item in file_items.items.iter_mut() {
+      if r.path == def_path && item.dec_range.contains_range(r.range) {
+        continue;
+      }
+      let used = match item.kind {
+        ItemKind::Val(_, idx) => idx == def.idx && name == Some(item.name),
+        ItemKind::Mod(_) => item.dec_range.contains_range(def_range),
+      };
+      item.used |= used;
+    }
+  }
+  items
+    .into_iter()
+    .filter_map(|(path, file_items)| {
+      let pos_db = &files[&path].pos_db;
+      let errors: Vec<_> = if !file_items.used && !file_items.has_effect {
+        // the items in the file are necessarily all unused, so just report the file.
+        let range = pos_db.range(TextRange::empty(0.into()))?;
+        vec![Error {
+          range,
+          message: "unused file: it defines nothing used elsewhere and has no effects".to_owned(),
+          code: 6002,
+          related: Vec::new(),
+        }]
+      } else {
+        file_items
+          .items
+          .into_iter()
+          .filter(|item| !item.used)
+          .filter_map(|item| {
+            Some(Error {
+              range: pos_db.range(item.name_range)?,
+              message: format!("unused {}: {}", item.kind.desc(), item.name),
+              code: 6001,
+              related: Vec::new(),
+            })
+          })
+          .collect()
+      };
+      (!errors.is_empty()).then_some((path, errors))
+    })
+    .collect()
+}
+
+/// returns an error for each of the source files not listed in any group.
+pub(crate) fn unlisted(files: &[PathId]) -> PathMap<Vec<Error>> {
+  files
+    .iter()
+    .map(|&path| {
+      let start = text_pos::Position {
+        line: 0,
+        character: 0,
+      };
+      let error = Error {
+        range: text_pos::Range { start, end: start },
+        message: "unused file: it is not listed in any group".to_owned(),
+        code: 6003,
+        related: Vec::new(),
+      };
+      (path, vec![error])
+    })
+    .collect()
+}
+
+fn file_items(file: &mlb_statics::SourceFile) -> FileItems<'_> {
+  let root = file.parsed.root.syntax();
+  let mut ret = FileItems {
+    items: Vec::new(),
+    has_effect: false,
+    used: false,
+  };
+  for idx in top_str_decs(file) {
+    let node = match file.lowered.ptrs.hir_to_ast(idx) {
+      Some(ptr) => ptr.to_node(root),
+      None => continue,
+    };
+    for (kind, name) in file.info.get_mod_names(idx) {
+      let kind = ItemKind::Mod(kind);
+      ret.items.push(item(kind, name, node.text_range(), &node));
+    }
+  }
+  for dec in top_decs(file) {
+    let node = match file.lowered.ptrs.hir_to_ast(dec.into()) {
+      Some(ptr) => ptr.to_node(root),
+      None => continue,
+    };
+    let dec_range = node.text_range();
+    match &file.lowered.arenas.dec[dec] {
+      hir::Dec::Val(_, val_binds) => {
+        let mut vars = Vec::<(hir::la_arena::Idx<hir::Pat>, &hir::Name)>::new();
+        for val_bind in val_binds {
+          pat_vars(file, &mut vars, val_bind.pat);
+        }
+        if vars.is_empty() {
+          // e.g. `val () = print "hi"`.
+          ret.has_effect = true;
+        }
+        for (pat, name) in vars {
+          let node = match file.lowered.ptrs.hir_to_ast(pat.into()) {
+            Some(ptr) => ptr.to_node(root),
+            None => continue,
+          };
+          let kind = ItemKind::Val(ValItemKind::Value, pat.into());
+          ret.items.push(item(kind, name, dec_range, &node));
+        }
+      }
+      hir::Dec::Datatype(dat_binds, _) => {
+        for con_bind in dat_binds.iter().flat_map(|x| x.cons.iter()) {
+          let kind = ItemKind::Val(ValItemKind::Constructor, dec.into());
+          ret.items.push(item(kind, &con_bind.name, dec_range, &node));
+        }
+      }
+      hir::Dec::Exception(ex_binds) => {
+        for ex_bind in ex_binds {
+          if let hir::ExBind::New(name, _) = ex_bind {
+            let kind = ItemKind::Val(ValItemKind::Exception, dec.into());
+            ret.items.push(item(kind, name, dec_range, &node));
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+  for name in effect_structures(file) {
+    ret.has_effect = true;
+    // the structure is used by running the file.
+    for item in ret.items.iter_mut() {
+      if matches!(item.kind, ItemKind::Mod(statics::ModKind::Structure)) && item.name == name {
+        item.used = true;
+      }
+    }
+  }
+  ret
+}
+
+/// returns the names of the structures declared at the top level of the file whose bodies do
+/// something when run besides define things, like `structure Main = struct val () = main () end`.
+fn effect_structures(file: &mlb_statics::SourceFile) -> Vec<&hir::Name> {
+  let mut ret = Vec::<&hir::Name>::new();
+  let mut str_decs = vec![file.lowered.root];
+  while let Some(str_dec) = str_decs.pop() {
+    let str_dec = match str_dec {
+      Some(x) => x,
+      None => continue,
+    };
+    match &file.lowered.arenas.str_dec[str_dec] {
+      hir::StrDec::Seq(xs) => str_decs.extend(xs.iter().copied()),
+      hir::StrDec::Local(local_dec, in_dec) => str_decs.extend([*local_dec, *in_dec]),
+      hir::StrDec::Structure(str_binds) => {
+        let names = str_binds
+          .iter()
+          .filter(|str_bind| str_exp_has_effect(file, str_bind.str_exp))
+          .map(|str_bind| &str_bind.name);
+        ret.extend(names);
+      }
+      hir::StrDec::Dec(_)
+      | hir::StrDec::Signature(_)
+      | hir::StrDec::Functor(_)
+      | hir::StrDec::FunSig(_) => {}
+    }
+  }
+  ret
+}
+
+fn str_exp_has_effect(file: &mlb_statics::SourceFile, str_exp: hir::StrExpIdx) -> bool {
+  let str_exp = match str_exp {
+    Some(x) => x,
+    None => return false,
+  };
+  match &file.lowered.arenas.str_exp[str_exp] {
+    hir::StrExp::Struct(str_dec) => str_dec_has_effect(file, *str_dec),
+    hir::StrExp::Ascription(str_exp, _, _) => str_exp_has_effect(file, *str_exp),
+    hir::StrExp::Let(str_dec, str_exp) => {
+      str_dec_has_effect(file, *str_dec) || str_exp_has_effect(file, *str_exp)
+    }
+    // we don't look into the functor.
+    hir::StrExp::Path(_) | hir::StrExp::App(_, _) => false,
+  }
+}
+
+fn str_dec_has_effect(file: &mlb_statics::SourceFile, str_dec: hir::StrDecIdx) -> bool {
+  let str_dec = match str_dec {
+    Some(x) => x,
+    None => return false,
+  };
+  match &file.lowered.arenas.str_dec[str_dec] {
+    hir::StrDec::Dec(dec) => dec_has_effect(file, *dec),
+    hir::StrDec::Structure(str_binds) => str_binds
+      .iter()
+      .any(|str_bind| str_exp_has_effect(file, str_bind.str_exp)),
+    hir::StrDec::Local(local_dec, in_dec) => {
+      str_dec_has_effect(file, *local_dec) || str_dec_has_effect(file, *in_dec)
+    }
+    hir::StrDec::Seq(xs) => xs.iter().any(|&x| str_dec_has_effect(file, x)),
+    hir::StrDec::Signature(_) | hir::StrDec::Functor(_) | hir::StrDec::FunSig(_) => false,
+  }
+}
+
+/// returns whether the dec has a `val` that binds no variables, like `val () = print "hi"`.
+fn dec_has_effect(file: &mlb_statics::SourceFile, dec: hir::DecIdx) -> bool {
+  let dec = match dec {
+    Some(x) => x,
+    None => return false,
+  };
+  match &file.lowered.arenas.dec[dec] {
+    hir::Dec::Val(_, val_binds) => {
+      let mut vars = Vec::<(hir::la_arena::Idx<hir::Pat>, &hir::Name)>::new();
+      for val_bind in val_binds {
+        pat_vars(file, &mut vars, val_bind.pat);
+      }
+      vars.is_empty()
+    }
+    hir::Dec::Local(local_dec, in_dec) => {
+      dec_has_effect(file, *local_dec) || dec_has_effect(file, *in_dec)
+    }
+    hir::Dec::Seq(xs) => xs.iter().any(|&x| dec_has_effect(file, x)),
+    _ => false,
+  }
+}
+
+fn item<'a>(
+  kind: ItemKind,
+  name: &'a hir::Name,
+  dec_range: TextRange,
+  node: &SyntaxNode,
+) -> Item<'a> {
+  Item {
+    kind,
+    name,
+    dec_range,
+    name_range: name_range(node, name),
+    used: false,
+  }
+}
+
+/// returns the core declarations at the top level of the file, outside of any structure.
+fn top_decs(file: &mlb_statics::SourceFile) -> Vec<hir::la_arena::Idx<hir::Dec>> {
+  let arenas = &file.lowered.arenas;
+  let mut ret = Vec::new();
+  let mut str_decs = vec![file.lowered.root];
+  let mut decs = Vec::<hir::DecIdx>::new();
+  while let Some(str_dec) = str_decs.pop() {
+    let str_dec = match str_dec {
+      Some(x) => x,
+      None => continue,
+    };
+    match &arenas.str_dec[str_dec] {
+      hir::StrDec::Dec(dec) => decs.push(*dec),
+      hir::StrDec::Seq(xs) => str_decs.extend(xs.iter().copied()),
+      hir::StrDec::Local(local_dec, in_dec) => str_decs.extend([*local_dec, *in_dec]),
+      hir::StrDec::Structure(_)
+      | hir::StrDec::Signature(_)
+      | hir::StrDec::Functor(_)
+      | hir::StrDec::FunSig(_) => {}
+    }
+  }
+  while let Some(dec) = decs.pop() {
+    let dec = match dec {
+      Some(x) => x,
+      None => continue,
+    };
+    match &arenas.dec[dec] {
+      hir::Dec::Seq(xs) => decs.extend(xs.iter().copied()),
+      hir::Dec::Local(local_dec, in_dec) => decs.extend([*local_dec, *in_dec]),
+      _ => ret.push(dec),
+    }
+  }
+  ret
+}
+
+/// pushes the variables bound by the pattern.
+fn pat_vars<'a>(
+  file: &'a mlb_statics::SourceFile,
+  ac: &mut Vec<(hir::la_arena::Idx<hir::Pat>, &'a hir::Name)>,
+  pat: hir::PatIdx,
+) {
+  let pat = match pat {
+    Some(x) => x,
+    None => return,
+  };
+  match &file.lowered.arenas.pat[pat] {
+    hir::Pat::Wild | hir::Pat::SCon(_) => {}
+    hir::Pat::Con(path, arg) => {
+      if arg.is_none() && file.info.get_val_kind(pat.into()) == Some(statics::ValKind::Val) {
+        ac.push((pat, path.last()));
+      }
+      if let Some(arg) = arg {
+        pat_vars(file, ac, *arg);
+      }
+    }
+    hir::Pat::Record { rows, .. } => {
+      for &(_, pat) in rows {
+        pat_vars(file, ac, pat);
+      }
+    }
+    hir::Pat::Typed(pat, _) => pat_vars(file, ac, *pat),
+    hir::Pat::As(name, inner) => {
+      ac.push((pat, name));
+      pat_vars(file, ac, *inner);
+    }
+    // every alternative binds the same variables.
+    hir::Pat::Or(or_pat) => pat_vars(file, ac, or_pat.first),
+  }
+}
+
+/// returns the references in the file to definitions.
+fn refs<'a>(path: PathId, file: &'a mlb_statics::SourceFile) -> Vec<Ref<'a>> {
+  let mut ret = Vec::<Ref<'a>>::new();
+  for idx in indices(file) {
+    let range = match file.lowered.ptrs.hir_to_ast(idx) {
+      Some(ptr) => ptr.text_range(),
+      None => continue,
+    };
+    let (hir_path, is_val) = match idx {
+      hir::Idx::Exp(exp) => match &file.lowered.arenas.exp[exp] {
+        hir::Exp::Path(p) => (Some(p), true),
+        _ => (None, false),
+      },
+      hir::Idx::Pat(pat) => match &file.lowered.arenas.pat[pat] {
+        hir::Pat::Con(p, _) => (Some(p), true),
+        _ => (None, false),
+      },
+      hir::Idx::Ty(ty) => match &file.lowered.arenas.ty[ty] {
+        hir::Ty::Con(_, p) => (Some(p), false),
+        _ => (None, false),
+      },
+      _ => (None, false),
+    };
+    let mut push = |def: statics::Def, name: Option<&'a hir::Name>| {
+      ret.push(Ref {
+        path,
+        range,
+        def,
+        name,
+      });
+    };
+    if let Some(def) = file.info.get_def(idx) {
+      let name = hir_path.filter(|_| is_val).map(hir::Path::last);
+      push(def, name);
+    }
+    // after ascription, the definitions of the values in a structure are in the signature, so the
+    // structure is used only by way of the first structure of the path.
+    if let Some(def) = file.info.get_str_def(idx) {
+      push(def, None);
+    }
+    for (_, mod_name) in file.info.get_mod_names(idx) {
+      if let Some(def) = file.info.get_mod_def(idx, mod_name.as_str()) {
+        push(def, None);
+      }
+    }
+  }
+  ret
+}
+
+/// pushes the source files of the group, and of the groups it includes, in the order they are
+/// checked.
+fn group_sources(
+  input: &crate::input::Input,
+  bas_dec: &mlb_hir::BasDec,
+  ac: &mut Vec<PathId>,
+  visited: &mut FxHashSet<PathId>,
+) {
+  match bas_dec {
+    mlb_hir::BasDec::Path(path, mlb_hir::PathKind::Sml) => ac.push(*path),
+    mlb_hir::BasDec::Path(path, mlb_hir::PathKind::Mlb) => {
+      if !visited.insert(*path) {
+        return;
+      }
+      if let Some(group) = input.groups.get(path) {
+        group_sources(input, &group.bas_dec, ac, visited);
+      }
+    }
+    mlb_hir::BasDec::Basis(_, bas_exp) => group_sources_exp(input, bas_exp, ac, visited),
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      group_sources(input, local_dec, ac, visited);
+      group_sources(input, in_dec, ac, visited);
+    }
+    mlb_hir::BasDec::Seq(decs) => {
+      for dec in decs {
+        group_sources(input, dec, ac, visited);
+      }
+    }
+    mlb_hir::BasDec::Open(_) | mlb_hir::BasDec::Export(_, _, _) | mlb_hir::BasDec::CmExport(_) => {}
+  }
+}
+
+fn group_sources_exp(
+  input: &crate::input::Input,
+  bas_exp: &mlb_hir::BasExp,
+  ac: &mut Vec<PathId>,
+  visited: &mut FxHashSet<PathId>,
+) {
+  match bas_exp {
+    mlb_hir::BasExp::Bas(dec) => group_sources(input, dec, ac, visited),
+    mlb_hir::BasExp::Let(dec, exp) => {
+      group_sources(input, dec, ac, visited);
+      group_sources_exp(input, exp, ac, visited);
+    }
+    mlb_hir::BasExp::Name(_) => {}
+  }
+}
+
+/// marks the structures, signatures, and functors exported by the group, whose source files are
+/// `sources`, as used.
+fn mark_exports(
+  items: &mut PathMap<FileItems<'_>>,
+  files: &Files,
+  sources: &[PathId],
+  bas_dec: &mlb_hir::BasDec,
+) {
+  match bas_dec {
+    mlb_hir::BasDec::Export(namespace, _, name) => {
+      mark_export(items, sources, *namespace, &name.val);
+    }
+    mlb_hir::BasDec::CmExport(export) => {
+      // functor signatures exported without the SML/NJ target are already errors, so it's fine to
+      // mark them too.
+      let lowered = |path: PathId| files.get(&path).map(|file| &file.lowered);
+      for (namespace, name) in mlb_statics::cm_export_names(export, lowered, true) {
+        mark_export(items, sources, namespace, &name.val);
+      }
+    }
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      mark_exports(items, files, sources, local_dec);
+      mark_exports(items, files, sources, in_dec);
+    }
+    mlb_hir::BasDec::Seq(decs) => {
+      for dec in decs {
+        mark_exports(items, files, sources, dec);
+      }
+    }
+    mlb_hir::BasDec::Basis(_, _) | mlb_hir::BasDec::Open(_) | mlb_hir::BasDec::Path(_, _) => {}
+  }
+}
+
+/// marks the structure, signature, or functor with the kind and name as used. this is the last one
+/// declared in the `sources`, since it shadows the others.
+fn mark_export(
+  items: &mut PathMap<FileItems<'_>>,
+  sources: &[PathId],
+  namespace: mlb_hir::Namespace,
+  name: &hir::Name,
+) {
+  let kind = match namespace {
+    mlb_hir::Namespace::Structure => statics::ModKind::Structure,
+    mlb_hir::Namespace::Signature => statics::ModKind::Signature,
+    mlb_hir::Namespace::Functor => statics::ModKind::Functor,
+    mlb_hir::Namespace::FunSig => statics::ModKind::FunSig,
+  };
+  for path in sources.iter().rev() {
+    let file_items = match items.get_mut(path) {
+      Some(x) => x,
+      None => continue,
+    };
+    let item = file_items
+      .items
+      .iter_mut()
+      .filter(|item| matches!(item.kind, ItemKind::Mod(k) if k == kind) && item.name == name)
+      .max_by_key(|item| item.dec_range.start());
+    if let Some(item) = item {
+      item.used = true;
+      file_items.used = true;
+      return;
+    }
+  }
+}
+
+/// returns the range of the first name token in the node with the given text, or the whole node if
+/// there is none.
+fn name_range(node: &SyntaxNode, name: &hir::Name) -> TextRange {
+  node
+    .descendants_with_tokens()
+    .filter_map(|x| x.into_token())
+    .find(|tok| tok.kind() == SyntaxKind::Name && tok.text() == name.as_str())
+    .map_or_else(|| node.text_range(), |tok| tok.text_range())
+}
@@ -0,0 +1,73 @@
+//! Collect the structures, signatures, and functors of a project, for documentation.
+
+use paths::{PathId, PathMap};
+
+/// A structure, signature, or functor declared at the top level of a source file.
+#[derive(Debug)]
+pub struct DocItem {
+  /// What it is.
+  pub kind: statics::ModKind,
+  /// The name.
+  pub name: String,
+  /// SML describing its signature.
+  pub sig: String,
+  /// The doc comment, if any. This is Markdown.
+  pub doc: Option<String>,
+  /// The path of the file declaring it.
+  pub path: PathId,
+}
+
+pub(crate) fn get(files: &PathMap<mlb_statics::SourceFile>, syms: &statics::Syms) -> Vec<DocItem> {
+  let mut ret = Vec::<DocItem>::new();
+  for (&path, file) in files.iter() {
+    for idx in top_str_decs(file) {
+      let doc = file.info.get_doc(idx);
+      ret.extend(
+        file
+          .info
+          .get_mods(syms, idx)
+          .into_iter()
+          .map(|(kind, name, sig)| DocItem {
+            kind,
+            name: name.as_str().to_owned(),
+            sig,
+            doc: doc.map(ToOwned::to_owned),
+            path,
+          }),
+      );
+    }
+  }
+  ret.sort_unstable_by(|a, b| {
+    a.kind
+      .cmp(&b.kind)
+      .then_with(|| a.name.cmp(&b.name))
+      .then_with(|| a.path.cmp(&b.path))
+  });
+  ret
+}
+
+/// returns the declarations of structures, signatures, and functors visible at the top level of the
+/// file.
+pub(crate) fn top_str_decs(file: &mlb_statics::SourceFile) -> Vec<hir::Idx> {
+  let mut ret = Vec::<hir::Idx>::new();
+  let mut stack = vec![file.lowered.root];
+  while let Some(str_dec) = stack.pop() {
+    let str_dec = match str_dec {
+      Some(x) => x,
+      None => continue,
+    };
+    match &file.lowered.arenas.str_dec[str_dec] {
+      hir::StrDec::Dec(_) => {}
+      hir::StrDec::Seq(str_decs) => stack.extend(str_decs.iter().copied()),
+      // only the `in` part of a `local` is visible outside it.
+      hir::StrDec::Local(_, in_dec) => stack.push(*in_dec),
+      hir::StrDec::Structure(_)
+      | hir::StrDec::Signature(_)
+      | hir::StrDec::Functor(_)
+      | hir::StrDec::FunSig(_) => {
+        ret.push(hir::Idx::StrDec(str_dec));
+      }
+    }
+  }
+  ret
+}
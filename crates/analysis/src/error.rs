@@ -9,4 +9,6 @@ pub struct Error {
   pub message: String,
   /// The error code.
   pub code: u16,
+  /// Other places in the same file related to the error, with messages about how they're related.
+  pub related: Vec<(Range, String)>,
 }
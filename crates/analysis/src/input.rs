@@ -14,8 +14,10 @@ pub struct Input {
   pub(crate) sources: PathMap<String>,
   /// A map from group paths to their (parsed) contents.
   pub(crate) groups: PathMap<Group>,
-  /// The root group id.
-  pub(crate) root_group_id: PathId,
+  /// The root group ids. Each is analyzed separately.
+  pub(crate) root_group_ids: Vec<PathId>,
+  /// The compiler to target.
+  pub(crate) target: Option<config::Target>,
 }
 
 impl Input {
@@ -23,6 +25,16 @@ impl Input {
   pub fn iter_sources(&self) -> impl Iterator<Item = WithPath<&str>> + '_ {
     self.sources.iter().map(|(&path, s)| path.wrap(s.as_str()))
   }
+
+  /// Return an iterator over the group paths, each with the paths of the groups and source files it
+  /// directly includes.
+  pub fn iter_groups(&self) -> impl Iterator<Item = WithPath<BTreeSet<PathId>>> + '_ {
+    self.groups.iter().map(|(&path, group)| {
+      let mut ac = BTreeSet::<PathId>::new();
+      bas_dec_paths(&mut ac, &group.bas_dec);
+      path.wrap(ac)
+    })
+  }
 }
 
 /// An error when getting input.
@@ -62,6 +74,7 @@ impl GetInputError {
       GetInputErrorKind::Mlb(_) => 1009,
       GetInputErrorKind::Cycle => 1010,
       GetInputErrorKind::Duplicate(_) => 1011,
+      GetInputErrorKind::NotMember => 1012,
       GetInputErrorKind::UnsupportedExport => 1999,
     }
   }
@@ -89,6 +102,7 @@ enum GetInputErrorKind {
   Mlb(mlb_syntax::Error),
   Cycle,
   Duplicate(hir::Name),
+  NotMember,
   /// must be last
   UnsupportedExport,
 }
@@ -114,6 +128,7 @@ impl fmt::Display for GetInputErrorKind {
       GetInputErrorKind::Mlb(e) => write!(f, "couldn't process ML Basis file: {e}"),
       GetInputErrorKind::Cycle => f.write_str("there is a cycle involving this path"),
       GetInputErrorKind::Duplicate(name) => write!(f, "duplicate name: {name}"),
+      GetInputErrorKind::NotMember => f.write_str("not a member of the group"),
       GetInputErrorKind::UnsupportedExport => f.write_str("unsupported export kind"),
     }
   }
@@ -173,19 +188,20 @@ pub(crate) struct Group {
   pub(crate) pos_db: text_pos::PositionDb,
 }
 
-struct RootGroup {
-  path: PathId,
-  kind: GroupPathKind,
+struct RootGroups {
+  groups: Vec<(PathId, GroupPathKind)>,
   path_vars: paths::slash_var_path::Env,
+  target: Option<config::Target>,
 }
 
-fn get_root_group<F>(fs: &F, root: &mut Root) -> Result<RootGroup>
+fn get_root_groups<F>(fs: &F, root: &mut Root) -> Result<RootGroups>
 where
   F: paths::FileSystem,
 {
   let mut root_group_source = Source::default();
   let config_path = root.paths.as_path().join(config::FILE_NAME);
   let mut path_vars = paths::slash_var_path::Env::default();
+  let mut target = None::<config::Target>;
   if let Ok(contents) = fs.read_to_string(&config_path) {
     let config: config::Root = match toml::from_str(&contents) {
       Ok(x) => x,
@@ -205,6 +221,7 @@ where
       });
     }
     if let Some(ws) = config.workspace {
+      target = ws.target;
       if let Some(ws_path_vars) = ws.path_vars {
         for (key, val) in ws_path_vars {
           match val {
@@ -224,29 +241,30 @@ where
         }
       }
       // try to get from the config.
-      if let (None, Some(path)) = (&root.group_path, ws.root) {
-        let path = root.paths.as_path().join(path.as_str());
-        match GroupPath::new(fs, path.clone()) {
-          Some(path) => {
-            root_group_source.path = Some(config_path);
-            root.group_path = Some(path);
-          }
-          None => {
+      if let (true, Some(roots)) = (root.group_paths.is_empty(), ws.root) {
+        for pattern in roots.as_slice() {
+          let mut group_paths: Vec<_> = glob(fs, root.paths.as_path(), pattern.as_str())
+            .into_iter()
+            .filter_map(|path| GroupPath::new(fs, path))
+            .collect();
+          if group_paths.is_empty() {
             return Err(GetInputError {
               source: Source {
                 path: Some(config_path),
                 range: None,
               },
-              path,
+              path: root.paths.as_path().join(pattern.as_str()),
               kind: GetInputErrorKind::NotGroup,
-            })
+            });
           }
+          root.group_paths.append(&mut group_paths);
         }
+        root_group_source.path = Some(config_path);
       }
     }
   }
   // if not, try to get one from the root dir.
-  if root.group_path.is_none() {
+  if root.group_paths.is_empty() {
     let dir_entries = fs
       .read_dir(root.paths.as_path())
       .map_err(|e| GetInputError {
@@ -256,7 +274,7 @@ where
       })?;
     for entry in dir_entries {
       if let Some(group_path) = GroupPath::new(fs, entry.clone()) {
-        match &root.group_path {
+        match root.group_paths.first() {
           Some(rgp) => {
             return Err(GetInputError {
               kind: GetInputErrorKind::MultipleRoots(rgp.path.clone(), entry.clone()),
@@ -267,28 +285,86 @@ where
               path: entry,
             })
           }
-          None => root.group_path = Some(group_path),
+          None => root.group_paths.push(group_path),
         }
       }
     }
   }
-  let root_group_path = root.group_path.as_ref().ok_or_else(|| GetInputError {
-    source: Source::default(),
-    path: root.paths.as_path().to_owned(),
-    kind: GetInputErrorKind::NoRoot,
-  })?;
-  Ok(RootGroup {
-    path: get_path_id(
-      fs,
-      &mut root.paths,
-      root_group_source,
-      root_group_path.path.as_path(),
-    )?,
-    kind: root_group_path.kind,
+  if root.group_paths.is_empty() {
+    return Err(GetInputError {
+      source: Source::default(),
+      path: root.paths.as_path().to_owned(),
+      kind: GetInputErrorKind::NoRoot,
+    });
+  }
+  let groups = root
+    .group_paths
+    .iter()
+    .map(|group_path| {
+      let path = get_path_id(
+        fs,
+        &mut root.paths,
+        root_group_source.clone(),
+        group_path.path.as_path(),
+      )?;
+      Ok((path, group_path.kind))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  Ok(RootGroups {
+    groups,
     path_vars,
+    target,
   })
 }
 
+/// returns the paths matching `pattern` relative to `dir`, where `*` in a component of `pattern`
+/// matches any sequence of characters in a file name.
+fn glob<F>(fs: &F, dir: &Path, pattern: &str) -> Vec<PathBuf>
+where
+  F: paths::FileSystem,
+{
+  let mut ret = vec![dir.to_owned()];
+  for component in pattern.split('/') {
+    if !component.contains('*') {
+      for path in ret.iter_mut() {
+        path.push(component);
+      }
+      continue;
+    }
+    ret = ret
+      .into_iter()
+      .flat_map(|path| {
+        // only keep the entries directly in `path`.
+        let entries = fs.read_dir(path.as_path()).unwrap_or_default();
+        let children: BTreeSet<_> = entries
+          .iter()
+          .filter_map(|entry| Some(path.join(entry.strip_prefix(&path).ok()?.iter().next()?)))
+          .collect();
+        children
+      })
+      .filter(|path| {
+        path
+          .file_name()
+          .and_then(|x| x.to_str())
+          .map_or(false, |name| wildcard_match(component, name))
+      })
+      .collect();
+  }
+  ret
+}
+
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+  match pattern.split_once('*') {
+    None => pattern == s,
+    Some((prefix, rest)) => match s.strip_prefix(prefix) {
+      None => false,
+      Some(s) => (0..=s.len())
+        .filter(|&idx| s.is_char_boundary(idx))
+        .any(|idx| wildcard_match(rest, &s[idx..])),
+    },
+  }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct GroupToProcess {
   /// the path that led us to `group_path`.
@@ -322,7 +398,7 @@ where
 #[derive(Debug)]
 pub struct Root {
   paths: paths::Root,
-  group_path: Option<GroupPath>,
+  group_paths: Vec<GroupPath>,
 }
 
 impl Root {
@@ -341,7 +417,7 @@ impl Root {
 pub fn get_root_dir(path: paths::CanonicalPathBuf) -> Root {
   Root {
     paths: paths::Root::new(path),
-    group_path: None,
+    group_paths: Vec::new(),
   }
 }
 
@@ -351,19 +427,19 @@ where
   F: paths::FileSystem,
 {
   let path = canonicalize(fs, path, &Source::default())?;
-  let (root_path, group_path) = match GroupPath::new(fs, path.clone().into_path_buf()) {
-    None => (path, None),
+  let (root_path, group_paths) = match GroupPath::new(fs, path.clone().into_path_buf()) {
+    None => (path, Vec::new()),
     Some(path) => {
       let parent = path.as_path().parent().expect("no parent");
       let rp = fs
         .canonicalize(parent)
         .expect("canonicalize parent of canonical path");
-      (rp, Some(path))
+      (rp, vec![path])
     }
   };
   Ok(Root {
     paths: paths::Root::new(root_path),
-    group_path,
+    group_paths,
   })
 }
 
@@ -373,46 +449,137 @@ pub fn get<F>(fs: &F, root: &mut Root) -> Result<Input>
 where
   F: paths::FileSystem,
 {
-  let root_group = get_root_group(fs, root)?;
-  let init = GroupToProcess {
-    containing_path: root_group.path,
-    containing_range: None,
-    group_path: root_group.path,
-  };
+  let root_groups = get_root_groups(fs, root)?;
   let mut sources = PathMap::<String>::default();
-  let groups = match root_group.kind {
+  let mut groups = PathMap::<Group>::default();
+  for &(path, kind) in root_groups.groups.iter() {
+    let init = GroupToProcess {
+      containing_path: path,
+      containing_range: None,
+      group_path: path,
+    };
+    get_groups(
+      fs,
+      root,
+      &root_groups,
+      &mut sources,
+      &mut groups,
+      kind,
+      init,
+    )?;
+  }
+  let graph: topo_sort::Graph<_> = groups
+    .iter()
+    .map(|(&path, group)| {
+      let mut ac = BTreeSet::<PathId>::new();
+      bas_dec_paths(&mut ac, &group.bas_dec);
+      (path, ac)
+    })
+    .collect();
+  if let Err(err) = topo_sort::get(&graph) {
+    return Err(GetInputError {
+      source: Source::default(),
+      path: root.paths.get_path(err.witness()).as_path().to_owned(),
+      kind: GetInputErrorKind::Cycle,
+    });
+  }
+  Ok(Input {
+    sources,
+    groups,
+    root_group_ids: root_groups
+      .groups
+      .into_iter()
+      .map(|(path, _)| path)
+      .collect(),
+    target: root_groups.target,
+  })
+}
+
+/// Returns the SML source files that are not in the `input`, but are in a directory that has a group
+/// or source file that is. Other directories, like build output directories, are not searched.
+pub fn get_unlisted_sources<F>(fs: &F, root: &mut Root, input: &Input) -> Vec<PathId>
+where
+  F: paths::FileSystem,
+{
+  let mut ret = Vec::<PathId>::new();
+  let dirs: FxHashSet<PathBuf> = input
+    .groups
+    .keys()
+    .chain(input.sources.keys())
+    .filter_map(|&path| {
+      let path = root.paths.get_path(path).as_path();
+      path.parent().map(ToOwned::to_owned)
+    })
+    .collect();
+  for dir in dirs {
+    for entry in fs.read_dir(dir.as_path()).unwrap_or_default() {
+      // only the entries directly in the dir.
+      if entry.parent() != Some(dir.as_path()) || !fs.is_file(entry.as_path()) {
+        continue;
+      }
+      let is_sml = entry
+        .extension()
+        .and_then(|x| x.to_str())
+        .map_or(false, |x| matches!(x, "sml" | "sig" | "fun"));
+      if !is_sml {
+        continue;
+      }
+      let id = match fs.canonicalize(entry.as_path()) {
+        Ok(canonical) => match root.paths.get_id(&canonical) {
+          Ok(x) => x,
+          Err(_) => continue,
+        },
+        Err(_) => continue,
+      };
+      if !input.sources.contains_key(&id) {
+        ret.push(id);
+      }
+    }
+  }
+  ret
+}
+
+/// adds the groups reachable from `init`, and the sources they contain, to `groups` and `sources`.
+fn get_groups<F>(
+  fs: &F,
+  root: &mut Root,
+  root_groups: &RootGroups,
+  sources: &mut PathMap<String>,
+  groups: &mut PathMap<Group>,
+  kind: GroupPathKind,
+  init: GroupToProcess,
+) -> Result<()>
+where
+  F: paths::FileSystem,
+{
+  let path_vars = &root_groups.path_vars;
+  match kind {
     GroupPathKind::Cm => {
       let mut cm_files = PathMap::<CmFile>::default();
+      let target = root_groups.target;
       get_cm_file(
         &mut root.paths,
         fs,
-        &root_group.path_vars,
-        &mut sources,
+        path_vars,
+        target,
+        sources,
         &mut cm_files,
         init,
       )?;
-      cm_files
-        .into_iter()
-        .map(|(path, cm_file)| {
-          let exports: Vec<_> = cm_file
-            .exports
-            .into_iter()
-            .map(|ex| mlb_hir::BasDec::Export(ex.namespace, ex.name.clone(), ex.name))
-            .collect();
-          let bas_dec = mlb_hir::BasDec::Local(
-            mlb_hir::BasDec::seq(cm_file.paths).into(),
-            mlb_hir::BasDec::seq(exports).into(),
-          );
-          let group = Group {
-            bas_dec,
-            pos_db: cm_file.pos_db.expect("no pos db"),
-          };
-          (path, group)
-        })
-        .collect()
+      groups.extend(cm_files.into_iter().map(|(path, cm_file)| {
+        let exports = mlb_hir::CmExport::Union(cm_file.exports);
+        let bas_dec = mlb_hir::BasDec::Local(
+          mlb_hir::BasDec::seq(cm_file.paths).into(),
+          mlb_hir::BasDec::CmExport(exports).into(),
+        );
+        let group = Group {
+          bas_dec,
+          pos_db: cm_file.pos_db.expect("no pos db"),
+        };
+        (path, group)
+      }));
     }
     GroupPathKind::Mlb => {
-      let mut groups = PathMap::<Group>::default();
       let mut stack = vec![init];
       while let Some(cur) = stack.pop() {
         if groups.contains_key(&cur.group_path) {
@@ -423,51 +590,30 @@ where
         let group_parent = group_path
           .parent()
           .expect("path from get_path has no parent");
-        let syntax_dec =
-          mlb_syntax::get(&contents, &root_group.path_vars).map_err(|e| GetInputError {
-            source: Source {
-              path: None,
-              range: pos_db.range(e.text_range()),
-            },
-            path: group_path.to_owned(),
-            kind: GetInputErrorKind::Mlb(e),
-          })?;
+        let syntax_dec = mlb_syntax::get(&contents, path_vars).map_err(|e| GetInputError {
+          source: Source {
+            path: None,
+            range: pos_db.range(e.text_range()),
+          },
+          path: group_path.to_owned(),
+          kind: GetInputErrorKind::Mlb(e),
+        })?;
         let mut cx = MlbCx {
           path: group_path,
           parent: group_parent,
           pos_db: &pos_db,
           fs,
           root: &mut root.paths,
-          sources: &mut sources,
+          sources,
           stack: &mut stack,
           path_id: cur.group_path,
         };
         let bas_dec = get_bas_dec(&mut cx, syntax_dec)?;
         groups.insert(cur.group_path, Group { bas_dec, pos_db });
       }
-      groups
     }
-  };
-  let graph: topo_sort::Graph<_> = groups
-    .iter()
-    .map(|(&path, group)| {
-      let mut ac = BTreeSet::<PathId>::new();
-      bas_dec_paths(&mut ac, &group.bas_dec);
-      (path, ac)
-    })
-    .collect();
-  if let Err(err) = topo_sort::get(&graph) {
-    return Err(GetInputError {
-      source: Source::default(),
-      path: root.paths.get_path(err.witness()).as_path().to_owned(),
-      kind: GetInputErrorKind::Cycle,
-    });
   }
-  Ok(Input {
-    sources,
-    groups,
-    root_group_id: root_group.path,
-  })
+  Ok(())
 }
 
 /// only derives default because we need to mark in-progress files as visited to prevent infinite
@@ -477,13 +623,8 @@ struct CmFile {
   /// only optional so this can derive default.
   pos_db: Option<text_pos::PositionDb>,
   paths: Vec<mlb_hir::BasDec>,
-  exports: Vec<Export>,
-}
-
-#[derive(Debug)]
-struct Export {
-  namespace: mlb_hir::Namespace,
-  name: text_size_util::WithRange<hir::Name>,
+  /// the exports, which are all exported.
+  exports: Vec<mlb_hir::CmExport>,
 }
 
 /// only recursive to support library exports, which ~necessitates the ability to know the exports
@@ -492,6 +633,7 @@ fn get_cm_file<F>(
   root: &mut paths::Root,
   fs: &F,
   path_vars: &paths::slash_var_path::Env,
+  target: Option<config::Target>,
   sources: &mut paths::PathMap<String>,
   cm_files: &mut paths::PathMap<CmFile>,
   cur: GroupToProcess,
@@ -539,7 +681,7 @@ where
             containing_range: source.range,
             group_path: path_id,
           };
-          get_cm_file(root, fs, path_vars, sources, cm_files, cur)?;
+          get_cm_file(root, fs, path_vars, target, sources, cm_files, cur)?;
           // NOTE this is a lie.
           mlb_hir::PathKind::Mlb
         }
@@ -547,49 +689,21 @@ where
       Ok(mlb_hir::BasDec::Path(path_id, kind))
     })
     .collect::<Result<Vec<_>>>()?;
-  let mut exports = Vec::<Export>::new();
+  let mut cx = CmExportCx {
+    root,
+    fs,
+    path_vars,
+    target,
+    sources,
+    cm_files,
+    group_id: cur.group_path,
+    group_path,
+    pos_db: &pos_db,
+    members: &paths,
+  };
+  let mut exports = Vec::<mlb_hir::CmExport>::new();
   for export in cm.exports {
-    match export {
-      cm::Export::Regular(ns, name) => {
-        let namespace = match ns.val {
-          cm::Namespace::Structure => mlb_hir::Namespace::Structure,
-          cm::Namespace::Signature => mlb_hir::Namespace::Signature,
-          cm::Namespace::Functor => mlb_hir::Namespace::Functor,
-          cm::Namespace::FunSig => {
-            return Err(GetInputError {
-              source: Source {
-                path: None,
-                range: pos_db.range(ns.range),
-              },
-              path: group_path.to_owned(),
-              kind: GetInputErrorKind::UnsupportedExport,
-            })
-          }
-        };
-        exports.push(Export { namespace, name });
-      }
-      cm::Export::Library(lib) => {
-        let source = Source {
-          path: Some(group_path.to_owned()),
-          range: pos_db.range(lib.range),
-        };
-        let path = group_parent.join(lib.val.as_path());
-        let path_id = get_path_id(fs, root, source.clone(), path.as_path())?;
-        let cur = GroupToProcess {
-          containing_path: cur.group_path,
-          containing_range: source.range,
-          group_path: path_id,
-        };
-        get_cm_file(root, fs, path_vars, sources, cm_files, cur)?;
-        let cm_file = cm_files
-          .get(&cur.group_path)
-          .expect("cm file should be set after get_cm_file");
-        exports.extend(cm_file.exports.iter().map(|ex| Export {
-          namespace: ex.namespace,
-          name: lib.wrap(ex.name.val.clone()),
-        }));
-      }
-    }
+    get_export(&mut cx, &mut exports, export)?;
   }
   let cm_file = CmFile {
     pos_db: Some(pos_db),
@@ -600,6 +714,171 @@ where
   Ok(())
 }
 
+/// the context for evaluating the exports of a CM file.
+struct CmExportCx<'a, F> {
+  root: &'a mut paths::Root,
+  fs: &'a F,
+  path_vars: &'a paths::slash_var_path::Env,
+  target: Option<config::Target>,
+  sources: &'a mut PathMap<String>,
+  cm_files: &'a mut PathMap<CmFile>,
+  /// the CM file with the exports.
+  group_id: PathId,
+  group_path: &'a Path,
+  pos_db: &'a text_pos::PositionDb,
+  /// the already-processed members of the CM file.
+  members: &'a [mlb_hir::BasDec],
+}
+
+/// pushes the names denoted by the export onto `ac`. there may be duplicates.
+fn get_export<F>(
+  cx: &mut CmExportCx<'_, F>,
+  ac: &mut Vec<mlb_hir::CmExport>,
+  export: cm::Export,
+) -> Result<()>
+where
+  F: paths::FileSystem,
+{
+  match export {
+    cm::Export::Regular(ns, name) => {
+      let namespace = match ns.val {
+        cm::Namespace::Structure => mlb_hir::Namespace::Structure,
+        cm::Namespace::Signature => mlb_hir::Namespace::Signature,
+        cm::Namespace::Functor => mlb_hir::Namespace::Functor,
+        // functor signatures are an SML/NJ extension.
+        cm::Namespace::FunSig if matches!(cx.target, Some(config::Target::SmlNj)) => {
+          mlb_hir::Namespace::FunSig
+        }
+        cm::Namespace::FunSig => {
+          return Err(GetInputError {
+            source: Source {
+              path: None,
+              range: cx.pos_db.range(ns.range),
+            },
+            path: cx.group_path.to_owned(),
+            kind: GetInputErrorKind::UnsupportedExport,
+          })
+        }
+      };
+      ac.push(mlb_hir::CmExport::Name(namespace, name));
+    }
+    cm::Export::Library(lib) => {
+      let source = Source {
+        path: Some(cx.group_path.to_owned()),
+        range: cx.pos_db.range(lib.range),
+      };
+      let group_parent = cx
+        .group_path
+        .parent()
+        .expect("path from get_path has no parent");
+      let path = group_parent.join(lib.val.as_path());
+      let path_id = get_path_id(cx.fs, cx.root, source.clone(), path.as_path())?;
+      let cur = GroupToProcess {
+        containing_path: cx.group_id,
+        containing_range: source.range,
+        group_path: path_id,
+      };
+      get_cm_file(
+        cx.root,
+        cx.fs,
+        cx.path_vars,
+        cx.target,
+        cx.sources,
+        cx.cm_files,
+        cur,
+      )?;
+      let cm_file = cx
+        .cm_files
+        .get(&cur.group_path)
+        .expect("cm file should be set after get_cm_file");
+      ac.extend(cm_file.exports.iter().map(|ex| ex.with_range(lib.range)));
+    }
+    // the names are only known once the file is lowered, since that depends on the fixities
+    // from the files before it.
+    cm::Export::Source(arg) => {
+      for path_id in export_members(cx, &arg, mlb_hir::PathKind::Sml)? {
+        ac.push(mlb_hir::CmExport::Source(arg.wrap(path_id)));
+      }
+    }
+    cm::Export::Group(arg) => {
+      // NOTE cm members are marked as mlb members.
+      for path_id in export_members(cx, &arg, mlb_hir::PathKind::Mlb)? {
+        let cm_file = cx
+          .cm_files
+          .get(&path_id)
+          .expect("cm member should be processed");
+        ac.extend(cm_file.exports.iter().map(|ex| ex.with_range(arg.range)));
+      }
+    }
+    cm::Export::Union(exports) => {
+      for export in exports {
+        get_export(cx, ac, export)?;
+      }
+    }
+    cm::Export::Difference(lhs, rhs) => {
+      let mut lhs_ac = Vec::<mlb_hir::CmExport>::new();
+      get_export(cx, &mut lhs_ac, *lhs)?;
+      let mut rhs_ac = Vec::<mlb_hir::CmExport>::new();
+      get_export(cx, &mut rhs_ac, *rhs)?;
+      ac.push(mlb_hir::CmExport::Difference(
+        mlb_hir::CmExport::Union(lhs_ac).into(),
+        mlb_hir::CmExport::Union(rhs_ac).into(),
+      ));
+    }
+    cm::Export::Intersection(lhs, rhs) => {
+      let mut lhs_ac = Vec::<mlb_hir::CmExport>::new();
+      get_export(cx, &mut lhs_ac, *lhs)?;
+      let mut rhs_ac = Vec::<mlb_hir::CmExport>::new();
+      get_export(cx, &mut rhs_ac, *rhs)?;
+      ac.push(mlb_hir::CmExport::Intersection(
+        mlb_hir::CmExport::Union(lhs_ac).into(),
+        mlb_hir::CmExport::Union(rhs_ac).into(),
+      ));
+    }
+  }
+  Ok(())
+}
+
+/// returns the members of the kind that the argument to `source` or `group` refers to. errors if
+/// the argument is a path that is not a member of that kind.
+fn export_members<F>(
+  cx: &mut CmExportCx<'_, F>,
+  arg: &text_size_util::WithRange<cm::PathOrMinus>,
+  kind: mlb_hir::PathKind,
+) -> Result<Vec<PathId>>
+where
+  F: paths::FileSystem,
+{
+  let mut members = cx.members.iter().filter_map(|bas_dec| match bas_dec {
+    mlb_hir::BasDec::Path(path_id, k) => (*k == kind).then_some(*path_id),
+    _ => None,
+  });
+  match &arg.val {
+    cm::PathOrMinus::Minus => Ok(members.collect()),
+    cm::PathOrMinus::Path(path) => {
+      let source = Source {
+        path: Some(cx.group_path.to_owned()),
+        range: cx.pos_db.range(arg.range),
+      };
+      let group_parent = cx
+        .group_path
+        .parent()
+        .expect("path from get_path has no parent");
+      let path = group_parent.join(path.as_path());
+      let path_id = get_path_id(cx.fs, cx.root, source.clone(), path.as_path())?;
+      if members.any(|x| x == path_id) {
+        Ok(vec![path_id])
+      } else {
+        Err(GetInputError {
+          source,
+          path,
+          kind: GetInputErrorKind::NotMember,
+        })
+      }
+    }
+  }
+}
+
 fn get_path_id<F>(
   fs: &F,
   root: &mut paths::Root,
@@ -760,7 +1039,7 @@ where
 
 fn bas_dec_paths(ac: &mut BTreeSet<PathId>, dec: &mlb_hir::BasDec) {
   match dec {
-    mlb_hir::BasDec::Open(_) | mlb_hir::BasDec::Export(_, _, _) => {}
+    mlb_hir::BasDec::Open(_) | mlb_hir::BasDec::Export(_, _, _) | mlb_hir::BasDec::CmExport(_) => {}
     mlb_hir::BasDec::Path(p, _) => {
       ac.insert(*p);
     }
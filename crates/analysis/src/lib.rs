@@ -2,18 +2,32 @@
 
 #![deny(missing_debug_implementations, missing_docs, rust_2018_idioms)]
 
+mod call_hierarchy;
+mod dead_code;
+mod doc_items;
 mod error;
+mod mod_graph;
+mod semantic_tokens;
+mod syntax_ranges;
 
 pub mod input;
 
+use fast_hash::FxHashMap;
 use fmt_util::sep_seq;
-use paths::{PathMap, WithPath};
+use paths::{PathId, PathMap, WithPath};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use syntax::ast::{AstNode as _, SyntaxNodePtr};
 use syntax::{rowan::TokenAtOffset, SyntaxKind, SyntaxToken};
 
+pub use call_hierarchy::{CallHierarchyItem, CallHierarchyItemKind, IncomingCall, OutgoingCall};
+pub use doc_items::DocItem;
 pub use error::Error;
-pub use mlb_statics::StdBasis;
+pub use mlb_statics::{FileCache, StdBasis};
+pub use mod_graph::ModName;
+pub use semantic_tokens::{SemanticToken, SemanticTokenKind};
+pub use statics::ModKind;
+pub use syntax_ranges::{FoldingRange, FoldingRangeKind};
 pub use text_pos::{Position, Range};
 
 /// The url to go to for information about errors.
@@ -26,6 +40,8 @@ pub struct Analysis {
   error_lines: config::ErrorLines,
   source_files: PathMap<mlb_statics::SourceFile>,
   syms: statics::Syms,
+  parallel: bool,
+  file_cache: Option<mlb_statics::FileCache>,
 }
 
 impl Analysis {
@@ -36,16 +52,30 @@ impl Analysis {
       error_lines,
       source_files: PathMap::default(),
       syms: statics::Syms::default(),
+      parallel: true,
+      file_cache: None,
     }
   }
 
+  /// Sets whether to check independent groups and basis bindings in parallel. The default is to do
+  /// so. The result is the same either way.
+  pub fn set_parallel(&mut self, parallel: bool) {
+    self.parallel = parallel;
+  }
+
+  /// Sets the cache of the results of checking source files. The default is to have none. The
+  /// result is the same either way.
+  pub fn set_file_cache(&mut self, file_cache: Option<mlb_statics::FileCache>) {
+    self.file_cache = file_cache;
+  }
+
   /// Given the contents of one isolated file, return the errors for it.
   pub fn get_one(&self, contents: &str) -> Vec<Error> {
     let mut fix_env = mlb_statics::STD_BASIS_FIX_ENV.clone();
     let (lex_errors, parsed, low) = mlb_statics::start_source_file(contents, &mut fix_env);
     let mut syms = self.std_basis.syms().clone();
     let basis = self.std_basis.basis().clone();
-    let mode = statics::Mode::Regular(None);
+    let mode = statics::Mode::Regular(None, statics::Dialect::default());
     let checked = statics::get(&mut syms, &basis, mode, &low.arenas, low.root);
     let mut info = checked.info;
     mlb_statics::doc_comment::get(parsed.root.syntax(), &low, &mut info);
@@ -56,6 +86,7 @@ impl Analysis {
       lowered: low,
       statics_errors: checked.errors,
       info,
+      others: Vec::new(),
     };
     source_file_errors(&file, &syms, self.error_lines)
   }
@@ -73,7 +104,10 @@ impl Analysis {
         &self.std_basis,
         &input.sources,
         &groups,
-        input.root_group_id,
+        &input.root_group_ids,
+        input.target,
+        self.parallel,
+        self.file_cache.as_ref(),
       )
     });
     self.source_files = res.sml;
@@ -87,6 +121,7 @@ impl Analysis {
             range: group.pos_db.range(err.range())?,
             message: err.to_string(),
             code: err.to_code(),
+            related: Vec::new(),
           }],
         ))
       }))
@@ -103,17 +138,23 @@ impl Analysis {
   pub fn get_md(&self, pos: WithPath<Position>) -> Option<(String, Range)> {
     let (file, tok, ptr, idx) = self.get_file_with_idx(pos)?;
     let ty_md = file.info.get_ty_md(&self.syms, idx);
-    let def_doc = file.info.get_def(idx).and_then(|def| {
-      let info = match def.path {
-        statics::DefPath::Regular(path) => &self.source_files.get(&path)?.info,
-        statics::DefPath::StdBasis(name) => self.std_basis.get_info(name)?,
-      };
-      info.get_doc(def.idx)
-    });
-    let parts: Vec<_> = [ty_md.as_deref(), def_doc, tok.kind().token_doc()]
-      .into_iter()
-      .flatten()
-      .collect();
+    let fills_md = if is_recovered_hole(file, idx) {
+      None
+    } else {
+      fills_md(&file.info.get_hole_fills(&self.syms, idx))
+    };
+    let mod_md = file.info.get_mod_md(&self.syms, idx, tok.text());
+    let def_doc = file.info.get_def(idx).and_then(|def| self.get_def_doc(def));
+    let parts: Vec<_> = [
+      ty_md.as_deref(),
+      fills_md.as_deref(),
+      mod_md.as_deref(),
+      def_doc,
+      tok.kind().token_doc(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
     let range = ptr.to_node(file.parsed.root.syntax()).text_range();
     let range = file.pos_db.range(range)?;
     Some((parts.join("\n\n---\n\n"), range))
@@ -121,8 +162,12 @@ impl Analysis {
 
   /// Returns the range of the definition of the item at this position.
   pub fn get_def(&self, pos: WithPath<Position>) -> Option<WithPath<Range>> {
-    let (file, _, _, idx) = self.get_file_with_idx(pos)?;
-    self.def_to_path_and_range(file.info.get_def(idx)?)
+    let (file, tok, _, idx) = self.get_file_with_idx(pos)?;
+    let def = file
+      .info
+      .get_mod_def(idx, tok.text())
+      .or_else(|| file.info.get_def(idx))?;
+    self.def_to_path_and_range(def)
   }
 
   /// Returns the ranges of the definitions of the types involved in the type of the item at this
@@ -151,15 +196,334 @@ impl Analysis {
     let head_ptr = SyntaxNodePtr::new(head_ast.syntax());
     let head = file.lowered.ptrs.ast_to_hir(head_ptr)?;
     let variants = file.info.get_variants(&self.syms, head)?;
+    let pats: Vec<_> = variants
+      .into_iter()
+      .map(|(name, has_arg)| {
+        if has_arg {
+          format!("{name} _")
+        } else {
+          name.as_str().to_owned()
+        }
+      })
+      .collect();
     let case = CaseDisplay {
       needs_starting_bar: case
         .matcher()
         .map_or(false, |x| x.match_rules().count() > 0),
-      variants: &variants,
+      pats: &pats,
+    };
+    Some((range, case.to_string()))
+  }
+
+  /// Given a position on a non-exhaustive `case` expression, return the code and its range to add
+  /// arms for the patterns it is missing.
+  pub fn fill_missing_arms(&self, pos: WithPath<Position>) -> Option<(Range, String)> {
+    let (file, _, ptr, _) = self.get_file_with_idx(pos)?;
+    let ptr = ptr.cast::<syntax::ast::CaseExp>()?;
+    let case = ptr.to_node(file.parsed.root.syntax());
+    let range = text_size_util::TextRange::empty(case.syntax().text_range().end());
+    let range = file.pos_db.range(range)?;
+    let case_ptr = SyntaxNodePtr::new(case.syntax());
+    let pats = file.statics_errors.iter().find_map(|err| {
+      let ptr = file.lowered.ptrs.hir_to_ast(err.idx())?;
+      if ptr == case_ptr {
+        err.missing_case_pats(&self.syms)
+      } else {
+        None
+      }
+    })?;
+    let case = CaseDisplay {
+      needs_starting_bar: true,
+      pats: &pats,
     };
     Some((range, case.to_string()))
   }
 
+  /// Given a position on an expression hole, return the range of the hole and the code to replace
+  /// it with for each way to fill it, best first.
+  pub fn fill_hole(&self, pos: WithPath<Position>) -> Option<(Range, Vec<String>)> {
+    let (file, _, ptr, idx) = self.get_file_with_idx(pos)?;
+    if is_recovered_hole(file, idx) {
+      return None;
+    }
+    let fills = file.info.get_hole_fills(&self.syms, idx);
+    if fills.is_empty() {
+      return None;
+    }
+    let range = ptr.to_node(file.parsed.root.syntax()).text_range();
+    let range = file.pos_db.range(range)?;
+    let fills: Vec<_> = fills
+      .iter()
+      .map(|fill| FillDisplay { fill }.to_string())
+      .collect();
+    Some((range, fills))
+  }
+
+  /// Returns the inlay hints for the types of things in this range, only of the kinds in `want`.
+  pub fn get_inlay_hints(
+    &self,
+    range: WithPath<Range>,
+    want: config::InlayHints,
+  ) -> Option<Vec<InlayHint>> {
+    let file = self.source_files.get(&range.path)?;
+    let root = file.parsed.root.syntax();
+    let range = text_size_util::TextRange::new(
+      file.pos_db.text_size(range.val.start)?,
+      file.pos_db.text_size(range.val.end)?,
+    );
+    let pats = file.lowered.arenas.pat.iter().filter_map(|(pat, _)| {
+      if !want.bindings {
+        return None;
+      }
+      let node = file.lowered.ptrs.hir_to_ast(pat.into())?.to_node(root);
+      let end = match node.kind() {
+        SyntaxKind::ConPat => node.text_range().end(),
+        // for `fun`, only the name of the function gets a hint, not the generated names.
+        SyntaxKind::FunBind => {
+          let fun_bind = syntax::ast::FunBind::cast(node)?;
+          let case = fun_bind.fun_bind_cases().next()?;
+          let name = match case.fun_bind_case_head()? {
+            syntax::ast::FunBindCaseHead::PrefixFunBindCaseHead(head) => head.name_star_eq()?,
+            syntax::ast::FunBindCaseHead::InfixFunBindCaseHead(_) => return None,
+          };
+          match &file.lowered.arenas.pat[pat] {
+            hir::Pat::Con(path, None) if path.last().as_str() == name.token.text() => {}
+            _ => return None,
+          }
+          name.token.text_range().end()
+        }
+        _ => return None,
+      };
+      Some((hir::Idx::from(pat), end))
+    });
+    let exps = file.lowered.arenas.exp.iter().filter_map(|(exp, _)| {
+      if !want.instances {
+        return None;
+      }
+      let node = file.lowered.ptrs.hir_to_ast(exp.into())?.to_node(root);
+      (node.kind() == SyntaxKind::PathExp).then(|| (hir::Idx::from(exp), node.text_range().end()))
+    });
+    let mut idxs: Vec<_> = pats
+      .chain(exps)
+      .filter(|&(_, end)| range.contains_inclusive(end))
+      .collect();
+    idxs.sort_by_key(|&(_, end)| end);
+    let ends: FxHashMap<_, _> = idxs.iter().copied().collect();
+    let hints = file
+      .info
+      .get_ty_hints(&self.syms, idxs.into_iter().map(|(idx, _)| idx))
+      .into_iter()
+      .filter_map(|(idx, ty)| {
+        let end = text_size_util::TextRange::empty(ends[&idx]);
+        let kind = match idx {
+          hir::Idx::Exp(_) => InlayHintKind::Instance,
+          _ => InlayHintKind::Binding,
+        };
+        Some(InlayHint {
+          position: file.pos_db.range(end)?.start,
+          label: format!(": {ty}"),
+          kind,
+        })
+      })
+      .collect();
+    Some(hints)
+  }
+
+  /// Returns the classified names in the file, optionally only those in the range.
+  pub fn get_semantic_tokens(
+    &self,
+    path: PathId,
+    range: Option<Range>,
+  ) -> Option<Vec<SemanticToken>> {
+    let file = self.source_files.get(&path)?;
+    let range = match range {
+      None => None,
+      Some(range) => Some(text_size_util::TextRange::new(
+        file.pos_db.text_size(range.start)?,
+        file.pos_db.text_size(range.end)?,
+      )),
+    };
+    Some(semantic_tokens::get(file, range))
+  }
+
+  /// Returns the folding ranges for the file.
+  pub fn get_folding_ranges(&self, path: PathId) -> Option<Vec<FoldingRange>> {
+    let file = self.source_files.get(&path)?;
+    Some(syntax_ranges::folding(
+      file.parsed.root.syntax(),
+      &file.pos_db,
+    ))
+  }
+
+  /// Like [`Self::get_folding_ranges`], but for one isolated file.
+  pub fn get_folding_ranges_one(&self, contents: &str) -> Vec<FoldingRange> {
+    let mut fix_env = mlb_statics::STD_BASIS_FIX_ENV.clone();
+    let (_, parsed, _) = mlb_statics::start_source_file(contents, &mut fix_env);
+    let pos_db = text_pos::PositionDb::new(contents);
+    syntax_ranges::folding(parsed.root.syntax(), &pos_db)
+  }
+
+  /// Returns the ranges of the syntax around this position, from innermost to outermost.
+  pub fn get_selection_ranges(&self, pos: WithPath<Position>) -> Option<Vec<Range>> {
+    let file = self.source_files.get(&pos.path)?;
+    let offset = file.pos_db.text_size(pos.val)?;
+    Some(syntax_ranges::selection(
+      file.parsed.root.syntax(),
+      &file.pos_db,
+      offset,
+    ))
+  }
+
+  /// Like [`Self::get_selection_ranges`], but for one isolated file.
+  pub fn get_selection_ranges_one(&self, contents: &str, pos: Position) -> Option<Vec<Range>> {
+    let mut fix_env = mlb_statics::STD_BASIS_FIX_ENV.clone();
+    let (_, parsed, _) = mlb_statics::start_source_file(contents, &mut fix_env);
+    let pos_db = text_pos::PositionDb::new(contents);
+    let offset = pos_db.text_size(pos)?;
+    Some(syntax_ranges::selection(
+      parsed.root.syntax(),
+      &pos_db,
+      offset,
+    ))
+  }
+
+  /// Returns signature help for the function application around this position.
+  pub fn get_signature_help(&self, pos: WithPath<Position>) -> Option<SignatureHelp> {
+    let file = self.source_files.get(&pos.path)?;
+    let offset = file.pos_db.text_size(pos.val)?;
+    let root = file.parsed.root.syntax();
+    if offset > root.text_range().end() {
+      return None;
+    }
+    // prefer the token to the left, since that's what was just typed, like `(` or `,`.
+    let tok = root.token_at_offset(offset).left_biased()?;
+    // the innermost application whose argument (not function) contains the position.
+    let app = tok
+      .parent()?
+      .ancestors()
+      .filter_map(syntax::ast::AppExp::cast)
+      .find(|app| {
+        app
+          .func()
+          .map_or(false, |func| func.syntax().text_range().end() <= offset)
+      })?;
+    // un-curry the application, collecting the arguments in order.
+    let mut args = vec![app.arg()];
+    let mut func = app.func()?;
+    while let syntax::ast::Exp::AppExp(inner) = func {
+      args.push(inner.arg());
+      func = inner.func()?;
+    }
+    args.reverse();
+    let func = match func {
+      syntax::ast::Exp::PathExp(x) => x,
+      _ => return None,
+    };
+    let idx = file
+      .lowered
+      .ptrs
+      .ast_to_hir(SyntaxNodePtr::new(func.syntax()))?;
+    let sig = file.info.get_fn_signature(&self.syms, idx)?;
+    let active_curried = args.len() - 1;
+    // if the active argument is a tuple, the active component is the number of commas before the
+    // position.
+    let active_component = match args.last().cloned().flatten() {
+      Some(syntax::ast::Exp::TupleExp(tup)) => tup
+        .exp_args()
+        .filter(|arg| {
+          arg
+            .syntax()
+            .children_with_tokens()
+            .any(|x| x.kind() == SyntaxKind::Comma && x.text_range().end() <= offset)
+        })
+        .count(),
+      _ => 0,
+    };
+    let mut label = format!("val {} : ", func.syntax().text());
+    let mut params = Vec::<std::ops::Range<usize>>::new();
+    let mut active_param = None::<usize>;
+    for (curried_idx, components) in sig.params.iter().enumerate() {
+      for (component_idx, component) in components.iter().enumerate() {
+        if component_idx != 0 {
+          label.push_str(" * ");
+        }
+        if curried_idx == active_curried
+          && component_idx == active_component.min(components.len() - 1)
+        {
+          active_param = Some(params.len());
+        }
+        let start = label.len();
+        label.push_str(component);
+        params.push(start..label.len());
+      }
+      label.push_str(" -> ");
+    }
+    label.push_str(&sig.res);
+    let doc = file
+      .info
+      .get_def(idx)
+      .and_then(|def| self.get_def_doc(def))
+      .map(ToOwned::to_owned);
+    Some(SignatureHelp {
+      label,
+      doc,
+      params,
+      active_param,
+    })
+  }
+
+  /// Returns the call hierarchy item for the binding named at this position, or for the function or
+  /// functor used at this position.
+  pub fn prepare_call_hierarchy(&self, pos: WithPath<Position>) -> Option<CallHierarchyItem> {
+    let file = self.source_files.get(&pos.path)?;
+    let tok = get_token(file, pos.val)?;
+    call_hierarchy::prepare(&self.source_files, pos.path, &tok)
+  }
+
+  /// Returns the calls to the item named at this position, grouped by the binding making them.
+  pub fn get_incoming_calls(&self, pos: WithPath<Position>) -> Option<Vec<IncomingCall>> {
+    let file = self.source_files.get(&pos.path)?;
+    let tok = get_token(file, pos.val)?;
+    call_hierarchy::incoming(&self.source_files, pos.path, &tok)
+  }
+
+  /// Returns the calls made by the item named at this position, grouped by the item called.
+  pub fn get_outgoing_calls(&self, pos: WithPath<Position>) -> Option<Vec<OutgoingCall>> {
+    let file = self.source_files.get(&pos.path)?;
+    let tok = get_token(file, pos.val)?;
+    call_hierarchy::outgoing(&self.source_files, pos.path, &tok)
+  }
+
+  /// Returns the structures, signatures, and functors declared at the top level of the files from
+  /// the last call to [`Self::get_many`], sorted by kind and then by name.
+  pub fn get_doc_items(&self) -> Vec<DocItem> {
+    doc_items::get(&self.source_files, &self.syms)
+  }
+
+  /// Returns, for each structure, signature, and functor declared at the top level of the files from
+  /// the last call to [`Self::get_many`], the others that it refers to.
+  pub fn get_mod_graph(&self) -> BTreeMap<ModName, BTreeSet<ModName>> {
+    mod_graph::get(&self.source_files)
+  }
+
+  /// Returns warnings for the top-level items of the files from the last call to
+  /// [`Self::get_many`] that are never used, and for the files that contribute nothing. `input`
+  /// should be what was passed to that call, and `unlisted` should be the source files not listed
+  /// in any group, as from [`input::get_unlisted_sources`].
+  pub fn get_dead_code(&self, input: &input::Input, unlisted: &[PathId]) -> PathMap<Vec<Error>> {
+    let mut ret = dead_code::get(&self.source_files, input);
+    ret.extend(dead_code::unlisted(unlisted));
+    ret
+  }
+
+  fn get_def_doc(&self, def: statics::Def) -> Option<&str> {
+    let info = match def.path {
+      statics::DefPath::Regular(path) => &self.source_files.get(&path)?.info,
+      statics::DefPath::StdBasis(name) => self.std_basis.get_info(name)?,
+    };
+    info.get_doc(def.idx)
+  }
+
   fn get_file_with_idx(
     &self,
     pos: WithPath<Position>,
@@ -197,6 +561,40 @@ impl Analysis {
   }
 }
 
+/// A hint to show inline in the source.
+#[derive(Debug)]
+pub struct InlayHint {
+  /// The position to show the hint at.
+  pub position: Position,
+  /// The text of the hint.
+  pub label: String,
+  /// The kind of hint.
+  pub kind: InlayHintKind,
+}
+
+/// A kind of inlay hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayHintKind {
+  /// The type of a variable bound by a pattern.
+  Binding,
+  /// The instantiated type of a polymorphic value where it is used.
+  Instance,
+}
+
+/// Help for the signature of a function being applied.
+#[derive(Debug)]
+pub struct SignatureHelp {
+  /// The signature, like `val f : int -> string`.
+  pub label: String,
+  /// Documentation for the function.
+  pub doc: Option<String>,
+  /// The ranges in the label of the parameters. Each component of a tuple parameter is its own
+  /// parameter.
+  pub params: Vec<std::ops::Range<usize>>,
+  /// The index into `params` of the parameter at the position.
+  pub active_param: Option<usize>,
+}
+
 fn get_token(file: &mlb_statics::SourceFile, pos: Position) -> Option<SyntaxToken> {
   let idx = file.pos_db.text_size(pos)?;
   if !file.parsed.root.syntax().text_range().contains(idx) {
@@ -234,10 +632,41 @@ fn priority(kind: SyntaxKind) -> u8 {
 /// The max number of errors per path.
 const MAX_ERRORS_PER_PATH: usize = 20;
 
+/// returns the errors from all the analyses of the file, without duplicates.
 fn source_file_errors(
   file: &mlb_statics::SourceFile,
   syms: &statics::Syms,
   lines: config::ErrorLines,
+) -> Vec<Error> {
+  let mut ret = one_source_file_errors(file, syms, lines);
+  for other in &file.others {
+    for err in one_source_file_errors(other, syms, lines) {
+      let key = error_key(&err);
+      if ret.iter().all(|e| error_key(e) != key) {
+        ret.push(err);
+      }
+    }
+  }
+  ret.truncate(MAX_ERRORS_PER_PATH);
+  ret
+}
+
+fn error_key(err: &Error) -> (u32, u32, u32, u32, u16, &str) {
+  let range = &err.range;
+  (
+    range.start.line,
+    range.start.character,
+    range.end.line,
+    range.end.character,
+    err.code,
+    err.message.as_str(),
+  )
+}
+
+fn one_source_file_errors(
+  file: &mlb_statics::SourceFile,
+  syms: &statics::Syms,
+  lines: config::ErrorLines,
 ) -> Vec<Error> {
   std::iter::empty()
     .chain(file.lex_errors.iter().filter_map(|err| {
@@ -245,6 +674,7 @@ fn source_file_errors(
         range: file.pos_db.range(err.range())?,
         message: err.display().to_string(),
         code: err.to_code(),
+        related: Vec::new(),
       })
     }))
     .chain(file.parsed.errors.iter().filter_map(|err| {
@@ -252,6 +682,7 @@ fn source_file_errors(
         range: file.pos_db.range(err.range())?,
         message: err.display().to_string(),
         code: err.to_code(),
+        related: Vec::new(),
       })
     }))
     .chain(file.lowered.errors.iter().filter_map(|err| {
@@ -259,30 +690,91 @@ fn source_file_errors(
         range: file.pos_db.range(err.range())?,
         message: err.display().to_string(),
         code: err.to_code(),
+        related: Vec::new(),
       })
     }))
     .chain(file.statics_errors.iter().filter_map(|err| {
-      let idx = err.idx();
-      let syntax = file
-        .lowered
-        .ptrs
-        .hir_to_ast(idx)
-        .expect("no pointer for idx");
+      if is_recovered_hole(file, err.idx()) {
+        return None;
+      }
+      let related = err
+        .related()
+        .and_then(|(idx, msg)| Some((statics_range(file, idx)?, msg.to_owned())));
       Some(Error {
-        range: file
-          .pos_db
-          .range(syntax.to_node(file.parsed.root.syntax()).text_range())?,
+        range: statics_range(file, err.idx())?,
         message: err.display(syms, file.info.meta_vars(), lines).to_string(),
         code: err.to_code(),
+        related: related.into_iter().collect(),
       })
     }))
     .take(MAX_ERRORS_PER_PATH)
     .collect()
 }
 
+fn statics_range(file: &mlb_statics::SourceFile, idx: hir::Idx) -> Option<Range> {
+  let syntax = file.lowered.ptrs.hir_to_ast(idx)?;
+  file
+    .pos_db
+    .range(syntax.to_node(file.parsed.root.syntax()).text_range())
+}
+
+/// Returns whether `idx` is a hole the parser made out of the tokens it skipped after a syntax
+/// error. That error is already reported, so errors for the hole itself would be redundant.
+fn is_recovered_hole(file: &mlb_statics::SourceFile, idx: hir::Idx) -> bool {
+  let node = match file.lowered.ptrs.hir_to_ast(idx) {
+    Some(ptr) => ptr.to_node(file.parsed.root.syntax()),
+    None => return false,
+  };
+  matches!(node.kind(), SyntaxKind::HoleExp | SyntaxKind::HoleDec)
+    && node
+      .first_token()
+      .map_or(true, |tok| tok.kind() != SyntaxKind::DotDotDot)
+}
+
+fn fills_md(fills: &[statics::HoleFill]) -> Option<String> {
+  if fills.is_empty() {
+    return None;
+  }
+  let mut ret = String::from("Values that fit this hole:\n");
+  for fill in fills {
+    ret.push_str(&format!("\n- `{} : {}`", fill.path, fill.ty));
+  }
+  Some(ret)
+}
+
+struct FillDisplay<'a> {
+  fill: &'a statics::HoleFill,
+}
+
+impl fmt::Display for FillDisplay<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let path = &self.fill.path;
+    let name = path.last();
+    // qualified names are never infix.
+    let needs_op = path.structures().is_empty()
+      && (!name.as_str().starts_with(|c: char| c.is_ascii_alphabetic())
+        || mlb_statics::STD_BASIS_FIX_ENV.contains_key(name));
+    let op = if needs_op { "op " } else { "" };
+    if self.fill.args.is_empty() {
+      return write!(f, "{op}{path}");
+    }
+    write!(f, "({op}{path}")?;
+    for &arg in &self.fill.args {
+      if arg == 1 {
+        f.write_str(" _")?;
+      } else {
+        f.write_str(" (")?;
+        sep_seq(f, ", ", std::iter::repeat("_").take(arg))?;
+        f.write_str(")")?;
+      }
+    }
+    f.write_str(")")
+  }
+}
+
 struct CaseDisplay<'a> {
   needs_starting_bar: bool,
-  variants: &'a [(hir::Name, bool)],
+  pats: &'a [String],
 }
 
 impl fmt::Display for CaseDisplay<'_> {
@@ -293,25 +785,17 @@ impl fmt::Display for CaseDisplay<'_> {
     } else {
       write!(f, "  ")?;
     }
-    let iter = self
-      .variants
-      .iter()
-      .map(|&(ref name, has_arg)| ArmDisplay { name, has_arg });
+    let iter = self.pats.iter().map(|pat| ArmDisplay { pat });
     sep_seq(f, "\n  | ", iter)
   }
 }
 
 struct ArmDisplay<'a> {
-  name: &'a hir::Name,
-  has_arg: bool,
+  pat: &'a str,
 }
 
 impl fmt::Display for ArmDisplay<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "{}", self.name)?;
-    if self.has_arg {
-      write!(f, " _")?;
-    }
-    write!(f, " => _")
+    write!(f, "{} => _", self.pat)
   }
 }
@@ -0,0 +1,115 @@
+//! Find which top-level structures, signatures, and functors refer to which others.
+
+use crate::doc_items::top_str_decs;
+use fast_hash::FxHashMap;
+use paths::{PathId, PathMap};
+use std::collections::{BTreeMap, BTreeSet};
+use text_size_util::TextRange;
+
+/// The kind, name, and path of a top-level structure, signature, or functor.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ModName {
+  /// What it is.
+  pub kind: statics::ModKind,
+  /// The name.
+  pub name: String,
+  /// The file it is declared in. Top-level modules in different files may have the same name.
+  pub path: PathId,
+}
+
+type Files = PathMap<mlb_statics::SourceFile>;
+
+/// A top-level declaration of some structures, signatures, or functors.
+struct TopDec {
+  range: TextRange,
+  names: Vec<ModName>,
+}
+
+pub(crate) fn get(files: &Files) -> BTreeMap<ModName, BTreeSet<ModName>> {
+  let top_decs: FxHashMap<PathId, Vec<TopDec>> = files
+    .iter()
+    .map(|(&path, file)| {
+      let decs = top_str_decs(file)
+        .into_iter()
+        .filter_map(|idx| {
+          let range = file.lowered.ptrs.hir_to_ast(idx)?.text_range();
+          let names = file
+            .info
+            .get_mod_names(idx)
+            .into_iter()
+            .map(|(kind, name)| ModName {
+              kind,
+              name: name.as_str().to_owned(),
+              path,
+            })
+            .collect();
+          Some(TopDec { range, names })
+        })
+        .collect();
+      (path, decs)
+    })
+    .collect();
+  let mut ret = BTreeMap::<ModName, BTreeSet<ModName>>::new();
+  for decs in top_decs.values() {
+    for name in decs.iter().flat_map(|dec| dec.names.iter()) {
+      ret.entry(name.clone()).or_default();
+    }
+  }
+  for (path, file) in files.iter() {
+    let decs = &top_decs[path];
+    for idx in indices(file) {
+      let from = match file.lowered.ptrs.hir_to_ast(idx) {
+        Some(ptr) => containing(decs, ptr.text_range()),
+        None => None,
+      };
+      let to = file
+        .info
+        .get_def(idx)
+        .and_then(|def| def_dec(files, &top_decs, def));
+      let (from, to) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => continue,
+      };
+      if std::ptr::eq(from, to) {
+        continue;
+      }
+      for name in from.names.iter() {
+        ret
+          .entry(name.clone())
+          .or_default()
+          .extend(to.names.iter().cloned());
+      }
+    }
+  }
+  ret
+}
+
+pub(crate) fn indices(file: &mlb_statics::SourceFile) -> impl Iterator<Item = hir::Idx> + '_ {
+  let arenas = &file.lowered.arenas;
+  std::iter::empty()
+    .chain(arenas.str_exp.iter().map(|(x, _)| hir::Idx::StrExp(x)))
+    .chain(arenas.sig_exp.iter().map(|(x, _)| hir::Idx::SigExp(x)))
+    .chain(arenas.spec.iter().map(|(x, _)| hir::Idx::Spec(x)))
+    .chain(arenas.exp.iter().map(|(x, _)| hir::Idx::Exp(x)))
+    .chain(arenas.dec.iter().map(|(x, _)| hir::Idx::Dec(x)))
+    .chain(arenas.pat.iter().map(|(x, _)| hir::Idx::Pat(x)))
+    .chain(arenas.ty.iter().map(|(x, _)| hir::Idx::Ty(x)))
+}
+
+/// returns the top-level declaration containing the definition.
+fn def_dec<'a>(
+  files: &Files,
+  top_decs: &'a FxHashMap<PathId, Vec<TopDec>>,
+  def: statics::Def,
+) -> Option<&'a TopDec> {
+  let path = match def.path {
+    statics::DefPath::Regular(path) => path,
+    statics::DefPath::StdBasis(_) => return None,
+  };
+  let ptr = files.get(&path)?.lowered.ptrs.hir_to_ast(def.idx)?;
+  containing(top_decs.get(&path)?, ptr.text_range())
+}
+
+fn containing(decs: &[TopDec], range: TextRange) -> Option<&TopDec> {
+  decs.iter().find(|dec| dec.range.contains_range(range))
+}
@@ -0,0 +1,176 @@
+//! Classify the names in a file, for semantic highlighting.
+
+use syntax::ast::{AstNode as _, SyntaxNodePtr};
+use syntax::{SyntaxKind as SK, SyntaxNode, SyntaxToken};
+use text_pos::Range;
+
+/// A classified name in a file.
+#[derive(Debug)]
+pub struct SemanticToken {
+  /// The range of the name.
+  pub range: Range,
+  /// What the name is.
+  pub kind: SemanticTokenKind,
+  /// Whether this is the site that defines the name.
+  pub definition: bool,
+  /// Whether the name is from the std basis.
+  pub std_basis: bool,
+}
+
+/// What a name is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticTokenKind {
+  /// A value variable.
+  Val,
+  /// A datatype constructor.
+  Con,
+  /// An exception constructor.
+  Exn,
+  /// A type constructor.
+  Ty,
+  /// A type variable.
+  TyVar,
+  /// A structure.
+  Structure,
+  /// A signature.
+  Signature,
+  /// A functor.
+  Functor,
+  /// A name used as an infix operator.
+  Infix,
+}
+
+pub(crate) fn get(
+  file: &mlb_statics::SourceFile,
+  range: Option<text_size_util::TextRange>,
+) -> Vec<SemanticToken> {
+  file
+    .parsed
+    .root
+    .syntax()
+    .descendants_with_tokens()
+    .filter_map(|x| x.into_token())
+    .filter(|tok| range.map_or(true, |r| r.contains_range(tok.text_range())))
+    .filter_map(|tok| {
+      let (kind, definition, std_basis) = classify(file, &tok)?;
+      Some(SemanticToken {
+        range: file.pos_db.range(tok.text_range())?,
+        kind,
+        definition,
+        std_basis,
+      })
+    })
+    .collect()
+}
+
+/// returns the kind, whether it is a definition site, and whether it is from the std basis.
+fn classify(
+  file: &mlb_statics::SourceFile,
+  tok: &SyntaxToken,
+) -> Option<(SemanticTokenKind, bool, bool)> {
+  let parent = tok.parent()?;
+  if tok.kind() == SK::TyVar {
+    let definition = parent.kind() == SK::TyVarArg;
+    return Some((SemanticTokenKind::TyVar, definition, false));
+  }
+  if !matches!(tok.kind(), SK::Name | SK::Star | SK::Eq) {
+    return None;
+  }
+  let ret = match parent.kind() {
+    SK::NameStarEqDot => return classify_path(file, &parent),
+    // the parser only makes these when the name is infix according to the fixity env.
+    SK::InfixExp | SK::InfixPat => {
+      let std_basis = infix_std_basis(file, &parent).unwrap_or(false);
+      return Some((SemanticTokenKind::Infix, false, std_basis));
+    }
+    SK::InfixFunBindCaseHead => (SemanticTokenKind::Infix, true),
+    SK::InfixDec | SK::InfixrDec | SK::NonfixDec => (SemanticTokenKind::Infix, false),
+    SK::PrefixFunBindCaseHead | SK::TypedNamePat | SK::AsPat | SK::LabPatRow | SK::ValDesc => {
+      (SemanticTokenKind::Val, true)
+    }
+    SK::ConBind => (SemanticTokenKind::Con, true),
+    SK::ExBind | SK::ExDesc => (SemanticTokenKind::Exn, true),
+    SK::TyBind | SK::DatBind | SK::TyDesc | SK::DatCopyDec | SK::DatCopySpec => {
+      (SemanticTokenKind::Ty, true)
+    }
+    SK::StrBind | SK::StrDesc | SK::FunctorArgNameSigExp => (SemanticTokenKind::Structure, true),
+    SK::SigBind => (SemanticTokenKind::Signature, true),
+    SK::NameSigExp => (SemanticTokenKind::Signature, false),
+    SK::FunctorBind => (SemanticTokenKind::Functor, true),
+    SK::FunSigBind => (SemanticTokenKind::Signature, true),
+    SK::FunctorArgFunctor => (SemanticTokenKind::Functor, true),
+    SK::FunSigName => (SemanticTokenKind::Signature, false),
+    SK::AppStrExp => (SemanticTokenKind::Functor, false),
+    _ => return None,
+  };
+  Some((ret.0, ret.1, false))
+}
+
+/// `seg` is a segment of a path.
+fn classify_path(
+  file: &mlb_statics::SourceFile,
+  seg: &SyntaxNode,
+) -> Option<(SemanticTokenKind, bool, bool)> {
+  let path = seg.parent()?;
+  let user = path.parent()?;
+  let idx = file.lowered.ptrs.ast_to_hir(SyntaxNodePtr::new(&user));
+  let std_basis = idx
+    .and_then(|idx| file.info.get_def(idx))
+    .map_or(false, is_std_basis);
+  // all but the last segment have a `.` after them, and are structures.
+  let is_last = !seg.children_with_tokens().any(|x| x.kind() == SK::Dot);
+  if !is_last {
+    return Some((SemanticTokenKind::Structure, false, std_basis));
+  }
+  let val_kind = || match idx.and_then(|idx| file.info.get_val_kind(idx)) {
+    None | Some(statics::ValKind::Val) => SemanticTokenKind::Val,
+    Some(statics::ValKind::Con) => SemanticTokenKind::Con,
+    Some(statics::ValKind::Exn) => SemanticTokenKind::Exn,
+  };
+  let ret = match user.kind() {
+    SK::PathExp => (val_kind(), false),
+    SK::ConPat => {
+      let kind = val_kind();
+      (kind, kind == SemanticTokenKind::Val)
+    }
+    SK::ConTy | SK::OneArgConTy | SK::WhereTypeSigExp | SK::DatCopyDec | SK::DatCopySpec => {
+      (SemanticTokenKind::Ty, false)
+    }
+    SK::EqPath => (SemanticTokenKind::Exn, false),
+    SK::PathEq => {
+      let sharing = user.parent()?;
+      let is_ty = sharing
+        .children_with_tokens()
+        .any(|x| x.kind() == SK::TypeKw);
+      let kind = if is_ty {
+        SemanticTokenKind::Ty
+      } else {
+        SemanticTokenKind::Structure
+      };
+      (kind, false)
+    }
+    SK::PathStrExp | SK::OpenDec | SK::WhereSigExp => (SemanticTokenKind::Structure, false),
+    _ => return None,
+  };
+  Some((ret.0, ret.1, std_basis))
+}
+
+/// returns whether the name of the `InfixExp` or `InfixPat` `node` is from the std basis.
+fn infix_std_basis(file: &mlb_statics::SourceFile, node: &SyntaxNode) -> Option<bool> {
+  let idx = file.lowered.ptrs.ast_to_hir(SyntaxNodePtr::new(node))?;
+  let idx = match idx {
+    // the function of an infix application is lowered with the pointer to the whole application,
+    // but the pointer maps back to the application.
+    hir::Idx::Exp(exp) => match &file.lowered.arenas.exp[exp] {
+      hir::Exp::App(func, _) => hir::Idx::from((*func)?),
+      _ => return None,
+    },
+    // an infix pattern is a single constructor pattern.
+    idx => idx,
+  };
+  Some(is_std_basis(file.info.get_def(idx)?))
+}
+
+fn is_std_basis(def: statics::Def) -> bool {
+  matches!(def.path, statics::DefPath::StdBasis(_))
+}
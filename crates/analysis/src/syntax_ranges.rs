@@ -0,0 +1,132 @@
+//! Ranges computed purely from the syntax tree, for folding and selecting.
+
+use syntax::ast::{AstNode as _, Matcher};
+use syntax::rowan::{TextRange, TextSize};
+use syntax::{SyntaxKind as SK, SyntaxNode, SyntaxToken};
+use text_pos::{PositionDb, Range};
+
+/// A range that may be folded.
+#[derive(Debug)]
+pub struct FoldingRange {
+  /// The range.
+  pub range: Range,
+  /// The kind of range.
+  pub kind: FoldingRangeKind,
+}
+
+/// A kind of folding range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldingRangeKind {
+  /// A region of code, like a `struct ... end`.
+  Region,
+  /// A block comment.
+  Comment,
+  /// Consecutive `open` declarations.
+  Imports,
+}
+
+pub(crate) fn folding(root: &SyntaxNode, pos_db: &PositionDb) -> Vec<FoldingRange> {
+  let mut ret = Vec::<(TextRange, FoldingRangeKind)>::new();
+  // the range of the current run of `open`s, and the last token of the last `open` in the run.
+  let mut open_run = None::<(TextRange, SyntaxToken)>;
+  for node in root.descendants() {
+    let is_region = match node.kind() {
+      SK::StructStrExp
+      | SK::SigSigExp
+      | SK::LetExp
+      | SK::LetStrExp
+      | SK::LocalDec
+      | SK::LocalStrDec => true,
+      SK::CaseExp | SK::FnExp | SK::HandleExp => node
+        .children()
+        .find_map(Matcher::cast)
+        .map_or(false, |matcher| matcher.match_rules().count() > 1),
+      SK::FunBind => {
+        node
+          .children()
+          .filter(|x| x.kind() == SK::FunBindCase)
+          .count()
+          > 1
+      }
+      SK::OpenDec => {
+        let range = node.text_range();
+        let last = match node.last_token() {
+          Some(x) => x,
+          None => continue,
+        };
+        // extend the current run if this `open` immediately follows it.
+        open_run = match open_run.take() {
+          Some((run, prev_last))
+            if next_non_trivia(&prev_last).map(|x| x.text_range().start())
+              == Some(range.start()) =>
+          {
+            Some((run.cover(range), last))
+          }
+          Some((run, _)) => {
+            ret.push((run, FoldingRangeKind::Imports));
+            Some((range, last))
+          }
+          None => Some((range, last)),
+        };
+        false
+      }
+      _ => false,
+    };
+    if is_region {
+      ret.push((node.text_range(), FoldingRangeKind::Region));
+    }
+  }
+  if let Some((run, _)) = open_run {
+    ret.push((run, FoldingRangeKind::Imports));
+  }
+  let comments = root
+    .descendants_with_tokens()
+    .filter_map(|x| x.into_token())
+    .filter(|x| x.kind() == SK::BlockComment)
+    .map(|x| (x.text_range(), FoldingRangeKind::Comment));
+  ret.extend(comments);
+  ret.sort_by_key(|&(range, _)| range.start());
+  ret
+    .into_iter()
+    .filter_map(|(range, kind)| {
+      let range = pos_db.range(range)?;
+      (range.start.line < range.end.line).then_some(FoldingRange { range, kind })
+    })
+    .collect()
+}
+
+/// the next token that is not trivia, skipping `;` since those may separate `open`s.
+fn next_non_trivia(tok: &SyntaxToken) -> Option<SyntaxToken> {
+  let mut tok = tok.next_token()?;
+  while matches!(
+    tok.kind(),
+    SK::Whitespace | SK::BlockComment | SK::Semicolon
+  ) {
+    tok = tok.next_token()?;
+  }
+  Some(tok)
+}
+
+/// returns the ranges of the syntax around the offset, from innermost to outermost.
+pub(crate) fn selection(root: &SyntaxNode, pos_db: &PositionDb, offset: TextSize) -> Vec<Range> {
+  let mut ranges = Vec::<TextRange>::new();
+  let tok = match root.token_at_offset(offset).right_biased() {
+    Some(x) => x,
+    None => return Vec::new(),
+  };
+  ranges.push(tok.text_range());
+  let nodes = tok
+    .parent()
+    .into_iter()
+    .flat_map(|node| node.ancestors())
+    .map(|node| node.text_range());
+  for range in nodes {
+    if ranges.last() != Some(&range) {
+      ranges.push(range);
+    }
+  }
+  ranges
+    .into_iter()
+    .filter_map(|range| pos_db.range(range))
+    .collect()
+}
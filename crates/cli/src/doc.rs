@@ -0,0 +1,279 @@
+//! Write HTML documentation for the structures, signatures, and functors of a project.
+
+use analysis::{DocItem, ModKind};
+use fast_hash::{FxHashMap, FxHashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+const KINDS: [(ModKind, &str); 4] = [
+  (ModKind::Structure, "Structures"),
+  (ModKind::Signature, "Signatures"),
+  (ModKind::Functor, "Functors"),
+  (ModKind::FunSig, "Functor signatures"),
+];
+
+/// Writes one page per item, and an index page, to `out`.
+pub(crate) fn write(
+  root: &analysis::input::Root,
+  items: &[DocItem],
+  out: &Path,
+) -> std::io::Result<()> {
+  std::fs::create_dir_all(out)?;
+  for (file, page) in get(root, items) {
+    std::fs::write(out.join(file), page)?;
+  }
+  Ok(())
+}
+
+/// Returns the file names and contents of the pages for the items, with the index page first.
+pub(crate) fn get(root: &analysis::input::Root, items: &[DocItem]) -> Vec<(String, String)> {
+  let pages = Pages::new(root, items);
+  let mut ret = Vec::with_capacity(items.len() + 1);
+  let mut index = String::new();
+  start_page(&mut index, "Index");
+  for (kind, heading) in KINDS {
+    let mut kind_items = items
+      .iter()
+      .enumerate()
+      .filter(|(_, item)| item.kind == kind)
+      .peekable();
+    if kind_items.peek().is_none() {
+      continue;
+    }
+    writeln!(index, "<h2>{heading}</h2>").unwrap();
+    writeln!(index, "<ul>").unwrap();
+    for (idx, item) in kind_items {
+      let file = &pages.file_names[idx];
+      write!(
+        index,
+        "<li><a href=\"{file}\"><code>{}</code></a>",
+        item.name
+      )
+      .unwrap();
+      if pages.is_ambiguous(item) {
+        write!(index, " (").unwrap();
+        escape(&mut index, &pages.rel_path(item));
+        write!(index, ")").unwrap();
+      }
+      writeln!(index, "</li>").unwrap();
+    }
+    writeln!(index, "</ul>").unwrap();
+  }
+  end_page(&mut index);
+  ret.push(("index.html".to_owned(), index));
+  for (idx, item) in items.iter().enumerate() {
+    let mut page = String::new();
+    let title = format!("{} {}", item.kind.keyword(), item.name);
+    start_page(&mut page, &title);
+    writeln!(page, "<p><a href=\"index.html\">Index</a></p>").unwrap();
+    writeln!(page, "<h1><code>{title}</code></h1>").unwrap();
+    write!(page, "<p>Defined in <code>").unwrap();
+    escape(&mut page, &pages.rel_path(item));
+    writeln!(page, "</code>.</p>").unwrap();
+    if let Some(doc) = &item.doc {
+      pulldown_cmark::html::push_html(&mut page, pulldown_cmark::Parser::new(doc));
+    }
+    write!(page, "<pre><code>").unwrap();
+    linked_code(&mut page, &pages, item, &item.sig);
+    writeln!(page, "</code></pre>").unwrap();
+    end_page(&mut page);
+    ret.push((pages.file_names[idx].clone(), page));
+  }
+  ret
+}
+
+/// The pages for the items, and how to link to them.
+struct Pages<'a> {
+  root: &'a analysis::input::Root,
+  items: &'a [DocItem],
+  /// The file name of the page for each item, in the same order as the items.
+  file_names: Vec<String>,
+  /// The indices of the items with each kind and name.
+  by_name: FxHashMap<(ModKind, &'a str), Vec<usize>>,
+  /// The names of the types declared at the top level of the signature of each item, in the same
+  /// order as the items.
+  ty_names: Vec<FxHashSet<&'a str>>,
+}
+
+impl<'a> Pages<'a> {
+  fn new(root: &'a analysis::input::Root, items: &'a [DocItem]) -> Self {
+    let mut by_name = FxHashMap::<(ModKind, &str), Vec<usize>>::default();
+    for (idx, item) in items.iter().enumerate() {
+      by_name
+        .entry((item.kind, item.name.as_str()))
+        .or_default()
+        .push(idx);
+    }
+    let mut ret = Self {
+      root,
+      items,
+      file_names: Vec::new(),
+      by_name,
+      ty_names: items
+        .iter()
+        .map(|item| item.sig.lines().filter_map(top_level_ty_name).collect())
+        .collect(),
+    };
+    let file_names = items
+      .iter()
+      .map(|item| {
+        let kw = item.kind.keyword();
+        if ret.is_ambiguous(item) {
+          // disambiguate with the path, since there are other items with this kind and name.
+          let path: String = ret
+            .rel_path(item)
+            .chars()
+            .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+            .collect();
+          format!("{kw}.{}.{path}.html", item.name)
+        } else {
+          format!("{kw}.{}.html", item.name)
+        }
+      })
+      .collect();
+    ret.file_names = file_names;
+    ret
+  }
+
+  fn rel_path(&self, item: &DocItem) -> String {
+    self
+      .root
+      .as_paths()
+      .get_rel_path(item.path)
+      .display()
+      .to_string()
+  }
+
+  /// Returns whether other items have the same kind and name as `item`.
+  fn is_ambiguous(&self, item: &DocItem) -> bool {
+    self
+      .by_name
+      .get(&(item.kind, item.name.as_str()))
+      .map_or(false, |idxs| idxs.len() > 1)
+  }
+
+  /// Returns the index of the item with the `kind` and `name`, as referred to from `from`. If there
+  /// are many such items, only one from the same file as `from` is chosen.
+  fn resolve(&self, from: &DocItem, kind: ModKind, name: &str) -> Option<usize> {
+    match self.by_name.get(&(kind, name))?.as_slice() {
+      [idx] => Some(*idx),
+      idxs => idxs
+        .iter()
+        .copied()
+        .find(|&idx| self.items[idx].path == from.path),
+    }
+  }
+}
+
+fn start_page(s: &mut String, title: &str) {
+  writeln!(s, "<!DOCTYPE html>").unwrap();
+  writeln!(s, "<html>").unwrap();
+  writeln!(s, "<head>").unwrap();
+  writeln!(s, "<meta charset=\"utf-8\">").unwrap();
+  writeln!(s, "<title>{title}</title>").unwrap();
+  writeln!(
+    s,
+    "<style>body {{ max-width: 50em; margin: auto; font-family: sans-serif; }}</style>"
+  )
+  .unwrap();
+  writeln!(s, "</head>").unwrap();
+  writeln!(s, "<body>").unwrap();
+}
+
+fn end_page(s: &mut String) {
+  writeln!(s, "</body>").unwrap();
+  writeln!(s, "</html>").unwrap();
+}
+
+/// Writes the SML `code` of the page for `from` as HTML, linking names of documented items to their
+/// pages, and marking the types declared at the top level so they may be linked to.
+///
+/// A qualified name like `Foo.t` links to the entry for the type `t` on the page for the structure
+/// `Foo`, or to the page itself if `t` is not a type declared there. Unqualified names only link to
+/// signatures, functors, and functor signatures, since an unqualified structure name may be a
+/// substructure.
+fn linked_code(s: &mut String, pages: &Pages<'_>, from: &DocItem, code: &str) {
+  for line in code.split_inclusive('\n') {
+    if let Some(name) = top_level_ty_name(line) {
+      write!(s, "<span id=\"{}\"></span>", ty_anchor(name)).unwrap();
+    }
+    linked_line(s, pages, from, line);
+  }
+}
+
+fn linked_line(s: &mut String, pages: &Pages<'_>, from: &DocItem, line: &str) {
+  let mut rest = line;
+  while !rest.is_empty() {
+    let len = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+    if len == 0 {
+      let c_len = rest.chars().next().map_or(1, char::len_utf8);
+      escape(s, &rest[..c_len]);
+      rest = &rest[c_len..];
+      continue;
+    }
+    let (name, new_rest) = rest.split_at(len);
+    rest = new_rest;
+    let href = match name.split_once('.') {
+      Some((structure, path)) => pages
+        .resolve(from, ModKind::Structure, structure)
+        .map(|idx| {
+          let file = &pages.file_names[idx];
+          if pages.ty_names[idx].contains(path) {
+            format!("{file}#{}", ty_anchor(path))
+          } else {
+            file.clone()
+          }
+        }),
+      None => [ModKind::Signature, ModKind::Functor, ModKind::FunSig]
+        .into_iter()
+        .find_map(|kind| pages.resolve(from, kind, name))
+        .map(|idx| pages.file_names[idx].clone()),
+    };
+    match href {
+      Some(href) => {
+        write!(s, "<a href=\"{href}\">").unwrap();
+        escape(s, name);
+        write!(s, "</a>").unwrap();
+      }
+      None => escape(s, name),
+    }
+  }
+}
+
+/// Returns the name of the type declared by the `line` of a signature, if it declares one at the
+/// top level of the signature.
+fn top_level_ty_name(line: &str) -> Option<&str> {
+  let rest = line.strip_prefix("  ")?;
+  let rest = rest
+    .strip_prefix("type ")
+    .or_else(|| rest.strip_prefix("datatype "))?;
+  // skip the type variables, if any.
+  let rest = if rest.starts_with('(') {
+    rest.split_once(") ")?.1
+  } else if rest.starts_with('\'') {
+    rest.split_once(' ')?.1
+  } else {
+    rest
+  };
+  rest.split_whitespace().next()
+}
+
+fn ty_anchor(name: &str) -> String {
+  format!("type.{name}")
+}
+
+fn is_name_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || matches!(c, '_' | '\'' | '.')
+}
+
+fn escape(s: &mut String, text: &str) {
+  for c in text.chars() {
+    match c {
+      '<' => s.push_str("&lt;"),
+      '>' => s.push_str("&gt;"),
+      '&' => s.push_str("&amp;"),
+      '"' => s.push_str("&quot;"),
+      c => s.push(c),
+    }
+  }
+}
@@ -0,0 +1,142 @@
+//! Write the dependency graphs of a project.
+
+use analysis::{ModKind, ModName};
+use fast_hash::FxHashMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+/// A format to write a graph in.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Format {
+  Dot,
+  Json,
+}
+
+impl std::str::FromStr for Format {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "dot" => Ok(Format::Dot),
+      "json" => Ok(Format::Json),
+      _ => Err(format!("unknown graph format: {s}")),
+    }
+  }
+}
+
+type Graph = BTreeMap<String, BTreeSet<String>>;
+
+/// Returns the graph of which groups include which groups and files, and, if given, the graph of
+/// which structures, signatures, and functors refer to which others.
+pub(crate) fn get(
+  root: &analysis::input::Root,
+  input: &analysis::input::Input,
+  mod_graph: Option<&BTreeMap<ModName, BTreeSet<ModName>>>,
+  format: Format,
+) -> String {
+  let paths = root.as_paths();
+  let path_name = |path| paths.get_rel_path(path).display().to_string();
+  let files: Graph = input
+    .iter_groups()
+    .map(|group| {
+      (
+        path_name(group.path),
+        group.val.into_iter().map(path_name).collect(),
+      )
+    })
+    .collect();
+  let mods: Option<Graph> = mod_graph.map(|graph| {
+    // every module is a key, so this counts each once.
+    let mut counts = FxHashMap::<(ModKind, &str), usize>::default();
+    for x in graph.keys() {
+      *counts.entry((x.kind, x.name.as_str())).or_default() += 1;
+    }
+    // only include the path for modules with the same kind and name as another.
+    let mod_name = |x: &ModName| {
+      let mut ret = format!("{} {}", x.kind.keyword(), x.name);
+      if counts
+        .get(&(x.kind, x.name.as_str()))
+        .map_or(false, |&n| n > 1)
+      {
+        write!(ret, " ({})", path_name(x.path)).unwrap();
+      }
+      ret
+    };
+    graph
+      .iter()
+      .map(|(from, to)| (mod_name(from), to.iter().map(mod_name).collect()))
+      .collect()
+  });
+  let mut ret = String::new();
+  match format {
+    Format::Dot => {
+      writeln!(ret, "digraph {{").unwrap();
+      // groups are boxes, to distinguish them from source files.
+      for group in files.keys() {
+        ret.push_str("  ");
+        quote(&mut ret, group);
+        ret.push_str(" [shape=box];\n");
+      }
+      for graph in std::iter::once(&files).chain(mods.as_ref()) {
+        for (from, to) in graph {
+          for to in to {
+            ret.push_str("  ");
+            quote(&mut ret, from);
+            ret.push_str(" -> ");
+            quote(&mut ret, to);
+            ret.push_str(";\n");
+          }
+        }
+      }
+      writeln!(ret, "}}").unwrap();
+    }
+    Format::Json => {
+      writeln!(ret, "{{").unwrap();
+      ret.push_str("  \"files\": ");
+      json_graph(&mut ret, &files);
+      if let Some(mods) = &mods {
+        ret.push_str(",\n  \"structures\": ");
+        json_graph(&mut ret, mods);
+      }
+      writeln!(ret, "\n}}").unwrap();
+    }
+  }
+  ret
+}
+
+fn json_graph(s: &mut String, graph: &Graph) {
+  s.push('{');
+  for (idx, (from, to)) in graph.iter().enumerate() {
+    if idx != 0 {
+      s.push(',');
+    }
+    s.push_str("\n    ");
+    quote(s, from);
+    s.push_str(": [");
+    for (idx, to) in to.iter().enumerate() {
+      if idx != 0 {
+        s.push_str(", ");
+      }
+      quote(s, to);
+    }
+    s.push(']');
+  }
+  if !graph.is_empty() {
+    s.push_str("\n  ");
+  }
+  s.push('}');
+}
+
+/// writes the string as a double-quoted string, which is valid for both DOT and JSON.
+fn quote(s: &mut String, x: &str) {
+  s.push('"');
+  for c in x.chars() {
+    match c {
+      '"' => s.push_str("\\\""),
+      '\\' => s.push_str("\\\\"),
+      '\n' => s.push_str("\\n"),
+      c => s.push(c),
+    }
+  }
+  s.push('"');
+}
@@ -1,3 +1,12 @@
+//! A CLI for Millet.
+
+mod doc;
+mod graph;
+mod watch;
+
+#[cfg(test)]
+mod tests;
+
 fn usage() {
   let current_exe_name = std::env::current_exe()
     .ok()
@@ -5,29 +14,90 @@ fn usage() {
     .unwrap_or_else(|| "<unknown>".to_owned());
   println!("usage:");
   println!("  {current_exe_name} [options] <path>");
+  println!("  {current_exe_name} doc [options] <path>");
+  println!("  {current_exe_name} graph [options] <path>");
   let rest_of_usage = r#"
+commands:
+  doc
+    also write HTML documentation for the structures, signatures, and functors of the project
+  graph
+    instead of analyzing, print the graph of which groups include which groups and source files
+
 options:
   -h, --help
     show this help
+  --out <dir>
+    for `doc`, the directory to write the documentation to. defaults to `millet-doc`
+  --format <format>
+    for `graph`, the format to print the graph in: `dot` or `json`. defaults to `dot`
+  --structures
+    for `graph`, also print the graph of which top-level structures, signatures, and functors
+    refer to which others
+  --dead-code
+    also warn about top-level items that are never used, and source files that contribute nothing
+  --watch
+    when checking, keep running, and whenever a source, group, or config file in the project
+    changes, check again and print only the errors that changed since the last check
 
 arguments:
   <path>
     path of the project to analyze. the path should either be:
     - a directory containing either:
       - a single .cm or .mlb file
-      - a millet.toml config file specifying a single .cm or .mlb file
+      - a millet.toml config file specifying the root .cm or .mlb files. each may contain `*`
+        wildcards, and every file matched is a root, checked separately
     - a .cm or .mlb file
 "#;
   print!("{rest_of_usage}");
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+  Check,
+  Doc,
+  Graph,
+}
+
+struct Args {
+  command: Command,
+  path: String,
+  out: Option<String>,
+  format: graph::Format,
+  structures: bool,
+  dead_code: bool,
+  watch: bool,
+}
+
+fn get_args(mut args: pico_args::Arguments) -> Result<Args, pico_args::Error> {
+  let out = args.opt_value_from_str("--out")?;
+  let format = args.opt_value_from_str("--format")?;
+  let structures = args.contains("--structures");
+  let dead_code = args.contains("--dead-code");
+  let watch = args.contains("--watch");
+  let first: String = args.free_from_str()?;
+  let (command, path) = match first.as_str() {
+    "doc" => (Command::Doc, args.free_from_str()?),
+    "graph" => (Command::Graph, args.free_from_str()?),
+    _ => (Command::Check, first),
+  };
+  Ok(Args {
+    command,
+    path,
+    out,
+    format: format.unwrap_or(graph::Format::Dot),
+    structures,
+    dead_code,
+    watch,
+  })
+}
+
 fn run() -> usize {
   let mut args = pico_args::Arguments::from_env();
   if args.contains(["-h", "--help"]) {
     usage();
     return 0;
   }
-  let path: String = match args.free_from_str() {
+  let args = match get_args(args) {
     Ok(x) => x,
     Err(e) => {
       println!("error[1997]: {e}");
@@ -35,42 +105,131 @@ fn run() -> usize {
     }
   };
   let fs = paths::RealFileSystem::default();
-  let mut root = match analysis::input::get_root(&fs, std::path::Path::new(path.as_str())) {
+  let path = std::path::Path::new(args.path.as_str());
+  if args.watch {
+    if args.command != Command::Check {
+      println!("error[1997]: --watch may only be used when checking");
+      return 1;
+    }
+    watch::run(&fs, path, args.dead_code);
+  }
+  let mut root = match analysis::input::get_root(&fs, path) {
     Ok(x) => x,
     Err(e) => {
-      handle_get_input_error(e);
+      println!("{}", input_error(&e));
       return 1;
     }
   };
   let inp = match analysis::input::get(&fs, &mut root) {
     Ok(x) => x,
     Err(e) => {
-      handle_get_input_error(e);
+      println!("{}", input_error(&e));
       return 1;
     }
   };
-  let mut an = analysis::Analysis::new(analysis::StdBasis::full(), config::ErrorLines::One);
-  let got = an.get_many(&inp);
-  let num_errors: usize = got.iter().map(|(_, errors)| errors.len()).sum();
-  for (path, errors) in got {
-    for e in errors {
-      let path = root.as_paths().get_rel_path(path).display();
-      println!(
-        "{}:{}: error[{}]: {}",
-        path, e.range.start, e.code, e.message
-      );
+  let mut an = analysis::Analysis::new(analysis::StdBasis::full_cached(), config::ErrorLines::One);
+  an.set_file_cache(analysis::FileCache::for_root(root.as_paths().as_path()));
+  if args.command == Command::Graph {
+    let mod_graph = args.structures.then(|| {
+      an.get_many(&inp);
+      an.get_mod_graph()
+    });
+    print!(
+      "{}",
+      graph::get(&root, &inp, mod_graph.as_ref(), args.format)
+    );
+    return 0;
+  }
+  let (diagnostics, num_errors) = get_diagnostics(&fs, &mut root, &inp, &mut an, args.dead_code);
+  for d in diagnostics {
+    println!("{d}");
+  }
+  if args.command == Command::Doc {
+    let out = args.out.as_deref().unwrap_or("millet-doc");
+    if let Err(e) = doc::write(&root, &an.get_doc_items(), std::path::Path::new(out)) {
+      println!("{out}: error: couldn't write documentation: {e}");
+      return num_errors + 1;
     }
   }
   num_errors
 }
 
-fn handle_get_input_error(e: analysis::input::GetInputError) {
-  print!("{}", e.path().display());
-  if let Some(r) = e.range() {
-    print!(":{}", r.start);
+/// An error or warning to print.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Diagnostic {
+  path: String,
+  /// like `1:2`, if there is one.
+  pos: Option<String>,
+  severity: Severity,
+  code: u16,
+  message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+  Error,
+  Warning,
+}
+
+impl std::fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.path)?;
+    if let Some(pos) = &self.pos {
+      write!(f, ":{pos}")?;
+    }
+    let severity = match self.severity {
+      Severity::Error => "error",
+      Severity::Warning => "warning",
+    };
+    write!(f, ": {severity}[{}]: {}", self.code, self.message)
+  }
+}
+
+/// Analyzes the input, returning the errors, and the warnings if `dead_code`, and the number of
+/// errors.
+fn get_diagnostics(
+  fs: &paths::RealFileSystem,
+  root: &mut analysis::input::Root,
+  inp: &analysis::input::Input,
+  an: &mut analysis::Analysis,
+  dead_code: bool,
+) -> (Vec<Diagnostic>, usize) {
+  let mut ret = Vec::<Diagnostic>::new();
+  let mut push = |root: &analysis::input::Root, path, e: analysis::Error, severity| {
+    ret.push(Diagnostic {
+      path: root.as_paths().get_rel_path(path).display().to_string(),
+      pos: Some(e.range.start.to_string()),
+      severity,
+      code: e.code,
+      message: e.message,
+    });
+  };
+  for (path, errors) in an.get_many(inp) {
+    for e in errors {
+      push(root, path, e, Severity::Error);
+    }
+  }
+  if dead_code {
+    let unlisted = analysis::input::get_unlisted_sources(fs, root, inp);
+    // these are warnings, so they don't count towards the number of errors.
+    for (path, warnings) in an.get_dead_code(inp, &unlisted) {
+      for w in warnings {
+        push(root, path, w, Severity::Warning);
+      }
+    }
+  }
+  let num_errors = ret.iter().filter(|d| d.severity == Severity::Error).count();
+  (ret, num_errors)
+}
+
+fn input_error(e: &analysis::input::GetInputError) -> Diagnostic {
+  Diagnostic {
+    path: e.path().display().to_string(),
+    pos: e.range().map(|r| r.start.to_string()),
+    severity: Severity::Error,
+    code: e.to_code(),
+    message: e.to_string(),
   }
-  let code = e.to_code();
-  println!(": error[{code}]: {e}");
 }
 
 fn main() {
@@ -0,0 +1,207 @@
+use paths::FileSystem as _;
+
+/// Analyzes `files`, which are the names and contents of the files in the root dir of an in-memory
+/// file system, with the minimal std basis.
+fn analyze(
+  files: &[(&str, &str)],
+) -> (
+  analysis::input::Root,
+  analysis::input::Input,
+  analysis::Analysis,
+) {
+  let root_dir = paths::RealFileSystem::default()
+    .canonicalize(std::path::Path::new("/"))
+    .unwrap();
+  let fs = paths::MemoryFileSystem::new(
+    files
+      .iter()
+      .map(|&(name, contents)| (root_dir.as_path().join(name), contents.to_owned()))
+      .collect(),
+  );
+  let mut root = analysis::input::get_root_dir(root_dir);
+  let input = analysis::input::get(&fs, &mut root).expect("couldn't get input");
+  let mut an = analysis::Analysis::new(analysis::StdBasis::minimal(), config::ErrorLines::One);
+  an.get_many(&input);
+  (root, input, an)
+}
+
+/// Returns the file names and contents of the doc pages for `files`.
+fn doc_pages(files: &[(&str, &str)]) -> Vec<(String, String)> {
+  let (root, _, an) = analyze(files);
+  crate::doc::get(&root, &an.get_doc_items())
+}
+
+fn get_page<'a>(pages: &'a [(String, String)], name: &str) -> &'a str {
+  pages
+    .iter()
+    .find(|(x, _)| x == name)
+    .map(|(_, page)| page.as_str())
+    .expect("no such page")
+}
+
+#[test]
+fn doc_same_name() {
+  let pages = doc_pages(&[
+    ("sources.mlb", "a.sml\nb.sml"),
+    ("a.sml", "structure Foo = struct end"),
+    ("b.sml", "structure Foo = struct val x = 1 end"),
+  ]);
+  let names: Vec<_> = pages.iter().map(|(x, _)| x.as_str()).collect();
+  assert_eq!(
+    vec![
+      "index.html",
+      "structure.Foo.a.sml.html",
+      "structure.Foo.b.sml.html"
+    ],
+    names
+  );
+  let index = get_page(&pages, "index.html");
+  assert!(index.contains("<a href=\"structure.Foo.a.sml.html\"><code>Foo</code></a> (a.sml)"));
+  assert!(index.contains("<a href=\"structure.Foo.b.sml.html\"><code>Foo</code></a> (b.sml)"));
+}
+
+#[test]
+fn doc_link_ty() {
+  let pages = doc_pages(&[
+    ("sources.mlb", "a.sml"),
+    (
+      "a.sml",
+      r#"
+structure Foo = struct datatype t = A end
+structure Baz = struct datatype t = B end
+structure Bar = struct val x = (Foo.A, Baz.B) end
+"#,
+    ),
+  ]);
+  let foo = get_page(&pages, "structure.Foo.html");
+  assert!(foo.contains("<span id=\"type.t\"></span>  datatype t = A"));
+  let bar = get_page(&pages, "structure.Bar.html");
+  let want = concat!(
+    "val x : <a href=\"structure.Foo.html#type.t\">Foo.t</a>",
+    " * <a href=\"structure.Baz.html#type.t\">Baz.t</a>"
+  );
+  assert!(bar.contains(want), "{bar}");
+}
+
+fn error(path: &str, pos: &str, message: &str) -> crate::Diagnostic {
+  crate::Diagnostic {
+    path: path.to_owned(),
+    pos: Some(pos.to_owned()),
+    severity: crate::Severity::Error,
+    code: 5001,
+    message: message.to_owned(),
+  }
+}
+
+/// Returns the lines printed for the diagnostics that are new and fixed.
+fn watch_diff(prev: &[crate::Diagnostic], cur: &[crate::Diagnostic]) -> (Vec<String>, Vec<String>) {
+  let (new, fixed) = crate::watch::diff(prev, cur);
+  let new = new.into_iter().map(ToString::to_string).collect();
+  let fixed = fixed.into_iter().map(ToString::to_string).collect();
+  (new, fixed)
+}
+
+#[test]
+fn watch_diff_moved() {
+  let prev = [error("a.sml", "1:9", "undefined value: x")];
+  let cur = [error("a.sml", "3:9", "undefined value: x")];
+  let (new, fixed) = watch_diff(&prev, &cur);
+  assert!(new.is_empty());
+  assert!(fixed.is_empty());
+}
+
+#[test]
+fn watch_diff_new_and_fixed() {
+  let prev = [
+    error("a.sml", "1:9", "undefined value: x"),
+    error("b.sml", "1:9", "undefined value: y"),
+  ];
+  let cur = [
+    error("a.sml", "1:9", "undefined value: x"),
+    error("b.sml", "2:9", "undefined value: z"),
+  ];
+  let (new, fixed) = watch_diff(&prev, &cur);
+  assert_eq!(new, ["b.sml:2:9: error[5001]: undefined value: z"]);
+  assert_eq!(fixed, ["b.sml:1:9: error[5001]: undefined value: y"]);
+}
+
+#[test]
+fn watch_diff_duplicates() {
+  let prev = [
+    error("a.sml", "1:9", "undefined value: x"),
+    error("a.sml", "2:9", "undefined value: x"),
+  ];
+  let cur = [error("a.sml", "2:9", "undefined value: x")];
+  let (new, fixed) = watch_diff(&prev, &cur);
+  assert!(new.is_empty());
+  assert_eq!(fixed.len(), 1);
+  let (new, fixed) = watch_diff(&cur, &prev);
+  assert_eq!(new.len(), 1);
+  assert!(fixed.is_empty());
+}
+
+/// Returns the graph for `files`, with the structures graph if `structures`.
+fn graph(files: &[(&str, &str)], structures: bool, format: crate::graph::Format) -> String {
+  let (root, input, an) = analyze(files);
+  let mod_graph = structures.then(|| an.get_mod_graph());
+  crate::graph::get(&root, &input, mod_graph.as_ref(), format)
+}
+
+const GRAPH_FILES: [(&str, &str); 3] = [
+  ("sources.mlb", "a.sml b.sml"),
+  ("a.sml", "structure A = struct val x = 1 end"),
+  ("b.sml", "structure B = struct val y = A.x end"),
+];
+
+#[test]
+fn graph_dot() {
+  let want = r#"digraph {
+  "sources.mlb" [shape=box];
+  "sources.mlb" -> "a.sml";
+  "sources.mlb" -> "b.sml";
+  "structure B" -> "structure A";
+}
+"#;
+  assert_eq!(want, graph(&GRAPH_FILES, true, crate::graph::Format::Dot));
+}
+
+#[test]
+fn graph_json() {
+  let want = r#"{
+  "files": {
+    "sources.mlb": ["a.sml", "b.sml"]
+  },
+  "structures": {
+    "structure A": [],
+    "structure B": ["structure A"]
+  }
+}
+"#;
+  assert_eq!(want, graph(&GRAPH_FILES, true, crate::graph::Format::Json));
+}
+
+#[test]
+fn graph_json_files_only() {
+  let want = r#"{
+  "files": {
+    "sources.mlb": ["a.sml", "b.sml"]
+  }
+}
+"#;
+  assert_eq!(want, graph(&GRAPH_FILES, false, crate::graph::Format::Json));
+}
+
+#[test]
+fn graph_same_name() {
+  let files = [
+    ("sources.mlb", "a.sml b.sml"),
+    ("a.sml", "structure A = struct val x = 1 end"),
+    (
+      "b.sml",
+      "structure A = struct val y = 2 end\nstructure B = struct val z = A.y end",
+    ),
+  ];
+  let got = graph(&files, true, crate::graph::Format::Dot);
+  assert!(got.contains(r#""structure B" -> "structure A (b.sml)";"#));
+  assert!(!got.contains(r#""structure B (b.sml)""#));
+}
@@ -0,0 +1,159 @@
+//! Check a project again whenever its files change.
+
+use crate::Diagnostic;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long to wait between looking for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The modification time and length of each watched path, or `None` if it doesn't exist.
+type Stamps = BTreeMap<PathBuf, Option<(Option<SystemTime>, u64)>>;
+
+/// Checks the project at `path`, then checks it again whenever a watched file in the project
+/// changes, printing only the diagnostics that were added or fixed since the last check.
+///
+/// There is no portable way to be notified of changes without pulling in a dependency, so this
+/// polls the modification times of the files. The watched files are the config file and the group
+/// and source files from the last check, and the directories they are in, so adding a file is
+/// noticed too.
+pub(crate) fn run(fs: &paths::RealFileSystem, path: &Path, dead_code: bool) -> ! {
+  let dir = match path.parent() {
+    _ if path.is_dir() => path,
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+  // reused across checks, so the std basis is only loaded once.
+  let mut an = analysis::Analysis::new(analysis::StdBasis::full_cached(), config::ErrorLines::One);
+  let mut root = None::<analysis::input::Root>;
+  // until we know the files of the project, watch the dir.
+  let mut watched = BTreeSet::from([dir.to_owned(), dir.join(config::FILE_NAME)]);
+  let mut stamps = None::<Stamps>;
+  let mut prev = Vec::<Diagnostic>::new();
+  loop {
+    let new_stamps = get_stamps(&watched);
+    if stamps.as_ref() == Some(&new_stamps) {
+      std::thread::sleep(POLL_INTERVAL);
+      continue;
+    }
+    stamps = Some(new_stamps);
+    let (mut cur, num_errors, files) = check(fs, path, &mut root, &mut an, dead_code);
+    if let Some(files) = files {
+      let new_watched = with_dirs(files);
+      if new_watched != watched {
+        watched = new_watched;
+        stamps = Some(get_stamps(&watched));
+      }
+    }
+    cur.sort_unstable();
+    let (new, fixed) = diff(&prev, &cur);
+    for d in &new {
+      println!("{d}");
+    }
+    for d in &fixed {
+      println!("fixed: {d}");
+    }
+    let suffix = if num_errors == 1 { "" } else { "s" };
+    println!(
+      "{num_errors} error{suffix} ({} new, {} fixed). watching for changes",
+      new.len(),
+      fixed.len()
+    );
+    prev = cur;
+  }
+}
+
+/// Returns the diagnostics that are in `cur` but not `prev`, and those in `prev` but not `cur`.
+///
+/// Diagnostics are compared by their path, severity, code, and message, but not their position, so
+/// that e.g. adding a line at the top of a file does not make every error in it look new. They are
+/// counted as a multiset, so that if there were two of the same diagnostic and now there is one,
+/// one is fixed.
+pub(crate) fn diff<'a>(
+  prev: &'a [Diagnostic],
+  cur: &'a [Diagnostic],
+) -> (Vec<&'a Diagnostic>, Vec<&'a Diagnostic>) {
+  (only_in(cur, prev), only_in(prev, cur))
+}
+
+/// returns the diagnostics in `xs` not in `ys`, counting duplicates.
+fn only_in<'a>(xs: &'a [Diagnostic], ys: &[Diagnostic]) -> Vec<&'a Diagnostic> {
+  let mut counts = BTreeMap::<_, usize>::new();
+  for y in ys {
+    *counts.entry(key(y)).or_default() += 1;
+  }
+  xs.iter()
+    .filter(|x| match counts.get_mut(&key(x)) {
+      Some(n) if *n > 0 => {
+        *n -= 1;
+        false
+      }
+      _ => true,
+    })
+    .collect()
+}
+
+fn key(d: &Diagnostic) -> (&str, crate::Severity, u16, &str) {
+  (d.path.as_str(), d.severity, d.code, d.message.as_str())
+}
+
+/// Returns the diagnostics for the project, the number of errors, and the config, group, and
+/// source files of the project, if we could get them.
+fn check(
+  fs: &paths::RealFileSystem,
+  path: &Path,
+  root: &mut Option<analysis::input::Root>,
+  an: &mut analysis::Analysis,
+  dead_code: bool,
+) -> (Vec<Diagnostic>, usize, Option<Vec<PathBuf>>) {
+  let root = match root {
+    Some(x) => x,
+    None => match analysis::input::get_root(fs, path) {
+      Ok(x) => {
+        an.set_file_cache(analysis::FileCache::for_root(x.as_paths().as_path()));
+        root.insert(x)
+      }
+      Err(e) => return (vec![crate::input_error(&e)], 1, None),
+    },
+  };
+  let inp = match analysis::input::get(fs, root) {
+    Ok(x) => x,
+    Err(e) => return (vec![crate::input_error(&e)], 1, None),
+  };
+  let root_paths = root.as_paths();
+  let files: Vec<_> = std::iter::once(root_paths.as_path().join(config::FILE_NAME))
+    .chain(
+      inp
+        .iter_sources()
+        .map(|x| x.path)
+        .chain(inp.iter_groups().map(|x| x.path))
+        .map(|x| root_paths.get_path(x).as_path().to_owned()),
+    )
+    .collect();
+  let (diagnostics, num_errors) = crate::get_diagnostics(fs, root, &inp, an, dead_code);
+  (diagnostics, num_errors, Some(files))
+}
+
+/// returns the files and the dirs they are in.
+fn with_dirs(files: Vec<PathBuf>) -> BTreeSet<PathBuf> {
+  let dirs: Vec<_> = files
+    .iter()
+    .filter_map(|x| x.parent().map(ToOwned::to_owned))
+    .collect();
+  files.into_iter().chain(dirs).collect()
+}
+
+/// Returns the stamps of the `paths`. A dir's modification time changes when files are added to or
+/// removed from it.
+fn get_stamps(paths: &BTreeSet<PathBuf>) -> Stamps {
+  paths
+    .iter()
+    .map(|path| {
+      let stamp = std::fs::metadata(path)
+        .ok()
+        .map(|md| (md.modified().ok(), md.len()));
+      (path.clone(), stamp)
+    })
+    .collect()
+}
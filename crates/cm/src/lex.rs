@@ -69,6 +69,10 @@ fn token<'s>(idx: &mut usize, b: u8, bs: &'s [u8]) -> Result<Option<Token<'s>>>
     "Library" | "library" => Token::Library,
     "Alias" | "alias" => Token::Alias,
     "Is" | "is" => Token::Is,
+    // these may appear in paths, so they are only tokens on their own.
+    "+" => Token::Plus,
+    "-" => Token::Minus,
+    "*" => Token::Star,
     s => Token::String(s),
   };
   Ok(Some(ret))
@@ -15,7 +15,7 @@ mod lower;
 mod parse;
 mod types;
 
-pub use types::{Class, CmFile, Error, Export, Namespace, PathKind, Result};
+pub use types::{Class, CmFile, Error, Export, Namespace, PathKind, PathOrMinus, Result};
 
 /// Turn the contents of a CM file into exports and members.
 pub fn get(s: &str, env: &paths::slash_var_path::Env) -> Result<CmFile> {
@@ -1,5 +1,5 @@
 use crate::types::{
-  Class, DescKind, Error, ErrorKind, Export, Member, Namespace, Result, Root, Token,
+  Class, DescKind, Error, ErrorKind, Export, Member, Namespace, PathOrMinus, Result, Root, Token,
 };
 use std::path::PathBuf;
 use text_size_util::{TextRange, WithRange};
@@ -33,6 +33,10 @@ impl<'a> Parser<'a> {
     self.cur_tok().map(|x| x.val)
   }
 
+  fn peek(&self) -> Option<Token<'a>> {
+    self.tokens.get(self.idx + 1).map(|x| x.val)
+  }
+
   fn err<T>(&self, kind: ErrorKind) -> Result<T> {
     Err(Error::new(kind, self.last_range))
   }
@@ -96,40 +100,135 @@ fn root(p: &mut Parser<'_>) -> Result<Root> {
   Ok(ret)
 }
 
+/// a sequence of exports, which denotes their union.
 fn exports(p: &mut Parser<'_>) -> Result<Vec<Export>> {
   let mut ret = Vec::<Export>::new();
+  while let Some(export) = export_union(p)? {
+    ret.push(export);
+  }
+  Ok(ret)
+}
+
+/// `+` and `-`, which have the same precedence and associate to the left.
+fn export_union(p: &mut Parser<'_>) -> Result<Option<Export>> {
+  let mut ret = match export_intersection(p)? {
+    Some(x) => x,
+    None => return Ok(None),
+  };
   loop {
-    let tok = p.cur_tok();
-    let tok = match tok {
-      Some(x) => x,
-      None => break,
-    };
-    let namespace = match tok.val {
-      Token::Structure => Namespace::Structure,
-      Token::Signature => Namespace::Signature,
-      Token::Functor => Namespace::Functor,
-      Token::FunSig => Namespace::FunSig,
-      Token::Library => {
-        p.bump();
-        p.eat(Token::LRound)?;
-        let s = p.string()?;
-        p.bump();
-        let pathname = path(p, s.val)?;
-        p.eat(Token::RRound)?;
-        if let Some(pathname) = pathname {
-          ret.push(Export::Library(s.wrap(pathname)));
-        }
-        continue;
-      }
+    let is_union = match p.cur() {
+      Some(Token::Plus) => true,
+      Some(Token::Minus) => false,
       _ => break,
     };
     p.bump();
-    let s = p.string()?;
-    let name = str_util::Name::new(s.val);
+    let rhs = match export_intersection(p)? {
+      Some(x) => x,
+      None => return p.err(ErrorKind::ExpectedExport),
+    };
+    ret = if is_union {
+      Export::Union(vec![ret, rhs])
+    } else {
+      Export::Difference(Box::new(ret), Box::new(rhs))
+    };
+  }
+  Ok(Some(ret))
+}
+
+/// `*`, which has higher precedence than `+` and `-` and associates to the left.
+fn export_intersection(p: &mut Parser<'_>) -> Result<Option<Export>> {
+  let mut ret = match export_atom(p)? {
+    Some(x) => x,
+    None => return Ok(None),
+  };
+  while p.cur() == Some(Token::Star) {
     p.bump();
-    ret.push(Export::Regular(tok.wrap(namespace), s.wrap(name)));
+    let rhs = match export_atom(p)? {
+      Some(x) => x,
+      None => return p.err(ErrorKind::ExpectedExport),
+    };
+    ret = Export::Intersection(Box::new(ret), Box::new(rhs));
   }
-  Ok(ret)
+  Ok(Some(ret))
+}
+
+fn export_atom(p: &mut Parser<'_>) -> Result<Option<Export>> {
+  let tok = match p.cur_tok() {
+    Some(x) => x,
+    None => return Ok(None),
+  };
+  let namespace = match tok.val {
+    Token::Structure => Namespace::Structure,
+    Token::Signature => Namespace::Signature,
+    Token::Functor => Namespace::Functor,
+    Token::FunSig => Namespace::FunSig,
+    Token::Library => {
+      p.bump();
+      p.eat(Token::LRound)?;
+      let s = p.string()?;
+      p.bump();
+      let pathname = path(p, s.val)?;
+      p.eat(Token::RRound)?;
+      let ret = match pathname {
+        Some(pathname) => Export::Library(s.wrap(pathname)),
+        // an ignored library exports nothing.
+        None => Export::Union(Vec::new()),
+      };
+      return Ok(Some(ret));
+    }
+    Token::Group => return source_or_group(p, Export::Group),
+    // `source` is only a keyword before a `(`, so members and exported names may still be called
+    // that.
+    Token::String("source") if p.peek() == Some(Token::LRound) => {
+      return source_or_group(p, Export::Source)
+    }
+    Token::LRound => {
+      p.bump();
+      let ret = exports(p)?;
+      p.eat(Token::RRound)?;
+      return Ok(Some(Export::Union(ret)));
+    }
+    _ => return Ok(None),
+  };
+  p.bump();
+  let s = p.string()?;
+  let name = str_util::Name::new(s.val);
+  p.bump();
+  Ok(Some(Export::Regular(tok.wrap(namespace), s.wrap(name))))
+}
+
+/// `source(...)` or `group(...)`, whose argument is made into an export with `f`.
+fn source_or_group(
+  p: &mut Parser<'_>,
+  f: fn(WithRange<PathOrMinus>) -> Export,
+) -> Result<Option<Export>> {
+  p.bump();
+  p.eat(Token::LRound)?;
+  let arg = match p.cur_tok() {
+    Some(WithRange {
+      val: Token::Minus,
+      range,
+    }) => {
+      p.bump();
+      WithRange {
+        val: PathOrMinus::Minus,
+        range,
+      }
+    }
+    _ => {
+      let s = p.string()?;
+      p.bump();
+      match path(p, s.val)? {
+        Some(pathname) => s.wrap(PathOrMinus::Path(pathname)),
+        None => {
+          p.eat(Token::RRound)?;
+          return Ok(Some(Export::Union(Vec::new())));
+        }
+      }
+    }
+  };
+  p.eat(Token::RRound)?;
+  Ok(Some(f(arg)))
 }
 
 fn members_tail(p: &mut Parser<'_>) -> Result<Vec<Member>> {
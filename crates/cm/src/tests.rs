@@ -1,4 +1,4 @@
-use crate::types::{Export, Namespace, PathKind};
+use crate::types::{Export, Namespace, PathKind, PathOrMinus};
 use std::path::PathBuf;
 use str_util::Name;
 
@@ -8,14 +8,7 @@ fn check(s: &str, want_exports: Vec<RawExport>, want_paths: &[(&str, PathKind)])
     .iter()
     .map(|&(s, kind)| (mk_path_buf(s), kind))
     .collect();
-  let got_exports: Vec<_> = file
-    .exports
-    .into_iter()
-    .map(|x| match x {
-      Export::Regular(ns, n) => RawExport::Regular(ns.val, n.val),
-      Export::Library(p) => RawExport::Library(p.val),
-    })
-    .collect();
+  let got_exports: Vec<_> = file.exports.into_iter().map(raw_export).collect();
   let got_paths: Vec<_> = file
     .paths
     .into_iter()
@@ -29,6 +22,33 @@ fn check(s: &str, want_exports: Vec<RawExport>, want_paths: &[(&str, PathKind)])
 enum RawExport {
   Regular(Namespace, Name),
   Library(PathBuf),
+  /// `None` is `-`.
+  Source(Option<PathBuf>),
+  /// `None` is `-`.
+  Group(Option<PathBuf>),
+  Union(Vec<RawExport>),
+  Difference(Box<RawExport>, Box<RawExport>),
+  Intersection(Box<RawExport>, Box<RawExport>),
+}
+
+fn raw_export(export: Export) -> RawExport {
+  let raw_path = |p: PathOrMinus| match p {
+    PathOrMinus::Path(p) => Some(p),
+    PathOrMinus::Minus => None,
+  };
+  match export {
+    Export::Regular(ns, n) => RawExport::Regular(ns.val, n.val),
+    Export::Library(p) => RawExport::Library(p.val),
+    Export::Source(p) => RawExport::Source(raw_path(p.val)),
+    Export::Group(p) => RawExport::Group(raw_path(p.val)),
+    Export::Union(es) => RawExport::Union(es.into_iter().map(raw_export).collect()),
+    Export::Difference(a, b) => {
+      RawExport::Difference(Box::new(raw_export(*a)), Box::new(raw_export(*b)))
+    }
+    Export::Intersection(a, b) => {
+      RawExport::Intersection(Box::new(raw_export(*a)), Box::new(raw_export(*b)))
+    }
+  }
 }
 
 fn mk_regular(ns: Namespace, name: &str) -> RawExport {
@@ -118,6 +138,76 @@ is
   );
 }
 
+#[test]
+fn export_expressions() {
+  check(
+    r#"
+Library
+  source(-) - structure Internal
+  group(sub/sources.cm) * (signature FOO functor Bar)
+  source(a.sml) + structure Quz - signature QUZ
+is
+  a.sml
+  b.sml
+  sub/sources.cm
+"#,
+    vec![
+      RawExport::Difference(
+        Box::new(RawExport::Source(None)),
+        Box::new(mk_regular(Namespace::Structure, "Internal")),
+      ),
+      RawExport::Intersection(
+        Box::new(RawExport::Group(Some(mk_path_buf("sub/sources.cm")))),
+        Box::new(RawExport::Union(vec![
+          mk_regular(Namespace::Signature, "FOO"),
+          mk_regular(Namespace::Functor, "Bar"),
+        ])),
+      ),
+      RawExport::Difference(
+        Box::new(RawExport::Union(vec![
+          RawExport::Source(Some(mk_path_buf("a.sml"))),
+          mk_regular(Namespace::Structure, "Quz"),
+        ])),
+        Box::new(mk_regular(Namespace::Signature, "QUZ")),
+      ),
+    ],
+    &[
+      ("a.sml", PathKind::Sml),
+      ("b.sml", PathKind::Sml),
+      ("sub/sources.cm", PathKind::Cm),
+    ],
+  );
+}
+
+#[test]
+fn source_as_name() {
+  check(
+    r#"
+Library
+  structure source
+  source(-)
+is
+  source : sml
+  a.sml
+"#,
+    vec![
+      mk_regular(Namespace::Structure, "source"),
+      RawExport::Source(None),
+    ],
+    &[("source", PathKind::Sml), ("a.sml", PathKind::Sml)],
+  );
+}
+
+#[test]
+fn export_expected() {
+  let e = crate::get(
+    r#"Library source(-) - is a.sml"#,
+    &paths::slash_var_path::Env::default(),
+  )
+  .unwrap_err();
+  assert!(e.to_string().contains("expected an export"));
+}
+
 #[test]
 fn unknown_class() {
   let e = crate::get(
@@ -12,6 +12,7 @@ pub(crate) enum ErrorKind {
   UnclosedComment,
   EmptyExportList,
   Expected(Token<'static>),
+  ExpectedExport,
   ExpectedString,
   ExpectedDesc,
   UnsupportedAlias,
@@ -42,6 +43,7 @@ impl fmt::Display for Error {
       ErrorKind::UnclosedComment => f.write_str("unclosed block comment"),
       ErrorKind::EmptyExportList => f.write_str("invalid empty export list"),
       ErrorKind::Expected(tok) => write!(f, "expected `{tok}`"),
+      ErrorKind::ExpectedExport => f.write_str("expected an export"),
       ErrorKind::ExpectedString => f.write_str("expected a string"),
       ErrorKind::ExpectedDesc => f.write_str("expected `Group`, `Library`, or `Alias`"),
       ErrorKind::UnsupportedAlias => f.write_str("unsupported: `Alias`"),
@@ -70,6 +72,9 @@ pub enum Token<'a> {
   Colon,
   LRound,
   RRound,
+  Plus,
+  Minus,
+  Star,
   String(&'a str),
 }
 
@@ -87,6 +92,9 @@ impl<'a> fmt::Display for Token<'a> {
       Token::Colon => f.write_str(":"),
       Token::LRound => f.write_str("("),
       Token::RRound => f.write_str(")"),
+      Token::Plus => f.write_str("+"),
+      Token::Minus => f.write_str("-"),
+      Token::Star => f.write_str("*"),
       Token::String(s) => s.fmt(f),
     }
   }
@@ -138,13 +146,32 @@ pub(crate) enum DescKind {
   Library,
 }
 
-/// An export, like `structure S`.
+/// An export, like `structure S`. Each export denotes a set of names.
 #[derive(Debug)]
 pub enum Export {
   /// A 'regular' export.
   Regular(WithRange<Namespace>, WithRange<Name>),
   /// A re-export of another CM library.
   Library(WithRange<PathBuf>),
+  /// The names defined by a source file member, or by all of them.
+  Source(WithRange<PathOrMinus>),
+  /// The names exported by a group member, or by all of them.
+  Group(WithRange<PathOrMinus>),
+  /// The names in any of the exports. A parenthesized list of exports, or `+`.
+  Union(Vec<Export>),
+  /// The names in the first export but not the second. `-`.
+  Difference(Box<Export>, Box<Export>),
+  /// The names in both exports. `*`.
+  Intersection(Box<Export>, Box<Export>),
+}
+
+/// The argument to `source` or `group` in an export.
+#[derive(Debug)]
+pub enum PathOrMinus {
+  /// A path to a member.
+  Path(PathBuf),
+  /// `-`, meaning all the members of that kind.
+  Minus,
 }
 
 /// A namespace, like `structure` in `structure S`.
@@ -21,11 +21,47 @@ pub struct Root {
 /// The workspace config.
 #[derive(Debug, Deserialize)]
 pub struct Workspace {
-  /// The root group filename.
-  pub root: Option<SmolStr>,
+  /// The root group filename(s).
+  pub root: Option<Roots>,
   /// Path vars, for expansion in MLB/CM paths.
   #[serde(rename = "path-vars")]
   pub path_vars: Option<FxHashMap<SmolStr, PathVar>>,
+  /// The compiler the code is meant for.
+  pub target: Option<Target>,
+}
+
+/// One or many root group filenames. Each may contain `*` wildcards.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Roots {
+  /// Just one.
+  One(SmolStr),
+  /// Many. Each is analyzed separately.
+  Many(Vec<SmolStr>),
+}
+
+impl Roots {
+  /// Returns the filenames.
+  pub fn as_slice(&self) -> &[SmolStr] {
+    match self {
+      Roots::One(x) => std::slice::from_ref(x),
+      Roots::Many(xs) => xs.as_slice(),
+    }
+  }
+}
+
+/// A compiler to target, which determines e.g. the ranges of integer types.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum Target {
+  /// SML/NJ, where `int` has 63 bits.
+  #[serde(rename = "smlnj")]
+  SmlNj,
+  /// MLton, where `int` has 32 bits.
+  #[serde(rename = "mlton")]
+  MLton,
+  /// Poly/ML, where `int` is arbitrary-precision.
+  #[serde(rename = "polyml")]
+  PolyMl,
 }
 
 /// A path var setting.
@@ -47,3 +83,34 @@ pub enum ErrorLines {
   /// Error messages may (or may not) have newlines.
   Many,
 }
+
+/// Options set by the language client.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Options {
+  /// Inlay hint options.
+  #[serde(rename = "inlayHints")]
+  pub inlay_hints: InlayHints,
+  /// Report unused top-level items and source files.
+  #[serde(rename = "deadCode")]
+  pub dead_code: bool,
+}
+
+/// Which inlay hints to show.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct InlayHints {
+  /// Show the types of variables bound by `val`, `fun`, and `fn`.
+  pub bindings: bool,
+  /// Show the instantiated types of polymorphic values where they are used.
+  pub instances: bool,
+}
+
+impl Default for InlayHints {
+  fn default() -> Self {
+    Self {
+      bindings: true,
+      instances: false,
+    }
+  }
+}
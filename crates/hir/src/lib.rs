@@ -5,6 +5,7 @@
 use std::fmt;
 
 use la_arena::Arena;
+use serde::{Deserialize, Serialize};
 
 pub use la_arena;
 pub use num_bigint::{BigInt, ParseBigIntError};
@@ -25,11 +26,34 @@ pub struct Arenas {
 macro_rules! mk_idx {
   ($($name:ident)*) => {
     #[doc = "An index into an arena."]
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(from = "IdxRepr", into = "IdxRepr")]
     pub enum Idx {
       $($name(la_arena::Idx<$name>),)*
     }
 
+    /// la_arena indices are not serializable, so we serialize their raw values.
+    #[derive(Serialize, Deserialize)]
+    enum IdxRepr {
+      $($name(u32),)*
+    }
+
+    impl From<Idx> for IdxRepr {
+      fn from(val: Idx) -> Self {
+        match val {
+          $(Idx::$name(x) => Self::$name(x.into_raw().into()),)*
+        }
+      }
+    }
+
+    impl From<IdxRepr> for Idx {
+      fn from(val: IdxRepr) -> Self {
+        match val {
+          $(IdxRepr::$name(x) => Self::$name(la_arena::Idx::from_raw(x.into())),)*
+        }
+      }
+    }
+
     $(
       impl From<la_arena::Idx<$name>> for Idx {
         fn from(val: la_arena::Idx<$name>) -> Self {
@@ -55,11 +79,27 @@ pub struct SigBind {
 #[derive(Debug)]
 pub struct FunctorBind {
   pub functor_name: Name,
-  pub param_name: Name,
-  pub param_sig: SigExpIdx,
+  /// The names and parameters. More than one is an SML/NJ extension, for a curried functor.
+  pub params: Vec<(Name, FunctorParam)>,
   pub body: StrExpIdx,
 }
 
+#[derive(Debug)]
+pub enum FunctorParam {
+  Structure(SigExpIdx),
+  /// SML/NJ extension. The name is of the functor signature.
+  Functor(Name),
+}
+
+/// SML/NJ extension.
+#[derive(Debug)]
+pub struct FunSigBind {
+  pub name: Name,
+  /// As for [`FunctorBind`].
+  pub params: Vec<(Name, FunctorParam)>,
+  pub body: SigExpIdx,
+}
+
 pub type StrDecIdx = OptIdx<StrDec>;
 pub type StrDecArena = Arena<StrDec>;
 
@@ -74,6 +114,8 @@ pub enum StrDec {
   Signature(Vec<SigBind>),
   /// technically a top dec in the Definition.
   Functor(Vec<FunctorBind>),
+  /// SML/NJ extension.
+  FunSig(Vec<FunSigBind>),
 }
 
 #[derive(Debug)]
@@ -90,7 +132,8 @@ pub enum StrExp {
   Struct(StrDecIdx),
   Path(Path),
   Ascription(StrExpIdx, Ascription, SigExpIdx),
-  App(Name, StrExpIdx),
+  /// More than one argument is an SML/NJ extension, for applying a curried functor.
+  App(Name, Vec<StrExpIdx>),
   Let(StrDecIdx, StrExpIdx),
 }
 
@@ -278,7 +321,7 @@ pub enum Ty {
   Fn(TyIdx, TyIdx),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Lab {
   Name(Name),
   Num(usize),
@@ -303,17 +346,45 @@ impl Lab {
 pub enum SCon {
   Int(Int),
   Real(f64),
-  Word(u32),
+  Word(Int),
   Char(char),
   String(SmolStr),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "IntRepr", into = "IntRepr")]
 pub enum Int {
   Finite(i32),
   Big(num_bigint::BigInt),
 }
 
+/// big ints are not serializable, so we serialize their decimal strings.
+#[derive(Serialize, Deserialize)]
+enum IntRepr {
+  Finite(i32),
+  Big(String),
+}
+
+impl From<Int> for IntRepr {
+  fn from(val: Int) -> Self {
+    match val {
+      Int::Finite(x) => Self::Finite(x),
+      Int::Big(x) => Self::Big(x.to_string()),
+    }
+  }
+}
+
+impl TryFrom<IntRepr> for Int {
+  type Error = ParseBigIntError;
+
+  fn try_from(val: IntRepr) -> Result<Self, Self::Error> {
+    match val {
+      IntRepr::Finite(x) => Ok(Self::Finite(x)),
+      IntRepr::Big(x) => x.parse().map(Self::Big),
+    }
+  }
+}
+
 impl fmt::Display for Int {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
@@ -323,7 +394,7 @@ impl fmt::Display for Int {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Path {
   structures: Vec<Name>,
   last: Name,
@@ -372,7 +443,7 @@ impl fmt::Display for Path {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TyVar(Name);
 
 impl TyVar {
@@ -4,7 +4,12 @@ mod state;
 
 fn run(conn: lsp_server::Connection, init: lsp_types::InitializeParams) -> anyhow::Result<()> {
   log::info!("startup main loop: {init:#?}");
-  let mut state = state::State::new(init.root_uri, conn.sender.clone());
+  // prefer the workspace folders, but fall back to the single root for older clients.
+  let roots = match init.workspace_folders {
+    Some(folders) => folders.into_iter().map(|folder| folder.uri).collect(),
+    None => init.root_uri.into_iter().collect(),
+  };
+  let mut state = state::State::new(roots, init.initialization_options, conn.sender.clone());
   for msg in conn.receiver.iter() {
     match msg {
       lsp_server::Message::Request(req) => {
@@ -4,7 +4,7 @@
 
 use anyhow::{anyhow, bail, Context, Result};
 use crossbeam_channel::Sender;
-use fast_hash::FxHashSet;
+use fast_hash::{FxHashMap, FxHashSet};
 use lsp_server::{ExtractError, Message, Notification, ReqQueue, Request, RequestId, Response};
 use lsp_types::{notification::Notification as _, Url};
 use std::ops::ControlFlow;
@@ -26,75 +26,187 @@ pub(crate) fn capabilities() -> lsp_types::ServerCapabilities {
     definition_provider: Some(lsp_types::OneOf::Left(true)),
     type_definition_provider: Some(lsp_types::TypeDefinitionProviderCapability::Simple(true)),
     code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
+    inlay_hint_provider: Some(lsp_types::OneOf::Left(true)),
+    signature_help_provider: Some(lsp_types::SignatureHelpOptions {
+      trigger_characters: Some(vec!["(".to_owned(), ",".to_owned(), " ".to_owned()]),
+      retrigger_characters: None,
+      work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+    }),
+    call_hierarchy_provider: Some(lsp_types::CallHierarchyServerCapability::Simple(true)),
+    folding_range_provider: Some(lsp_types::FoldingRangeProviderCapability::Simple(true)),
+    selection_range_provider: Some(lsp_types::SelectionRangeProviderCapability::Simple(true)),
+    semantic_tokens_provider: Some(
+      lsp_types::SemanticTokensServerCapabilities::SemanticTokensOptions(
+        lsp_types::SemanticTokensOptions {
+          work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+          legend: lsp_types::SemanticTokensLegend {
+            token_types: TOKEN_TYPES.to_vec(),
+            token_modifiers: TOKEN_MODIFIERS.to_vec(),
+          },
+          range: Some(true),
+          full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
+        },
+      ),
+    ),
+    workspace: Some(lsp_types::WorkspaceServerCapabilities {
+      workspace_folders: Some(lsp_types::WorkspaceFoldersServerCapabilities {
+        supported: Some(true),
+        change_notifications: Some(lsp_types::OneOf::Left(true)),
+      }),
+      file_operations: None,
+    }),
     ..Default::default()
   }
 }
 
 const MAX_FILES_WITH_ERRORS: usize = 20;
 
+/// The order must match [`token_type`].
+const TOKEN_TYPES: [lsp_types::SemanticTokenType; 9] = [
+  lsp_types::SemanticTokenType::VARIABLE,
+  lsp_types::SemanticTokenType::ENUM_MEMBER,
+  lsp_types::SemanticTokenType::new("exception"),
+  lsp_types::SemanticTokenType::TYPE,
+  lsp_types::SemanticTokenType::TYPE_PARAMETER,
+  lsp_types::SemanticTokenType::NAMESPACE,
+  lsp_types::SemanticTokenType::INTERFACE,
+  lsp_types::SemanticTokenType::new("functor"),
+  lsp_types::SemanticTokenType::OPERATOR,
+];
+
+/// The order must match [`semantic_tokens`].
+const TOKEN_MODIFIERS: [lsp_types::SemanticTokenModifier; 2] = [
+  lsp_types::SemanticTokenModifier::DECLARATION,
+  lsp_types::SemanticTokenModifier::DEFAULT_LIBRARY,
+];
+
+/// A workspace folder. Each has its own analysis, since path ids are not shared between roots.
 struct Root {
+  url: Url,
   input: analysis::input::Root,
+  analysis: analysis::Analysis,
   has_diagnostics: FxHashSet<Url>,
 }
 
 /// The state of the language server. Only this may do IO. (Well, also the [`lsp_server`] channels
 /// that communicate over stdin and stdout.)
 pub(crate) struct State {
-  root: Option<Root>,
+  roots: Vec<Root>,
   sender: Sender<Message>,
   req_queue: ReqQueue<(), ()>,
+  std_basis: analysis::StdBasis,
+  /// Only used when there is no root.
   analysis: analysis::Analysis,
   file_system: paths::RealFileSystem,
+  options: config::Options,
+  /// The contents of the open files. Only used when there is no root.
+  open_files: FxHashMap<Url, String>,
 }
 
 impl State {
-  pub(crate) fn new(root: Option<Url>, sender: Sender<Message>) -> Self {
-    let file_system = paths::RealFileSystem::default();
-    let mut root = root
-      .map(|url| canonical_path_buf(&file_system, &url))
-      .transpose();
+  pub(crate) fn new(
+    roots: Vec<Url>,
+    options: Option<serde_json::Value>,
+    sender: Sender<Message>,
+  ) -> Self {
+    let std_basis = analysis::StdBasis::full_cached();
     let mut ret = Self {
-      root: root
-        .as_mut()
-        .ok()
-        .and_then(Option::take)
-        .map(|root_path| Root {
-          input: analysis::input::get_root_dir(root_path),
-          has_diagnostics: FxHashSet::default(),
-        }),
+      roots: Vec::new(),
       sender,
       req_queue: ReqQueue::default(),
-      analysis: analysis::Analysis::new(analysis::StdBasis::full(), config::ErrorLines::Many),
-      file_system,
+      analysis: analysis::Analysis::new(std_basis.clone(), config::ErrorLines::Many),
+      std_basis,
+      file_system: paths::RealFileSystem::default(),
+      options: get_options(options),
+      open_files: FxHashMap::default(),
     };
-    if let Err(e) = root {
-      ret.show_error(format!("{e:#}"));
+    for url in roots {
+      ret.add_root(url);
+    }
+    ret
+  }
+
+  fn add_root(&mut self, url: Url) {
+    if self.roots.iter().any(|root| root.url == url) {
+      return;
     }
-    if let Some(root) = &ret.root {
-      // not sure if possible to only listen to millet.toml. "nested alternate groups are not
-      // allowed" at time of writing
-      let glob_pattern = format!(
-        "{}/**/*.{{sml,sig,fun,cm,mlb,toml}}",
-        root.input.as_paths().as_path().display()
-      );
-      ret.send_request::<lsp_types::request::RegisterCapability>(lsp_types::RegistrationParams {
-        registrations: vec![lsp_types::Registration {
-          id: lsp_types::notification::DidChangeWatchedFiles::METHOD.to_owned(),
+    let root_path = match canonical_path_buf(&self.file_system, &url) {
+      Ok(x) => x,
+      Err(e) => {
+        self.show_error(format!("{e:#}"));
+        return;
+      }
+    };
+    let mut root = Root {
+      url,
+      input: analysis::input::get_root_dir(root_path),
+      analysis: analysis::Analysis::new(self.std_basis.clone(), config::ErrorLines::Many),
+      has_diagnostics: FxHashSet::default(),
+    };
+    let file_cache = analysis::FileCache::for_root(root.input.as_paths().as_path());
+    root.analysis.set_file_cache(file_cache);
+    // not sure if possible to only listen to millet.toml. "nested alternate groups are not
+    // allowed" at time of writing
+    let glob_pattern = format!(
+      "{}/**/*.{{sml,sig,fun,cm,mlb,toml}}",
+      root.input.as_paths().as_path().display()
+    );
+    self.send_request::<lsp_types::request::RegisterCapability>(lsp_types::RegistrationParams {
+      registrations: vec![lsp_types::Registration {
+        id: watcher_registration_id(&root.url),
+        method: lsp_types::notification::DidChangeWatchedFiles::METHOD.to_owned(),
+        register_options: Some(
+          serde_json::to_value(lsp_types::DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![lsp_types::FileSystemWatcher {
+              glob_pattern,
+              kind: None,
+            }],
+          })
+          .unwrap(),
+        ),
+      }],
+    });
+    self.publish_root_diagnostics(&mut root);
+    self.roots.push(root);
+  }
+
+  fn remove_root(&mut self, url: &Url) {
+    let idx = match self.roots.iter().position(|root| root.url == *url) {
+      Some(x) => x,
+      None => {
+        log::warn!("tried to remove a non-existent root: {url}");
+        return;
+      }
+    };
+    let root = self.roots.remove(idx);
+    self.send_request::<lsp_types::request::UnregisterCapability>(
+      lsp_types::UnregistrationParams {
+        unregisterations: vec![lsp_types::Unregistration {
+          id: watcher_registration_id(&root.url),
           method: lsp_types::notification::DidChangeWatchedFiles::METHOD.to_owned(),
-          register_options: Some(
-            serde_json::to_value(lsp_types::DidChangeWatchedFilesRegistrationOptions {
-              watchers: vec![lsp_types::FileSystemWatcher {
-                glob_pattern,
-                kind: None,
-              }],
-            })
-            .unwrap(),
-          ),
         }],
-      });
-      ret.publish_diagnostics();
+      },
+    );
+    for url in root.has_diagnostics {
+      self.send_diagnostics(url, Vec::new());
     }
-    ret
+  }
+
+  /// Returns the index of the root a request is about, based on the document it mentions. If
+  /// roots are nested, the innermost one wins.
+  fn root_idx(&self, req: &Request) -> Option<usize> {
+    let url = ["/textDocument/uri", "/item/uri"]
+      .iter()
+      .find_map(|ptr| req.params.pointer(ptr))?
+      .as_str()?;
+    let path = canonical_path_buf(&self.file_system, &Url::parse(url).ok()?).ok()?;
+    self
+      .roots
+      .iter()
+      .enumerate()
+      .filter(|(_, root)| path.as_path().starts_with(root.input.as_paths().as_path()))
+      .max_by_key(|(_, root)| root.input.as_paths().as_path().as_os_str().len())
+      .map(|(idx, _)| idx)
   }
 
   fn send_request<R>(&mut self, params: R::Params)
@@ -126,21 +238,57 @@ impl State {
   pub(crate) fn handle_request(&mut self, req: Request) {
     log::info!("got request: {req:?}");
     self.req_queue.incoming.register(req.id.clone(), ());
-    let mut root = match self.root.take() {
-      Some(x) => x,
-      None => {
-        // TODO could improve support for no-root mode.
-        log::warn!("can't handle request with no root");
-        self.send_response(Response::new_ok(req.id, None::<()>));
-        return;
+    if self.roots.is_empty() {
+      match self.handle_request_no_root(req) {
+        ControlFlow::Break(Ok(())) => {}
+        ControlFlow::Break(Err(e)) => log::error!("couldn't handle request: {e}"),
+        ControlFlow::Continue(req) => {
+          // TODO could improve support for no-root mode.
+          log::warn!("can't handle request with no root: {req:?}");
+          self.send_response(Response::new_ok(req.id, None::<()>));
+        }
       }
-    };
+      return;
+    }
+    let idx = self.root_idx(&req).unwrap_or(0);
+    let mut root = self.roots.remove(idx);
     match self.handle_request_(&mut root, req) {
       ControlFlow::Break(Ok(())) => {}
       ControlFlow::Break(Err(e)) => log::error!("couldn't handle request: {e}"),
       ControlFlow::Continue(req) => log::warn!("unhandled request: {req:?}"),
     }
-    self.root = Some(root);
+    self.roots.insert(idx, root);
+  }
+
+  fn handle_request_no_root(&mut self, mut r: Request) -> ControlFlow<Result<()>, Request> {
+    r = try_request::<lsp_types::request::FoldingRangeRequest, _>(r, |id, params| {
+      let res = self
+        .open_files
+        .get(&params.text_document.uri)
+        .map(|contents| folding_ranges(self.analysis.get_folding_ranges_one(contents)));
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::SelectionRangeRequest, _>(r, |id, params| {
+      let res = self
+        .open_files
+        .get(&params.text_document.uri)
+        .map(|contents| {
+          params
+            .positions
+            .into_iter()
+            .map(|pos| {
+              let ranges = self
+                .analysis
+                .get_selection_ranges_one(contents, analysis_position(pos));
+              selection_range(ranges.unwrap_or_default())
+            })
+            .collect::<Vec<_>>()
+        });
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    ControlFlow::Continue(r)
   }
 
   fn handle_request_(
@@ -151,7 +299,7 @@ impl State {
     r = try_request::<lsp_types::request::HoverRequest, _>(r, |id, params| {
       let params = params.text_document_position_params;
       let pos = text_doc_pos_params(&self.file_system, root, params)?;
-      let res = self
+      let res = root
         .analysis
         .get_md(pos)
         .map(|(value, range)| lsp_types::Hover {
@@ -167,7 +315,7 @@ impl State {
     r = try_request::<lsp_types::request::GotoDefinition, _>(r, |id, params| {
       let params = params.text_document_position_params;
       let pos = text_doc_pos_params(&self.file_system, root, params)?;
-      let res = self
+      let res = root
         .analysis
         .get_def(pos)
         .and_then(|range| lsp_location(root, range).map(lsp_types::GotoDefinitionResponse::Scalar));
@@ -177,7 +325,7 @@ impl State {
     r = try_request::<lsp_types::request::GotoTypeDefinition, _>(r, |id, params| {
       let params = params.text_document_position_params;
       let pos = text_doc_pos_params(&self.file_system, root, params)?;
-      let locs: Vec<_> = self
+      let locs: Vec<_> = root
         .analysis
         .get_ty_defs(pos)
         .into_iter()
@@ -193,34 +341,181 @@ impl State {
       let path = url_to_path_id(&self.file_system, root, &url)?;
       let range = analysis_range(params.range);
       let mut actions = Vec::<lsp_types::CodeActionOrCommand>::new();
-      if let Some((range, new_text)) = self.analysis.fill_case(path.wrap(range.start)) {
-        actions.push(lsp_types::CodeActionOrCommand::CodeAction(
-          lsp_types::CodeAction {
-            title: "Fill case".to_owned(),
-            kind: Some(lsp_types::CodeActionKind::QUICKFIX),
-            edit: Some(lsp_types::WorkspaceEdit {
-              document_changes: Some(lsp_types::DocumentChanges::Edits(vec![
-                lsp_types::TextDocumentEdit {
-                  text_document: lsp_types::OptionalVersionedTextDocumentIdentifier {
-                    uri: url,
-                    version: None,
-                  },
-                  edits: vec![lsp_types::OneOf::Left(lsp_types::TextEdit {
-                    range: lsp_range(range),
-                    new_text,
-                  })],
-                },
-              ])),
-              ..Default::default()
-            }),
-            ..Default::default()
-          },
+      if let Some((range, new_text)) = root.analysis.fill_case(path.wrap(range.start)) {
+        actions.push(quick_fix(
+          "Fill case".to_owned(),
+          url.clone(),
+          range,
+          new_text,
+        ));
+      }
+      if let Some((range, new_text)) = root.analysis.fill_missing_arms(path.wrap(range.start)) {
+        actions.push(quick_fix(
+          "Add missing arms".to_owned(),
+          url.clone(),
+          range,
+          new_text,
         ));
       }
+      if let Some((range, fills)) = root.analysis.fill_hole(path.wrap(range.start)) {
+        for new_text in fills {
+          actions.push(quick_fix(
+            format!("Fill hole with `{new_text}`"),
+            url.clone(),
+            range,
+            new_text,
+          ));
+        }
+      }
       self.send_response(Response::new_ok(id, actions));
       Ok(())
     })?;
     // TODO do CodeActionResolveRequest and lazily compute the edit
+    r = try_request::<lsp_types::request::InlayHintRequest, _>(r, |id, params| {
+      let path = url_to_path_id(&self.file_system, root, &params.text_document.uri)?;
+      let range = path.wrap(analysis_range(params.range));
+      let res: Vec<_> = root
+        .analysis
+        .get_inlay_hints(range, self.options.inlay_hints)
+        .into_iter()
+        .flatten()
+        .map(|hint| lsp_types::InlayHint {
+          position: lsp_position(hint.position),
+          label: lsp_types::InlayHintLabel::String(hint.label),
+          kind: Some(lsp_types::InlayHintKind::TYPE),
+          text_edits: None,
+          tooltip: None,
+          padding_left: None,
+          padding_right: None,
+          data: None,
+        })
+        .collect();
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::SignatureHelpRequest, _>(r, |id, params| {
+      let params = params.text_document_position_params;
+      let pos = text_doc_pos_params(&self.file_system, root, params)?;
+      let res = root.analysis.get_signature_help(pos).map(|help| {
+        let parameters = help
+          .params
+          .iter()
+          .map(|param| lsp_types::ParameterInformation {
+            label: lsp_types::ParameterLabel::LabelOffsets([
+              utf16_len(&help.label[..param.start]),
+              utf16_len(&help.label[..param.end]),
+            ]),
+            documentation: None,
+          })
+          .collect();
+        let documentation = help.doc.map(|value| {
+          lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+            kind: lsp_types::MarkupKind::Markdown,
+            value,
+          })
+        });
+        let active_parameter = help.active_param.and_then(|x| u32::try_from(x).ok());
+        lsp_types::SignatureHelp {
+          signatures: vec![lsp_types::SignatureInformation {
+            label: help.label,
+            documentation,
+            parameters: Some(parameters),
+            active_parameter,
+          }],
+          active_signature: Some(0),
+          active_parameter,
+        }
+      });
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::SemanticTokensFullRequest, _>(r, |id, params| {
+      let path = url_to_path_id(&self.file_system, root, &params.text_document.uri)?;
+      let res = root
+        .analysis
+        .get_semantic_tokens(path, None)
+        .map(|toks| lsp_types::SemanticTokensResult::Tokens(semantic_tokens(toks)));
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::SemanticTokensRangeRequest, _>(r, |id, params| {
+      let path = url_to_path_id(&self.file_system, root, &params.text_document.uri)?;
+      let range = analysis_range(params.range);
+      let res = root
+        .analysis
+        .get_semantic_tokens(path, Some(range))
+        .map(|toks| lsp_types::SemanticTokensRangeResult::Tokens(semantic_tokens(toks)));
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::CallHierarchyPrepare, _>(r, |id, params| {
+      let params = params.text_document_position_params;
+      let pos = text_doc_pos_params(&self.file_system, root, params)?;
+      let res = root
+        .analysis
+        .prepare_call_hierarchy(pos)
+        .and_then(|item| call_hierarchy_item(root, item))
+        .map(|item| vec![item]);
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::CallHierarchyIncomingCalls, _>(r, |id, params| {
+      let item = params.item;
+      let path = url_to_path_id(&self.file_system, root, &item.uri)?;
+      let pos = path.wrap(analysis_position(item.selection_range.start));
+      let res = root.analysis.get_incoming_calls(pos).map(|calls| {
+        calls
+          .into_iter()
+          .filter_map(|call| {
+            Some(lsp_types::CallHierarchyIncomingCall {
+              from: call_hierarchy_item(root, call.from)?,
+              from_ranges: call.from_ranges.into_iter().map(lsp_range).collect(),
+            })
+          })
+          .collect::<Vec<_>>()
+      });
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::CallHierarchyOutgoingCalls, _>(r, |id, params| {
+      let item = params.item;
+      let path = url_to_path_id(&self.file_system, root, &item.uri)?;
+      let pos = path.wrap(analysis_position(item.selection_range.start));
+      let res = root.analysis.get_outgoing_calls(pos).map(|calls| {
+        calls
+          .into_iter()
+          .filter_map(|call| {
+            Some(lsp_types::CallHierarchyOutgoingCall {
+              to: call_hierarchy_item(root, call.to)?,
+              from_ranges: call.from_ranges.into_iter().map(lsp_range).collect(),
+            })
+          })
+          .collect::<Vec<_>>()
+      });
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::FoldingRangeRequest, _>(r, |id, params| {
+      let path = url_to_path_id(&self.file_system, root, &params.text_document.uri)?;
+      let res = root.analysis.get_folding_ranges(path).map(folding_ranges);
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+    r = try_request::<lsp_types::request::SelectionRangeRequest, _>(r, |id, params| {
+      let path = url_to_path_id(&self.file_system, root, &params.text_document.uri)?;
+      let res: Vec<_> = params
+        .positions
+        .into_iter()
+        .map(|pos| {
+          let ranges = self
+            .analysis
+            .get_selection_ranges(path.wrap(analysis_position(pos)));
+          selection_range(ranges.unwrap_or_default())
+        })
+        .collect();
+      self.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
     ControlFlow::Continue(r)
   }
 
@@ -243,35 +538,52 @@ impl State {
 
   fn handle_notification_(&mut self, mut n: Notification) -> ControlFlow<Result<()>, Notification> {
     n = try_notification::<lsp_types::notification::DidChangeWatchedFiles, _>(n, |_| {
-      if self.root.is_none() {
-        return;
-      }
       self.publish_diagnostics();
     })?;
+    n = try_notification::<lsp_types::notification::DidChangeWorkspaceFolders, _>(n, |params| {
+      for folder in params.event.removed {
+        self.remove_root(&folder.uri);
+      }
+      for folder in params.event.added {
+        self.add_root(folder.uri);
+      }
+    })?;
     n = try_notification::<lsp_types::notification::DidOpenTextDocument, _>(n, |params| {
-      if self.root.is_some() {
+      if !self.roots.is_empty() {
         return;
       }
       let url = params.text_document.uri;
       let text = params.text_document.text;
-      self.publish_diagnostics_one(url, &text);
+      self.publish_diagnostics_one(url.clone(), &text);
+      self.open_files.insert(url, text);
     })?;
     n = try_notification::<lsp_types::notification::DidSaveTextDocument, _>(n, |params| {
-      if self.root.is_some() {
+      if !self.roots.is_empty() {
         return;
       }
       let url = params.text_document.uri;
       if let Some(text) = params.text {
-        self.publish_diagnostics_one(url, &text);
+        self.publish_diagnostics_one(url.clone(), &text);
+        self.open_files.insert(url, text);
       }
     })?;
     n = try_notification::<lsp_types::notification::DidCloseTextDocument, _>(n, |params| {
-      if self.root.is_some() {
+      if !self.roots.is_empty() {
         return;
       }
       let url = params.text_document.uri;
+      self.open_files.remove(&url);
       self.send_diagnostics(url, Vec::new());
     })?;
+    n = try_notification::<lsp_types::notification::DidChangeConfiguration, _>(n, |mut params| {
+      // the client sends all of its settings under the "millet" section.
+      let options = params
+        .settings
+        .get_mut("millet")
+        .and_then(|x| x.get_mut("server"))
+        .map(serde_json::Value::take);
+      self.options = get_options(options);
+    })?;
     ControlFlow::Continue(n)
   }
 
@@ -289,11 +601,15 @@ impl State {
 
   // diagnostics //
 
-  fn publish_diagnostics(&mut self) -> bool {
-    let mut root = match self.root.take() {
-      Some(x) => x,
-      None => return false,
-    };
+  fn publish_diagnostics(&mut self) {
+    let mut roots = std::mem::take(&mut self.roots);
+    for root in roots.iter_mut() {
+      self.publish_root_diagnostics(root);
+    }
+    self.roots = roots;
+  }
+
+  fn publish_root_diagnostics(&mut self, root: &mut Root) {
     let mut has_diagnostics = FxHashSet::<Url>::default();
     let input = elapsed::log("input::get", || {
       analysis::input::get(&self.file_system, &mut root.input)
@@ -319,11 +635,17 @@ impl State {
         if !did_send_as_diagnostic {
           self.show_error(format!("{}: {}", e.path().display(), e));
         }
-        self.root = Some(root);
-        return false;
+        return;
       }
     };
-    let got_many = elapsed::log("get_many", || self.analysis.get_many(&input));
+    let mut got_many = elapsed::log("get_many", || root.analysis.get_many(&input));
+    if self.options.dead_code {
+      let unlisted =
+        analysis::input::get_unlisted_sources(&self.file_system, &mut root.input, &input);
+      for (path, errors) in root.analysis.get_dead_code(&input, &unlisted) {
+        got_many.entry(path).or_default().extend(errors);
+      }
+    }
     for (path_id, errors) in got_many {
       let path = root.input.as_paths().get_path(path_id);
       let url = match file_url(path.as_path()) {
@@ -333,7 +655,7 @@ impl State {
           continue;
         }
       };
-      let ds = diagnostics(errors);
+      let ds = diagnostics(&url, errors);
       if ds.is_empty() || has_diagnostics.len() >= MAX_FILES_WITH_ERRORS {
         continue;
       }
@@ -341,7 +663,7 @@ impl State {
       self.send_diagnostics(url, ds);
     }
     // this is the old one.
-    for url in root.has_diagnostics {
+    for url in std::mem::take(&mut root.has_diagnostics) {
       // this is the new one.
       if has_diagnostics.contains(&url) {
         // had old diagnostics, and has new diagnostics. we just sent the new ones.
@@ -351,12 +673,11 @@ impl State {
       self.send_diagnostics(url, Vec::new());
     }
     root.has_diagnostics = has_diagnostics;
-    self.root = Some(root);
-    true
   }
 
   fn publish_diagnostics_one(&mut self, url: Url, text: &str) {
-    self.send_diagnostics(url, diagnostics(self.analysis.get_one(text)));
+    let ds = diagnostics(&url, self.analysis.get_one(text));
+    self.send_diagnostics(url, ds);
   }
 
   fn send_diagnostics(&mut self, url: Url, diagnostics: Vec<lsp_types::Diagnostic>) {
@@ -370,6 +691,17 @@ impl State {
   }
 }
 
+fn get_options(value: Option<serde_json::Value>) -> config::Options {
+  match value.map(serde_json::from_value) {
+    None => config::Options::default(),
+    Some(Ok(x)) => x,
+    Some(Err(e)) => {
+      log::warn!("invalid options: {e}");
+      config::Options::default()
+    }
+  }
+}
+
 fn try_request<R, F>(req: Request, f: F) -> ControlFlow<Result<()>, Request>
 where
   R: lsp_types::request::Request,
@@ -417,33 +749,94 @@ where
   }
 }
 
+fn watcher_registration_id(url: &Url) -> String {
+  format!(
+    "{}/{}",
+    lsp_types::notification::DidChangeWatchedFiles::METHOD,
+    url
+  )
+}
+
 fn file_url(path: &std::path::Path) -> Result<Url> {
   Url::parse(&format!("file://{}", path.display())).with_context(|| "couldn't parse URL")
 }
 
-fn diagnostics(errors: Vec<analysis::Error>) -> Vec<lsp_types::Diagnostic> {
+fn diagnostics(url: &Url, errors: Vec<analysis::Error>) -> Vec<lsp_types::Diagnostic> {
   errors
     .into_iter()
-    .map(|err| diagnostic(err.message, Some(err.range), err.code))
+    .map(|err| {
+      let mut ret = diagnostic(err.message, Some(err.range), err.code);
+      if !err.related.is_empty() {
+        let related =
+          err
+            .related
+            .into_iter()
+            .map(|(range, message)| lsp_types::DiagnosticRelatedInformation {
+              location: lsp_types::Location {
+                uri: url.clone(),
+                range: lsp_range(range),
+              },
+              message,
+            });
+        ret.related_information = Some(related.collect());
+      }
+      ret
+    })
     .collect()
 }
 
 fn diagnostic(message: String, range: Option<analysis::Range>, code: u16) -> lsp_types::Diagnostic {
   let href =
     Url::parse(&format!("{}#{}", analysis::ERRORS_URL, code)).expect("couldn't parse error URL");
+  // 6xxx are for unused items, which aren't really errors.
+  let unused = (6000..7000).contains(&code);
+  let (severity, tags) = if unused {
+    let tags = vec![lsp_types::DiagnosticTag::UNNECESSARY];
+    (lsp_types::DiagnosticSeverity::WARNING, Some(tags))
+  } else {
+    (lsp_types::DiagnosticSeverity::ERROR, None)
+  };
   lsp_types::Diagnostic {
     range: range.map(lsp_range).unwrap_or_default(),
-    severity: Some(lsp_types::DiagnosticSeverity::ERROR),
+    severity: Some(severity),
     code: Some(lsp_types::NumberOrString::Number(code.into())),
     code_description: Some(lsp_types::CodeDescription { href }),
     source: Some("millet".to_owned()),
     message,
     related_information: None,
-    tags: None,
+    tags,
     data: None,
   }
 }
 
+fn quick_fix(
+  title: String,
+  url: Url,
+  range: analysis::Range,
+  new_text: String,
+) -> lsp_types::CodeActionOrCommand {
+  lsp_types::CodeActionOrCommand::CodeAction(lsp_types::CodeAction {
+    title,
+    kind: Some(lsp_types::CodeActionKind::QUICKFIX),
+    edit: Some(lsp_types::WorkspaceEdit {
+      document_changes: Some(lsp_types::DocumentChanges::Edits(vec![
+        lsp_types::TextDocumentEdit {
+          text_document: lsp_types::OptionalVersionedTextDocumentIdentifier {
+            uri: url,
+            version: None,
+          },
+          edits: vec![lsp_types::OneOf::Left(lsp_types::TextEdit {
+            range: lsp_range(range),
+            new_text,
+          })],
+        },
+      ])),
+      ..Default::default()
+    }),
+    ..Default::default()
+  })
+}
+
 fn lsp_range(range: analysis::Range) -> lsp_types::Range {
   lsp_types::Range {
     start: lsp_position(range.start),
@@ -475,6 +868,120 @@ fn lsp_location(
   })
 }
 
+fn call_hierarchy_item(
+  root: &Root,
+  item: analysis::CallHierarchyItem,
+) -> Option<lsp_types::CallHierarchyItem> {
+  let uri = match file_url(root.input.as_paths().get_path(item.path).as_path()) {
+    Ok(x) => x,
+    Err(e) => {
+      log::error!("{e:#}");
+      return None;
+    }
+  };
+  let kind = match item.kind {
+    analysis::CallHierarchyItemKind::Fun => lsp_types::SymbolKind::FUNCTION,
+    analysis::CallHierarchyItemKind::Val => lsp_types::SymbolKind::VARIABLE,
+    analysis::CallHierarchyItemKind::Structure => lsp_types::SymbolKind::MODULE,
+    analysis::CallHierarchyItemKind::Functor => lsp_types::SymbolKind::CLASS,
+  };
+  Some(lsp_types::CallHierarchyItem {
+    name: item.name,
+    kind,
+    tags: None,
+    detail: None,
+    uri,
+    range: lsp_range(item.range),
+    selection_range: lsp_range(item.name_range),
+    data: None,
+  })
+}
+
+fn folding_ranges(ranges: Vec<analysis::FoldingRange>) -> Vec<lsp_types::FoldingRange> {
+  ranges
+    .into_iter()
+    .map(|x| lsp_types::FoldingRange {
+      start_line: x.range.start.line,
+      start_character: None,
+      end_line: x.range.end.line,
+      end_character: None,
+      kind: Some(match x.kind {
+        analysis::FoldingRangeKind::Region => lsp_types::FoldingRangeKind::Region,
+        analysis::FoldingRangeKind::Comment => lsp_types::FoldingRangeKind::Comment,
+        analysis::FoldingRangeKind::Imports => lsp_types::FoldingRangeKind::Imports,
+      }),
+    })
+    .collect()
+}
+
+/// the ranges go from innermost to outermost.
+fn selection_range(ranges: Vec<analysis::Range>) -> lsp_types::SelectionRange {
+  let mut ret = None::<lsp_types::SelectionRange>;
+  for range in ranges.into_iter().rev() {
+    ret = Some(lsp_types::SelectionRange {
+      range: lsp_range(range),
+      parent: ret.map(Box::new),
+    });
+  }
+  ret.unwrap_or(lsp_types::SelectionRange {
+    range: lsp_types::Range::default(),
+    parent: None,
+  })
+}
+
+fn token_type(kind: analysis::SemanticTokenKind) -> u32 {
+  match kind {
+    analysis::SemanticTokenKind::Val => 0,
+    analysis::SemanticTokenKind::Con => 1,
+    analysis::SemanticTokenKind::Exn => 2,
+    analysis::SemanticTokenKind::Ty => 3,
+    analysis::SemanticTokenKind::TyVar => 4,
+    analysis::SemanticTokenKind::Structure => 5,
+    analysis::SemanticTokenKind::Signature => 6,
+    analysis::SemanticTokenKind::Functor => 7,
+    analysis::SemanticTokenKind::Infix => 8,
+  }
+}
+
+/// the tokens must be sorted by position. lsp wants each token relative to the previous one.
+fn semantic_tokens(toks: Vec<analysis::SemanticToken>) -> lsp_types::SemanticTokens {
+  let mut prev = analysis::Position {
+    line: 0,
+    character: 0,
+  };
+  let data = toks
+    .into_iter()
+    .filter(|tok| tok.range.start.line == tok.range.end.line)
+    .map(|tok| {
+      let start = tok.range.start;
+      let delta_line = start.line - prev.line;
+      let delta_start = if delta_line == 0 {
+        start.character - prev.character
+      } else {
+        start.character
+      };
+      let ret = lsp_types::SemanticToken {
+        delta_line,
+        delta_start,
+        length: tok.range.end.character - start.character,
+        token_type: token_type(tok.kind),
+        token_modifiers_bitset: u32::from(tok.definition) | (u32::from(tok.std_basis) << 1),
+      };
+      prev = start;
+      ret
+    })
+    .collect();
+  lsp_types::SemanticTokens {
+    result_id: None,
+    data,
+  }
+}
+
+/// lsp positions and offsets are in utf-16 code units.
+fn utf16_len(s: &str) -> u32 {
+  s.encode_utf16().count().try_into().unwrap_or(u32::MAX)
+}
+
 fn analysis_position(pos: lsp_types::Position) -> analysis::Position {
   analysis::Position {
     line: pos.line,
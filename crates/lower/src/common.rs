@@ -14,7 +14,6 @@ pub(crate) fn get_scon(cx: &mut Cx, scon: ast::SCon) -> Option<hir::SCon> {
       if neg {
         chars.next();
       }
-      let mul = if neg { -1 } else { 1 };
       let radix: u32 = if chars.as_str().starts_with("0x") {
         chars.next();
         chars.next();
@@ -22,17 +21,7 @@ pub(crate) fn get_scon(cx: &mut Cx, scon: ast::SCon) -> Option<hir::SCon> {
       } else {
         10
       };
-      let n = match i32::from_str_radix(chars.as_str(), radix) {
-        Ok(x) => hir::Int::Finite(mul * x),
-        Err(_) => match hir::BigInt::from_str_radix(chars.as_str(), radix) {
-          Ok(x) => hir::Int::Big(x),
-          Err(e) => {
-            cx.err(tok.text_range(), ErrorKind::InvalidBigIntLit(e));
-            hir::Int::Finite(0)
-          }
-        },
-      };
-      hir::SCon::Int(n)
+      hir::SCon::Int(get_int(cx, &tok, neg, chars.as_str(), radix))
     }
     ast::SConKind::RealLit => {
       let owned: String;
@@ -63,14 +52,7 @@ pub(crate) fn get_scon(cx: &mut Cx, scon: ast::SCon) -> Option<hir::SCon> {
       } else {
         10
       };
-      let n = match u32::from_str_radix(chars.as_str(), radix) {
-        Ok(x) => x,
-        Err(e) => {
-          cx.err(tok.text_range(), ErrorKind::InvalidIntLit(e));
-          0
-        }
-      };
-      hir::SCon::Word(n)
+      hir::SCon::Word(get_int(cx, &tok, false, chars.as_str(), radix))
     }
     ast::SConKind::CharLit => hir::SCon::Char(
       tok
@@ -87,6 +69,27 @@ pub(crate) fn get_scon(cx: &mut Cx, scon: ast::SCon) -> Option<hir::SCon> {
   Some(ret)
 }
 
+/// keeps the full precision of the literal, since its type (and therefore its range) is only known
+/// after statics.
+fn get_int(
+  cx: &mut Cx,
+  tok: &syntax::SyntaxToken,
+  neg: bool,
+  digits: &str,
+  radix: u32,
+) -> hir::Int {
+  match i32::from_str_radix(digits, radix) {
+    Ok(x) => hir::Int::Finite(if neg { -x } else { x }),
+    Err(_) => match hir::BigInt::from_str_radix(digits, radix) {
+      Ok(x) => hir::Int::Big(if neg { -x } else { x }),
+      Err(e) => {
+        cx.err(tok.text_range(), ErrorKind::InvalidBigIntLit(e));
+        hir::Int::Finite(0)
+      }
+    },
+  }
+}
+
 pub(crate) fn get_name(n: Option<syntax::SyntaxToken>) -> Option<hir::Name> {
   n.map(|tok| hir::Name::new(tok.text()))
 }
@@ -1,10 +1,24 @@
 use crate::top_dec;
 use crate::util::{Cx, Lower};
-use syntax::ast;
+use syntax::ast::{self, AstNode as _, SyntaxNodePtr};
 
 /// Does the conversion.
 pub fn get(root: &ast::Root) -> Lower {
   let mut cx = Cx::default();
-  let idx = top_dec::get_str_dec(&mut cx, root.str_dec());
+  // usually there is one str dec, but after some syntax errors there may be more.
+  let mut str_decs: Vec<_> = root
+    .syntax()
+    .children()
+    .filter_map(ast::StrDec::cast)
+    .map(|str_dec| top_dec::get_str_dec(&mut cx, Some(str_dec)))
+    .collect();
+  let idx = if str_decs.len() <= 1 {
+    str_decs.pop().flatten()
+  } else {
+    cx.str_dec(
+      hir::StrDec::Seq(str_decs),
+      SyntaxNodePtr::new(root.syntax()),
+    )
+  };
   cx.finish(idx)
 }
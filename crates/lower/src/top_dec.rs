@@ -54,33 +54,77 @@ fn get_str_dec_one(cx: &mut Cx, str_dec: ast::StrDecOne) -> hir::StrDecIdx {
         .functor_binds()
         .filter_map(|fun_bind| {
           let functor_name = get_name(fun_bind.functor_name())?;
-          let body = with_ascription_tail(cx, fun_bind.body(), fun_bind.ascription_tail());
-          let (param_name, param_sig, body) = match fun_bind.functor_arg()? {
-            ast::FunctorArg::FunctorArgNameSigExp(arg) => {
-              (get_name(arg.name())?, get_sig_exp(cx, arg.sig_exp()), body)
-            }
-            ast::FunctorArg::Spec(arg) => {
-              let param_name = cx.fresh();
-              let param_sig = hir::SigExp::Spec(get_spec(cx, Some(arg)));
-              let param_sig = cx.sig_exp(param_sig, ptr.clone());
-              let dec = cx.dec(
-                hir::Dec::Open(vec![hir::Path::one(param_name.clone())]),
-                ptr.clone(),
-              );
-              let str_dec = cx.str_dec(hir::StrDec::Dec(dec), ptr.clone());
-              let body = cx.str_exp(hir::StrExp::Let(str_dec, body), ptr.clone());
-              (param_name, param_sig, body)
-            }
-          };
+          let mut body = with_ascription_tail(cx, fun_bind.body(), fun_bind.ascription_tail());
+          let mut params = Vec::<(hir::Name, hir::FunctorParam)>::new();
+          // the generated names of the params given as specs, which are opened for the body.
+          let mut opens = Vec::<hir::Path>::new();
+          for paren in fun_bind.functor_arg_parens() {
+            let param = match paren.functor_arg()? {
+              ast::FunctorArg::FunctorArgNameSigExp(arg) => {
+                let param = hir::FunctorParam::Structure(get_sig_exp(cx, arg.sig_exp()));
+                (get_name(arg.name())?, param)
+              }
+              ast::FunctorArg::FunctorArgFunctor(arg) => {
+                let param = hir::FunctorParam::Functor(get_name(arg.fun_sig_name()?.name())?);
+                (get_name(arg.name())?, param)
+              }
+              ast::FunctorArg::Spec(arg) => {
+                let param_name = cx.fresh();
+                let param_sig = hir::SigExp::Spec(get_spec(cx, Some(arg)));
+                let param_sig = cx.sig_exp(param_sig, ptr.clone());
+                opens.push(hir::Path::one(param_name.clone()));
+                (param_name, hir::FunctorParam::Structure(param_sig))
+              }
+            };
+            params.push(param);
+          }
+          if !opens.is_empty() {
+            let dec = cx.dec(hir::Dec::Open(opens), ptr.clone());
+            let str_dec = cx.str_dec(hir::StrDec::Dec(dec), ptr.clone());
+            body = cx.str_exp(hir::StrExp::Let(str_dec, body), ptr.clone());
+          }
           Some(hir::FunctorBind {
             functor_name,
-            param_name,
-            param_sig,
+            params,
             body,
           })
         })
         .collect(),
     ),
+    ast::StrDecOne::FunSigDec(str_dec) => hir::StrDec::FunSig(
+      str_dec
+        .fun_sig_binds()
+        .filter_map(|fun_sig_bind| {
+          let mut params = Vec::<(hir::Name, hir::FunctorParam)>::new();
+          for paren in fun_sig_bind.functor_arg_parens() {
+            let param = match paren.functor_arg()? {
+              ast::FunctorArg::FunctorArgNameSigExp(arg) => {
+                let param = hir::FunctorParam::Structure(get_sig_exp(cx, arg.sig_exp()));
+                (get_name(arg.name())?, param)
+              }
+              ast::FunctorArg::FunctorArgFunctor(arg) => {
+                let param = hir::FunctorParam::Functor(get_name(arg.fun_sig_name()?.name())?);
+                (get_name(arg.name())?, param)
+              }
+              // unlike for functors, there's no way to open the argument in a signature expression.
+              ast::FunctorArg::Spec(_) => {
+                cx.err(
+                  fun_sig_bind.syntax().text_range(),
+                  ErrorKind::Unsupported("`funsig` with a specification argument"),
+                );
+                return None;
+              }
+            };
+            params.push(param);
+          }
+          Some(hir::FunSigBind {
+            name: get_name(fun_sig_bind.name())?,
+            params,
+            body: get_sig_exp(cx, fun_sig_bind.sig_exp()),
+          })
+        })
+        .collect(),
+    ),
   };
   cx.str_dec(res, ptr)
 }
@@ -95,16 +139,22 @@ fn get_str_exp(cx: &mut Cx, str_exp: Option<ast::StrExp>) -> hir::StrExpIdx {
       let (kind, sig_exp) = ascription_tail(cx, str_exp.ascription_tail());
       hir::StrExp::Ascription(get_str_exp(cx, str_exp.str_exp()), kind, sig_exp)
     }
-    ast::StrExp::AppStrExp(str_exp) => hir::StrExp::App(
-      get_name(str_exp.name())?,
-      match str_exp.app_str_exp_arg()? {
-        ast::AppStrExpArg::AppStrExpArgStrExp(arg) => get_str_exp(cx, arg.str_exp()),
-        ast::AppStrExpArg::StrDec(arg) => {
-          let sd = get_str_dec(cx, Some(arg));
-          cx.str_exp(hir::StrExp::Struct(sd), ptr.clone())
-        }
-      },
-    ),
+    ast::StrExp::AppStrExp(str_exp) => {
+      let args: Option<Vec<_>> = str_exp
+        .app_str_exp_arg_parens()
+        .map(|paren| {
+          let arg = match paren.app_str_exp_arg()? {
+            ast::AppStrExpArg::AppStrExpArgStrExp(arg) => get_str_exp(cx, arg.str_exp()),
+            ast::AppStrExpArg::StrDec(arg) => {
+              let sd = get_str_dec(cx, Some(arg));
+              cx.str_exp(hir::StrExp::Struct(sd), ptr.clone())
+            }
+          };
+          Some(arg)
+        })
+        .collect();
+      hir::StrExp::App(get_name(str_exp.name())?, args?)
+    }
     ast::StrExp::LetStrExp(str_exp) => hir::StrExp::Let(
       get_str_dec(cx, str_exp.str_dec()),
       get_str_exp(cx, str_exp.str_exp()),
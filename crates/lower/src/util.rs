@@ -51,7 +51,7 @@ impl Error {
     match self.kind {
       ErrorKind::FunBindMismatchedName(_, _) => 4001,
       ErrorKind::FunBindWrongNumPats(_, _) => 4002,
-      ErrorKind::InvalidIntLit(_) | ErrorKind::InvalidBigIntLit(_) => 4003,
+      ErrorKind::InvalidBigIntLit(_) => 4003,
       ErrorKind::InvalidRealLit(_) => 4004,
       ErrorKind::InvalidNumLab(_) | ErrorKind::ZeroNumLab => 4005,
       ErrorKind::MultipleRestPatRows => 4006,
@@ -67,7 +67,6 @@ impl Error {
 pub(crate) enum ErrorKind {
   FunBindMismatchedName(String, String),
   FunBindWrongNumPats(usize, usize),
-  InvalidIntLit(std::num::ParseIntError),
   InvalidBigIntLit(hir::ParseBigIntError),
   InvalidRealLit(std::num::ParseFloatError),
   InvalidNumLab(std::num::ParseIntError),
@@ -92,7 +91,6 @@ impl fmt::Display for ErrorKind {
       ErrorKind::FunBindWrongNumPats(want, got) => {
         write!(f, "expected {want} patterns, found {got}")
       }
-      ErrorKind::InvalidIntLit(e) => write!(f, "invalid literal: {e}"),
       ErrorKind::InvalidBigIntLit(e) => write!(f, "invalid literal: {e}"),
       ErrorKind::InvalidRealLit(e) => write!(f, "invalid literal: {e}"),
       ErrorKind::InvalidNumLab(e) => write!(f, "invalid numeric label: {e}"),
@@ -2,7 +2,7 @@
 
 #![deny(missing_debug_implementations, rust_2018_idioms)]
 
-use text_size_util::WithRange;
+use text_size_util::{TextRange, WithRange};
 
 #[derive(Debug, Clone)]
 pub enum BasDec {
@@ -10,6 +10,8 @@ pub enum BasDec {
   Open(WithRange<hir::Name>),
   Local(Box<BasDec>, Box<BasDec>),
   Export(Namespace, WithRange<hir::Name>, WithRange<hir::Name>),
+  /// SML/NJ extension, only from CM. Exports each name the export evaluates to as itself.
+  CmExport(CmExport),
   Seq(Vec<BasDec>),
   Path(paths::PathId, PathKind),
 }
@@ -24,6 +26,54 @@ impl BasDec {
   }
 }
 
+/// SML/NJ extension. The exports of a CM file.
+///
+/// The names exported by a source file depend on how it was parsed, which depends on the fixities
+/// declared by the files before it. So this is evaluated after the members of the CM file are
+/// checked.
+#[derive(Debug, Clone)]
+pub enum CmExport {
+  /// The name in the namespace.
+  Name(Namespace, WithRange<hir::Name>),
+  /// The structures, signatures, functors, and functor signatures declared at the top level of the
+  /// source file with the path. The range is where the file was exported.
+  Source(WithRange<paths::PathId>),
+  /// The names in any of these.
+  Union(Vec<CmExport>),
+  /// The names in the first but not the second.
+  Difference(Box<CmExport>, Box<CmExport>),
+  /// The names in both.
+  Intersection(Box<CmExport>, Box<CmExport>),
+}
+
+impl CmExport {
+  /// Returns this with every range replaced by `range`, as when another CM file re-exports it.
+  pub fn with_range(&self, range: TextRange) -> Self {
+    match self {
+      CmExport::Name(ns, name) => CmExport::Name(
+        *ns,
+        WithRange {
+          val: name.val.clone(),
+          range,
+        },
+      ),
+      CmExport::Source(path) => CmExport::Source(WithRange {
+        val: path.val,
+        range,
+      }),
+      CmExport::Union(exports) => {
+        CmExport::Union(exports.iter().map(|x| x.with_range(range)).collect())
+      }
+      CmExport::Difference(lhs, rhs) => {
+        CmExport::Difference(lhs.with_range(range).into(), rhs.with_range(range).into())
+      }
+      CmExport::Intersection(lhs, rhs) => {
+        CmExport::Intersection(lhs.with_range(range).into(), rhs.with_range(range).into())
+      }
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum BasExp {
   Bas(BasDec),
@@ -31,14 +81,16 @@ pub enum BasExp {
   Let(BasDec, Box<BasExp>),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Namespace {
   Structure,
   Signature,
   Functor,
+  /// SML/NJ extension, only from CM.
+  FunSig,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PathKind {
   Sml,
   Mlb,
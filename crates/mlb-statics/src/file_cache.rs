@@ -0,0 +1,136 @@
+use crate::std_basis::{
+  bincode_options, cache_dir, invalid_data, remove_stale, versioned_hasher, write_atomic,
+};
+use bincode::Options as _;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher as _};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A cache of the results of checking source files, kept on disk, so that unchanged files need not
+/// be checked again after a restart.
+///
+/// Each entry is for a source file included at some place in a group file. It is keyed by a hash of
+/// the contents of the file and of everything that could affect checking it, like the files checked
+/// before it. When that hash changes, the file is checked again and the entry is replaced.
+#[derive(Debug, Clone)]
+pub struct FileCache {
+  dir: PathBuf,
+  /// a hash of the version of this, mixed into every key.
+  salt: u64,
+}
+
+impl FileCache {
+  /// Returns a cache for the project at `root`, in the cache directory of the platform, or `None`
+  /// if there isn't one.
+  pub fn for_root(root: &Path) -> Option<Self> {
+    let dir = cache_dir()?
+      .join("files")
+      .join(format!("{:016x}", hash_of(root)));
+    Self::new(dir)
+  }
+
+  /// Returns a cache in `dir`, or `None` if the current executable could not be identified, since
+  /// entries written by one version must not be read by another. Also removes stale entries.
+  pub fn new(dir: PathBuf) -> Option<Self> {
+    let salt = versioned_hasher()?.finish();
+    if dir.exists() {
+      remove_stale(&dir, ENTRY_PREFIX, None);
+    }
+    Some(Self { dir, salt })
+  }
+
+  pub(crate) fn salt(&self) -> u64 {
+    self.salt
+  }
+
+  fn entry_path(&self, slot: u64) -> PathBuf {
+    self.dir.join(format!("{ENTRY_PREFIX}{slot:016x}.bin"))
+  }
+
+  /// Returns the entry in `slot`, if there is one and it is for `key`. Any error doing so is logged
+  /// and otherwise ignored.
+  pub(crate) fn get(&self, slot: u64, key: u64) -> Option<CachedFile> {
+    let path = self.entry_path(slot);
+    let file = match std::fs::File::open(&path) {
+      Ok(x) => x,
+      Err(e) => {
+        if e.kind() != io::ErrorKind::NotFound {
+          log::warn!("couldn't open {}: {e}", path.display());
+        }
+        return None;
+      }
+    };
+    let mut r = io::BufReader::new(file);
+    let entry_key: u64 = match bincode_options().deserialize_from(&mut r) {
+      Ok(x) => x,
+      Err(e) => {
+        log::warn!("couldn't read {}: {e}", path.display());
+        return None;
+      }
+    };
+    if entry_key != key {
+      // something that affects checking the file changed, so the entry will be replaced.
+      return None;
+    }
+    match bincode_options().deserialize_from(&mut r) {
+      Ok(x) => Some(x),
+      Err(e) => {
+        log::warn!("couldn't read {}: {e}", path.display());
+        None
+      }
+    }
+  }
+
+  /// Writes the entry for `key` in `slot`, replacing any old one. Any error doing so is logged and
+  /// otherwise ignored.
+  pub(crate) fn put(&self, slot: u64, key: u64, entry: &CachedFileRef<'_>) {
+    let path = self.entry_path(slot);
+    let res = std::fs::create_dir_all(&self.dir).and_then(|()| {
+      write_atomic(&path, |w| {
+        bincode_options()
+          .serialize_into(&mut *w, &key)
+          .map_err(invalid_data)?;
+        bincode_options()
+          .serialize_into(w, entry)
+          .map_err(invalid_data)
+      })
+    });
+    if let Err(e) = res {
+      log::warn!("couldn't write {}: {e}", path.display());
+    }
+  }
+}
+
+const ENTRY_PREFIX: &str = "file-";
+
+/// The result of checking a source file.
+#[derive(Serialize)]
+pub(crate) struct CachedFileRef<'a> {
+  /// the symbols generated by checking the file.
+  pub(crate) syms: &'a statics::SymsDelta,
+  pub(crate) basis: &'a statics::basis::Basis,
+  pub(crate) errors: statics::ErrorsReprRef<'a>,
+  pub(crate) info: &'a statics::Info,
+}
+
+/// A [`CachedFileRef`] read back.
+#[derive(Deserialize)]
+pub(crate) struct CachedFile {
+  pub(crate) syms: statics::SymsDelta,
+  pub(crate) basis: statics::basis::Basis,
+  pub(crate) errors: statics::ErrorsRepr,
+  pub(crate) info: statics::Info,
+}
+
+/// Returns a hash of `x` that is the same across runs of the same executable.
+pub(crate) fn hash_of<T>(x: T) -> u64
+where
+  T: Hash,
+{
+  // `DefaultHasher::new` always uses the same keys.
+  let mut hasher = DefaultHasher::new();
+  x.hash(&mut hasher);
+  hasher.finish()
+}
@@ -4,12 +4,18 @@
 
 pub mod doc_comment;
 
+mod file_cache;
 mod std_basis;
 
-use fast_hash::FxHashMap;
+use fast_hash::{FxHashMap, FxHashSet};
+use file_cache::hash_of;
+use std::collections::hash_map::Entry;
 use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use syntax::ast::AstNode;
 
+pub use file_cache::FileCache;
 pub use parse::parser::STD_BASIS as STD_BASIS_FIX_ENV;
 pub use std_basis::StdBasis;
 
@@ -36,6 +42,9 @@ pub struct SourceFile {
   pub lowered: lower::Lower,
   pub statics_errors: Vec<statics::Error>,
   pub info: statics::Info,
+  /// The later analyses of this file, if it was included more than once, as by two root groups.
+  /// Their errors should be reported too.
+  pub others: Vec<SourceFile>,
 }
 
 /// An error.
@@ -75,6 +84,7 @@ enum Item {
   Structure,
   Signature,
   Functor,
+  FunSig,
 }
 
 impl fmt::Display for Item {
@@ -84,21 +94,106 @@ impl fmt::Display for Item {
       Item::Structure => "structure",
       Item::Signature => "signature",
       Item::Functor => "functor",
+      Item::FunSig => "functor signature",
     };
     f.write_str(s)
   }
 }
 
-struct Cx {
+/// Something that happened while checking a group file, in the order it happened.
+enum Event {
+  /// A source file was checked.
+  Sml(paths::PathId, CheckedFile),
+  /// An error was found in the group file.
+  Error(Error),
+  /// A group file was referred to. If this is the first reference to it, the events from checking
+  /// it go here.
+  Group(paths::PathId),
+}
+
+/// A source file that was checked, possibly on another thread.
+struct CheckedFile {
+  lex_errors: Vec<lex::Error>,
+  /// the syntax tree can't be sent between threads, but its green node can.
+  green: syntax::rowan::GreenNode,
+  parse_errors: Vec<parse::parser::Error>,
+  lowered: lower::Lower,
+  statics_errors: Vec<statics::Error>,
+  info: statics::Info,
+}
+
+impl CheckedFile {
+  fn finish(self, contents: &str) -> SourceFile {
+    let root = syntax::SyntaxNode::new_root(self.green);
+    SourceFile {
+      pos_db: text_pos::PositionDb::new(contents),
+      lex_errors: self.lex_errors,
+      parsed: parse::Parse {
+        root: syntax::ast::Root::cast(root).expect("root of a parse should be a Root"),
+        errors: self.parse_errors,
+      },
+      lowered: self.lowered,
+      statics_errors: self.statics_errors,
+      info: self.info,
+      others: Vec::new(),
+    }
+  }
+}
+
+struct Cx<'a> {
   syms: statics::Syms,
-  cache: paths::PathMap<MBasis>,
-  sml: paths::PathMap<SourceFile>,
-  mlb_errors: Vec<Error>,
+  /// the segment of `syms` after the last one this may generate symbols in.
+  next_seg: usize,
+  /// a hash of everything checked so far that could affect what is checked next. see
+  /// [`FileCache`].
+  key: u64,
+  /// a hash of where in the group files this is, which doesn't change when their contents do.
+  slot: u64,
+  shared: Shared<'a>,
+  events: Vec<Event>,
+}
+
+/// What the contexts for checking a group file share.
+#[derive(Clone, Copy)]
+struct Shared<'a> {
+  /// the results of checking the group files that the group file being checked refers to.
+  groups: &'a paths::PathMap<Arc<CheckedGroup>>,
+  /// see [`start_source_files`].
+  started: &'a Mutex<paths::PathMap<StartedSourceFile>>,
+  parallel: bool,
 }
 
-impl Cx {
+impl<'a> Cx<'a> {
+  /// returns a new context for checking something that generates symbols in the `segments`.
+  fn new(
+    syms: &statics::Syms,
+    segments: std::ops::Range<usize>,
+    key: u64,
+    slot: u64,
+    shared: Shared<'a>,
+  ) -> Self {
+    let mut syms = syms.clone();
+    syms.start_segment(segments.start);
+    Self {
+      syms,
+      next_seg: segments.start + 1,
+      key: hash_of((key, segments.start)),
+      slot,
+      shared,
+      events: Vec::new(),
+    }
+  }
+
   fn undef(&mut self, path: paths::PathId, item: Item, name: text_size_util::WithRange<hir::Name>) {
-    self.mlb_errors.push(Error { path, item, name });
+    self.events.push(Event::Error(Error { path, item, name }));
+  }
+
+  /// changes the key to also be a hash of `x`.
+  fn fold<T>(&mut self, x: T)
+  where
+    T: std::hash::Hash,
+  {
+    self.key = hash_of((self.key, x));
   }
 }
 
@@ -107,6 +202,11 @@ struct Files<'a> {
   sml: &'a paths::PathMap<String>,
   mlb: &'a paths::PathMap<&'a mlb_hir::BasDec>,
   std_basis: &'a MBasis,
+  /// whether the SML/NJ extensions are allowed.
+  smlnj: bool,
+  cache: Option<&'a FileCache>,
+  /// a hash of everything besides the files that could affect checking them.
+  key: u64,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -125,19 +225,42 @@ impl MBasis {
   }
 }
 
+/// The result of checking a group file.
+struct CheckedGroup {
+  m_basis: MBasis,
+  /// the key of the context that checked the group file, after checking it.
+  key: u64,
+}
+
 /// Runs analysis.
+///
+/// Each group file is checked once, after the group files it refers to. Each group file, and each
+/// `basis` binding checked at once with others, generates symbols in its own segments of the
+/// [`statics::Syms`], decided before checking anything.
+///
+/// So if `parallel`, the source files are lexed, parsed, and lowered on many threads first, the
+/// group files that don't depend on each other are checked on different threads, and so are the
+/// `basis` bindings next to each other in a group file that don't refer to each other. The result
+/// is the same either way.
+///
+/// If there is a `cache`, the results of checking source files are read from it when nothing that
+/// could affect them changed, and written to it otherwise. The result is the same either way too.
 pub fn get(
   std_basis: &StdBasis,
   sml: &paths::PathMap<String>,
   mlb: &paths::PathMap<&mlb_hir::BasDec>,
-  root_mlb: paths::PathId,
+  root_mlbs: &[paths::PathId],
+  target: Option<config::Target>,
+  parallel: bool,
+  cache: Option<&FileCache>,
 ) -> MlbStatics {
-  let mut cx = Cx {
-    syms: std_basis.syms().clone(),
-    cache: paths::PathMap::default(),
-    sml: paths::PathMap::default(),
-    mlb_errors: Vec::new(),
-  };
+  let mut syms = std_basis.syms().clone();
+  statics::basis::set_target(&mut syms, std_basis.basis(), target);
+  let key = hash_of((
+    cache.map(FileCache::salt),
+    std_basis.key(),
+    target.map(|x| x as u8),
+  ));
   let std_basis = MBasis {
     fix_env: STD_BASIS_FIX_ENV.clone(),
     bas_env: FxHashMap::default(),
@@ -147,17 +270,382 @@ pub fn get(
     sml,
     mlb,
     std_basis: &std_basis,
+    smlnj: matches!(target, Some(config::Target::SmlNj)),
+    cache,
+    key,
+  };
+  let tasks = get_tasks(files, root_mlbs, syms.num_segments());
+  let started = if parallel {
+    start_source_files(sml)
+  } else {
+    paths::PathMap::default()
+  };
+  let started = Mutex::new(started);
+  let events = if parallel {
+    check_tasks_parallel(files, &tasks, &mut syms, &started)
+  } else {
+    check_tasks(files, &tasks, &mut syms, &started)
+  };
+  let mut ret = MlbStatics {
+    mlb_errors: Vec::new(),
+    syms,
+    sml: paths::PathMap::default(),
+  };
+  let task_idx: paths::PathMap<_> = tasks
+    .iter()
+    .enumerate()
+    .map(|(idx, task)| (task.path, idx))
+    .collect();
+  let mut events: Vec<_> = events.into_iter().map(Some).collect();
+  for &root in root_mlbs {
+    add_events(&mut ret, files, &task_idx, &mut events, root);
+  }
+  ret
+}
+
+/// A group file to check.
+struct Task {
+  path: paths::PathId,
+  /// the indices of the tasks for the group files this refers to. they come before this.
+  deps: Vec<usize>,
+  /// the segments of the syms this generates symbols in.
+  segments: std::ops::Range<usize>,
+}
+
+/// Returns the tasks for the group files reachable from the roots. Each comes after the tasks it
+/// depends on. The first segment of the syms for the tasks is `seg`.
+fn get_tasks(files: Files<'_>, root_mlbs: &[paths::PathId], mut seg: usize) -> Vec<Task> {
+  let mut ret = Vec::<Task>::new();
+  let mut task_idx = paths::PathMap::<Option<usize>>::default();
+  for &root in root_mlbs {
+    add_task(files, &mut ret, &mut task_idx, &mut seg, root);
+  }
+  ret
+}
+
+/// the index in `task_idx` is `None` while the tasks the group file depends on are being added.
+fn add_task(
+  files: Files<'_>,
+  ac: &mut Vec<Task>,
+  task_idx: &mut paths::PathMap<Option<usize>>,
+  seg: &mut usize,
+  path: paths::PathId,
+) -> Option<usize> {
+  if let Some(&idx) = task_idx.get(&path) {
+    // if it's `None`, there's a cycle, which getting the input already reported.
+    return idx;
+  }
+  task_idx.insert(path, None);
+  let dec = files.mlb.get(&path).expect("no mlb file for path id");
+  let mut group_paths = Vec::<paths::PathId>::new();
+  bas_dec_group_paths(&mut group_paths, dec);
+  let deps: Vec<_> = group_paths
+    .into_iter()
+    .filter_map(|path| add_task(files, ac, task_idx, seg, path))
+    .collect();
+  let start = *seg;
+  *seg += 1 + bas_dec_extra_segments(dec);
+  let ret = ac.len();
+  ac.push(Task {
+    path,
+    deps,
+    segments: start..*seg,
+  });
+  task_idx.insert(path, Some(ret));
+  Some(ret)
+}
+
+/// adds the paths of the group files the `dec` refers to, without duplicates.
+fn bas_dec_group_paths(ac: &mut Vec<paths::PathId>, dec: &mlb_hir::BasDec) {
+  match dec {
+    mlb_hir::BasDec::Basis(_, exp) => bas_exp_group_paths(ac, exp),
+    mlb_hir::BasDec::Open(_) | mlb_hir::BasDec::Export(..) | mlb_hir::BasDec::CmExport(_) => {}
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      bas_dec_group_paths(ac, local_dec);
+      bas_dec_group_paths(ac, in_dec);
+    }
+    mlb_hir::BasDec::Seq(decs) => {
+      for dec in decs {
+        bas_dec_group_paths(ac, dec);
+      }
+    }
+    mlb_hir::BasDec::Path(path, kind) => {
+      if matches!(kind, mlb_hir::PathKind::Mlb) && !ac.contains(path) {
+        ac.push(*path);
+      }
+    }
+  }
+}
+
+fn bas_exp_group_paths(ac: &mut Vec<paths::PathId>, exp: &mlb_hir::BasExp) {
+  match exp {
+    mlb_hir::BasExp::Bas(dec) => bas_dec_group_paths(ac, dec),
+    mlb_hir::BasExp::Name(_) => {}
+    mlb_hir::BasExp::Let(dec, exp) => {
+      bas_dec_group_paths(ac, dec);
+      bas_exp_group_paths(ac, exp);
+    }
+  }
+}
+
+/// returns how many segments of the syms checking the `dec` needs, besides the one it starts with.
+/// keep in sync with [`get_bas_dec`].
+fn bas_dec_extra_segments(dec: &mlb_hir::BasDec) -> usize {
+  match dec {
+    mlb_hir::BasDec::Basis(_, exp) => bas_exp_extra_segments(exp),
+    mlb_hir::BasDec::Open(_)
+    | mlb_hir::BasDec::Export(..)
+    | mlb_hir::BasDec::CmExport(_)
+    | mlb_hir::BasDec::Path(_, _) => 0,
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      bas_dec_extra_segments(local_dec) + bas_dec_extra_segments(in_dec)
+    }
+    mlb_hir::BasDec::Seq(decs) => {
+      let mut ret = 0usize;
+      let mut decs = decs.as_slice();
+      while !decs.is_empty() {
+        let n = independent_bases(decs);
+        if n >= 2 {
+          // each gets its own segments, then we start a new one after them.
+          ret += decs[..n]
+            .iter()
+            .map(|dec| 1 + bas_dec_extra_segments(dec))
+            .sum::<usize>();
+          ret += 1;
+          decs = &decs[n..];
+        } else {
+          ret += bas_dec_extra_segments(&decs[0]);
+          decs = &decs[1..];
+        }
+      }
+      ret
+    }
+  }
+}
+
+fn bas_exp_extra_segments(exp: &mlb_hir::BasExp) -> usize {
+  match exp {
+    mlb_hir::BasExp::Bas(dec) => bas_dec_extra_segments(dec),
+    mlb_hir::BasExp::Name(_) => 0,
+    mlb_hir::BasExp::Let(dec, exp) => bas_dec_extra_segments(dec) + bas_exp_extra_segments(exp),
+  }
+}
+
+/// returns how many of the `decs`, from the start, are `basis` bindings that don't refer to the
+/// bases bound by the ones before them, so they may be checked at once.
+fn independent_bases(decs: &[mlb_hir::BasDec]) -> usize {
+  let mut bound = Vec::<&hir::Name>::new();
+  for (idx, dec) in decs.iter().enumerate() {
+    let (name, exp) = match dec {
+      mlb_hir::BasDec::Basis(name, exp) => (name, exp),
+      _ => return idx,
+    };
+    let mut names = Vec::<&hir::Name>::new();
+    bas_exp_names(&mut names, exp);
+    if names.iter().any(|name| bound.contains(name)) {
+      return idx;
+    }
+    bound.push(&name.val);
+  }
+  decs.len()
+}
+
+/// adds the names of the bases the `dec` refers to, even those it binds itself.
+fn bas_dec_names<'d>(ac: &mut Vec<&'d hir::Name>, dec: &'d mlb_hir::BasDec) {
+  match dec {
+    mlb_hir::BasDec::Basis(_, exp) => bas_exp_names(ac, exp),
+    mlb_hir::BasDec::Open(name) => ac.push(&name.val),
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      bas_dec_names(ac, local_dec);
+      bas_dec_names(ac, in_dec);
+    }
+    mlb_hir::BasDec::Seq(decs) => {
+      for dec in decs {
+        bas_dec_names(ac, dec);
+      }
+    }
+    mlb_hir::BasDec::Export(..) | mlb_hir::BasDec::CmExport(_) | mlb_hir::BasDec::Path(_, _) => {}
+  }
+}
+
+fn bas_exp_names<'d>(ac: &mut Vec<&'d hir::Name>, exp: &'d mlb_hir::BasExp) {
+  match exp {
+    mlb_hir::BasExp::Bas(dec) => bas_dec_names(ac, dec),
+    mlb_hir::BasExp::Name(name) => ac.push(&name.val),
+    mlb_hir::BasExp::Let(dec, exp) => {
+      bas_dec_names(ac, dec);
+      bas_exp_names(ac, exp);
+    }
+  }
+}
+
+/// Checks the tasks one after another, returning the events for each.
+fn check_tasks(
+  files: Files<'_>,
+  tasks: &[Task],
+  syms: &mut statics::Syms,
+  started: &Mutex<paths::PathMap<StartedSourceFile>>,
+) -> Vec<Vec<Event>> {
+  let mut groups = paths::PathMap::<Arc<CheckedGroup>>::default();
+  let mut ret = Vec::with_capacity(tasks.len());
+  for task in tasks {
+    let cx = task_cx(files, task, syms, &groups, started, false);
+    let (cx_syms, group, events) = check_task(cx, files, task.path);
+    syms.join(cx_syms);
+    groups.insert(task.path, Arc::new(group));
+    ret.push(events);
+  }
+  ret
+}
+
+/// Like [`check_tasks`], but checks each task on one of many threads, as soon as the tasks it
+/// depends on are done.
+fn check_tasks_parallel(
+  files: Files<'_>,
+  tasks: &[Task],
+  syms: &mut statics::Syms,
+  started: &Mutex<paths::PathMap<StartedSourceFile>>,
+) -> Vec<Vec<Event>> {
+  let mut dependents = vec![Vec::<usize>::new(); tasks.len()];
+  let mut waiting: Vec<_> = tasks.iter().map(|task| task.deps.len()).collect();
+  for (idx, task) in tasks.iter().enumerate() {
+    for &dep in task.deps.iter() {
+      dependents[dep].push(idx);
+    }
+  }
+  let mut ret: Vec<_> = tasks.iter().map(|_| Vec::<Event>::new()).collect();
+  let mut results: Vec<_> = tasks.iter().map(|_| None::<Arc<CheckedGroup>>).collect();
+  let num_threads = std::thread::available_parallelism()
+    .map_or(1, std::num::NonZeroUsize::get)
+    .min(tasks.len());
+  std::thread::scope(|scope| {
+    // each job is a task index, the syms and group files for it, and where to send the result.
+    let (job_tx, job_rx) =
+      mpsc::channel::<(usize, statics::Syms, paths::PathMap<Arc<CheckedGroup>>)>();
+    let (done_tx, done_rx) = mpsc::channel::<(usize, statics::Syms, CheckedGroup, Vec<Event>)>();
+    let job_rx = Mutex::new(job_rx);
+    for _ in 0..num_threads {
+      let (job_rx, done_tx) = (&job_rx, done_tx.clone());
+      scope.spawn(move || loop {
+        let job = job_rx.lock().expect("job receiver lock poisoned").recv();
+        let (idx, syms, groups) = match job {
+          Ok(x) => x,
+          // no more jobs.
+          Err(_) => break,
+        };
+        let task = &tasks[idx];
+        let cx = task_cx(files, task, &syms, &groups, started, true);
+        let (syms, group, events) = check_task(cx, files, task.path);
+        done_tx
+          .send((idx, syms, group, events))
+          .expect("task result receiver dropped");
+      });
+    }
+    drop(done_tx);
+    let send = |idx: usize, syms: &statics::Syms, results: &[Option<Arc<CheckedGroup>>]| {
+      let task = &tasks[idx];
+      let groups: paths::PathMap<_> = task
+        .deps
+        .iter()
+        .filter_map(|&dep| Some((tasks[dep].path, results[dep].clone()?)))
+        .collect();
+      job_tx
+        .send((idx, syms.clone(), groups))
+        .expect("job receiver dropped");
+    };
+    for (idx, &n) in waiting.iter().enumerate() {
+      if n == 0 {
+        send(idx, syms, &results);
+      }
+    }
+    for _ in 0..tasks.len() {
+      let (idx, task_syms, group, events) = done_rx.recv().expect("thread for tasks panicked");
+      syms.join(task_syms);
+      results[idx] = Some(Arc::new(group));
+      ret[idx] = events;
+      for &dependent in dependents[idx].iter() {
+        waiting[dependent] -= 1;
+        if waiting[dependent] == 0 {
+          send(dependent, syms, &results);
+        }
+      }
+    }
+    drop(job_tx);
+  });
+  ret
+}
+
+fn task_cx<'a>(
+  files: Files<'_>,
+  task: &Task,
+  syms: &statics::Syms,
+  groups: &'a paths::PathMap<Arc<CheckedGroup>>,
+  started: &'a Mutex<paths::PathMap<StartedSourceFile>>,
+  parallel: bool,
+) -> Cx<'a> {
+  let slot = hash_of(task.path.to_raw());
+  let shared = Shared {
+    groups,
+    started,
+    parallel,
+  };
+  Cx::new(syms, task.segments.clone(), files.key, slot, shared)
+}
+
+/// Checks the group file at `path`, returning the symbols, the result, and the events.
+fn check_task(
+  mut cx: Cx<'_>,
+  files: Files<'_>,
+  path: paths::PathId,
+) -> (statics::Syms, CheckedGroup, Vec<Event>) {
+  let dec = files.mlb.get(&path).expect("no mlb file for path id");
+  let mut ac = MBasis::default();
+  get_bas_dec(&mut cx, files, path, files.std_basis, &mut ac, dec);
+  let group = CheckedGroup {
+    m_basis: ac,
+    key: cx.key,
   };
-  get_group_file(&mut cx, files, &mut MBasis::default(), root_mlb);
-  MlbStatics {
-    mlb_errors: cx.mlb_errors,
-    syms: cx.syms,
-    sml: cx.sml,
+  (cx.syms, group, cx.events)
+}
+
+/// Adds the events from checking the group file at `path` to `ac`, unless they were already added.
+///
+/// This visits the group files in the order they would be checked if they were checked one at a
+/// time, each when it is first referred to.
+fn add_events(
+  ac: &mut MlbStatics,
+  files: Files<'_>,
+  task_idx: &paths::PathMap<usize>,
+  events: &mut [Option<Vec<Event>>],
+  path: paths::PathId,
+) {
+  let group_events = match task_idx.get(&path).and_then(|&idx| events[idx].take()) {
+    Some(x) => x,
+    None => return,
+  };
+  for event in group_events {
+    match event {
+      Event::Sml(path, file) => {
+        let contents = files.sml.get(&path).expect("no sml file for path id");
+        let file = file.finish(contents);
+        // a single source file might be included by two different groups, as when two root
+        // groups share a file. the analyses may differ, since the file may see different things
+        // in each, so we keep them all. the first is used for everything but errors.
+        match ac.sml.entry(path) {
+          Entry::Occupied(mut entry) => entry.get_mut().others.push(file),
+          Entry::Vacant(entry) => {
+            entry.insert(file);
+          }
+        }
+      }
+      Event::Error(e) => ac.mlb_errors.push(e),
+      Event::Group(path) => add_events(ac, files, task_idx, events, path),
+    }
   }
 }
 
 fn get_bas_exp(
-  cx: &mut Cx,
+  cx: &mut Cx<'_>,
   files: Files<'_>,
   path: paths::PathId,
   scope: &MBasis,
@@ -165,23 +653,36 @@ fn get_bas_exp(
   exp: &mlb_hir::BasExp,
 ) {
   match exp {
-    mlb_hir::BasExp::Bas(dec) => get_bas_dec(cx, files, path, scope, ac, dec),
-    mlb_hir::BasExp::Name(name) => match scope.bas_env.get(&name.val) {
-      None => cx.undef(path, Item::Basis, name.clone()),
-      Some(mb) => ac.append(mb.clone()),
-    },
+    mlb_hir::BasExp::Bas(dec) => {
+      cx.fold("bas");
+      get_bas_dec(cx, files, path, scope, ac, dec);
+      cx.fold("end");
+    }
+    mlb_hir::BasExp::Name(name) => {
+      cx.fold(("name", &name.val));
+      match scope.bas_env.get(&name.val) {
+        None => cx.undef(path, Item::Basis, name.clone()),
+        Some(mb) => ac.append(mb.clone()),
+      }
+    }
     mlb_hir::BasExp::Let(dec, exp) => {
+      cx.fold("let");
       let mut let_m_basis = MBasis::default();
       get_bas_dec(cx, files, path, scope, &mut let_m_basis, dec);
       let mut scope = scope.clone();
       scope.append(let_m_basis);
+      cx.fold("in");
       get_bas_exp(cx, files, path, &scope, ac, exp);
+      cx.fold("end");
     }
   }
 }
 
+/// keep the segments this uses in sync with [`bas_dec_extra_segments`].
+///
+/// this folds into the key of the `cx` everything that could affect checking what comes after.
 fn get_bas_dec(
-  cx: &mut Cx,
+  cx: &mut Cx<'_>,
   files: Files<'_>,
   path: paths::PathId,
   scope: &MBasis,
@@ -190,90 +691,396 @@ fn get_bas_dec(
 ) {
   match dec {
     mlb_hir::BasDec::Basis(name, exp) => {
+      cx.fold(("basis", &name.val));
       let mut exp_m_basis = MBasis::default();
       get_bas_exp(cx, files, path, scope, &mut exp_m_basis, exp);
       ac.bas_env.insert(name.val.clone(), exp_m_basis);
     }
-    mlb_hir::BasDec::Open(name) => match scope.bas_env.get(&name.val) {
-      None => cx.undef(path, Item::Basis, name.clone()),
-      Some(mb) => ac.append(mb.clone()),
-    },
+    mlb_hir::BasDec::Open(name) => {
+      cx.fold(("open", &name.val));
+      match scope.bas_env.get(&name.val) {
+        None => cx.undef(path, Item::Basis, name.clone()),
+        Some(mb) => ac.append(mb.clone()),
+      }
+    }
     mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      cx.fold("local");
       let mut local_m_basis = MBasis::default();
       get_bas_dec(cx, files, path, scope, &mut local_m_basis, local_dec);
       let mut scope = scope.clone();
       scope.append(local_m_basis);
+      cx.fold("in");
       get_bas_dec(cx, files, path, &scope, ac, in_dec);
+      cx.fold("end");
     }
-    // NOTE this doesn't do any of the stuff with the side conditions with the ty names and whatnot.
-    // those might be necessary.
-    mlb_hir::BasDec::Export(ns, lhs, rhs) => match ns {
-      mlb_hir::Namespace::Structure => {
-        if !ac.basis.add_str(lhs.val.clone(), &scope.basis, &rhs.val) {
-          cx.undef(path, Item::Structure, rhs.clone());
-        }
-      }
-      mlb_hir::Namespace::Signature => {
-        if !ac.basis.add_sig(lhs.val.clone(), &scope.basis, &rhs.val) {
-          cx.undef(path, Item::Signature, rhs.clone());
-        }
-      }
-      mlb_hir::Namespace::Functor => {
-        if !ac.basis.add_fun(lhs.val.clone(), &scope.basis, &rhs.val) {
-          cx.undef(path, Item::Functor, rhs.clone());
-        }
+    mlb_hir::BasDec::Export(ns, lhs, rhs) => {
+      cx.fold(("export", *ns as u8, &lhs.val, &rhs.val));
+      get_export(cx, path, scope, ac, *ns, lhs, rhs);
+    }
+    // SML/NJ extension
+    mlb_hir::BasDec::CmExport(export) => {
+      // the members were checked already. if one was checked more than once, use the first.
+      let lowered: paths::PathMap<_> = cx
+        .events
+        .iter()
+        .rev()
+        .filter_map(|event| match event {
+          Event::Sml(path, file) => Some((*path, &file.lowered)),
+          Event::Error(_) | Event::Group(_) => None,
+        })
+        .collect();
+      let lowered = |path: paths::PathId| lowered.get(&path).copied();
+      let names = cm_export_names(export, lowered, files.smlnj);
+      cx.fold(("cm export", names.len()));
+      for (ns, name) in names {
+        cx.fold((ns as u8, &name.val));
+        get_export(cx, path, scope, ac, ns, &name, &name);
       }
-    },
+    }
     mlb_hir::BasDec::Seq(decs) => {
+      cx.fold(("seq", decs.len()));
       let mut scope = scope.clone();
-      for dec in decs {
-        let mut one_m_basis = MBasis::default();
-        get_bas_dec(cx, files, path, &scope, &mut one_m_basis, dec);
-        scope.append(one_m_basis.clone());
-        ac.append(one_m_basis);
+      let mut decs = decs.as_slice();
+      while !decs.is_empty() {
+        let n = independent_bases(decs);
+        let m_bases = if n >= 2 {
+          get_bases(cx, files, path, &scope, &decs[..n])
+        } else {
+          let mut one_m_basis = MBasis::default();
+          get_bas_dec(cx, files, path, &scope, &mut one_m_basis, &decs[0]);
+          vec![one_m_basis]
+        };
+        for m_basis in m_bases {
+          scope.append(m_basis.clone());
+          ac.append(m_basis);
+        }
+        decs = &decs[n.max(1)..];
       }
     }
     mlb_hir::BasDec::Path(path, kind) => match kind {
       mlb_hir::PathKind::Sml => {
         let contents = files.sml.get(path).expect("no sml file for path id");
-        let mut fix_env = scope.fix_env.clone();
-        let (lex_errors, parsed, low) = start_source_file(contents, &mut fix_env);
-        let mode = statics::Mode::Regular(Some(*path));
-        let checked = statics::get(&mut cx.syms, &scope.basis, mode, &low.arenas, low.root);
-        let mut info = checked.info;
-        doc_comment::get(parsed.root.syntax(), &low, &mut info);
-        let file = SourceFile {
-          pos_db: text_pos::PositionDb::new(contents),
+        let started = cx
+          .shared
+          .started
+          .lock()
+          .expect("started source files lock poisoned")
+          .remove(path);
+        let (lex_errors, parsed, low, fix_env) =
+          match started.and_then(|x| x.finish(&scope.fix_env)) {
+            Some(x) => x,
+            None => {
+              let mut fix_env = scope.fix_env.clone();
+              let (lex_errors, parsed, low) = start_source_file(contents, &mut fix_env);
+              (lex_errors, parsed, low, fix_env)
+            }
+          };
+        let dialect = statics::Dialect {
+          smlnj_modules: files.smlnj,
+        };
+        let mode = statics::Mode::Regular(Some(*path), dialect);
+        cx.fold(("sml", path.to_raw(), contents));
+        let key = cx.key;
+        // the same file may be included more than once in a group file.
+        let occurrence = cx
+          .events
+          .iter()
+          .filter(|event| matches!(event, Event::Sml(other, _) if other == path))
+          .count();
+        let slot = hash_of((cx.slot, path.to_raw(), occurrence));
+        let cached = files
+          .cache
+          .and_then(|cache| get_cached(cache, slot, key, &mut cx.syms, &scope.basis));
+        let checked = match cached {
+          Some(x) => x,
+          None => {
+            let checkpoint = cx.syms.checkpoint();
+            let checked = statics::get(&mut cx.syms, &scope.basis, mode, &low.arenas, low.root);
+            let mut info = checked.info;
+            doc_comment::get(parsed.root.syntax(), &low, &mut info);
+            if let Some(cache) = files.cache {
+              let entry = file_cache::CachedFileRef {
+                syms: &cx.syms.since(checkpoint),
+                basis: &checked.basis,
+                errors: statics::ErrorsReprRef::new(&checked.errors, &scope.basis),
+                info: &info,
+              };
+              cache.put(slot, key, &entry);
+            }
+            statics::Statics {
+              info,
+              errors: checked.errors,
+              basis: checked.basis,
+            }
+          }
+        };
+        let file = CheckedFile {
           lex_errors,
-          parsed,
+          green: parsed.root.syntax().green().into_owned(),
+          parse_errors: parsed.errors,
           lowered: low,
           statics_errors: checked.errors,
-          info,
+          info: checked.info,
         };
         ac.append(MBasis {
           fix_env,
           bas_env: FxHashMap::default(),
           basis: checked.basis,
         });
-        // NOTE: we would like to assert that the insert returns None, but actually it may not
-        // always.
-        //
-        // this is because a single source file might be included by two different groups. in such a
-        // case, it's not actually clear what errors we should emit. (it might be confusing to
-        // re-emit some of the same errors, but also maybe have different errors from different
-        // analyses, on the same file.)
-        //
-        // this drops the errors from any previous analyses of this file on the floor.
-        cx.sml.insert(*path, file);
+        cx.events.push(Event::Sml(*path, file));
+      }
+      mlb_hir::PathKind::Mlb => {
+        // the group files this refers to were checked first.
+        let group = cx.shared.groups.get(path);
+        cx.fold(("mlb", path.to_raw(), group.map(|x| x.key)));
+        if let Some(group) = group {
+          ac.append(group.m_basis.clone());
+        }
+        cx.events.push(Event::Group(*path));
       }
-      mlb_hir::PathKind::Mlb => match cx.cache.get(path) {
-        Some(mb) => ac.append(mb.clone()),
-        None => get_group_file(cx, files, ac, *path),
-      },
     },
   }
 }
 
+/// returns the result of checking a source file from the `cache`, if it's there, generating its
+/// symbols in `syms`.
+fn get_cached(
+  cache: &FileCache,
+  slot: u64,
+  key: u64,
+  syms: &mut statics::Syms,
+  scope: &statics::basis::Basis,
+) -> Option<statics::Statics> {
+  let file = cache.get(slot, key)?;
+  let errors = file.errors.into_errors(scope)?;
+  if !syms.replay(file.syms) {
+    return None;
+  }
+  Some(statics::Statics {
+    info: file.info,
+    errors,
+    basis: file.basis,
+  })
+}
+
+/// Checks the `basis` bindings in `decs`, which don't refer to each other, at once, returning the
+/// basis each defines.
+fn get_bases(
+  cx: &mut Cx<'_>,
+  files: Files<'_>,
+  path: paths::PathId,
+  scope: &MBasis,
+  decs: &[mlb_hir::BasDec],
+) -> Vec<MBasis> {
+  let cxs: Vec<_> = decs
+    .iter()
+    .enumerate()
+    .map(|(idx, dec)| {
+      let start = cx.next_seg;
+      cx.next_seg += 1 + bas_dec_extra_segments(dec);
+      let slot = hash_of((cx.slot, cx.events.len(), idx));
+      Cx::new(&cx.syms, start..cx.next_seg, cx.key, slot, cx.shared)
+    })
+    .collect();
+  let get_one = |mut one_cx: Cx<'_>, dec: &mlb_hir::BasDec| {
+    let mut m_basis = MBasis::default();
+    get_bas_dec(&mut one_cx, files, path, scope, &mut m_basis, dec);
+    (one_cx, m_basis)
+  };
+  let done: Vec<_> = if cx.shared.parallel {
+    std::thread::scope(|s| {
+      let handles: Vec<_> = cxs
+        .into_iter()
+        .zip(decs)
+        .map(|(one_cx, dec)| s.spawn(move || get_one(one_cx, dec)))
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().expect("thread for basis binding panicked"))
+        .collect()
+    })
+  } else {
+    cxs
+      .into_iter()
+      .zip(decs)
+      .map(|(one_cx, dec)| get_one(one_cx, dec))
+      .collect()
+  };
+  let mut ret = Vec::with_capacity(done.len());
+  for (one_cx, m_basis) in done {
+    cx.fold(one_cx.key);
+    cx.syms.join(one_cx.syms);
+    cx.events.extend(one_cx.events);
+    ret.push(m_basis);
+  }
+  // the bindings generated symbols in later segments, so start a new one after them.
+  cx.syms.start_segment(cx.next_seg);
+  cx.next_seg += 1;
+  ret
+}
+
+// NOTE this doesn't do any of the stuff with the side conditions with the ty names and whatnot.
+// those might be necessary.
+fn get_export(
+  cx: &mut Cx<'_>,
+  path: paths::PathId,
+  scope: &MBasis,
+  ac: &mut MBasis,
+  ns: mlb_hir::Namespace,
+  lhs: &text_size_util::WithRange<hir::Name>,
+  rhs: &text_size_util::WithRange<hir::Name>,
+) {
+  match ns {
+    mlb_hir::Namespace::Structure => {
+      if !ac.basis.add_str(lhs.val.clone(), &scope.basis, &rhs.val) {
+        cx.undef(path, Item::Structure, rhs.clone());
+      }
+    }
+    mlb_hir::Namespace::Signature => {
+      if !ac.basis.add_sig(lhs.val.clone(), &scope.basis, &rhs.val) {
+        cx.undef(path, Item::Signature, rhs.clone());
+      }
+    }
+    mlb_hir::Namespace::Functor => {
+      if !ac.basis.add_fun(lhs.val.clone(), &scope.basis, &rhs.val) {
+        cx.undef(path, Item::Functor, rhs.clone());
+      }
+    }
+    mlb_hir::Namespace::FunSig => {
+      if !ac
+        .basis
+        .add_fun_sig(lhs.val.clone(), &scope.basis, &rhs.val)
+      {
+        cx.undef(path, Item::FunSig, rhs.clone());
+      }
+    }
+  }
+}
+
+type CmExportName = (mlb_hir::Namespace, text_size_util::WithRange<hir::Name>);
+
+/// Returns the names the CM export evaluates to, without duplicates.
+///
+/// The names exported by a source file are read from its lowered HIR, which `lowered` returns, so
+/// the members of the CM file should be lowered first. Functor signatures in source files are only
+/// exported if `smlnj`, since otherwise the statics report them.
+pub fn cm_export_names<'l, F>(
+  export: &mlb_hir::CmExport,
+  lowered: F,
+  smlnj: bool,
+) -> Vec<CmExportName>
+where
+  F: Fn(paths::PathId) -> Option<&'l lower::Lower> + Copy,
+{
+  let mut names = Vec::new();
+  get_cm_export(&mut names, export, lowered, smlnj);
+  let mut ret = Vec::<CmExportName>::new();
+  for name in names {
+    if !ret.iter().any(|x| same_name(x, &name)) {
+      ret.push(name);
+    }
+  }
+  ret
+}
+
+fn same_name(x: &CmExportName, y: &CmExportName) -> bool {
+  x.0 == y.0 && x.1.val == y.1.val
+}
+
+fn get_cm_export<'l, F>(
+  ac: &mut Vec<CmExportName>,
+  export: &mlb_hir::CmExport,
+  lowered: F,
+  smlnj: bool,
+) where
+  F: Fn(paths::PathId) -> Option<&'l lower::Lower> + Copy,
+{
+  match export {
+    mlb_hir::CmExport::Name(ns, name) => ac.push((*ns, name.clone())),
+    mlb_hir::CmExport::Source(path) => {
+      // if the file was not checked, as for a library that is re-exported but not a member, nothing
+      // in it is in scope to export anyway.
+      if let Some(low) = lowered(path.val) {
+        let mut names = Vec::<(mlb_hir::Namespace, hir::Name)>::new();
+        str_dec_exports(&mut names, &low.arenas, low.root, smlnj);
+        ac.extend(names.into_iter().map(|(ns, name)| (ns, path.wrap(name))));
+      }
+    }
+    mlb_hir::CmExport::Union(exports) => {
+      for export in exports {
+        get_cm_export(ac, export, lowered, smlnj);
+      }
+    }
+    mlb_hir::CmExport::Difference(lhs, rhs) => {
+      let rhs = cm_export_names(rhs, lowered, smlnj);
+      let lhs = cm_export_names(lhs, lowered, smlnj);
+      ac.extend(
+        lhs
+          .into_iter()
+          .filter(|x| !rhs.iter().any(|y| same_name(x, y))),
+      );
+    }
+    mlb_hir::CmExport::Intersection(lhs, rhs) => {
+      let rhs = cm_export_names(rhs, lowered, smlnj);
+      let lhs = cm_export_names(lhs, lowered, smlnj);
+      ac.extend(
+        lhs
+          .into_iter()
+          .filter(|x| rhs.iter().any(|y| same_name(x, y))),
+      );
+    }
+  }
+}
+
+/// adds the structures, signatures, functors, and functor signatures declared at the top level of
+/// the source to `ac`.
+fn str_dec_exports(
+  ac: &mut Vec<(mlb_hir::Namespace, hir::Name)>,
+  arenas: &hir::Arenas,
+  str_dec: hir::StrDecIdx,
+  smlnj: bool,
+) {
+  let str_dec = match str_dec {
+    Some(x) => x,
+    None => return,
+  };
+  match &arenas.str_dec[str_dec] {
+    hir::StrDec::Dec(_) => {}
+    hir::StrDec::Structure(str_binds) => ac.extend(
+      str_binds
+        .iter()
+        .map(|x| (mlb_hir::Namespace::Structure, x.name.clone())),
+    ),
+    hir::StrDec::Signature(sig_binds) => ac.extend(
+      sig_binds
+        .iter()
+        .map(|x| (mlb_hir::Namespace::Signature, x.name.clone())),
+    ),
+    hir::StrDec::Functor(fun_binds) => ac.extend(
+      fun_binds
+        .iter()
+        .map(|x| (mlb_hir::Namespace::Functor, x.functor_name.clone())),
+    ),
+    hir::StrDec::FunSig(fun_sig_binds) => {
+      // without the SML/NJ target, the statics will report these.
+      if smlnj {
+        ac.extend(
+          fun_sig_binds
+            .iter()
+            .map(|x| (mlb_hir::Namespace::FunSig, x.name.clone())),
+        );
+      }
+    }
+    // only the `in` part of a `local` is visible outside it.
+    hir::StrDec::Local(_, in_dec) => str_dec_exports(ac, arenas, *in_dec, smlnj),
+    hir::StrDec::Seq(str_decs) => {
+      for &str_dec in str_decs {
+        str_dec_exports(ac, arenas, str_dec, smlnj);
+      }
+    }
+  }
+}
+
 /// Processes a single source file.
 pub fn start_source_file(
   contents: &str,
@@ -286,11 +1093,115 @@ pub fn start_source_file(
   (lexed.errors, parsed, lowered)
 }
 
-/// Processes a single group file.
-fn get_group_file(cx: &mut Cx, files: Files<'_>, ac: &mut MBasis, path: paths::PathId) {
-  let dec = files.mlb.get(&path).expect("no mlb file for path id");
-  let mut path_ac = MBasis::default();
-  get_bas_dec(cx, files, path, files.std_basis, &mut path_ac, dec);
-  cx.cache.insert(path, path_ac.clone());
-  ac.append(path_ac);
+/// A source file that was lexed, parsed, and lowered, possibly on another thread.
+struct StartedSourceFile {
+  lex_errors: Vec<lex::Error>,
+  /// the syntax tree can't be sent between threads, but its green node can.
+  green: syntax::rowan::GreenNode,
+  parse_errors: Vec<parse::parser::Error>,
+  lowered: lower::Lower,
+  /// the fixities in scope after the file.
+  fix_env: parse::parser::FixEnv,
+}
+
+impl StartedSourceFile {
+  fn new(contents: &str) -> Self {
+    let mut fix_env = STD_BASIS_FIX_ENV.clone();
+    let (lex_errors, parsed, lowered) = start_source_file(contents, &mut fix_env);
+    Self {
+      lex_errors,
+      green: parsed.root.syntax().green().into_owned(),
+      parse_errors: parsed.errors,
+      lowered,
+      fix_env,
+    }
+  }
+
+  /// Finishes this file, which has `fix_env` in scope, or returns `None` if it must be started
+  /// again because it mentions a name whose fixity `fix_env` changes from the std basis.
+  fn finish(
+    self,
+    fix_env: &parse::parser::FixEnv,
+  ) -> Option<(
+    Vec<lex::Error>,
+    parse::Parse,
+    lower::Lower,
+    parse::parser::FixEnv,
+  )> {
+    let root = syntax::SyntaxNode::new_root(self.green);
+    let changed = fixity_changes(&STD_BASIS_FIX_ENV, fix_env);
+    if !changed.is_empty() {
+      let mentions_changed = root
+        .descendants_with_tokens()
+        .filter_map(syntax::rowan::NodeOrToken::into_token)
+        .any(|tok| changed.contains(tok.text()));
+      if mentions_changed {
+        return None;
+      }
+    }
+    // the fixities after the file are those in scope before it, with the file's own changes.
+    let mut out_fix_env = fix_env.clone();
+    for name in fixity_changes(&STD_BASIS_FIX_ENV, &self.fix_env) {
+      match self.fix_env.get(name) {
+        Some(&infix) => {
+          out_fix_env.insert(hir::Name::new(name), infix);
+        }
+        None => {
+          out_fix_env.remove(name);
+        }
+      }
+    }
+    let parsed = parse::Parse {
+      root: syntax::ast::Root::cast(root).expect("root of a parse should be a Root"),
+      errors: self.parse_errors,
+    };
+    Some((self.lex_errors, parsed, self.lowered, out_fix_env))
+  }
+}
+
+/// returns the names whose fixities differ between the two.
+fn fixity_changes<'e>(
+  a: &'e parse::parser::FixEnv,
+  b: &'e parse::parser::FixEnv,
+) -> FxHashSet<&'e str> {
+  let a_changes = a.iter().filter(|&(name, infix)| b.get(name) != Some(infix));
+  let b_changes = b.iter().filter(|&(name, infix)| a.get(name) != Some(infix));
+  a_changes
+    .chain(b_changes)
+    .map(|(name, _)| name.as_str())
+    .collect()
+}
+
+/// Starts all the source files in parallel, since that doesn't depend on anything else, except for
+/// the fixities in scope. We assume only the std basis fixities are in scope, which is true for the
+/// files of most projects. A file that turns out to mention a name whose fixity is different where
+/// it is included is started again then (see [`StartedSourceFile::finish`]).
+fn start_source_files(sml: &paths::PathMap<String>) -> paths::PathMap<StartedSourceFile> {
+  let sml: Vec<_> = sml.iter().collect();
+  let next = AtomicUsize::new(0);
+  let num_threads = std::thread::available_parallelism()
+    .map_or(1, std::num::NonZeroUsize::get)
+    .min(sml.len());
+  std::thread::scope(|scope| {
+    let (sml, next) = (&sml, &next);
+    let handles: Vec<_> = (0..num_threads)
+      .map(|_| {
+        scope.spawn(move || {
+          let mut ac = Vec::<(paths::PathId, StartedSourceFile)>::new();
+          while let Some(&(&path, contents)) = sml.get(next.fetch_add(1, Ordering::Relaxed)) {
+            ac.push((path, StartedSourceFile::new(contents)));
+          }
+          ac
+        })
+      })
+      .collect();
+    handles
+      .into_iter()
+      .flat_map(|handle| {
+        handle
+          .join()
+          .expect("thread for starting source files panicked")
+      })
+      .collect()
+  })
 }
@@ -1,6 +1,12 @@
 use crate::{doc_comment, start_source_file};
+use bincode::Options as _;
 use fast_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use statics::{basis, Info, Syms};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash as _, Hasher as _};
+use std::io;
+use std::path::{Path, PathBuf};
 use syntax::ast::AstNode as _;
 
 /// A standard basis.
@@ -9,6 +15,8 @@ pub struct StdBasis {
   syms: Syms,
   basis: basis::Basis,
   info: FxHashMap<&'static str, Info>,
+  /// a hash of the files this was made from.
+  key: u64,
 }
 
 impl StdBasis {
@@ -20,14 +28,80 @@ impl StdBasis {
 
   /// The full standard basis, as documented in the public SML basis library docs.
   pub fn full() -> Self {
-    get_std_basis(
-      std::iter::empty()
-        .chain(sml_libs::primitive::FILES)
-        .chain(sml_libs::std_basis::FILES)
-        .chain(sml_libs::std_basis_extra::FILES)
-        .chain(sml_libs::sml_nj::FILES)
-        .copied(),
-    )
+    get_std_basis(full_files())
+  }
+
+  /// Like [`Self::full`], but reads a snapshot of the result from the cache directory if there is
+  /// one, and writes one there if not. Any error doing so is logged and otherwise ignored.
+  pub fn full_cached() -> Self {
+    let path = match snapshot_path() {
+      Some(x) => x,
+      None => return Self::full(),
+    };
+    match std::fs::File::open(&path) {
+      Ok(file) => match Self::read(io::BufReader::new(file)) {
+        Ok(x) => return x,
+        Err(e) => log::warn!("couldn't read {}: {e}", path.display()),
+      },
+      Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+      Err(e) => log::warn!("couldn't open {}: {e}", path.display()),
+    }
+    let ret = Self::full();
+    if let Err(e) = write_snapshot(&ret, &path) {
+      log::warn!("couldn't write {}: {e}", path.display());
+    }
+    ret
+  }
+
+  /// Writes a snapshot of this, to be read back with [`Self::read`].
+  ///
+  /// # Errors
+  ///
+  /// If writing failed.
+  pub fn write<W>(&self, w: W) -> io::Result<()>
+  where
+    W: io::Write,
+  {
+    let snapshot = SnapshotRef {
+      syms: &self.syms,
+      basis: &self.basis,
+      info: self.info.iter().map(|(&name, info)| (name, info)).collect(),
+      key: self.key,
+    };
+    bincode_options()
+      .serialize_into(w, &snapshot)
+      .map_err(invalid_data)
+  }
+
+  /// Reads a snapshot written by [`Self::write`].
+  ///
+  /// # Errors
+  ///
+  /// If reading failed, or the snapshot was not valid.
+  pub fn read<R>(r: R) -> io::Result<Self>
+  where
+    R: io::Read,
+  {
+    let snapshot: Snapshot = bincode_options()
+      .deserialize_from(r)
+      .map_err(invalid_data)?;
+    let info = snapshot
+      .info
+      .into_iter()
+      .map(|(name, info)| {
+        // get the `&'static str` for the name.
+        match full_files().find(|&(x, _)| x == name) {
+          Some((name, _)) => Ok((name, info)),
+          None => Err(invalid_data(format!("unknown std basis file: {name}"))),
+        }
+      })
+      .collect::<io::Result<_>>()?;
+    Ok(Self {
+      syms: snapshot.syms,
+      basis: snapshot.basis,
+      info,
+      key: snapshot.key,
+    })
   }
 
   /// Returns the symbols for this.
@@ -44,6 +118,168 @@ impl StdBasis {
   pub fn get_info(&self, s: &str) -> Option<&Info> {
     self.info.get(s)
   }
+
+  pub(crate) fn key(&self) -> u64 {
+    self.key
+  }
+}
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+  syms: &'a Syms,
+  basis: &'a basis::Basis,
+  info: Vec<(&'static str, &'a Info)>,
+  key: u64,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+  syms: Syms,
+  basis: basis::Basis,
+  info: Vec<(String, Info)>,
+  key: u64,
+}
+
+pub(crate) fn invalid_data<E>(e: E) -> io::Error
+where
+  E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+  io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Increment when the snapshot format, or the format of the entries of a
+/// [`FileCache`](crate::FileCache), changes in a way that the other parts of their keys would not
+/// catch.
+const SNAPSHOT_VERSION: u32 = 8;
+
+/// The max size of a snapshot in bytes, so reading a corrupt snapshot can't allocate without bound.
+const MAX_SNAPSHOT_LEN: u64 = 1 << 26;
+
+pub(crate) fn bincode_options() -> impl bincode::Options {
+  bincode::options().with_limit(MAX_SNAPSHOT_LEN)
+}
+
+fn full_files() -> impl Iterator<Item = (&'static str, &'static str)> {
+  std::iter::empty()
+    .chain(sml_libs::primitive::FILES)
+    .chain(sml_libs::std_basis::FILES)
+    .chain(sml_libs::std_basis_extra::FILES)
+    .chain(sml_libs::sml_nj::FILES)
+    .copied()
+}
+
+/// Returns a hasher that has hashed the snapshot version and the current executable, so that what is
+/// keyed by its hash is never used by a different version of either.
+pub(crate) fn versioned_hasher() -> Option<DefaultHasher> {
+  let exe = std::env::current_exe().ok()?;
+  let exe = std::fs::metadata(exe).ok()?;
+  // `DefaultHasher::new` always uses the same keys, so the hash is the same across runs.
+  let mut hasher = DefaultHasher::new();
+  SNAPSHOT_VERSION.hash(&mut hasher);
+  exe.len().hash(&mut hasher);
+  exe.modified().ok()?.hash(&mut hasher);
+  Some(hasher)
+}
+
+/// Returns the path of the snapshot of the full std basis. The file name is a hash of the std basis
+/// files and of the current executable, so a snapshot is never used by a different version of
+/// either.
+fn snapshot_path() -> Option<PathBuf> {
+  let mut hasher = versioned_hasher()?;
+  files_key(full_files()).hash(&mut hasher);
+  let name = format!("{SNAPSHOT_PREFIX}{:016x}.bin", hasher.finish());
+  Some(cache_dir()?.join(name))
+}
+
+/// Returns a hash of the std basis files.
+fn files_key<I>(files: I) -> u64
+where
+  I: Iterator<Item = (&'static str, &'static str)>,
+{
+  let mut hasher = DefaultHasher::new();
+  for (name, contents) in files {
+    name.hash(&mut hasher);
+    contents.hash(&mut hasher);
+  }
+  hasher.finish()
+}
+
+const SNAPSHOT_PREFIX: &str = "std-basis-";
+
+/// Returns the directory for Millet's cache, following the conventions of the platform.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+  let base = match std::env::var_os("XDG_CACHE_HOME") {
+    Some(x) => PathBuf::from(x),
+    None => match std::env::var_os("LOCALAPPDATA") {
+      Some(x) => PathBuf::from(x),
+      None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    },
+  };
+  Some(base.join("millet"))
+}
+
+/// Cache files not modified for this long are assumed to be from versions no longer in use.
+const STALE_AGE: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Writes the snapshot, and removes stale snapshots.
+fn write_snapshot(std_basis: &StdBasis, path: &Path) -> io::Result<()> {
+  let dir = path.parent().expect("snapshot path has no parent");
+  std::fs::create_dir_all(dir)?;
+  remove_stale(dir, SNAPSHOT_PREFIX, Some(path));
+  write_atomic(path, |w| std_basis.write(w))
+}
+
+/// Writes to a temporary file unique to this process first, then renames it to `path`, so a
+/// concurrent reader or writer never sees a partially written file.
+pub(crate) fn write_atomic<F>(path: &Path, f: F) -> io::Result<()>
+where
+  F: FnOnce(&mut io::BufWriter<std::fs::File>) -> io::Result<()>,
+{
+  let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+  let mut file = io::BufWriter::new(std::fs::File::create(&tmp)?);
+  let res = f(&mut file).and_then(|()| io::Write::flush(&mut file));
+  drop(file);
+  let res = res.and_then(|()| std::fs::rename(&tmp, path));
+  if res.is_err() {
+    // ignore the error, since we're already reporting one.
+    let _ = std::fs::remove_file(&tmp);
+  }
+  res
+}
+
+/// Removes the files in `dir` whose names start with `prefix`, including leftover temporary files,
+/// other than `keep` if given, that have not been modified recently. Other versions may still be
+/// using the others. Any error doing so is logged and otherwise ignored.
+pub(crate) fn remove_stale(dir: &Path, prefix: &str, keep: Option<&Path>) {
+  let entries = match std::fs::read_dir(dir) {
+    Ok(x) => x,
+    Err(e) => {
+      log::warn!("couldn't read {}: {e}", dir.display());
+      return;
+    }
+  };
+  for entry in entries.flatten() {
+    let entry_path = entry.path();
+    let is_ours = entry
+      .file_name()
+      .to_str()
+      .map_or(false, |x| x.starts_with(prefix));
+    if !is_ours || keep == Some(entry_path.as_path()) {
+      continue;
+    }
+    let is_stale = entry
+      .metadata()
+      .and_then(|x| x.modified())
+      .ok()
+      .and_then(|x| x.elapsed().ok())
+      .map_or(false, |x| x > STALE_AGE);
+    if !is_stale {
+      continue;
+    }
+    if let Err(e) = std::fs::remove_file(&entry_path) {
+      log::warn!("couldn't remove {}: {e}", entry_path.display());
+    }
+  }
 }
 
 const STREAM_IO_REGULAR: &str = "  structure StreamIO : STREAM_IO";
@@ -58,9 +294,12 @@ fn get_std_basis<I>(files: I) -> StdBasis
 where
   I: Iterator<Item = (&'static str, &'static str)>,
 {
+  let files: Vec<_> = files.collect();
+  let key = files_key(files.iter().copied());
   let (mut syms, mut basis) = basis::minimal();
   let mut imperative_io_hack = None::<String>;
   let info: FxHashMap<_, _> = files
+    .into_iter()
     .map(|(name, mut contents)| {
       if name == "imperative-io.sml" {
         let mut lines: Vec<_> = contents
@@ -115,5 +354,11 @@ where
       (name, info)
     })
     .collect();
-  StdBasis { syms, basis, info }
+  basis::set_target(&mut syms, &basis, None);
+  StdBasis {
+    syms,
+    basis,
+    info,
+    key,
+  }
 }
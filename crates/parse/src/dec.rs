@@ -2,15 +2,47 @@ use crate::exp::exp;
 use crate::parser::{ErrorKind, Exited, Expected, Infix, Parser};
 use crate::pat::{at_pat, pat};
 use crate::ty::{of_ty, ty, ty_annotation, ty_var_seq};
-use crate::util::{eat_name_star, many_sep, maybe_semi_sep, must, name_star_eq, path};
+use crate::util::{eat_name_star, many_sep, maybe_semi_sep, must, name_star_eq, path, recover};
 use syntax::SyntaxKind as SK;
 
+/// the tokens that start a declaration, at either the core or module level, or that may follow a
+/// sequence of declarations. when skipping tokens in a sequence of declarations after an error, we
+/// stop at these.
+const DEC_RECOVERY: [SK; 19] = [
+  SK::DotDotDot,
+  SK::ValKw,
+  SK::FunKw,
+  SK::TypeKw,
+  SK::DatatypeKw,
+  SK::AbstypeKw,
+  SK::ExceptionKw,
+  SK::LocalKw,
+  SK::OpenKw,
+  SK::InfixKw,
+  SK::InfixrKw,
+  SK::NonfixKw,
+  SK::DoKw,
+  SK::StructureKw,
+  SK::SignatureKw,
+  SK::FunctorKw,
+  SK::FunsigKw,
+  SK::InKw,
+  SK::Semicolon,
+];
+
 pub(crate) fn dec(p: &mut Parser<'_>) -> Exited {
   let en = p.enter();
-  maybe_semi_sep(p, SK::DecInSeq, dec_one);
+  maybe_semi_sep(p, SK::DecInSeq, |p| dec_one_or_hole(p, Expected::Dec));
   p.exit(en, SK::Dec)
 }
 
+/// parses a declaration, or if there is none, skips tokens up to the next one, wrapping them in a
+/// `HoleDec` and erroring with `e`. either way, what is parsed is a `DecOne`, so a hole made from
+/// skipped tokens lowers to a declaration hole, just like `...` does.
+pub(crate) fn dec_one_or_hole(p: &mut Parser<'_>, e: Expected) -> bool {
+  dec_one(p) || recover(p, &DEC_RECOVERY, SK::HoleDec, e)
+}
+
 pub(crate) fn dec_one(p: &mut Parser<'_>) -> bool {
   let en = p.enter();
   if p.at(SK::DotDotDot) {
@@ -3,13 +3,50 @@ use crate::parser::{ErrorKind, Exited, Expected, Infix, Parser};
 use crate::pat::pat;
 use crate::ty::ty;
 use crate::util::{
-  comma_sep, lab, many_sep, must, name_star_eq, path, path_no_infix, scon, should_break,
+  comma_sep, lab, many_sep, must, name_star_eq, path, path_no_infix, recover, scon, should_break,
   ShouldBreak,
 };
 use syntax::SyntaxKind as SK;
 
+/// the tokens that may follow an expression, or start a declaration. when skipping tokens in place
+/// of a missing expression, we stop at these.
+const EXP_RECOVERY: [SK; 25] = [
+  SK::Comma,
+  SK::Semicolon,
+  SK::Bar,
+  SK::EqGt,
+  SK::ThenKw,
+  SK::ElseKw,
+  SK::OfKw,
+  SK::DoKw,
+  SK::AndKw,
+  SK::InKw,
+  SK::ValKw,
+  SK::FunKw,
+  SK::TypeKw,
+  SK::DatatypeKw,
+  SK::AbstypeKw,
+  SK::ExceptionKw,
+  SK::LocalKw,
+  SK::OpenKw,
+  SK::InfixKw,
+  SK::InfixrKw,
+  SK::NonfixKw,
+  SK::StructureKw,
+  SK::SignatureKw,
+  SK::FunctorKw,
+  SK::FunsigKw,
+];
+
+/// if there is no expression, but there are tokens that can't follow one, wraps those tokens in a
+/// hole expression, so the enclosing construct can still be checked.
 pub(crate) fn exp(p: &mut Parser<'_>) -> bool {
-  must(p, |p| exp_prec(p, ExpPrec::Min), Expected::Exp)
+  if exp_prec(p, ExpPrec::Min).is_some() || recover(p, &EXP_RECOVERY, SK::HoleExp, Expected::Exp) {
+    true
+  } else {
+    p.error(ErrorKind::Expected(Expected::Exp));
+    false
+  }
 }
 
 fn exp_prec(p: &mut Parser<'_>, min_prec: ExpPrec) -> Option<Exited> {
@@ -53,16 +53,31 @@ pub(crate) struct Parser<'a> {
   tok_idx: usize,
   events: Vec<Option<Event>>,
   fix_env: &'a mut FixEnv,
+  /// the sorted indices of the tokens that open a construct with no matching `end`.
+  unclosed: Vec<usize>,
 }
 
 impl<'a> Parser<'a> {
   /// Returns a new parser for the given tokens.
   pub(crate) fn new(tokens: &'a [Token<'a, SK>], fix_env: &'a mut FixEnv) -> Self {
+    // match each `end` with the nearest unmatched opener before it. the openers left over are
+    // unclosed.
+    let mut unclosed = Vec::<usize>::new();
+    for (idx, tok) in tokens.iter().enumerate() {
+      match tok.kind {
+        SK::StructKw | SK::SigKw | SK::LetKw | SK::LocalKw | SK::AbstypeKw => unclosed.push(idx),
+        SK::EndKw => {
+          unclosed.pop();
+        }
+        _ => {}
+      }
+    }
     Self {
       tokens,
       tok_idx: 0,
       events: Vec::new(),
       fix_env,
+      unclosed,
     }
   }
 
@@ -178,6 +193,12 @@ impl<'a> Parser<'a> {
     ret
   }
 
+  /// Returns whether the current token opens a construct, like `struct`, that has no matching
+  /// `end` anywhere after it.
+  pub(crate) fn is_unclosed(&mut self) -> bool {
+    self.peek().is_some() && self.unclosed.binary_search(&self.tok_idx).is_ok()
+  }
+
   /// Records an error at the current token.
   pub(crate) fn error(&mut self, kind: ErrorKind) {
     self.events.push(Some(Event::Error(kind)));
@@ -229,6 +250,10 @@ impl<'a> Parser<'a> {
           }
         }
         Event::Exit => {
+          // the root must contain all the trivia, so the text of the tree is the text of the input.
+          if levels == 1 {
+            self.eat_trivia(&mut sink);
+          }
           sink.exit();
           levels -= 1;
           // keep as much trivia as possible outside of top-level items.
@@ -384,7 +409,7 @@ impl fmt::Debug for Event {
 // sml-specific types //
 
 /// Information about an infix name.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Infix {
   /// The precedence.
   pub prec: u16,
@@ -480,8 +505,9 @@ impl fmt::Display for ErrorKind {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub(crate) enum Expected {
+  Dec,
   Exp,
   Lab,
   Pat,
@@ -497,6 +523,7 @@ pub(crate) enum Expected {
 impl fmt::Display for Expected {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
+      Expected::Dec => f.write_str("a declaration"),
       Expected::Exp => f.write_str("an expression"),
       Expected::Lab => f.write_str("a label"),
       Expected::Pat => f.write_str("a pattern"),
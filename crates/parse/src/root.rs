@@ -1,11 +1,11 @@
 use crate::parser::{ErrorKind, Expected, Parser};
-use crate::top_dec::str_dec;
+use crate::top_dec::str_dec_until;
 use syntax::SyntaxKind as SK;
 
 pub(crate) fn root(p: &mut Parser<'_>) {
   let entered = p.enter();
   while p.peek().is_some() {
-    if !str_dec(p) {
+    if !str_dec_until(p, &[], Expected::Item) {
       // avoid infinite loop
       p.error(ErrorKind::Expected(Expected::Item));
       p.bump();
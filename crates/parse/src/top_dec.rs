@@ -1,12 +1,43 @@
-use crate::dec::{dat_binds, datatype_copy, dec_one};
+use crate::dec::{dat_binds, datatype_copy, dec_one, dec_one_or_hole};
 use crate::parser::{Exited, Expected, Parser};
 use crate::ty::{of_ty, ty, ty_var_seq};
 use crate::util::{eat_name_star, many_sep, maybe_semi_sep, must, path};
 use syntax::SyntaxKind as SK;
 
+/// the tokens that start an item usually only written at the top level. a `struct` with no
+/// matching `end` probably ends before the first of these.
+const TOP_DEC_START: [SK; 4] = [
+  SK::StructureKw,
+  SK::SignatureKw,
+  SK::FunctorKw,
+  SK::FunsigKw,
+];
+
 pub(crate) fn str_dec(p: &mut Parser<'_>) -> bool {
+  str_dec_until(p, &[], Expected::Dec)
+}
+
+/// parses structure-level declarations until before one of `stop`. skips tokens that can't start
+/// a declaration, erroring with `e`.
+pub(crate) fn str_dec_until(p: &mut Parser<'_>, stop: &[SK], e: Expected) -> bool {
   let en = p.enter();
-  let ret = maybe_semi_sep(p, SK::StrDecInSeq, str_dec_one);
+  let ret = maybe_semi_sep(p, SK::StrDecInSeq, |p| {
+    if stop.iter().any(|&kind| p.at(kind)) {
+      return false;
+    }
+    if str_dec_one(p) {
+      return true;
+    }
+    // a `DecStrDec` holds its `DecOne` directly, with no `Dec` in between.
+    let en = p.enter();
+    if dec_one_or_hole(p, e) {
+      p.exit(en, SK::DecStrDec);
+      true
+    } else {
+      p.abandon(en);
+      false
+    }
+  });
   p.exit(en, SK::StrDec);
   ret
 }
@@ -19,17 +50,7 @@ fn str_dec_one(p: &mut Parser<'_>) -> bool {
       if p.eat(SK::Name).is_none() {
         return false;
       }
-      p.eat(SK::LRound);
-      if p.at(SK::Name) {
-        let en = p.enter();
-        p.bump();
-        p.eat(SK::Colon);
-        must(p, sig_exp, Expected::SigExp);
-        p.exit(en, SK::FunctorArgNameSigExp);
-      } else {
-        spec(p);
-      }
-      p.eat(SK::RRound);
+      functor_arg_parens(p);
       if ascription(p) {
         ascription_tail(p);
       }
@@ -38,6 +59,18 @@ fn str_dec_one(p: &mut Parser<'_>) -> bool {
       true
     });
     p.exit(en, SK::FunctorDec);
+  } else if p.at(SK::FunsigKw) {
+    p.bump();
+    many_sep(p, SK::AndKw, SK::FunSigBind, |p| {
+      if p.eat(SK::Name).is_none() {
+        return false;
+      }
+      functor_arg_parens(p);
+      p.eat(SK::Eq);
+      must(p, sig_exp, Expected::SigExp);
+      true
+    });
+    p.exit(en, SK::FunSigDec);
   } else if p.at(SK::SignatureKw) {
     p.bump();
     many_sep(p, SK::AndKw, SK::SigBind, |p| {
@@ -80,11 +113,45 @@ fn str_dec_one(p: &mut Parser<'_>) -> bool {
   true
 }
 
+/// the parenthesized arguments of a functor or functor signature. there is always at least one.
+fn functor_arg_parens(p: &mut Parser<'_>) {
+  functor_arg_paren(p);
+  while p.at(SK::LRound) {
+    functor_arg_paren(p);
+  }
+}
+
+fn functor_arg_paren(p: &mut Parser<'_>) {
+  let en = p.enter();
+  p.eat(SK::LRound);
+  if p.at(SK::Name) {
+    let en = p.enter();
+    p.bump();
+    p.eat(SK::Colon);
+    must(p, sig_exp, Expected::SigExp);
+    p.exit(en, SK::FunctorArgNameSigExp);
+  } else if p.at(SK::FunctorKw) {
+    let en = p.enter();
+    p.bump();
+    p.eat(SK::Name);
+    p.eat(SK::Colon);
+    let name = p.enter();
+    p.eat(SK::Name);
+    p.exit(name, SK::FunSigName);
+    p.exit(en, SK::FunctorArgFunctor);
+  } else {
+    spec(p);
+  }
+  p.eat(SK::RRound);
+  p.exit(en, SK::FunctorArgParen);
+}
+
 fn str_exp(p: &mut Parser<'_>) -> Option<Exited> {
   let en = p.enter();
   let mut ex = if p.at(SK::StructKw) {
+    let stop: &[SK] = if p.is_unclosed() { &TOP_DEC_START } else { &[] };
     p.bump();
-    str_dec(p);
+    str_dec_until(p, stop, Expected::Dec);
     p.eat(SK::EndKw);
     p.exit(en, SK::StructStrExp)
   } else if p.at(SK::LetKw) {
@@ -99,15 +166,10 @@ fn str_exp(p: &mut Parser<'_>) -> Option<Exited> {
     p.exit(en, SK::PathStrExp)
   } else if p.at(SK::Name) {
     p.bump();
-    p.eat(SK::LRound);
-    let arg = p.enter();
-    if str_exp(p).is_some() {
-      p.exit(arg, SK::AppStrExpArgStrExp);
-    } else {
-      p.abandon(arg);
-      str_dec(p);
+    app_str_exp_arg_paren(p);
+    while p.at(SK::LRound) {
+      app_str_exp_arg_paren(p);
     }
-    p.eat(SK::RRound);
     p.exit(en, SK::AppStrExp)
   } else {
     p.abandon(en);
@@ -121,6 +183,20 @@ fn str_exp(p: &mut Parser<'_>) -> Option<Exited> {
   Some(ex)
 }
 
+fn app_str_exp_arg_paren(p: &mut Parser<'_>) {
+  let en = p.enter();
+  p.eat(SK::LRound);
+  let arg = p.enter();
+  if str_exp(p).is_some() {
+    p.exit(arg, SK::AppStrExpArgStrExp);
+  } else {
+    p.abandon(arg);
+    str_dec(p);
+  }
+  p.eat(SK::RRound);
+  p.exit(en, SK::AppStrExpArgParen);
+}
+
 fn ascription(p: &mut Parser<'_>) -> bool {
   p.at(SK::Colon) || p.at(SK::ColonGt)
 }
@@ -207,7 +283,10 @@ fn spec_one(p: &mut Parser<'_>) -> bool {
       true
     });
     p.exit(en, SK::ExSpec);
-  } else if p.at(SK::StructureKw) {
+  } else if p.at(SK::StructureKw) && !p.at_n(2, SK::Eq) && !p.at_n(2, SK::ColonGt) {
+    // `structure S =` and `structure S :>` start declarations, not specs. so if we see those, the
+    // enclosing signature is probably missing its `end`, and we stop here to let the declaration
+    // be parsed after the signature.
     p.bump();
     many_sep(p, SK::AndKw, SK::StrDesc, |p| {
       if p.eat(SK::Name).is_none() {
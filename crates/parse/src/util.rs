@@ -1,4 +1,4 @@
-use crate::parser::{Assoc, ErrorKind, Exited, Expected, Infix, Parser};
+use crate::parser::{Assoc, Entered, ErrorKind, Exited, Expected, Infix, Parser};
 use syntax::{token::Token, SyntaxKind as SK};
 
 /// emits an error and returns false if `f` failed. otherwise returns `true`.
@@ -13,6 +13,50 @@ where
   ret
 }
 
+/// if not at the end of input or a token in `recovery`, errors with `e`, then skips tokens until
+/// one of those, wrapping the skipped tokens in `wrap`. returns whether it skipped anything.
+///
+/// also stops at a closing token, like `)` or `end`, unless it closes an opening token that was
+/// skipped. this keeps the error local, so whatever encloses the skipped tokens can still end as
+/// normal.
+pub(crate) fn recover(p: &mut Parser<'_>, recovery: &[SK], wrap: SK, e: Expected) -> bool {
+  let mut entered = None::<Entered>;
+  let mut depth = 0usize;
+  while let Some(tok) = p.peek() {
+    let closes = matches!(tok.kind, SK::RRound | SK::RSquare | SK::RCurly | SK::EndKw);
+    if depth == 0 && (closes || recovery.contains(&tok.kind)) {
+      break;
+    }
+    if entered.is_none() {
+      p.error(ErrorKind::Expected(e));
+      entered = Some(p.enter());
+    }
+    if closes {
+      depth -= 1;
+    } else if matches!(
+      tok.kind,
+      SK::LRound
+        | SK::LSquare
+        | SK::LCurly
+        | SK::LetKw
+        | SK::LocalKw
+        | SK::StructKw
+        | SK::SigKw
+        | SK::AbstypeKw
+    ) {
+      depth += 1;
+    }
+    p.bump();
+  }
+  match entered {
+    Some(en) => {
+      p.exit(en, wrap);
+      true
+    }
+    None => false,
+  }
+}
+
 /// similar to `many_sep`, but:
 /// - always uses `;` as the separator
 /// - allows the separator to not be present
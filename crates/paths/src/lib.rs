@@ -69,6 +69,17 @@ impl PathId {
   pub fn wrap<T>(self, val: T) -> WithPath<T> {
     WithPath { path: self, val }
   }
+
+  /// Returns the raw value of this id, as for writing it down.
+  pub fn to_raw(self) -> usize {
+    self.0
+  }
+
+  /// Returns the id with the raw value, as returned by [`Self::to_raw`]. The id only means the same
+  /// path as before if it is used with a [`Root`] that gave the same ids to the same paths.
+  pub fn from_raw(raw: usize) -> Self {
+    Self(raw)
+  }
 }
 
 /// A pair of path id and value.
@@ -1,13 +1,15 @@
 //! Bases.
 
 use crate::types::{
-  Bs, CompositeOverload, Env, EnvLike as _, EnvStack, FunEnv, IdStatus, Overload, RecordTy, SigEnv,
-  StrEnv, Sym, Syms, Ty, TyEnv, TyInfo, TyScheme, TyVarKind, ValEnv, ValInfo,
+  BasicOverload, Bs, CompositeOverload, Env, EnvLike as _, EnvStack, FunEnv, FunSigEnv, IdStatus,
+  Overload, RecordTy, SigEnv, StrEnv, Sym, Syms, Ty, TyEnv, TyInfo, TyScheme, TyVarKind, ValEnv,
+  ValInfo,
 };
 use fast_hash::map;
+use serde::{Deserialize, Serialize};
 
 /// A basis.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Basis {
   pub(crate) inner: Bs,
 }
@@ -53,6 +55,68 @@ impl Basis {
     self.inner.as_mut_fun_env().insert(name, env);
     true
   }
+
+  /// Adds the functor signature named `other_name` from `other` into `self` with the name `name`,
+  /// or returns `false` if this was not possible.
+  pub fn add_fun_sig(&mut self, name: hir::Name, other: &Self, other_name: &hir::Name) -> bool {
+    let env = match other.inner.fun_sig_env.get(other_name) {
+      Some(x) => x.clone(),
+      None => return false,
+    };
+    self.inner.as_mut_fun_sig_env().insert(name, env);
+    true
+  }
+}
+
+/// Records how many bits the integer and word types in `basis` have under the `target`, so
+/// literals of those types may be checked.
+///
+/// With no target, a literal is only out of range if it is out of range under every target.
+pub fn set_target(syms: &mut Syms, basis: &Basis, target: Option<config::Target>) {
+  let (int, word, fixed_int) = match target {
+    None => (None, 63, 64),
+    Some(config::Target::SmlNj) => (Some(63), 63, 64),
+    Some(config::Target::MLton) => (Some(32), 32, 64),
+    Some(config::Target::PolyMl) => (None, 63, 63),
+  };
+  let bits = syms.lit_bits_mut();
+  bits.clear();
+  if let Some(int) = int {
+    bits.insert(Sym::INT, int);
+  }
+  bits.insert(Sym::WORD, word);
+  // a std basis may make some of these aliases for each other, like `Word64` for `LargeWord`. if
+  // the aliases disagree, the literal must fit in the smallest.
+  let others = [
+    ("FixedInt", "int", fixed_int),
+    ("Position", "int", fixed_int),
+    ("Int8", "int", 8),
+    ("Int16", "int", 16),
+    ("Int32", "int", 32),
+    ("Int64", "int", 64),
+    ("Word8", "word", 8),
+    ("Word16", "word", 16),
+    ("Word31", "word", 31),
+    ("Word32", "word", 32),
+    ("Word64", "word", 64),
+    ("LargeWord", "word", 64),
+    ("SysWord", "word", 64),
+  ];
+  for (structure, ty, n) in others {
+    let ty_info = basis
+      .inner
+      .env
+      .get_str(&hir::Name::new(structure))
+      .and_then(|env| env.ty_env.get(&hir::Name::new(ty)));
+    let sym = match ty_info.map(|x| &x.ty_scheme.ty) {
+      Some(Ty::Con(args, sym)) if args.is_empty() => *sym,
+      _ => continue,
+    };
+    bits
+      .entry(sym)
+      .and_modify(|x| *x = (*x).min(n))
+      .or_insert(n);
+  }
 }
 
 /// Returns the minimal basis and symbols.
@@ -96,6 +160,9 @@ pub fn minimal() -> (Syms, Basis) {
     }
   };
   insert_special(&mut syms, Sym::REF, ref_info);
+  for (sym, names) in [(Sym::BOOL, ["false", "true"]), (Sym::LIST, ["nil", "::"])] {
+    syms.set_con_order(sym, names.into_iter().map(hir::Name::new).collect());
+  }
   let aliases = [("unit", Ty::Record(RecordTy::new())), ("exn", Ty::EXN)];
   let ty_env: TyEnv = syms
     .iter()
@@ -110,12 +177,14 @@ pub fn minimal() -> (Syms, Basis) {
     }))
     .collect();
   let fns = {
-    let num_pair_to_num = overloaded(CompositeOverload::Num, |a| Ty::fun(dup(a.clone()), a));
-    let real_pair_to_real = TyScheme::zero(Ty::fun(dup(Ty::REAL), Ty::REAL));
-    let numtxt_pair_to_bool = overloaded(CompositeOverload::NumTxt, |a| Ty::fun(dup(a), Ty::BOOL));
-    let realint_to_realint = overloaded(CompositeOverload::RealInt, |a| Ty::fun(a.clone(), a));
+    let num_pair_to_num = composite(CompositeOverload::Num, |a| Ty::fun(dup(a.clone()), a));
+    let real_pair_to_real = overloaded(Overload::Basic(BasicOverload::Real), |a| {
+      Ty::fun(dup(a.clone()), a)
+    });
+    let numtxt_pair_to_bool = composite(CompositeOverload::NumTxt, |a| Ty::fun(dup(a), Ty::BOOL));
+    let realint_to_realint = composite(CompositeOverload::RealInt, |a| Ty::fun(a.clone(), a));
     let wordint_pair_to_wordint =
-      overloaded(CompositeOverload::WordInt, |a| Ty::fun(dup(a.clone()), a));
+      composite(CompositeOverload::WordInt, |a| Ty::fun(dup(a.clone()), a));
     let equality_pair_to_bool = TyScheme::one(|a| {
       let t = Ty::fun(dup(a), Ty::BOOL);
       (t, Some(TyVarKind::Equality))
@@ -153,6 +222,7 @@ pub fn minimal() -> (Syms, Basis) {
     inner: Bs {
       fun_env: FunEnv::default().into(),
       sig_env: SigEnv::default().into(),
+      fun_sig_env: FunSigEnv::default().into(),
       env: EnvStack::one(Env {
         str_env: StrEnv::default(),
         ty_env,
@@ -165,7 +235,7 @@ pub fn minimal() -> (Syms, Basis) {
 }
 
 fn insert_special(syms: &mut Syms, sym: Sym, ty_info: TyInfo) {
-  let started = syms.start(hir::Name::new(sym.special().unwrap()));
+  let started = syms.start(hir::Path::one(hir::Name::new(sym.special().unwrap())));
   assert_eq!(sym, started.sym());
   syms.finish(started, ty_info);
 }
@@ -198,11 +268,18 @@ where
     .collect()
 }
 
-fn overloaded<F>(x: CompositeOverload, f: F) -> TyScheme
+fn composite<F>(x: CompositeOverload, f: F) -> TyScheme
+where
+  F: FnOnce(Ty) -> Ty,
+{
+  overloaded(Overload::Composite(x), f)
+}
+
+fn overloaded<F>(x: Overload, f: F) -> TyScheme
 where
   F: FnOnce(Ty) -> Ty,
 {
-  TyScheme::one(|a| (f(a), Some(TyVarKind::Overloaded(Overload::Composite(x)))))
+  TyScheme::one(|a| (f(a), Some(TyVarKind::Overloaded(x))))
 }
 
 fn dup(ty: Ty) -> Ty {
@@ -1,12 +1,13 @@
 use crate::error::{ErrorKind, Item};
 use crate::get_env::{get_env_from_str_path, get_ty_info, get_val_info};
+use crate::info::ModItem;
 use crate::pat_match::Pat;
 use crate::st::St;
 use crate::types::{
   generalize, generalize_fixed, Cx, Env, EnvLike as _, FixedTyVars, Generalizable,
   HasRecordMetaVars, IdStatus, StartedSym, Ty, TyEnv, TyInfo, TyScheme, ValEnv, ValInfo,
 };
-use crate::unify::unify;
+use crate::unify::unify_src;
 use crate::util::{apply, ins_check_name, ins_no_dupe};
 use crate::{exp, pat, ty};
 use fast_hash::{FxHashMap, FxHashSet};
@@ -39,7 +40,8 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, env: &mut Env, dec: h
         // sml_def(25)
         let (pm_pat, mut want) = get_pat_and_src_exp(st, &cx, ars, &mut ve, val_bind, &mut src_exp);
         let got = exp::get(st, &cx, ars, val_bind.exp);
-        unify(st, want.clone(), got, dec.into());
+        let want_src = val_bind.pat.map(Into::into);
+        unify_src(st, &cx.env, want.clone(), got, dec.into(), want_src);
         apply(st.subst(), &mut want);
         st.insert_bind(
           pm_pat,
@@ -73,7 +75,8 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, env: &mut Env, dec: h
           }
         }
         let got = exp::get(st, &cx, ars, val_bind.exp);
-        unify(st, want.clone(), got, dec.into());
+        let want_src = val_bind.pat.map(Into::into);
+        unify_src(st, &cx.env, want.clone(), got, dec.into(), want_src);
         apply(st.subst(), &mut want);
         st.insert_bind(pm_pat, want, dec.into());
       }
@@ -180,7 +183,11 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, env: &mut Env, dec: h
     hir::Dec::Open(paths) => {
       for path in paths {
         match get_env_from_str_path(&cx.env, path) {
-          Ok(got_env) => env.append(&mut got_env.clone()),
+          Ok(got_env) => {
+            let item = ModItem::Structure(got_env.clone());
+            st.info().insert_mod(dec.into(), path.last().clone(), item);
+            env.append(&mut got_env.clone());
+          }
           Err(e) => st.err(dec, e),
         }
       }
@@ -287,7 +294,7 @@ pub(crate) fn get_dat_binds(
   // do a first pass through the datatypes to allow for recursive reference, and to put them in
   // scope for the types.
   for dat_bind in dat_binds.iter() {
-    let started = st.syms.start(dat_bind.name.clone());
+    let started = st.start_sym(dat_bind.name.clone());
     // just create the fixed ty vars, do not bring them into the scope of the cx yet.
     let mut fixed = FixedTyVars::default();
     for ty_var in dat_bind.ty_vars.iter() {
@@ -354,6 +361,8 @@ pub(crate) fn get_dat_binds(
       }
     }
     let mut val_env = ValEnv::default();
+    let con_order = dat_bind.cons.iter().map(|x| x.name.clone()).collect();
+    st.syms.set_con_order(datatype.started.sym(), con_order);
     // sml_def(29), sml_def(82)
     for con_bind in dat_bind.cons.iter() {
       let mut ty = datatype.out_ty.clone();
@@ -0,0 +1,219 @@
+//! Display environments as signatures, for hovering over structures, signatures, and functors.
+
+use crate::fmt_util::ty_var_name;
+use crate::types::{
+  Env, FunParam, FunSig, IdStatus, MetaVarInfo, MetaVarNames, Syms, Ty, TyInfo, TyPrec, TyVarKind,
+};
+use std::fmt;
+
+pub(crate) fn env<'a>(env: &'a Env, syms: &'a Syms, mv_info: &'a MetaVarInfo) -> EnvDisplay<'a> {
+  EnvDisplay {
+    env,
+    syms,
+    mv_info,
+    indent: 0,
+  }
+}
+
+pub(crate) fn functor<'a>(
+  name: &'a hir::Name,
+  fun_sig: &'a FunSig,
+  syms: &'a Syms,
+  mv_info: &'a MetaVarInfo,
+) -> FunctorDisplay<'a> {
+  FunctorDisplay {
+    name,
+    fun_sig,
+    syms,
+    mv_info,
+    is_fun_sig: false,
+  }
+}
+
+pub(crate) fn fun_sig<'a>(
+  name: &'a hir::Name,
+  fun_sig: &'a FunSig,
+  syms: &'a Syms,
+  mv_info: &'a MetaVarInfo,
+) -> FunctorDisplay<'a> {
+  FunctorDisplay {
+    name,
+    fun_sig,
+    syms,
+    mv_info,
+    is_fun_sig: true,
+  }
+}
+
+pub(crate) struct EnvDisplay<'a> {
+  env: &'a Env,
+  syms: &'a Syms,
+  mv_info: &'a MetaVarInfo,
+  indent: usize,
+}
+
+impl fmt::Display for EnvDisplay<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("sig\n")?;
+    let indent = self.indent + 1;
+    let mut strs: Vec<_> = self.env.str_env.iter().collect();
+    strs.sort_unstable_by_key(|&(name, _)| name);
+    for (name, env) in strs {
+      write_indent(f, indent)?;
+      write!(f, "structure {name} : ")?;
+      let env = EnvDisplay {
+        env,
+        syms: self.syms,
+        mv_info: self.mv_info,
+        indent,
+      };
+      writeln!(f, "{env}")?;
+    }
+    let mut tys: Vec<_> = self.env.ty_env.iter().collect();
+    tys.sort_unstable_by_key(|&(name, _)| name);
+    for (name, ty_info) in tys {
+      write_indent(f, indent)?;
+      self.ty(f, name, ty_info)?;
+      f.write_str("\n")?;
+    }
+    let mut vals: Vec<_> = self.env.val_env.iter().collect();
+    vals.sort_unstable_by_key(|&(name, _)| name);
+    for (name, val_info) in vals {
+      let ty_scheme = &val_info.ty_scheme;
+      let mut mvs = MetaVarNames::new(self.mv_info);
+      mvs.extend_for(&ty_scheme.ty);
+      match val_info.id_status {
+        // shown with their datatypes.
+        IdStatus::Con => continue,
+        IdStatus::Exn(_) => {
+          write_indent(f, indent)?;
+          write!(f, "exception {name}")?;
+          if let Ty::Fn(param, _) = &ty_scheme.ty {
+            let param = ty_scheme.display_part(param, TyPrec::Arrow, &mvs, self.syms);
+            write!(f, " of {param}")?;
+          }
+        }
+        IdStatus::Val => {
+          write_indent(f, indent)?;
+          let ty_scheme = ty_scheme.display(&mvs, self.syms);
+          write!(f, "val {name} : {ty_scheme}")?;
+        }
+      }
+      f.write_str("\n")?;
+    }
+    write_indent(f, self.indent)?;
+    f.write_str("end")
+  }
+}
+
+impl EnvDisplay<'_> {
+  fn ty(&self, f: &mut fmt::Formatter<'_>, name: &hir::Name, ty_info: &TyInfo) -> fmt::Result {
+    let ty_scheme = &ty_info.ty_scheme;
+    let mut mvs = MetaVarNames::new(self.mv_info);
+    mvs.extend_for(&ty_scheme.ty);
+    let kw = if ty_info.val_env.is_empty() {
+      "type"
+    } else {
+      "datatype"
+    };
+    write!(f, "{kw} ")?;
+    let params: Vec<_> = ty_scheme
+      .bound_vars
+      .kinds()
+      .enumerate()
+      .map(|(idx, kind)| ty_var_name(matches!(kind, Some(TyVarKind::Equality)), idx).to_string())
+      .collect();
+    match params.as_slice() {
+      [] => {}
+      [param] => write!(f, "{param} ")?,
+      params => write!(f, "({}) ", params.join(", "))?,
+    }
+    write!(f, "{name}")?;
+    if !ty_info.val_env.is_empty() {
+      let mut cons: Vec<_> = ty_info.val_env.iter().collect();
+      cons.sort_unstable_by_key(|&(name, _)| name);
+      f.write_str(" =")?;
+      for (idx, (con_name, val_info)) in cons.into_iter().enumerate() {
+        let sep = if idx == 0 { " " } else { " | " };
+        write!(f, "{sep}{con_name}")?;
+        let con_ty_scheme = &val_info.ty_scheme;
+        if let Ty::Fn(param, _) = &con_ty_scheme.ty {
+          let param = con_ty_scheme.display_part(param, TyPrec::Arrow, &mvs, self.syms);
+          write!(f, " of {param}")?;
+        }
+      }
+      return Ok(());
+    }
+    // abstract types are those whose definition is just themselves applied to their params.
+    let is_abstract = match &ty_scheme.ty {
+      Ty::Con(args, sym) => {
+        args.len() == params.len()
+          && args.iter().all(|arg| matches!(arg, Ty::BoundVar(_)))
+          && self
+            .syms
+            .get(sym)
+            .map_or(false, |(sym_name, _)| sym_name == name)
+      }
+      _ => false,
+    };
+    if !is_abstract {
+      let ty_scheme = ty_scheme.display(&mvs, self.syms);
+      write!(f, " = {ty_scheme}")?;
+    }
+    Ok(())
+  }
+}
+
+pub(crate) struct FunctorDisplay<'a> {
+  name: &'a hir::Name,
+  fun_sig: &'a FunSig,
+  syms: &'a Syms,
+  mv_info: &'a MetaVarInfo,
+  /// whether to display as a `funsig` instead of a `functor`.
+  is_fun_sig: bool,
+}
+
+impl fmt::Display for FunctorDisplay<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (kw, sep) = if self.is_fun_sig {
+      ("funsig", "=")
+    } else {
+      ("functor", ":")
+    };
+    write!(f, "{kw} {}", self.name)?;
+    let mut fun_sig = self.fun_sig;
+    loop {
+      let param_name = &fun_sig.param_name;
+      match &fun_sig.param {
+        FunParam::Sig(param) => {
+          let param_env = env(&param.env, self.syms, self.mv_info);
+          // lowering generates the param name when the param is given as a spec.
+          if param_name
+            .as_str()
+            .starts_with(|c: char| c.is_ascii_digit())
+          {
+            write!(f, " ({param_env})")?;
+          } else {
+            write!(f, " ({param_name} : {param_env})")?;
+          }
+        }
+        FunParam::FunSig(fun_sig_name, _) => {
+          write!(f, " (functor {param_name} : {fun_sig_name})")?;
+        }
+      }
+      match &fun_sig.result {
+        Some(result) => fun_sig = result,
+        None => break,
+      }
+    }
+    let body_env = env(&fun_sig.body_env, self.syms, self.mv_info);
+    write!(f, " {sep} {body_env}")
+  }
+}
+
+fn write_indent(f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+  for _ in 0..indent {
+    f.write_str("  ")?;
+  }
+  Ok(())
+}
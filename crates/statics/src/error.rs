@@ -1,14 +1,23 @@
-use crate::pat_match::{Con, Pat, VariantName};
-use crate::types::{MetaTyVar, MetaVarInfo, MetaVarNames, Sym, Syms, Ty};
+use crate::basis::Basis;
+use crate::pat_match::{pats_serde, Con, Pat, VariantName};
+use crate::types::{
+  deserialize_static_str, Env, EnvStack, MetaTyVar, MetaVarInfo, MetaVarNames, Sym, Syms, Ty,
+};
+use fast_hash::{FxHashMap, FxHashSet};
 use fmt_util::{comma_seq, sep_seq};
 use pattern_match::RawPat;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::Arc;
 
 /// A statics error.
 #[derive(Debug)]
 pub struct Error {
   pub(crate) idx: hir::Idx,
   pub(crate) kind: ErrorKind,
+  /// The environment where the error happened, if the error mentions types or constructors. The
+  /// names of these are displayed as they could be written there.
+  pub(crate) env: Option<EnvStack>,
 }
 
 impl Error {
@@ -26,12 +35,54 @@ impl Error {
   ) -> impl fmt::Display + 'a {
     ErrorKindDisplay {
       kind: &self.kind,
+      env: self.env.as_ref(),
       syms,
       mv_info,
       lines,
     }
   }
 
+  /// Returns the [`hir::Idx`] of something related to this, with a message about how it's related.
+  pub fn related(&self) -> Option<(hir::Idx, &'static str)> {
+    match &self.kind {
+      ErrorKind::MismatchedTypes {
+        want_src: Some(idx),
+        ..
+      } => Some((*idx, "the expected type comes from here")),
+      _ => None,
+    }
+  }
+
+  /// Returns the patterns missing from the `case` this is about, if this is a non-exhaustive `case`
+  /// error. The constructors are qualified as they could be written at the `case`.
+  pub fn missing_case_pats(&self, syms: &Syms) -> Option<Vec<String>> {
+    match &self.kind {
+      ErrorKind::NonExhaustiveCase(pats) => {
+        let paths = self.env.as_ref().map(|env| {
+          let mut want = FxHashSet::<Sym>::default();
+          for pat in pats {
+            variant_syms(&mut want, pat);
+          }
+          env.ty_name_paths(syms, &want)
+        });
+        let ret = pats
+          .iter()
+          .map(|pat| {
+            let pat = PatDisplay {
+              pat,
+              syms,
+              paths: paths.as_ref(),
+              prec: PatPrec::Min,
+            };
+            pat.to_string()
+          })
+          .collect();
+        Some(ret)
+      }
+      _ => None,
+    }
+  }
+
   /// Return the code for this.
   pub fn to_code(&self) -> u16 {
     match self.kind {
@@ -40,7 +91,7 @@ impl Error {
       ErrorKind::Missing(_, _) => 5003,
       ErrorKind::Extra(_, _) => 5004,
       ErrorKind::Circularity(_, _) => 5005,
-      ErrorKind::MismatchedTypes(_, _) => 5006,
+      ErrorKind::MismatchedTypes { .. } => 5006,
       ErrorKind::AppLhsNotFn(_) => 5007,
       ErrorKind::DuplicateLab(_) => 5008,
       ErrorKind::RealPat => 5009,
@@ -64,25 +115,152 @@ impl Error {
       ErrorKind::TyHole => 5027,
       ErrorKind::DecHole => 5028,
       ErrorKind::BindPolymorphicExpansiveExp => 5029,
+      ErrorKind::LitOutOfRange(_, _) => 5030,
+      ErrorKind::NeedsSmlNj(_) => 5031,
+      ErrorKind::FunctorArgNotFunctor => 5032,
+      ErrorKind::FunctorParamMismatch => 5033,
+      ErrorKind::WrongNumFunctorArgs(_, _) => 5034,
+      ErrorKind::WrongNumFunctorParams(_, _) => 5035,
       ErrorKind::Unsupported(_) => 5999,
     }
   }
 }
 
-#[derive(Debug)]
+/// Errors in a form that may be serialized, to be read back as an [`ErrorsRepr`].
+///
+/// The environments of the errors are mostly the environment of the basis they were found in, which
+/// may be large, so the parts of them from that basis are referred to instead of written out.
+#[derive(Debug, Serialize)]
+pub struct ErrorsReprRef<'a> {
+  /// the parts of the environments not from the basis. each is written once, even if many errors
+  /// share it.
+  envs: Vec<&'a Env>,
+  errors: Vec<ErrorRepr<&'a ErrorKind>>,
+}
+
+/// Errors read back from an [`ErrorsReprRef`].
+#[derive(Debug, Deserialize)]
+pub struct ErrorsRepr {
+  envs: Vec<Env>,
+  errors: Vec<ErrorRepr<ErrorKind>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ErrorRepr<K> {
+  idx: hir::Idx,
+  kind: K,
+  env: Option<Vec<EnvRef>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum EnvRef {
+  /// an index into the envs of the basis.
+  Basis(usize),
+  /// an index into the envs of the repr.
+  Own(usize),
+}
+
+impl<'a> ErrorsReprRef<'a> {
+  /// Returns the form of the `errors`, which were found in `basis`.
+  pub fn new(errors: &'a [Error], basis: &Basis) -> Self {
+    let basis_envs: FxHashMap<_, _> = basis_env_ptrs(basis).collect();
+    let mut own_envs = FxHashMap::<*const Env, usize>::default();
+    let mut envs = Vec::<&'a Env>::new();
+    let errors = errors
+      .iter()
+      .map(|e| {
+        let env = e.env.as_ref().map(|env| {
+          env
+            .arcs()
+            .iter()
+            .map(|arc| {
+              let ptr = Arc::as_ptr(arc);
+              if let Some(&idx) = basis_envs.get(&ptr) {
+                return EnvRef::Basis(idx);
+              }
+              let idx = *own_envs.entry(ptr).or_insert_with(|| {
+                envs.push(arc.as_ref());
+                envs.len() - 1
+              });
+              EnvRef::Own(idx)
+            })
+            .collect()
+        });
+        ErrorRepr {
+          idx: e.idx,
+          kind: &e.kind,
+          env,
+        }
+      })
+      .collect();
+    Self { envs, errors }
+  }
+}
+
+impl ErrorsRepr {
+  /// Returns the errors, which must have been found in `basis`, or `None` if this refers to an
+  /// environment not in `basis`.
+  pub fn into_errors(self, basis: &Basis) -> Option<Vec<Error>> {
+    let basis_envs = basis.inner.env.arcs();
+    let own_envs: Vec<_> = self.envs.into_iter().map(Arc::new).collect();
+    self
+      .errors
+      .into_iter()
+      .map(|e| {
+        let env = match e.env {
+          None => None,
+          Some(env) => {
+            let arcs = env
+              .into_iter()
+              .map(|env| match env {
+                EnvRef::Basis(idx) => basis_envs.get(idx).cloned(),
+                EnvRef::Own(idx) => own_envs.get(idx).cloned(),
+              })
+              .collect::<Option<Vec<_>>>()?;
+            Some(EnvStack::from_arcs(arcs))
+          }
+        };
+        Some(Error {
+          idx: e.idx,
+          kind: e.kind,
+          env,
+        })
+      })
+      .collect()
+  }
+}
+
+fn basis_env_ptrs(basis: &Basis) -> impl Iterator<Item = (*const Env, usize)> + '_ {
+  basis
+    .inner
+    .env
+    .arcs()
+    .iter()
+    .enumerate()
+    .map(|(idx, arc)| (Arc::as_ptr(arc), idx))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) enum ErrorKind {
   Undefined(Item, hir::Name),
   Duplicate(Item, hir::Name),
   Missing(Item, hir::Name),
   Extra(Item, hir::Name),
   Circularity(MetaTyVar, Ty),
-  MismatchedTypes(Ty, Ty),
+  MismatchedTypes {
+    want: Ty,
+    got: Ty,
+    /// The differing subterms of `want` and `got`, if they are not the whole types.
+    part: Option<(Ty, Ty)>,
+    /// The index of the thing that caused `want` to be wanted.
+    want_src: Option<hir::Idx>,
+  },
   AppLhsNotFn(Ty),
   DuplicateLab(hir::Lab),
   RealPat,
   UnreachablePattern,
-  NonExhaustiveCase(Vec<Pat>),
-  NonExhaustiveBinding(Vec<Pat>),
+  NonExhaustiveCase(#[serde(with = "pats_serde")] Vec<Pat>),
+  NonExhaustiveBinding(#[serde(with = "pats_serde")] Vec<Pat>),
   PatValIdStatus,
   ConPatMustNotHaveArg,
   ConPatMustHaveArg,
@@ -100,11 +278,17 @@ pub(crate) enum ErrorKind {
   TyHole,
   DecHole,
   BindPolymorphicExpansiveExp,
+  LitOutOfRange(Ty, u16),
+  NeedsSmlNj(#[serde(deserialize_with = "deserialize_static_str")] &'static str),
+  FunctorArgNotFunctor,
+  FunctorParamMismatch,
+  WrongNumFunctorArgs(usize, usize),
+  WrongNumFunctorParams(usize, usize),
   /// must be last
-  Unsupported(&'static str),
+  Unsupported(#[serde(deserialize_with = "deserialize_static_str")] &'static str),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) enum Item {
   Val,
   Ty,
@@ -112,6 +296,7 @@ pub(crate) enum Item {
   Struct,
   Sig,
   Functor,
+  FunSig,
 }
 
 impl fmt::Display for Item {
@@ -123,17 +308,34 @@ impl fmt::Display for Item {
       Item::Struct => f.write_str("structure"),
       Item::Sig => f.write_str("signature"),
       Item::Functor => f.write_str("functor"),
+      Item::FunSig => f.write_str("functor signature"),
     }
   }
 }
 
 struct ErrorKindDisplay<'a> {
   kind: &'a ErrorKind,
+  env: Option<&'a EnvStack>,
   syms: &'a Syms,
   mv_info: &'a MetaVarInfo,
   lines: config::ErrorLines,
 }
 
+impl<'a> ErrorKindDisplay<'a> {
+  /// returns names for the meta vars in the `tys`, which also name the types in them as they could
+  /// be written where the error happened.
+  fn meta_var_names(&self, tys: &[&Ty]) -> MetaVarNames<'a> {
+    let mut ret = MetaVarNames::new(self.mv_info);
+    for ty in tys {
+      ret.extend_for(ty);
+    }
+    if let Some(env) = self.env {
+      ret.set_env(env, self.syms);
+    }
+    ret
+  }
+}
+
 impl fmt::Display for ErrorKindDisplay<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self.kind {
@@ -142,31 +344,46 @@ impl fmt::Display for ErrorKindDisplay<'_> {
       ErrorKind::Missing(item, name) => write!(f, "missing {item} required by signature: {name}"),
       ErrorKind::Extra(item, name) => write!(f, "extra {item} not present in signature: {name}"),
       ErrorKind::Circularity(mv, ty) => {
-        let mut mvs = MetaVarNames::new(self.mv_info);
-        mvs.extend_for(ty);
+        let mvs = self.meta_var_names(&[ty]);
         let name = mvs.get(mv).ok_or(fmt::Error)?;
         let ty = ty.display(&mvs, self.syms);
         write!(f, "attempted to a set a type variable {name} ")?;
         write!(f, "to a type containing that variable: {ty}")
       }
-      ErrorKind::MismatchedTypes(want, got) => {
-        let mut mvs = MetaVarNames::new(self.mv_info);
-        mvs.extend_for(want);
-        mvs.extend_for(got);
+      ErrorKind::MismatchedTypes {
+        want, got, part, ..
+      } => {
+        let mvs = match part {
+          Some((part_want, part_got)) => self.meta_var_names(&[want, got, part_want, part_got]),
+          None => self.meta_var_names(&[want, got]),
+        };
         let want = want.display(&mvs, self.syms);
         let got = got.display(&mvs, self.syms);
         match self.lines {
-          config::ErrorLines::One => write!(f, "expected {want}, found {got}"),
+          config::ErrorLines::One => write!(f, "expected {want}, found {got}")?,
           config::ErrorLines::Many => {
             writeln!(f, "mismatched types:")?;
             writeln!(f, "  expected {want}")?;
-            write!(f, "     found {got}")
+            write!(f, "     found {got}")?;
           }
         }
+        if let Some((want, got)) = part {
+          let want = want.display(&mvs, self.syms);
+          let got = got.display(&mvs, self.syms);
+          match self.lines {
+            config::ErrorLines::One => write!(f, " (in particular, expected {want}, found {got})")?,
+            config::ErrorLines::Many => {
+              writeln!(f)?;
+              writeln!(f, "in particular:")?;
+              writeln!(f, "  expected {want}")?;
+              write!(f, "     found {got}")?;
+            }
+          }
+        }
+        Ok(())
       }
       ErrorKind::AppLhsNotFn(got) => {
-        let mut mvs = MetaVarNames::new(self.mv_info);
-        mvs.extend_for(got);
+        let mvs = self.meta_var_names(&[got]);
         let got = got.display(&mvs, self.syms);
         write!(f, "expected a function type, found {got}")
       }
@@ -203,8 +420,7 @@ impl fmt::Display for ErrorKindDisplay<'_> {
       }
       ErrorKind::DecNotAllowedHere => f.write_str("declaration not allowed here"),
       ErrorKind::ExpHole(ty) => {
-        let mut mvs = MetaVarNames::new(self.mv_info);
-        mvs.extend_for(ty);
+        let mvs = self.meta_var_names(&[ty]);
         let ty = ty.display(&mvs, self.syms);
         write!(f, "expression hole with type {ty}")
       }
@@ -213,6 +429,29 @@ impl fmt::Display for ErrorKindDisplay<'_> {
       ErrorKind::BindPolymorphicExpansiveExp => {
         f.write_str("cannot bind expansive polymorphic expression")
       }
+      ErrorKind::LitOutOfRange(ty, bits) => {
+        let mvs = self.meta_var_names(&[ty]);
+        let ty = ty.display(&mvs, self.syms);
+        write!(f, "literal out of range for {bits}-bit type {ty}")
+      }
+      ErrorKind::NeedsSmlNj(s) => write!(f, "{s} requires the `smlnj` target"),
+      ErrorKind::FunctorArgNotFunctor => {
+        f.write_str("expected the name of a functor as the argument")
+      }
+      ErrorKind::FunctorParamMismatch => {
+        f.write_str("mismatched functor parameters: one takes a structure, the other a functor")
+      }
+      ErrorKind::WrongNumFunctorArgs(want, got) => {
+        let s = if *want == 1 { "" } else { "s" };
+        write!(f, "expected {want} functor argument{s}, found {got}")
+      }
+      ErrorKind::WrongNumFunctorParams(want, got) => {
+        let s = if *want == 1 { "" } else { "s" };
+        write!(
+          f,
+          "expected a functor with {want} parameter{s}, found one with {got}"
+        )
+      }
       ErrorKind::Unsupported(s) => write!(f, "unsupported language construct: {s}"),
     }
   }
@@ -230,11 +469,12 @@ fn non_exhaustive(
   let iter = pats.iter().take(max_len).map(|pat| PatDisplay {
     pat,
     syms,
+    paths: None,
     prec: PatPrec::Min,
   });
   comma_seq(f, iter)?;
   if pats.len() > max_len {
-    write!(f, ", and {} others", pats.len() - max_len)?;
+    write!(f, ", and {} more", pats.len() - max_len)?;
   }
   Ok(())
 }
@@ -242,6 +482,8 @@ fn non_exhaustive(
 struct PatDisplay<'a> {
   pat: &'a Pat,
   syms: &'a Syms,
+  /// if present, the paths to the types of the constructors, which qualify the constructors.
+  paths: Option<&'a FxHashMap<Sym, Vec<hir::Name>>>,
   prec: PatPrec,
 }
 
@@ -286,32 +528,51 @@ impl<'a> fmt::Display for PatDisplay<'a> {
               args.iter().map(|pat| PatDisplay {
                 pat,
                 syms: self.syms,
+                paths: self.paths,
                 prec: PatPrec::Min,
               }),
             )?;
             f.write_str(")")?;
           } else {
-            f.write_str("{")?;
-            comma_seq(
-              f,
-              labels
-                .iter()
-                .zip(args)
-                .map(|(lab, pat)| RowDisplay::Row {
-                  lab,
-                  pat,
-                  syms: self.syms,
-                })
-                .chain(allows_other.then_some(RowDisplay::Rest)),
-            )?;
-            f.write_str("}")?;
+            // rows that could be anything don't matter, so we elide them with `...`.
+            let rows: Vec<_> = labels
+              .iter()
+              .zip(args)
+              .filter(|(_, pat)| !matches!(pat.raw, RawPat::Con(Con::Any, _)))
+              .collect();
+            if rows.is_empty() {
+              f.write_str("_")?;
+            } else {
+              let has_rest = *allows_other || rows.len() < labels.len();
+              f.write_str("{")?;
+              comma_seq(
+                f,
+                rows
+                  .into_iter()
+                  .map(|(lab, pat)| RowDisplay::Row {
+                    lab,
+                    pat,
+                    syms: self.syms,
+                    paths: self.paths,
+                  })
+                  .chain(has_rest.then_some(RowDisplay::Rest)),
+              )?;
+              f.write_str("}")?;
+            }
           }
         }
-        Con::Variant(_, name) => {
+        Con::Variant(sym, name) => {
           let name = match name {
             VariantName::Name(name) => name.as_str(),
             VariantName::Exn(exn) => self.syms.get_exn(exn).0.as_str(),
           };
+          // qualify the constructor by the structures its type is in. the last name of the path is
+          // the type itself.
+          let structures = self
+            .paths
+            .and_then(|paths| paths.get(sym))
+            .and_then(|path| path.split_last())
+            .map_or(&[][..], |(_, structures)| structures);
           let needs_paren = !args.is_empty() && matches!(self.prec, PatPrec::App);
           // these names are guaranteed not to be rebound, so they always are list constructors.
           if matches!(name, "nil" | "::") {
@@ -325,6 +586,7 @@ impl<'a> fmt::Display for PatDisplay<'a> {
                   ac.into_iter().map(|pat| PatDisplay {
                     pat,
                     syms: self.syms,
+                    paths: self.paths,
                     prec: PatPrec::Min,
                   }),
                 )?;
@@ -340,6 +602,7 @@ impl<'a> fmt::Display for PatDisplay<'a> {
                   ac.into_iter().map(|pat| PatDisplay {
                     pat,
                     syms: self.syms,
+                    paths: self.paths,
                     prec: PatPrec::App,
                   }),
                 )?;
@@ -352,6 +615,9 @@ impl<'a> fmt::Display for PatDisplay<'a> {
             if needs_paren {
               f.write_str("(")?;
             }
+            for structure in structures {
+              write!(f, "{structure}.")?;
+            }
             f.write_str(name)?;
             if !args.is_empty() {
               f.write_str(" ")?;
@@ -360,6 +626,7 @@ impl<'a> fmt::Display for PatDisplay<'a> {
                 args.iter().map(|pat| PatDisplay {
                   pat,
                   syms: self.syms,
+                  paths: self.paths,
                   prec: PatPrec::App,
                 }),
               )?;
@@ -378,6 +645,7 @@ impl<'a> fmt::Display for PatDisplay<'a> {
           pats.iter().map(|pat| PatDisplay {
             pat,
             syms: self.syms,
+            paths: self.paths,
             prec: PatPrec::Min,
           }),
         )?;
@@ -388,6 +656,25 @@ impl<'a> fmt::Display for PatDisplay<'a> {
   }
 }
 
+/// adds the syms of the types of the non-exception constructors in the pat to `ac`.
+fn variant_syms(ac: &mut FxHashSet<Sym>, pat: &Pat) {
+  match &pat.raw {
+    RawPat::Con(con, args) => {
+      if let Con::Variant(sym, VariantName::Name(_)) = con {
+        ac.insert(*sym);
+      }
+      for arg in args {
+        variant_syms(ac, arg);
+      }
+    }
+    RawPat::Or(pats) => {
+      for pat in pats {
+        variant_syms(ac, pat);
+      }
+    }
+  }
+}
+
 enum ListPatLen {
   Known,
   Unknown,
@@ -452,6 +739,7 @@ enum RowDisplay<'a> {
     lab: &'a hir::Lab,
     pat: &'a Pat,
     syms: &'a Syms,
+    paths: Option<&'a FxHashMap<Sym, Vec<hir::Name>>>,
   },
   Rest,
 }
@@ -459,12 +747,18 @@ enum RowDisplay<'a> {
 impl<'a> fmt::Display for RowDisplay<'a> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
-      RowDisplay::Row { lab, pat, syms } => {
+      RowDisplay::Row {
+        lab,
+        pat,
+        syms,
+        paths,
+      } => {
         lab.fmt(f)?;
         f.write_str(" = ")?;
         let pd = PatDisplay {
           pat,
           syms,
+          paths: *paths,
           prec: PatPrec::Min,
         };
         pd.fmt(f)
@@ -1,12 +1,12 @@
 use crate::error::{ErrorKind, Item};
-use crate::get_env::get_val_info;
-use crate::info::TyEntry;
+use crate::get_env::{get_str_def, get_val_info};
+use crate::info::{TyEntry, ValKind};
 use crate::pat_match::Pat;
 use crate::st::St;
 use crate::types::{
   Cx, Def, Env, EnvLike as _, Generalizable, Sym, SymsMarker, Ty, TyScheme, ValEnv,
 };
-use crate::unify::unify;
+use crate::unify::{unify, unify_src};
 use crate::util::{apply, get_scon, instantiate, record};
 use crate::{dec, pat, ty};
 
@@ -21,14 +21,16 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, exp: hir::ExpIdx) ->
   let ret = match &ars.exp[exp] {
     hir::Exp::Hole => {
       let mv = st.meta_gen.gen(Generalizable::Always);
-      st.insert_hole(mv, exp.into());
+      st.insert_hole(mv, exp.into(), &cx.env);
       Ty::MetaVar(mv)
     }
     // sml_def(1)
-    hir::Exp::SCon(scon) => get_scon(st, scon, Generalizable::Always),
+    hir::Exp::SCon(scon) => get_scon(st, &cx.env, exp.into(), scon, Generalizable::Always),
     // sml_def(2)
     hir::Exp::Path(path) => match get_val_info(&cx.env, path) {
       Ok(Some(val_info)) => {
+        st.info()
+          .insert_val_kind(exp.into(), ValKind::from(&val_info.id_status));
         ty_scheme = Some(val_info.ty_scheme.clone());
         def = val_info.def;
         instantiate(st, val_info.ty_scheme.clone(), Generalizable::Always)
@@ -73,16 +75,21 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, exp: hir::ExpIdx) ->
         Ty::MetaVar(_) => {
           let mut ret = Ty::MetaVar(st.meta_gen.gen(Generalizable::Always));
           let got = Ty::fun(arg_ty, ret.clone());
-          unify(st, func_ty, got, exp.into());
+          unify_src(st, &cx.env, func_ty, got, exp.into(), func.map(Into::into));
           apply(st.subst(), &mut ret);
           ret
         }
         Ty::FixedVar(_) | Ty::Record(_) | Ty::Con(_, _) => {
-          st.err(func.unwrap_or(exp), ErrorKind::AppLhsNotFn(func_ty));
+          st.err_in(
+            &cx.env,
+            func.unwrap_or(exp),
+            ErrorKind::AppLhsNotFn(func_ty),
+          );
           Ty::None
         }
         Ty::Fn(want_arg, mut want_res) => {
-          unify(st, *want_arg, arg_ty, arg.unwrap_or(exp).into());
+          let idx = arg.unwrap_or(exp).into();
+          unify_src(st, &cx.env, *want_arg, arg_ty, idx, func.map(Into::into));
           apply(st.subst(), want_res.as_mut());
           *want_res
         }
@@ -93,8 +100,8 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, exp: hir::ExpIdx) ->
       let mut exp_ty = get(st, cx, ars, *inner);
       let (pats, param, res) = get_matcher(st, cx, ars, matcher, exp.into());
       let idx = inner.unwrap_or(exp);
-      unify(st, Ty::EXN, param.clone(), idx.into());
-      unify(st, exp_ty.clone(), res, idx.into());
+      unify(st, &cx.env, Ty::EXN, param.clone(), idx.into());
+      unify(st, &cx.env, exp_ty.clone(), res, idx.into());
       apply(st.subst(), &mut exp_ty);
       st.insert_handle(pats, param, idx.into());
       exp_ty
@@ -102,20 +109,21 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, exp: hir::ExpIdx) ->
     // sml_def(11)
     hir::Exp::Raise(inner) => {
       let got = get(st, cx, ars, *inner);
-      unify(st, Ty::EXN, got, inner.unwrap_or(exp).into());
+      unify(st, &cx.env, Ty::EXN, got, inner.unwrap_or(exp).into());
       Ty::MetaVar(st.meta_gen.gen(Generalizable::Always))
     }
     // sml_def(12)
     hir::Exp::Fn(matcher) => {
       let (pats, param, res) = get_matcher(st, cx, ars, matcher, exp.into());
-      st.insert_case(pats, param.clone(), exp.into());
+      st.insert_case(pats, param.clone(), exp.into(), &cx.env);
       Ty::fun(param, res)
     }
     // sml_def(9)
     hir::Exp::Typed(inner, want) => {
       let got = get(st, cx, ars, *inner);
+      let want_src = want.map(Into::into);
       let mut want = ty::get(st, cx, ars, *want);
-      unify(st, want.clone(), got, exp.into());
+      unify_src(st, &cx.env, want.clone(), got, exp.into(), want_src);
       apply(st.subst(), &mut want);
       want
     }
@@ -125,6 +133,11 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, exp: hir::ExpIdx) ->
     ty_scheme,
   };
   st.info().insert(exp.into(), Some(ty_entry), def);
+  if let hir::Exp::Path(path) = &ars.exp[exp] {
+    if let Some(str_def) = get_str_def(&cx.env, path) {
+      st.info().insert_str_def(exp.into(), str_def);
+    }
+  }
   ret
 }
 
@@ -139,6 +152,8 @@ fn get_matcher(
   let mut param_ty = Ty::MetaVar(st.meta_gen.gen(Generalizable::Always));
   let mut res_ty = Ty::MetaVar(st.meta_gen.gen(Generalizable::Always));
   let mut pats = Vec::<Pat>::new();
+  // the first arm's pattern and expression determine the types wanted for all later arms.
+  let mut want_src = None::<(hir::Idx, hir::Idx)>;
   st.meta_gen.inc_rank();
   // sml_def(14)
   for &(pat, exp) in matcher {
@@ -151,9 +166,10 @@ fn get_matcher(
     });
     let exp_ty = get(st, &cx, ars, exp);
     let pi = pat.map_or(idx, Into::into);
-    unify(st, param_ty.clone(), pat_ty, pi);
     let ei = exp.map_or(idx, Into::into);
-    unify(st, res_ty.clone(), exp_ty, ei);
+    let (want_pi, want_ei) = *want_src.get_or_insert((pi, ei));
+    unify_src(st, &cx.env, param_ty.clone(), pat_ty, pi, Some(want_pi));
+    unify_src(st, &cx.env, res_ty.clone(), exp_ty, ei, Some(want_ei));
     apply(st.subst(), &mut param_ty);
     apply(st.subst(), &mut res_ty);
     pats.push(pm_pat);
@@ -30,3 +30,20 @@ pub(crate) fn idx_to_name(idx: usize) -> impl Iterator<Item = char> {
   let ch = char::from((rem as u8) + b'a');
   std::iter::repeat(ch).take(quot + 1)
 }
+
+/// returns the shortest suffix of the names in `path` that is not also a suffix of any of the
+/// `others`, or all of the names if there is no such suffix.
+pub(crate) fn unambiguous_suffix<'a>(
+  path: &'a hir::Path,
+  others: &[&hir::Path],
+) -> Vec<&'a hir::Name> {
+  let names: Vec<_> = path.all_names().collect();
+  let others: Vec<Vec<_>> = others.iter().map(|x| x.all_names().collect()).collect();
+  for len in 1..names.len() {
+    let suffix = &names[names.len() - len..];
+    if others.iter().all(|other| !other.ends_with(suffix)) {
+      return suffix.to_vec();
+    }
+  }
+  names
+}
@@ -1,13 +1,19 @@
 use crate::fmt_util::ty_var_name;
 use crate::st::St;
-use crate::types::{Generalizable, Ty, TyScheme, TyVarKind};
+use crate::types::{EnvStack, Generalizable, Ty, TyScheme, TyVarKind};
 use crate::unify::{unify, unify_, Result};
 use crate::util::{apply_bv, instantiate};
 
 /// emits no error iff `lhs` and `rhs` are equal ty schemes.
-pub(crate) fn eq_ty_scheme(st: &mut St, lhs: TyScheme, rhs: TyScheme, idx: hir::Idx) {
-  generalizes(st, lhs.clone(), &rhs, idx);
-  generalizes(st, rhs, &lhs, idx);
+pub(crate) fn eq_ty_scheme(
+  st: &mut St,
+  env: &EnvStack,
+  lhs: TyScheme,
+  rhs: TyScheme,
+  idx: hir::Idx,
+) {
+  generalizes(st, env, lhs.clone(), &rhs, idx);
+  generalizes(st, env, rhs, &lhs, idx);
 }
 
 /// we _maybe_ could use `#[derive(PartialEq, Eq)]` for [`Ty`] and [`crate::types::BoundTyVars`],
@@ -45,9 +51,15 @@ fn prepare_generalize(st: &mut St, general: TyScheme, specific: &TyScheme) -> (T
 }
 
 /// emits no error iff `general` generalizes `specific`.
-pub(crate) fn generalizes(st: &mut St, general: TyScheme, specific: &TyScheme, idx: hir::Idx) {
+pub(crate) fn generalizes(
+  st: &mut St,
+  env: &EnvStack,
+  general: TyScheme,
+  specific: &TyScheme,
+  idx: hir::Idx,
+) {
   let (general, specific) = prepare_generalize(st, general, specific);
-  unify(st, specific, general, idx)
+  unify(st, env, specific, general, idx)
 }
 
 fn generalizes_no_emit(st: &mut St, general: TyScheme, specific: &TyScheme) -> Result {
@@ -1,5 +1,5 @@
 use crate::error::{ErrorKind, Item};
-use crate::types::{Env, EnvLike, TyInfo, ValInfo};
+use crate::types::{Def, Env, EnvLike, TyInfo, ValInfo};
 
 /// uses the `names` to traverse through the `StrEnv`s of successive `env`s, returning either the
 /// final `env` or an error for the first name that was unbound.
@@ -28,6 +28,14 @@ where
   Ok(Some(env))
 }
 
+/// returns the definition of the first structure of the `path`, if it is qualified.
+pub(crate) fn get_str_def<E>(env: &E, path: &hir::Path) -> Option<Def>
+where
+  E: EnvLike,
+{
+  env.get_str(path.structures().first()?)?.def
+}
+
 pub(crate) fn get_env_from_str_path<'e, E>(
   env: &'e E,
   path: &hir::Path,
@@ -1,27 +1,115 @@
-use crate::types::{Def, DefPath, MetaVarInfo, MetaVarNames, Syms, Ty, TyScheme};
+use crate::env_display;
+use crate::types::{
+  static_str, Def, DefPath, Env, FunSig, IdStatus, MetaVarInfo, MetaVarNames, Sig, Syms, Ty,
+  TyPrec, TyScheme,
+};
 use crate::util::ty_syms;
 use fast_hash::FxHashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Write as _;
 
 /// Information about HIR indices.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Info {
   mode: Mode,
   store: FxHashMap<hir::Idx, InfoEntry>,
+  val_kinds: FxHashMap<hir::Idx, ValKind>,
+  fills: FxHashMap<hir::Idx, Vec<Fill>>,
   pub(crate) meta_vars: MetaVarInfo,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TyEntry {
   pub(crate) ty: Ty,
   pub(crate) ty_scheme: Option<TyScheme>,
 }
 
-#[derive(Debug, Default, Clone)]
+/// A way to fill an expression hole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Fill {
+  pub(crate) path: hir::Path,
+  pub(crate) args: Vec<usize>,
+  /// the type of the value named by `path`, instantiated to fit the hole.
+  pub(crate) ty: Ty,
+}
+
+/// A structure-level item, named at an index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum ModItem {
+  Structure(Env),
+  Signature(Sig),
+  Functor(FunSig),
+  FunSig(FunSig),
+}
+
+impl ModItem {
+  fn def(&self) -> Option<Def> {
+    match self {
+      ModItem::Structure(env) => env.def,
+      ModItem::Signature(sig) => sig.env.def,
+      ModItem::Functor(fun_sig) | ModItem::FunSig(fun_sig) => fun_sig.body_env.def,
+    }
+  }
+
+  fn kind(&self) -> ModKind {
+    match self {
+      ModItem::Structure(_) => ModKind::Structure,
+      ModItem::Signature(_) => ModKind::Signature,
+      ModItem::Functor(_) => ModKind::Functor,
+      ModItem::FunSig(_) => ModKind::FunSig,
+    }
+  }
+
+  fn display(&self, name: &hir::Name, syms: &Syms, mv_info: &MetaVarInfo) -> String {
+    match self {
+      ModItem::Structure(env) => {
+        let env = env_display::env(env, syms, mv_info);
+        format!("structure {name} : {env}")
+      }
+      ModItem::Signature(sig) => {
+        let env = env_display::env(&sig.env, syms, mv_info);
+        format!("signature {name} = {env}")
+      }
+      ModItem::Functor(fun_sig) => env_display::functor(name, fun_sig, syms, mv_info).to_string(),
+      ModItem::FunSig(fun_sig) => env_display::fun_sig(name, fun_sig, syms, mv_info).to_string(),
+    }
+  }
+}
+
+/// What a structure-level item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ModKind {
+  /// A structure.
+  Structure,
+  /// A signature.
+  Signature,
+  /// A functor.
+  Functor,
+  /// A functor signature. SML/NJ extension.
+  FunSig,
+}
+
+impl ModKind {
+  /// Returns the keyword that introduces this kind of item.
+  pub fn keyword(&self) -> &'static str {
+    match self {
+      ModKind::Structure => "structure",
+      ModKind::Signature => "signature",
+      ModKind::Functor => "functor",
+      ModKind::FunSig => "funsig",
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 struct InfoEntry {
   ty_entry: Option<TyEntry>,
   def: Option<Def>,
+  /// the definition of the first structure of a qualified path, like `S` in `S.x`.
+  str_def: Option<Def>,
   doc: Option<String>,
+  /// there may be many, e.g. for `structure A = X and B = Y` or `open A B`.
+  mods: Vec<(hir::Name, ModItem)>,
 }
 
 impl Info {
@@ -29,6 +117,8 @@ impl Info {
     Self {
       mode,
       store: FxHashMap::default(),
+      val_kinds: FxHashMap::default(),
+      fills: FxHashMap::default(),
       meta_vars: MetaVarInfo::default(),
     }
   }
@@ -43,11 +133,31 @@ impl Info {
         ty_entry
       }),
       def,
+      str_def: None,
       doc: None,
+      mods: Vec::new(),
     };
     assert!(self.store.insert(idx, entry).is_none());
   }
 
+  /// Must be called after [`Self::insert`] for the `idx`, if that is called at all.
+  pub(crate) fn insert_mod(&mut self, idx: hir::Idx, name: hir::Name, item: ModItem) {
+    self.store.entry(idx).or_default().mods.push((name, item));
+  }
+
+  /// Must be called after [`Self::insert`] for the `idx`, if that is called at all.
+  pub(crate) fn insert_str_def(&mut self, idx: hir::Idx, def: Def) {
+    self.store.entry(idx).or_default().str_def = Some(def);
+  }
+
+  pub(crate) fn insert_val_kind(&mut self, idx: hir::Idx, val_kind: ValKind) {
+    self.val_kinds.insert(idx, val_kind);
+  }
+
+  pub(crate) fn insert_fills(&mut self, idx: hir::Idx, fills: Vec<Fill>) {
+    self.fills.insert(idx, fills);
+  }
+
   /// Add documentation to an index. Returns the old doc.
   pub fn add_doc(&mut self, idx: hir::Idx, doc: String) -> Option<String> {
     self.store.entry(idx).or_default().doc.replace(doc)
@@ -94,6 +204,160 @@ impl Info {
     Some(())
   }
 
+  /// Returns a Markdown string with the signature of the structure, signature, or functor named
+  /// `name` at this index. If there is no such name but there is only one item at the index, uses
+  /// that.
+  pub fn get_mod_md(&self, syms: &Syms, idx: hir::Idx, name: &str) -> Option<String> {
+    let (name, item) = self.get_mod(idx, name)?;
+    let mut ret = String::new();
+    writeln!(ret, "```sml").unwrap();
+    writeln!(ret, "{}", item.display(name, syms, &self.meta_vars)).unwrap();
+    writeln!(ret, "```").unwrap();
+    Some(ret)
+  }
+
+  /// Returns the kinds and names of all the structures, signatures, and functors named at this
+  /// index.
+  pub fn get_mod_names(&self, idx: hir::Idx) -> Vec<(ModKind, &hir::Name)> {
+    let mods = match self.store.get(&idx) {
+      Some(entry) => entry.mods.as_slice(),
+      None => return Vec::new(),
+    };
+    mods
+      .iter()
+      .map(|(name, item)| (item.kind(), name))
+      .collect()
+  }
+
+  /// Returns all the structures, signatures, and functors named at this index, each with SML
+  /// describing its signature.
+  pub fn get_mods(&self, syms: &Syms, idx: hir::Idx) -> Vec<(ModKind, &hir::Name, String)> {
+    let mods = match self.store.get(&idx) {
+      Some(entry) => entry.mods.as_slice(),
+      None => return Vec::new(),
+    };
+    mods
+      .iter()
+      .map(|(name, item)| (item.kind(), name, item.display(name, syms, &self.meta_vars)))
+      .collect()
+  }
+
+  /// Returns the definition site of the structure, signature, or functor named `name` at this
+  /// index, with the same fallback as [`Self::get_mod_md`].
+  pub fn get_mod_def(&self, idx: hir::Idx, name: &str) -> Option<Def> {
+    self.get_mod(idx, name)?.1.def()
+  }
+
+  fn get_mod(&self, idx: hir::Idx, name: &str) -> Option<(&hir::Name, &ModItem)> {
+    let mods = &self.store.get(&idx)?.mods;
+    let ret = match mods.iter().find(|(x, _)| x.as_str() == name) {
+      Some(x) => x,
+      None => match mods.as_slice() {
+        [x] => x,
+        _ => return None,
+      },
+    };
+    Some((&ret.0, &ret.1))
+  }
+
+  /// Returns the types to show as hints for the indices, in the order given. Meta type variables are
+  /// named consistently across all of the returned types.
+  ///
+  /// Only variables bound by patterns and usages of polymorphic values get a hint.
+  pub fn get_ty_hints<I>(&self, syms: &Syms, idxs: I) -> Vec<(hir::Idx, String)>
+  where
+    I: IntoIterator<Item = hir::Idx>,
+  {
+    let mut mvs = MetaVarNames::new(&self.meta_vars);
+    idxs
+      .into_iter()
+      .filter_map(|idx| {
+        let ty_entry = self.store.get(&idx)?.ty_entry.as_ref()?;
+        let wants_hint = match idx {
+          hir::Idx::Pat(_) => matches!(self.val_kinds.get(&idx), Some(ValKind::Val)),
+          hir::Idx::Exp(_) => ty_entry.ty_scheme.is_some(),
+          _ => false,
+        };
+        if !wants_hint || matches!(ty_entry.ty, Ty::None) {
+          return None;
+        }
+        mvs.extend_for(&ty_entry.ty);
+        Some((idx, ty_entry.ty.display(&mvs, syms).to_string()))
+      })
+      .collect()
+  }
+
+  /// Returns the type of the function at this index, split into its curried parameters.
+  pub fn get_fn_signature(&self, syms: &Syms, idx: hir::Idx) -> Option<FnSignature> {
+    let ty_entry = self.store.get(&idx)?.ty_entry.as_ref()?;
+    let ty_scheme = match &ty_entry.ty_scheme {
+      Some(x) => x.clone(),
+      None => TyScheme::zero(ty_entry.ty.clone()),
+    };
+    let mut mvs = MetaVarNames::new(&self.meta_vars);
+    mvs.extend_for(&ty_scheme.ty);
+    let mut params = Vec::<Vec<String>>::new();
+    let mut ty = &ty_scheme.ty;
+    while let Ty::Fn(param, res) = ty {
+      let param = match param.as_ref() {
+        Ty::Record(rows)
+          if rows.len() > 1
+            && rows
+              .keys()
+              .enumerate()
+              .all(|(idx, lab)| hir::Lab::tuple(idx) == *lab) =>
+        {
+          rows
+            .values()
+            .map(|ty| {
+              ty_scheme
+                .display_part(ty, TyPrec::App, &mvs, syms)
+                .to_string()
+            })
+            .collect()
+        }
+        param => vec![ty_scheme
+          .display_part(param, TyPrec::Star, &mvs, syms)
+          .to_string()],
+      };
+      params.push(param);
+      ty = res.as_ref();
+    }
+    if params.is_empty() {
+      return None;
+    }
+    let res = ty_scheme
+      .display_part(ty, TyPrec::Arrow, &mvs, syms)
+      .to_string();
+    Some(FnSignature { params, res })
+  }
+
+  /// Returns the ways to fill the expression hole at this index, best first.
+  pub fn get_hole_fills(&self, syms: &Syms, idx: hir::Idx) -> Vec<HoleFill> {
+    let fills = match self.fills.get(&idx) {
+      Some(x) => x.as_slice(),
+      None => return Vec::new(),
+    };
+    fills
+      .iter()
+      .map(|fill| {
+        let mut mvs = MetaVarNames::new(&self.meta_vars);
+        mvs.extend_for(&fill.ty);
+        HoleFill {
+          path: fill.path.clone(),
+          args: fill.args.clone(),
+          ty: fill.ty.display(&mvs, syms).to_string(),
+        }
+      })
+      .collect()
+  }
+
+  /// Returns what kind of value the name at this index refers to. For patterns, `Val` means the
+  /// name is a variable bound by the pattern.
+  pub fn get_val_kind(&self, idx: hir::Idx) -> Option<ValKind> {
+    self.val_kinds.get(&idx).copied()
+  }
+
   /// Returns documentation for this index.
   pub fn get_doc(&self, idx: hir::Idx) -> Option<&str> {
     self.store.get(&idx)?.doc.as_deref()
@@ -104,6 +368,11 @@ impl Info {
     self.store.get(&idx)?.def
   }
 
+  /// Returns the definition site of the first structure of the qualified path at the idx.
+  pub fn get_str_def(&self, idx: hir::Idx) -> Option<Def> {
+    self.store.get(&idx)?.str_def
+  }
+
   /// Returns the definition site of the type for the idx.
   pub fn get_ty_defs(&self, syms: &Syms, idx: hir::Idx) -> Option<Vec<Def>> {
     let ty_entry = self.store.get(&idx)?.ty_entry.as_ref()?;
@@ -137,16 +406,61 @@ impl Info {
         (name.clone(), has_arg)
       })
       .collect();
-    ret.sort_unstable();
+    ret.sort_unstable_by(|(a, _), (b, _)| {
+      (syms.con_idx(&sym, a), a).cmp(&(syms.con_idx(&sym, b), b))
+    });
     Some(ret)
   }
 }
 
+/// What kind of value a name refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValKind {
+  /// A regular value.
+  Val,
+  /// A datatype constructor.
+  Con,
+  /// An exception constructor.
+  Exn,
+}
+
+impl From<&IdStatus> for ValKind {
+  fn from(val: &IdStatus) -> Self {
+    match val {
+      IdStatus::Val => Self::Val,
+      IdStatus::Con => Self::Con,
+      IdStatus::Exn(_) => Self::Exn,
+    }
+  }
+}
+
+/// The type of a function, split up for showing signature help.
+#[derive(Debug)]
+pub struct FnSignature {
+  /// The curried parameters. Each is the types of the components of a tuple parameter, or just the
+  /// type of the parameter if it is not a tuple.
+  pub params: Vec<Vec<String>>,
+  /// The result type, after all the curried parameters.
+  pub res: String,
+}
+
+/// A way to fill an expression hole with a value in scope or a constructor.
+#[derive(Debug)]
+pub struct HoleFill {
+  /// The path to the value.
+  pub path: hir::Path,
+  /// The curried arguments to apply the value to, which will themselves be holes. Each is the number
+  /// of components of a tuple argument, or 1 if the argument is not a tuple.
+  pub args: Vec<usize>,
+  /// The type of the value, as used to fill the hole.
+  pub ty: String,
+}
+
 /// The mode for checking.
 #[derive(Debug, Clone)]
 pub enum Mode {
   /// Regular checking. The default.
-  Regular(Option<paths::PathId>),
+  Regular(Option<paths::PathId>, Dialect),
   /// Standard basis checking. Notably, ascription structure expressions will not check to see if
   /// they actually match the signature.
   ///
@@ -154,9 +468,17 @@ pub enum Mode {
   StdBasis(&'static str),
 }
 
+/// Which extensions to Standard ML are allowed when checking regularly.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Dialect {
+  /// Whether the SML/NJ extensions to the module language, like `funsig` and curried functors, are
+  /// allowed.
+  pub smlnj_modules: bool,
+}
+
 impl Mode {
   pub(crate) fn is_regular(&self) -> bool {
-    matches!(self, Self::Regular(_))
+    matches!(self, Self::Regular(..))
   }
 
   pub(crate) fn is_std_basis(&self) -> bool {
@@ -165,8 +487,48 @@ impl Mode {
 
   pub(crate) fn path(&self) -> Option<DefPath> {
     match self {
-      Self::Regular(p) => p.map(DefPath::Regular),
+      Self::Regular(p, _) => p.map(DefPath::Regular),
       Self::StdBasis(name) => Some(DefPath::StdBasis(name)),
     }
   }
+
+  pub(crate) fn smlnj_modules(&self) -> bool {
+    match self {
+      Self::Regular(_, dialect) => dialect.smlnj_modules,
+      Self::StdBasis(_) => false,
+    }
+  }
+}
+
+/// Like with [`DefPath`], a regular path is serialized as its raw [`paths::PathId`].
+impl Serialize for Mode {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let repr = match self {
+      Mode::Regular(path, dialect) => ModeRepr::Regular(path.map(paths::PathId::to_raw), *dialect),
+      Mode::StdBasis(name) => ModeRepr::StdBasis(*name),
+    };
+    repr.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Mode {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let ret = match ModeRepr::<String>::deserialize(deserializer)? {
+      ModeRepr::Regular(raw, dialect) => Mode::Regular(raw.map(paths::PathId::from_raw), dialect),
+      ModeRepr::StdBasis(name) => Mode::StdBasis(static_str(name)),
+    };
+    Ok(ret)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+enum ModeRepr<S> {
+  Regular(Option<usize>, Dialect),
+  StdBasis(S),
 }
@@ -7,6 +7,7 @@
 #![deny(missing_debug_implementations, missing_docs, rust_2018_idioms)]
 
 mod dec;
+mod env_display;
 mod error;
 mod exp;
 mod fmt_util;
@@ -24,9 +25,9 @@ mod util;
 
 pub mod basis;
 
-pub use error::Error;
-pub use info::{Info, Mode};
-pub use types::{Def, DefPath, MetaVarInfo, Syms};
+pub use error::{Error, ErrorsRepr, ErrorsReprRef};
+pub use info::{Dialect, FnSignature, HoleFill, Info, ModKind, Mode, ValKind};
+pub use types::{Def, DefPath, MetaVarInfo, Syms, SymsCheckpoint, SymsDelta};
 
 /// The result of statics.
 #[derive(Debug)]
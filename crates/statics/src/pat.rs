@@ -1,7 +1,7 @@
 use crate::error::{ErrorKind, Item};
 use crate::generalizes::eq_ty_scheme;
-use crate::get_env::get_val_info;
-use crate::info::TyEntry;
+use crate::get_env::{get_str_def, get_val_info};
+use crate::info::{TyEntry, ValKind};
 use crate::pat_match::{Con, Pat, VariantName};
 use crate::st::St;
 use crate::ty;
@@ -9,7 +9,7 @@ use crate::types::{
   Cx, Def, EnvLike as _, Generalizable, IdStatus, SubstEntry, Ty, TyScheme, TyVarKind, ValEnv,
   ValInfo,
 };
-use crate::unify::unify;
+use crate::unify::{unify, unify_src};
 use crate::util::{apply, get_scon, ins_check_name, instantiate, record};
 use std::collections::BTreeSet;
 
@@ -31,6 +31,11 @@ pub(crate) fn get(
     ty_scheme,
   };
   st.info().insert(pat_.into(), Some(ty_entry), def);
+  if let hir::Pat::Con(path, _) = &ars.pat[pat_] {
+    if let Some(str_def) = get_str_def(&cx.env, path) {
+      st.info().insert_str_def(pat_.into(), str_def);
+    }
+  }
   (pat, ty)
 }
 
@@ -56,11 +61,11 @@ fn get_(
           st.err(pat_, ErrorKind::RealPat);
           Con::Any
         }
-        hir::SCon::Word(w) => Con::Word(*w),
+        hir::SCon::Word(w) => Con::Word(w.clone()),
         hir::SCon::Char(c) => Con::Char(*c),
         hir::SCon::String(s) => Con::String(s.clone()),
       };
-      let t = get_scon(st, scon, g);
+      let t = get_scon(st, &cx.env, pat_.into(), scon, g);
       (Pat::zero(con, pat), t)
     }
     hir::Pat::Con(path, arg) => {
@@ -77,6 +82,7 @@ fn get_(
       if is_var {
         let (pm_pat, ty) = any(st, pat, g);
         insert_name(st, ve, path.last().clone(), ty.clone(), pat_.into());
+        st.info().insert_val_kind(pat_.into(), ValKind::Val);
         return ((pm_pat, ty), ty_scheme, def);
       }
       let val_info = match maybe_val_info {
@@ -86,6 +92,8 @@ fn get_(
           return (any(st, pat, g), ty_scheme, def);
         }
       };
+      st.info()
+        .insert_val_kind(pat_.into(), ValKind::from(&val_info.id_status));
       let variant_name = match &val_info.id_status {
         IdStatus::Val => {
           st.err(pat_, ErrorKind::PatValIdStatus);
@@ -124,7 +132,7 @@ fn get_(
               Pat::zero(Con::Any, pat)
             }
             Some((arg_pat, arg_ty)) => {
-              unify(st, *param_ty, arg_ty, pat_.into());
+              unify(st, &cx.env, *param_ty, arg_ty, pat_.into());
               apply(st.subst(), &mut res_ty);
               arg_pat
             }
@@ -165,8 +173,10 @@ fn get_(
     // sml_def(42)
     hir::Pat::Typed(inner, want) => {
       let (pm_pat, got) = get(st, cx, ars, ve, *inner, g);
+      let want_src = want.map(Into::into);
       let mut want = ty::get(st, cx, ars, *want);
-      unify(st, want.clone(), got, inner.unwrap_or(pat_).into());
+      let idx = inner.unwrap_or(pat_).into();
+      unify_src(st, &cx.env, want.clone(), got, idx, want_src);
       apply(st.subst(), &mut want);
       (pm_pat, want)
     }
@@ -188,7 +198,14 @@ fn get_(
         let (rest_pm_pat, rest_ty) = get(st, cx, ars, &mut rest_ve, pat, g);
         pm_pats.push(rest_pm_pat);
         let idx = hir::Idx::from(pat.unwrap_or(pat_));
-        unify(st, ty.clone(), rest_ty, idx);
+        unify_src(
+          st,
+          &cx.env,
+          ty.clone(),
+          rest_ty,
+          idx,
+          or_pat.first.map(Into::into),
+        );
         apply(st.subst(), &mut ty);
         for (name, fst_val_info) in fst_ve.iter() {
           let rest_val_info = match rest_ve.remove(name) {
@@ -202,7 +219,7 @@ fn get_(
           assert!(rest_val_info.id_status.same_kind_as(&IdStatus::Val));
           let fst_ty_scheme = fst_val_info.ty_scheme.clone();
           let rest_ty_scheme = rest_val_info.ty_scheme.clone();
-          eq_ty_scheme(st, fst_ty_scheme, rest_ty_scheme, idx);
+          eq_ty_scheme(st, &cx.env, fst_ty_scheme, rest_ty_scheme, idx);
         }
         if let Some(name) = rest_ve.into_keys().next() {
           st.err(idx, ErrorKind::OrPatNotSameBindings(name));
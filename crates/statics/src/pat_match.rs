@@ -1,7 +1,9 @@
 use crate::types::{Exn, Sym, Syms, Ty};
 use crate::util::apply_bv;
 use fast_hash::FxHashSet;
-use pattern_match::{CheckError, Result};
+use pattern_match::{CheckError, RawPat, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::BTreeSet;
 
 pub(crate) type Pat = pattern_match::Pat<Lang>;
@@ -115,11 +117,11 @@ impl pattern_match::Lang for Lang {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub(crate) enum Con {
   Any,
   Int(hir::Int),
-  Word(u32),
+  Word(hir::Int),
   Char(char),
   String(hir::SmolStr),
   Record {
@@ -129,8 +131,123 @@ pub(crate) enum Con {
   Variant(Sym, VariantName),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub(crate) enum VariantName {
   Name(hir::Name),
   Exn(Exn),
 }
+
+/// removes the pats that are covered by other pats, and sorts the rest by the order in which their
+/// constructors were declared.
+pub(crate) fn minimize(syms: &Syms, pats: Vec<Pat>) -> Vec<Pat> {
+  let mut ret = Vec::<Pat>::with_capacity(pats.len());
+  for pat in pats {
+    if ret.iter().any(|other| covers(other, &pat)) {
+      continue;
+    }
+    ret.retain(|other| !covers(&pat, other));
+    ret.push(pat);
+  }
+  ret.sort_by(|a, b| cmp_pat(syms, a, b));
+  ret
+}
+
+/// returns whether every value matched by `rhs` is matched by `lhs`.
+fn covers(lhs: &Pat, rhs: &Pat) -> bool {
+  match (&lhs.raw, &rhs.raw) {
+    (RawPat::Con(Con::Any, _), _) => true,
+    (_, RawPat::Or(rhs)) => rhs.iter().all(|rhs| covers(lhs, rhs)),
+    (RawPat::Or(lhs), _) => lhs.iter().any(|lhs| covers(lhs, rhs)),
+    (RawPat::Con(lhs_con, lhs_args), RawPat::Con(rhs_con, rhs_args)) => {
+      lhs_con == rhs_con
+        && lhs_args.len() == rhs_args.len()
+        && lhs_args.iter().zip(rhs_args).all(|(l, r)| covers(l, r))
+    }
+  }
+}
+
+fn cmp_pat(syms: &Syms, lhs: &Pat, rhs: &Pat) -> Ordering {
+  match (&lhs.raw, &rhs.raw) {
+    (RawPat::Con(lhs_con, lhs_args), RawPat::Con(rhs_con, rhs_args)) => {
+      cmp_con(syms, lhs_con, rhs_con).then_with(|| {
+        lhs_args
+          .iter()
+          .zip(rhs_args)
+          .map(|(l, r)| cmp_pat(syms, l, r))
+          .find(|x| x.is_ne())
+          .unwrap_or(Ordering::Equal)
+      })
+    }
+    (RawPat::Con(_, _), RawPat::Or(_)) => Ordering::Less,
+    (RawPat::Or(_), RawPat::Con(_, _)) => Ordering::Greater,
+    (RawPat::Or(_), RawPat::Or(_)) => Ordering::Equal,
+  }
+}
+
+fn cmp_con(syms: &Syms, lhs: &Con, rhs: &Con) -> Ordering {
+  match (lhs, rhs) {
+    (Con::Any, Con::Any) => Ordering::Equal,
+    // wildcards go last.
+    (Con::Any, _) => Ordering::Greater,
+    (_, Con::Any) => Ordering::Less,
+    (Con::Char(lhs), Con::Char(rhs)) => lhs.cmp(rhs),
+    (Con::String(lhs), Con::String(rhs)) => lhs.cmp(rhs),
+    (Con::Variant(sym, VariantName::Name(lhs)), Con::Variant(_, VariantName::Name(rhs))) => {
+      let lhs = (syms.con_idx(sym, lhs), lhs);
+      let rhs = (syms.con_idx(sym, rhs), rhs);
+      lhs.cmp(&rhs)
+    }
+    (Con::Variant(_, VariantName::Exn(lhs)), Con::Variant(_, VariantName::Exn(rhs))) => {
+      lhs.cmp(rhs)
+    }
+    _ => Ordering::Equal,
+  }
+}
+
+/// pats are not serializable, so we serialize their raw parts. the indices are only used while
+/// checking, so they are not kept.
+#[derive(Serialize, Deserialize)]
+enum PatRepr {
+  Con(Con, Vec<PatRepr>),
+  Or(Vec<PatRepr>),
+}
+
+impl From<&Pat> for PatRepr {
+  fn from(val: &Pat) -> Self {
+    match &val.raw {
+      RawPat::Con(con, args) => Self::Con(con.clone(), args.iter().map(Self::from).collect()),
+      RawPat::Or(pats) => Self::Or(pats.iter().map(Self::from).collect()),
+    }
+  }
+}
+
+impl From<PatRepr> for Pat {
+  fn from(val: PatRepr) -> Self {
+    match val {
+      PatRepr::Con(con, args) => Pat::con(con, args.into_iter().map(Pat::from).collect(), None),
+      PatRepr::Or(pats) => Pat::or(pats.into_iter().map(Pat::from).collect(), None),
+    }
+  }
+}
+
+/// For `#[serde(with = ...)]` on fields of pats.
+pub(crate) mod pats_serde {
+  use super::{Pat, PatRepr};
+  use serde::{Deserialize as _, Deserializer, Serialize as _, Serializer};
+
+  pub(crate) fn serialize<S>(pats: &[Pat], serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let pats: Vec<_> = pats.iter().map(PatRepr::from).collect();
+    pats.serialize(serializer)
+  }
+
+  pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Pat>, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let pats = Vec::<PatRepr>::deserialize(deserializer)?;
+    Ok(pats.into_iter().map(Pat::from).collect())
+  }
+}
@@ -1,8 +1,13 @@
 use crate::error::{Error, ErrorKind};
-use crate::info::{Info, Mode};
-use crate::pat_match::{Lang, Pat};
-use crate::types::{Def, FixedTyVar, FixedTyVarGen, MetaTyVar, MetaTyVarGen, Subst, Syms, Ty};
-use crate::util::apply;
+use crate::info::{Fill, Info, Mode};
+use crate::pat_match::{self, Lang, Pat};
+use crate::types::{
+  Def, EnvStack, FixedTyVar, FixedTyVarGen, Generalizable, MetaTyVar, MetaTyVarGen, StartedSym,
+  Subst, Sym, Syms, Ty,
+};
+use crate::unify::unify_;
+use crate::util::{apply, instantiate};
+use fast_hash::FxHashSet;
 
 /// The state.
 ///
@@ -17,7 +22,10 @@ pub(crate) struct St {
   fixed_gen: FixedTyVarGen,
   info: Info,
   matches: Vec<Match>,
-  holes: Vec<(MetaTyVar, hir::Idx)>,
+  holes: Vec<Hole>,
+  lits: Vec<Lit>,
+  /// the names of the structures being bound, from outermost to innermost.
+  pub(crate) structures: Vec<hir::Name>,
   pub(crate) syms: Syms,
 }
 
@@ -31,6 +39,8 @@ impl St {
       info: Info::new(mode),
       matches: Vec::new(),
       holes: Vec::new(),
+      lits: Vec::new(),
+      structures: Vec::new(),
       syms,
     }
   }
@@ -54,9 +64,29 @@ impl St {
     self.errors.push(Error {
       idx: idx.into(),
       kind,
+      env: None,
     })
   }
 
+  /// like [`Self::err`], but for errors that mention types, which are displayed as they could be
+  /// written in `env`.
+  pub(crate) fn err_in<I>(&mut self, env: &EnvStack, idx: I, kind: ErrorKind)
+  where
+    I: Into<hir::Idx>,
+  {
+    self.errors.push(Error {
+      idx: idx.into(),
+      kind,
+      env: Some(env.clone()),
+    })
+  }
+
+  /// starts a new sym for a type named `name` defined in the current structure.
+  pub(crate) fn start_sym(&mut self, name: hir::Name) -> StartedSym {
+    let path = hir::Path::new(self.structures.iter().cloned(), name);
+    self.syms.start(path)
+  }
+
   pub(crate) fn gen_fixed_var(&mut self, ty_var: hir::TyVar) -> FixedTyVar {
     self.fixed_gen.gen(ty_var)
   }
@@ -81,29 +111,89 @@ impl St {
     })
   }
 
-  pub(crate) fn insert_case(&mut self, pats: Vec<Pat>, want: Ty, idx: hir::Idx) {
+  /// the `env` is where the `case` is, so the missing patterns can be written as they could be
+  /// written there.
+  pub(crate) fn insert_case(&mut self, pats: Vec<Pat>, want: Ty, idx: hir::Idx, env: &EnvStack) {
     self.matches.push(Match {
-      kind: MatchKind::Case(pats),
+      kind: MatchKind::Case(pats, env.clone()),
       want,
       idx,
     })
   }
 
-  pub(crate) fn insert_hole(&mut self, mv: MetaTyVar, idx: hir::Idx) {
-    self.holes.push((mv, idx));
+  pub(crate) fn insert_hole(&mut self, mv: MetaTyVar, idx: hir::Idx, env: &EnvStack) {
+    self.holes.push(Hole {
+      mv,
+      idx,
+      env: env.clone(),
+    });
+  }
+
+  pub(crate) fn insert_lit(
+    &mut self,
+    idx: hir::Idx,
+    env: &EnvStack,
+    ty: Ty,
+    value: hir::Int,
+    signed: bool,
+  ) {
+    // skip the common case of small literals, so we need not keep the env for them.
+    if lit_in_range(&value, MIN_LIT_BITS, signed) {
+      return;
+    }
+    self.lits.push(Lit {
+      idx,
+      env: env.clone(),
+      ty,
+      value,
+      signed,
+    });
   }
 
   pub(crate) fn finish(mut self) -> (Syms, Vec<Error>, Info) {
-    let lang = Lang { syms: self.syms };
-    let mut errors = self.errors;
-    for (mv, idx) in self.holes {
-      let mut ty = Ty::MetaVar(mv);
+    let mut hole_errors = Vec::<Error>::with_capacity(self.holes.len());
+    for hole in std::mem::take(&mut self.holes) {
+      let mut ty = Ty::MetaVar(hole.mv);
       apply(&self.subst, &mut ty);
-      errors.push(Error {
-        idx,
+      let fills = get_fills(&mut self, &ty, &hole.env);
+      if !fills.is_empty() {
+        self.info.insert_fills(hole.idx, fills);
+      }
+      hole_errors.push(Error {
+        idx: hole.idx,
         kind: ErrorKind::ExpHole(ty),
+        env: Some(hole.env),
       });
     }
+    let lang = Lang { syms: self.syms };
+    let mut errors = self.errors;
+    errors.append(&mut hole_errors);
+    for mut lit in self.lits {
+      apply(&self.subst, &mut lit.ty);
+      let sym = match lit.ty {
+        Ty::Con(_, sym) => sym,
+        // not otherwise constrained, so it was defaulted.
+        Ty::MetaVar(_) => {
+          if lit.signed {
+            Sym::INT
+          } else {
+            Sym::WORD
+          }
+        }
+        _ => continue,
+      };
+      let bits = match lang.syms.lit_bits(&sym) {
+        Some(x) => x,
+        None => continue,
+      };
+      if !lit_in_range(&lit.value, bits, lit.signed) {
+        errors.push(Error {
+          idx: lit.idx,
+          kind: ErrorKind::LitOutOfRange(Ty::zero(sym), bits),
+          env: Some(lit.env),
+        });
+      }
+    }
     for mut m in self.matches {
       apply(&self.subst, &mut m.want);
       match m.kind {
@@ -113,15 +203,17 @@ impl St {
             errors.push(Error {
               idx: m.idx,
               kind: ErrorKind::NonExhaustiveBinding(missing),
+              env: None,
             });
           }
         }
-        MatchKind::Case(pats) => {
+        MatchKind::Case(pats, env) => {
           let missing = get_match(&mut errors, &lang, pats, m.want);
           if !missing.is_empty() {
             errors.push(Error {
               idx: m.idx,
               kind: ErrorKind::NonExhaustiveCase(missing),
+              env: Some(env),
             });
           }
         }
@@ -138,6 +230,128 @@ impl St {
   }
 }
 
+/// the fewest bits that any type with literals has. a literal in range for this many bits is in
+/// range for any such type.
+const MIN_LIT_BITS: u16 = 8;
+
+fn lit_in_range(value: &hir::Int, bits: u16, signed: bool) -> bool {
+  let value = match value {
+    hir::Int::Finite(x) => hir::BigInt::from(*x),
+    hir::Int::Big(x) => x.clone(),
+  };
+  let one = hir::BigInt::from(1);
+  if signed {
+    let max = one << (bits - 1);
+    -&max <= value && value < max
+  } else {
+    let max = one << bits;
+    hir::BigInt::from(0) <= value && value < max
+  }
+}
+
+/// the most curried arguments to apply a value to when trying to fill a hole with it.
+const MAX_FILL_ARGS: usize = 3;
+
+/// the most ways to fill a hole to report.
+const MAX_FILLS: usize = 10;
+
+/// returns the ways to fill a hole of type `want`, best first.
+///
+/// tries each value in scope at the hole, and each constructor of the type of the hole. a value
+/// that has type `want` is better than one that has to be applied to arguments to get it, and a
+/// value with a monomorphic type is better than a polymorphic one.
+fn get_fills(st: &mut St, want: &Ty, env: &EnvStack) -> Vec<Fill> {
+  match want {
+    // everything would fit.
+    Ty::None => return Vec::new(),
+    Ty::MetaVar(mv) if st.subst.get(mv).is_none() => return Vec::new(),
+    _ => {}
+  }
+  let vals = env.vals();
+  let in_scope: FxHashSet<_> = vals.iter().map(|&(name, _)| name).collect();
+  // constructors not in scope are qualified like their type is.
+  let cons: Vec<_> = match want {
+    Ty::Con(_, sym) => match (st.syms.get_path(sym), st.syms.get(sym)) {
+      (Some(ty_path), Some((_, ty_info))) => ty_info
+        .val_env
+        .iter()
+        .filter(|(name, _)| !in_scope.contains(name))
+        .map(|(name, val_info)| {
+          let path = hir::Path::new(ty_path.structures().iter().cloned(), name.clone());
+          (path, val_info.ty_scheme.clone())
+        })
+        .collect(),
+      _ => Vec::new(),
+    },
+    _ => Vec::new(),
+  };
+  // clone each ty scheme only when we try it.
+  let vals = vals
+    .into_iter()
+    .map(|(name, val_info)| (hir::Path::one(name.clone()), val_info.ty_scheme.clone()));
+  let mut ret = Vec::<(bool, Fill)>::new();
+  for (path, ty_scheme) in vals.chain(cons) {
+    let polymorphic = !ty_scheme.bound_vars.is_empty();
+    // undo all the changes to the subst from trying this value, instead of cloning the subst.
+    let start = st.subst.mark();
+    let ty = instantiate(st, ty_scheme, Generalizable::Always);
+    let mut params = Vec::<Ty>::with_capacity(MAX_FILL_ARGS);
+    let mut got = ty.clone();
+    loop {
+      let mark = st.subst.mark();
+      if unify_(st, want.clone(), got.clone()).is_ok() {
+        let mut ty = ty;
+        apply(&st.subst, &mut ty);
+        let args: Vec<_> = params
+          .into_iter()
+          .map(|mut param| {
+            apply(&st.subst, &mut param);
+            tuple_len(&param)
+          })
+          .collect();
+        ret.push((polymorphic, Fill { path, args, ty }));
+        break;
+      }
+      st.subst.undo_to(mark);
+      if params.len() == MAX_FILL_ARGS {
+        break;
+      }
+      got = match got {
+        Ty::Fn(param, res) => {
+          params.push(*param);
+          *res
+        }
+        _ => break,
+      };
+    }
+    st.subst.undo_to(start);
+  }
+  st.subst.stop_undo_log();
+  ret.sort_by(|(a_poly, a), (b_poly, b)| {
+    let a = (a.args.len(), a_poly, a.path.structures(), a.path.last());
+    let b = (b.args.len(), b_poly, b.path.structures(), b.path.last());
+    a.cmp(&b)
+  });
+  ret.truncate(MAX_FILLS);
+  ret.into_iter().map(|(_, fill)| fill).collect()
+}
+
+/// returns the number of components of a tuple type, or 1 if it is not a tuple type.
+fn tuple_len(ty: &Ty) -> usize {
+  match ty {
+    Ty::Record(rows)
+      if rows.len() > 1
+        && rows
+          .keys()
+          .enumerate()
+          .all(|(idx, lab)| hir::Lab::tuple(idx) == *lab) =>
+    {
+      rows.len()
+    }
+    _ => 1,
+  }
+}
+
 /// returns the missing pats
 fn get_match(errors: &mut Vec<Error>, lang: &Lang, pats: Vec<Pat>, ty: Ty) -> Vec<Pat> {
   let ck = pattern_match::check(lang, pats, ty);
@@ -152,9 +366,28 @@ fn get_match(errors: &mut Vec<Error>, lang: &Lang, pats: Vec<Pat>, ty: Ty) -> Ve
     errors.push(Error {
       idx: idx.into(),
       kind: ErrorKind::UnreachablePattern,
+      env: None,
     });
   }
-  ck.missing
+  pat_match::minimize(&lang.syms, ck.missing)
+}
+
+#[derive(Debug)]
+struct Hole {
+  mv: MetaTyVar,
+  idx: hir::Idx,
+  /// the environment at the hole, from which we get the values in scope to fill it.
+  env: EnvStack,
+}
+
+#[derive(Debug)]
+struct Lit {
+  idx: hir::Idx,
+  /// the environment at the literal.
+  env: EnvStack,
+  ty: Ty,
+  value: hir::Int,
+  signed: bool,
 }
 
 #[derive(Debug)]
@@ -167,6 +400,6 @@ struct Match {
 #[derive(Debug)]
 enum MatchKind {
   Bind(Pat),
-  Case(Vec<Pat>),
+  Case(Vec<Pat>, EnvStack),
   Handle(Vec<Pat>),
 }
@@ -1,11 +1,12 @@
 use crate::error::{ErrorKind, Item};
 use crate::generalizes::{eq_ty_scheme, eq_ty_scheme_no_emit, generalizes};
 use crate::get_env::{get_env_from_str_path, get_ty_info, get_ty_info_raw};
+use crate::info::ModItem;
 use crate::st::St;
 use crate::types::{
-  generalize, generalize_fixed, BasicOverload, Bs, Env, EnvLike, EnvStack, FunEnv, FunSig,
-  HasRecordMetaVars, IdStatus, Sig, SigEnv, StartedSym, StrEnv, Sym, Ty, TyEnv, TyInfo, TyNameSet,
-  TyScheme, TyVarKind, ValEnv, ValInfo,
+  generalize, generalize_fixed, BasicOverload, Bs, Env, EnvLike, EnvStack, FunEnv, FunParam,
+  FunSig, FunSigEnv, HasRecordMetaVars, IdStatus, Sig, SigEnv, StartedSym, StrEnv, Sym, Ty, TyEnv,
+  TyInfo, TyNameSet, TyScheme, TyVarKind, ValEnv, ValInfo,
 };
 use crate::util::{apply_bv, ignore, ins_check_name, ins_no_dupe, ty_syms};
 use crate::{dec, ty};
@@ -17,6 +18,7 @@ pub(crate) fn get(st: &mut St, bs: &Bs, ars: &hir::Arenas, top_dec: hir::StrDecI
   Bs {
     fun_env: ac.fun_env,
     sig_env: ac.sig_env,
+    fun_sig_env: ac.fun_sig_env,
     env: EnvStack::one(ac.env),
   }
 }
@@ -55,7 +57,12 @@ fn get_str_dec(
       let mut str_env = StrEnv::default();
       for str_bind in str_binds {
         let mut env = Env::with_def(st.def(str_dec.into()));
+        st.structures.push(str_bind.name.clone());
         get_str_exp(st, bs, ars, &mut env, str_bind.str_exp);
+        st.structures.pop();
+        let item = ModItem::Structure(env.clone());
+        st.info()
+          .insert_mod(str_dec.into(), str_bind.name.clone(), item);
         if let Some(e) = ins_no_dupe(&mut str_env, str_bind.name.clone(), env, Item::Struct) {
           st.err(str_dec, e);
         }
@@ -107,6 +114,9 @@ fn get_str_dec(
         let mut env = Env::with_def(st.def(str_dec.into()));
         get_sig_exp(st, bs, ars, &mut env, sig_bind.sig_exp);
         let sig = env_to_sig(bs, env);
+        let item = ModItem::Signature(sig.clone());
+        st.info()
+          .insert_mod(str_dec.into(), sig_bind.name.clone(), item);
         if let Some(e) = ins_no_dupe(&mut sig_env, sig_bind.name.clone(), sig, Item::Sig) {
           st.err(str_dec, e);
         }
@@ -125,37 +135,179 @@ fn get_str_dec(
       let mut fun_env = FunEnv::default();
       // sml_def(86)
       for fun_bind in fun_binds {
-        let mut param_env = Env::default();
-        get_sig_exp(st, bs, ars, &mut param_env, fun_bind.param_sig);
-        let param_sig = env_to_sig(bs, param_env);
-        let mut bs_clone = bs.clone();
-        bs_clone.env.push(Env {
-          str_env: map([(fun_bind.param_name.clone(), param_sig.env.clone())]),
-          ..Default::default()
-        });
+        if !st.mode().smlnj_modules() {
+          let has_functor_param = fun_bind
+            .params
+            .iter()
+            .any(|(_, param)| matches!(param, hir::FunctorParam::Functor(_)));
+          if has_functor_param {
+            st.err(str_dec, ErrorKind::NeedsSmlNj("a functor parameter"));
+          }
+          if fun_bind.params.len() > 1 {
+            st.err(str_dec, ErrorKind::NeedsSmlNj("a curried functor"));
+          }
+        }
+        let (params, bs_clone) = get_fun_params(st, bs, ars, &fun_bind.params, str_dec.into());
         let mut body_env = Env::with_def(st.def(str_dec.into()));
         get_str_exp(st, &bs_clone, ars, &mut body_env, fun_bind.body);
-        let mut body_ty_names = TyNameSet::default();
-        env_syms(&mut |x| ignore(body_ty_names.insert(x)), &body_env);
-        bs_syms(&mut |x| ignore(body_ty_names.remove(&x)), &bs_clone);
-        for sym in param_sig.ty_names.iter() {
-          body_ty_names.remove(sym);
-        }
-        let fun_name = fun_bind.functor_name.clone();
-        let fun_sig = FunSig {
-          param: param_sig,
-          body_ty_names,
-          body_env,
+        let fun_sig = match get_fun_sig(&bs_clone, params, body_env) {
+          Some(x) => x,
+          None => continue,
         };
+        let fun_name = fun_bind.functor_name.clone();
+        let item = ModItem::Functor(fun_sig.clone());
+        st.info().insert_mod(str_dec.into(), fun_name.clone(), item);
         if let Some(e) = ins_no_dupe(&mut fun_env, fun_name, fun_sig, Item::Functor) {
           st.err(str_dec, e);
         }
       }
       ac.as_mut_fun_env().extend(fun_env);
     }
+    // SML/NJ extension
+    hir::StrDec::FunSig(fun_sig_binds) => {
+      let ac = match ac {
+        StrDecAc::Bs(ac) => ac,
+        StrDecAc::Env(_) => {
+          st.err(str_dec, ErrorKind::DecNotAllowedHere);
+          return;
+        }
+      };
+      if !st.mode().smlnj_modules() {
+        st.err(str_dec, ErrorKind::NeedsSmlNj("`funsig`"));
+      }
+      let mut fun_sig_env = FunSigEnv::default();
+      for fun_sig_bind in fun_sig_binds {
+        let (params, bs_clone) = get_fun_params(st, bs, ars, &fun_sig_bind.params, str_dec.into());
+        let mut body_env = Env::with_def(st.def(str_dec.into()));
+        get_sig_exp(st, &bs_clone, ars, &mut body_env, fun_sig_bind.body);
+        let fun_sig = match get_fun_sig(&bs_clone, params, body_env) {
+          Some(x) => x,
+          None => continue,
+        };
+        let item = ModItem::FunSig(fun_sig.clone());
+        st.info()
+          .insert_mod(str_dec.into(), fun_sig_bind.name.clone(), item);
+        let name = fun_sig_bind.name.clone();
+        if let Some(e) = ins_no_dupe(&mut fun_sig_env, name, fun_sig, Item::FunSig) {
+          st.err(str_dec, e);
+        }
+      }
+      ac.as_mut_fun_sig_env().extend(fun_sig_env);
+    }
+  }
+}
+
+/// returns the parameters of a functor or functor signature, and the basis in which to check the
+/// body, which has the parameters added. each parameter is checked in the basis with the ones
+/// before it added.
+fn get_fun_params(
+  st: &mut St,
+  bs: &Bs,
+  ars: &hir::Arenas,
+  params: &[(hir::Name, hir::FunctorParam)],
+  idx: hir::Idx,
+) -> (Vec<(hir::Name, FunParam)>, Bs) {
+  let mut bs = bs.clone();
+  let mut ret = Vec::with_capacity(params.len());
+  for (param_name, param) in params {
+    let (param, new_bs) = get_fun_param(st, &bs, ars, param_name, param, idx);
+    bs = new_bs;
+    ret.push((param_name.clone(), param));
+  }
+  (ret, bs)
+}
+
+/// returns the signature of a functor or functor signature with the `params` and `body_env`, which
+/// was checked in `bs`. it is curried if there is more than one param. returns `None` iff there are
+/// no params.
+fn get_fun_sig(bs: &Bs, params: Vec<(hir::Name, FunParam)>, body_env: Env) -> Option<FunSig> {
+  let def = body_env.def;
+  let mut params = params.into_iter().rev();
+  let (param_name, param) = params.next()?;
+  let body_ty_names = get_body_ty_names(bs, &param, &body_env);
+  let mut ret = FunSig {
+    param_name,
+    param,
+    body_ty_names,
+    body_env,
+    result: None,
+  };
+  for (param_name, param) in params {
+    ret = FunSig {
+      param_name,
+      param,
+      body_ty_names: TyNameSet::default(),
+      body_env: Env::with_def(def),
+      result: Some(Box::new(ret)),
+    };
+  }
+  Some(ret)
+}
+
+/// returns the parameter of a functor or functor signature, and the basis in which to check the
+/// body, which has the parameter added.
+fn get_fun_param(
+  st: &mut St,
+  bs: &Bs,
+  ars: &hir::Arenas,
+  param_name: &hir::Name,
+  param: &hir::FunctorParam,
+  idx: hir::Idx,
+) -> (FunParam, Bs) {
+  let mut bs_clone = bs.clone();
+  match param {
+    hir::FunctorParam::Structure(sig_exp) => {
+      let mut param_env = Env::default();
+      get_sig_exp(st, bs, ars, &mut param_env, *sig_exp);
+      let param_sig = env_to_sig(bs, param_env);
+      bs_clone.env.push(Env {
+        str_env: map([(param_name.clone(), param_sig.env.clone())]),
+        ..Default::default()
+      });
+      (FunParam::Sig(param_sig), bs_clone)
+    }
+    // SML/NJ extension
+    hir::FunctorParam::Functor(fun_sig_name) => {
+      let fun_sig = match bs.fun_sig_env.get(fun_sig_name) {
+        Some(fun_sig) => fun_sig.clone(),
+        None => {
+          st.err(
+            idx,
+            ErrorKind::Undefined(Item::FunSig, fun_sig_name.clone()),
+          );
+          FunSig {
+            param_name: param_name.clone(),
+            param: FunParam::Sig(Sig {
+              ty_names: TyNameSet::default(),
+              env: Env::default(),
+            }),
+            body_ty_names: TyNameSet::default(),
+            body_env: Env::default(),
+            result: None,
+          }
+        }
+      };
+      bs_clone
+        .as_mut_fun_env()
+        .insert(param_name.clone(), fun_sig.clone());
+      (
+        FunParam::FunSig(fun_sig_name.clone(), Box::new(fun_sig)),
+        bs_clone,
+      )
+    }
   }
 }
 
+/// returns the ty names in the body of a functor or functor signature that are not from the
+/// basis or the parameter.
+fn get_body_ty_names(bs: &Bs, param: &FunParam, body_env: &Env) -> TyNameSet {
+  let mut ret = TyNameSet::default();
+  env_syms(&mut |x| ignore(ret.insert(x)), body_env);
+  bs_syms(&mut |x| ignore(ret.remove(&x)), bs);
+  fun_param_syms(&mut |x| ignore(ret.remove(&x)), param);
+  ret
+}
+
 fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: hir::StrExpIdx) {
   let str_exp = match str_exp {
     Some(x) => x,
@@ -168,6 +320,9 @@ fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: h
     hir::StrExp::Path(path) => match get_env_from_str_path(&bs.env, path) {
       Ok(got_env) => {
         st.info().insert(str_exp.into(), None, got_env.def);
+        let item = ModItem::Structure(got_env.clone());
+        st.info()
+          .insert_mod(str_exp.into(), path.last().clone(), item);
         ac.append(&mut got_env.clone());
       }
       Err(e) => st.err(str_exp, e),
@@ -184,7 +339,7 @@ fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: h
       if st.mode().is_regular() {
         env_instance_sig(st, &mut subst, &str_exp_env, &sig, str_exp.into());
         env_realize(&subst, &mut to_add);
-        env_enrich(st, &str_exp_env, &to_add, str_exp.into());
+        env_enrich(st, &bs.env, &str_exp_env, &to_add, str_exp.into());
       }
       if matches!(asc, hir::Ascription::Opaque) {
         subst.clear();
@@ -197,7 +352,10 @@ fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: h
         match &ty_info.ty_scheme.ty {
           Ty::Con(args, sym) => {
             assert!(args.is_empty());
-            st.syms.overloads()[ov].push(*sym);
+            let syms = &mut st.syms.overloads()[ov];
+            if !syms.contains(sym) {
+              syms.push(*sym);
+            }
           }
           _ => unreachable!(),
         }
@@ -205,19 +363,43 @@ fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: h
       ac.append(&mut to_add);
     }
     // sml_def(54)
-    hir::StrExp::App(fun_name, arg_str_exp) => match bs.fun_env.get(fun_name) {
+    hir::StrExp::App(fun_name, args) => match bs.fun_env.get(fun_name) {
       Some(fun_sig) => {
-        let mut arg_env = Env::default();
-        get_str_exp(st, bs, ars, &mut arg_env, *arg_str_exp);
-        let mut subst = TyRealization::default();
-        let mut to_add = fun_sig.body_env.clone();
-        let arg_idx = hir::Idx::from(arg_str_exp.unwrap_or(str_exp));
-        env_instance_sig(st, &mut subst, &arg_env, &fun_sig.param, arg_idx);
-        gen_fresh_syms(st, &mut subst, &fun_sig.body_ty_names);
-        env_realize(&subst, &mut to_add);
-        let mut param_env = fun_sig.param.env.clone();
-        env_realize(&subst, &mut param_env);
-        env_enrich(st, &arg_env, &param_env, arg_idx);
+        if args.len() > 1 && !st.mode().smlnj_modules() {
+          st.err(str_exp, ErrorKind::NeedsSmlNj("applying a curried functor"));
+        }
+        let num_params = fun_sig.num_params();
+        if args.len() != num_params {
+          st.err(
+            str_exp,
+            ErrorKind::WrongNumFunctorArgs(num_params, args.len()),
+          );
+        }
+        // the functor that the next arg is applied to.
+        let mut cur = Some(fun_sig.clone());
+        let mut to_add = Env::default();
+        for &arg_str_exp in args {
+          let arg_idx = hir::Idx::from(arg_str_exp.unwrap_or(str_exp));
+          let fun_sig = match cur.take() {
+            Some(x) => x,
+            // too many args. still check them for errors in them.
+            None => {
+              get_str_exp(st, bs, ars, &mut Env::default(), arg_str_exp);
+              continue;
+            }
+          };
+          let subst = get_functor_app(st, bs, ars, &fun_sig, arg_str_exp, arg_idx);
+          match fun_sig.result {
+            Some(mut result) => {
+              fun_sig_realize(&subst, &mut result);
+              cur = Some(*result);
+            }
+            None => {
+              to_add = fun_sig.body_env;
+              env_realize(&subst, &mut to_add);
+            }
+          }
+        }
         let def = st.def(str_exp.into());
         for env in to_add.str_env.values_mut() {
           env.def = def;
@@ -229,6 +411,8 @@ fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: h
           val_info.def = def;
         }
         st.info().insert(str_exp.into(), None, fun_sig.body_env.def);
+        let item = ModItem::Functor(fun_sig.clone());
+        st.info().insert_mod(str_exp.into(), fun_name.clone(), item);
         ac.append(&mut to_add);
       }
       None => st.err(
@@ -247,6 +431,122 @@ fn get_str_exp(st: &mut St, bs: &Bs, ars: &hir::Arenas, ac: &mut Env, str_exp: h
   }
 }
 
+/// checks the argument to a functor with the signature `fun_sig`. returns the realization of the
+/// ty names in the parameter and body of the functor for this application.
+fn get_functor_app(
+  st: &mut St,
+  bs: &Bs,
+  ars: &hir::Arenas,
+  fun_sig: &FunSig,
+  arg_str_exp: hir::StrExpIdx,
+  arg_idx: hir::Idx,
+) -> TyRealization {
+  let mut subst = TyRealization::default();
+  match &fun_sig.param {
+    FunParam::Sig(param) => {
+      let mut arg_env = Env::default();
+      get_str_exp(st, bs, ars, &mut arg_env, arg_str_exp);
+      env_instance_sig(st, &mut subst, &arg_env, param, arg_idx);
+      gen_fresh_syms(st, &mut subst, &fun_sig.body_ty_names);
+      let mut param_env = param.env.clone();
+      env_realize(&subst, &mut param_env);
+      env_enrich(st, &bs.env, &arg_env, &param_env, arg_idx);
+    }
+    // SML/NJ extension
+    FunParam::FunSig(_, want) => {
+      if let Some(got) = get_functor_arg(st, bs, ars, arg_str_exp) {
+        if st.mode().is_regular() {
+          fun_sig_match(st, &bs.env, &got, want, arg_idx);
+        }
+      }
+      gen_fresh_syms(st, &mut subst, &fun_sig.body_ty_names);
+    }
+  }
+  subst
+}
+
+/// returns the signature of the functor named by the argument to a functor that takes a functor.
+fn get_functor_arg(
+  st: &mut St,
+  bs: &Bs,
+  ars: &hir::Arenas,
+  arg_str_exp: hir::StrExpIdx,
+) -> Option<FunSig> {
+  let arg_str_exp = arg_str_exp?;
+  let name = match &ars.str_exp[arg_str_exp] {
+    hir::StrExp::Path(path) if path.structures().is_empty() => path.last(),
+    _ => {
+      st.err(arg_str_exp, ErrorKind::FunctorArgNotFunctor);
+      return None;
+    }
+  };
+  match bs.fun_env.get(name) {
+    Some(fun_sig) => {
+      st.info()
+        .insert(arg_str_exp.into(), None, fun_sig.body_env.def);
+      let item = ModItem::Functor(fun_sig.clone());
+      st.info().insert_mod(arg_str_exp.into(), name.clone(), item);
+      Some(fun_sig.clone())
+    }
+    None => {
+      st.err(
+        arg_str_exp,
+        ErrorKind::Undefined(Item::Functor, name.clone()),
+      );
+      None
+    }
+  }
+}
+
+/// checks that a functor with the signature `got` may be used where a functor with the signature
+/// `want` is wanted.
+///
+/// the parameter is contravariant: `got` must accept any argument that `want` accepts. the body is
+/// covariant: what `got` returns must match what `want` says is returned. for curried functors, the
+/// functor returned is matched in the same way.
+fn fun_sig_match(st: &mut St, env: &EnvStack, got: &FunSig, want: &FunSig, idx: hir::Idx) {
+  let (got_num, want_num) = (got.num_params(), want.num_params());
+  if got_num != want_num {
+    st.err(idx, ErrorKind::WrongNumFunctorParams(want_num, got_num));
+    return;
+  }
+  let mut subst = TyRealization::default();
+  match (&got.param, &want.param) {
+    (FunParam::Sig(got_param), FunParam::Sig(want_param)) => {
+      env_instance_sig(st, &mut subst, &want_param.env, got_param, idx);
+      let mut got_param_env = got_param.env.clone();
+      env_realize(&subst, &mut got_param_env);
+      env_enrich(st, env, &want_param.env, &got_param_env, idx);
+    }
+    (FunParam::FunSig(_, got_param), FunParam::FunSig(_, want_param)) => {
+      fun_sig_match(st, env, want_param, got_param, idx);
+    }
+    (FunParam::Sig(_), FunParam::FunSig(_, _)) | (FunParam::FunSig(_, _), FunParam::Sig(_)) => {
+      st.err(idx, ErrorKind::FunctorParamMismatch);
+      return;
+    }
+  }
+  gen_fresh_syms(st, &mut subst, &got.body_ty_names);
+  // they have the same number of params, so both or neither have a result.
+  if let (Some(got_result), Some(want_result)) = (&got.result, &want.result) {
+    let mut got_result = got_result.as_ref().clone();
+    fun_sig_realize(&subst, &mut got_result);
+    fun_sig_match(st, env, &got_result, want_result, idx);
+    return;
+  }
+  let mut got_body_env = got.body_env.clone();
+  env_realize(&subst, &mut got_body_env);
+  let want_body = Sig {
+    ty_names: want.body_ty_names.clone(),
+    env: want.body_env.clone(),
+  };
+  let mut want_subst = TyRealization::default();
+  env_instance_sig(st, &mut want_subst, &got_body_env, &want_body, idx);
+  let mut want_body_env = want_body.env;
+  env_realize(&want_subst, &mut want_body_env);
+  env_enrich(st, env, &got_body_env, &want_body_env, idx);
+}
+
 fn get_sig_exp(
   st: &mut St,
   bs: &Bs,
@@ -272,11 +572,16 @@ fn get_sig_exp(
         let mut sig_env = sig.env.clone();
         env_realize(&subst, &mut sig_env);
         st.info().insert(sig_exp.into(), None, sig.env.def);
+        let item = ModItem::Signature(sig.clone());
+        st.info().insert_mod(sig_exp.into(), name.clone(), item);
         ac.append(&mut sig_env);
         if st.mode().is_std_basis() {
           match name.as_str() {
-            "WORD" => Some(BasicOverload::Word),
             "INTEGER" | "INT_INF" => Some(BasicOverload::Int),
+            "REAL" => Some(BasicOverload::Real),
+            "WORD" => Some(BasicOverload::Word),
+            "STRING" => Some(BasicOverload::String),
+            "CHAR" => Some(BasicOverload::Char),
             _ => None,
           }
         } else {
@@ -370,7 +675,7 @@ fn gen_fresh_syms(st: &mut St, subst: &mut TyRealization, ty_names: &TyNameSet)
     let (name, ty_info) = st.syms.get(&sym).unwrap();
     let name = name.clone();
     let mut ty_info = ty_info.clone();
-    let started = st.syms.start(name);
+    let started = st.start_sym(name);
     let ty_scheme = TyScheme::n_ary(ty_info.ty_scheme.bound_vars.kinds().cloned(), started.sym());
     ty_info.ty_scheme = ty_scheme.clone();
     ac.push((started, ty_info));
@@ -626,7 +931,7 @@ fn get_ty_cons(structures: &mut Vec<hir::Name>, ac: &mut FxHashSet<hir::Path>, e
 // sml_def(80). TODO equality checks
 fn get_ty_desc(st: &mut St, ty_env: &mut TyEnv, ty_desc: &hir::TyDesc, idx: hir::Idx) {
   let mut ty_vars = FxHashSet::<&hir::TyVar>::default();
-  let started = st.syms.start(ty_desc.name.clone());
+  let started = st.start_sym(ty_desc.name.clone());
   for ty_var in ty_desc.ty_vars.iter() {
     if !ty_vars.insert(ty_var) {
       let e = ErrorKind::Duplicate(Item::TyVar, ty_var.as_name().clone());
@@ -713,29 +1018,35 @@ fn bound_ty_name_to_path<'e>(
 // exactly the same keys (names). or we could add a special env only for use here that has the
 // indices?
 
-fn env_enrich(st: &mut St, general: &Env, specific: &Env, idx: hir::Idx) {
+fn env_enrich(st: &mut St, env: &EnvStack, general: &Env, specific: &Env, idx: hir::Idx) {
   for (name, specific) in specific.str_env.iter() {
     match general.str_env.get(name) {
-      Some(general) => env_enrich(st, general, specific, idx),
+      Some(general) => env_enrich(st, env, general, specific, idx),
       None => st.err(idx, ErrorKind::Missing(Item::Struct, name.clone())),
     }
   }
   for (name, specific) in specific.ty_env.iter() {
     match general.ty_env.get(name) {
-      Some(general) => ty_info_enrich(st, general.clone(), specific.clone(), idx),
+      Some(general) => ty_info_enrich(st, env, general.clone(), specific.clone(), idx),
       None => st.err(idx, ErrorKind::Missing(Item::Ty, name.clone())),
     }
   }
   for (name, specific) in specific.val_env.iter() {
     match general.val_env.get(name) {
-      Some(general) => val_info_enrich(st, general.clone(), specific, name, idx),
+      Some(general) => val_info_enrich(st, env, general.clone(), specific, name, idx),
       None => st.err(idx, ErrorKind::Missing(Item::Val, name.clone())),
     }
   }
 }
 
-fn ty_info_enrich(st: &mut St, mut general: TyInfo, specific: TyInfo, idx: hir::Idx) {
-  eq_ty_scheme(st, general.ty_scheme, specific.ty_scheme, idx);
+fn ty_info_enrich(
+  st: &mut St,
+  env: &EnvStack,
+  mut general: TyInfo,
+  specific: TyInfo,
+  idx: hir::Idx,
+) {
+  eq_ty_scheme(st, env, general.ty_scheme, specific.ty_scheme, idx);
   if specific.val_env.is_empty() {
     return;
   }
@@ -745,7 +1056,7 @@ fn ty_info_enrich(st: &mut St, mut general: TyInfo, specific: TyInfo, idx: hir::
         if !general.id_status.same_kind_as(&specific.id_status) {
           st.err(idx, ErrorKind::WrongIdStatus(name.clone()));
         }
-        eq_ty_scheme(st, general.ty_scheme, specific.ty_scheme, idx);
+        eq_ty_scheme(st, env, general.ty_scheme, specific.ty_scheme, idx);
       }
       None => st.err(idx, ErrorKind::Missing(Item::Val, name.clone())),
     }
@@ -757,12 +1068,13 @@ fn ty_info_enrich(st: &mut St, mut general: TyInfo, specific: TyInfo, idx: hir::
 
 fn val_info_enrich(
   st: &mut St,
+  env: &EnvStack,
   general: ValInfo,
   specific: &ValInfo,
   name: &hir::Name,
   idx: hir::Idx,
 ) {
-  generalizes(st, general.ty_scheme, &specific.ty_scheme, idx);
+  generalizes(st, env, general.ty_scheme, &specific.ty_scheme, idx);
   if !general.id_status.same_kind_as(&specific.id_status)
     && !matches!(specific.id_status, IdStatus::Val)
   {
@@ -772,6 +1084,17 @@ fn val_info_enrich(
 
 // uh... recursion schemes??
 
+fn fun_sig_realize(subst: &TyRealization, fun_sig: &mut FunSig) {
+  match &mut fun_sig.param {
+    FunParam::Sig(sig) => env_realize(subst, &mut sig.env),
+    FunParam::FunSig(_, fun_sig) => fun_sig_realize(subst, fun_sig),
+  }
+  env_realize(subst, &mut fun_sig.body_env);
+  if let Some(result) = &mut fun_sig.result {
+    fun_sig_realize(subst, result);
+  }
+}
+
 fn env_realize(subst: &TyRealization, env: &mut Env) {
   for env in env.str_env.values_mut() {
     env_realize(subst, env);
@@ -819,18 +1142,35 @@ fn ty_realize(subst: &TyRealization, ty: &mut Ty) {
 
 fn bs_syms<F: FnMut(Sym)>(f: &mut F, bs: &Bs) {
   for fun_sig in bs.fun_env.values() {
-    sig_syms(f, &fun_sig.param);
-    for &sym in fun_sig.body_ty_names.iter() {
-      f(sym);
-    }
-    env_syms(f, &fun_sig.body_env);
+    fun_sig_syms(f, fun_sig);
   }
   for sig in bs.sig_env.values() {
     sig_syms(f, sig);
   }
+  for fun_sig in bs.fun_sig_env.values() {
+    fun_sig_syms(f, fun_sig);
+  }
   env_syms(f, &bs.env);
 }
 
+fn fun_sig_syms<F: FnMut(Sym)>(f: &mut F, fun_sig: &FunSig) {
+  fun_param_syms(f, &fun_sig.param);
+  for &sym in fun_sig.body_ty_names.iter() {
+    f(sym);
+  }
+  env_syms(f, &fun_sig.body_env);
+  if let Some(result) = &fun_sig.result {
+    fun_sig_syms(f, result);
+  }
+}
+
+fn fun_param_syms<F: FnMut(Sym)>(f: &mut F, param: &FunParam) {
+  match param {
+    FunParam::Sig(sig) => sig_syms(f, sig),
+    FunParam::FunSig(_, fun_sig) => fun_sig_syms(f, fun_sig),
+  }
+}
+
 fn sig_syms<F: FnMut(Sym)>(f: &mut F, sig: &Sig) {
   for &sym in sig.ty_names.iter() {
     f(sym);
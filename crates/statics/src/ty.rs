@@ -1,5 +1,5 @@
 use crate::error::{ErrorKind, Item};
-use crate::get_env::get_ty_info;
+use crate::get_env::{get_str_def, get_ty_info};
 use crate::info::TyEntry;
 use crate::st::St;
 use crate::types::{Cx, Def, Ty, TyScheme};
@@ -67,5 +67,10 @@ pub(crate) fn get(st: &mut St, cx: &Cx, ars: &hir::Arenas, ty: hir::TyIdx) -> Ty
     ty_scheme,
   };
   st.info().insert(ty.into(), Some(ty_entry), def);
+  if let hir::Ty::Con(_, path) = &ars.ty[ty] {
+    if let Some(str_def) = get_str_def(&cx.env, path) {
+      st.info().insert_str_def(ty.into(), str_def);
+    }
+  }
   ret
 }
@@ -3,17 +3,18 @@
 //! Probably the single most important file in this crate. Lots of types used pervasively across
 //! this crate are defined here.
 
-use crate::fmt_util::{idx_to_name, ty_var_name};
+use crate::fmt_util::{idx_to_name, ty_var_name, unambiguous_suffix};
+use crate::util::ty_syms;
 use drop_bomb::DropBomb;
 use fast_hash::{FxHashMap, FxHashSet};
-use fmt_util::comma_seq;
-use std::collections::BTreeMap;
+use fmt_util::{comma_seq, sep_seq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
-use std::sync::Arc;
-use uniq::{Uniq, UniqGen};
+use std::sync::{Arc, Mutex};
 
 /// Definition: Type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum Ty {
   None,
   /// Can only appear when this Ty is wrapped in a TyScheme.
@@ -148,10 +149,7 @@ impl<'a> fmt::Display for TyDisplay<'a> {
           }
           f.write_str(" ")?;
         }
-        match self.syms.get(sym) {
-          None => f.write_str("exn")?,
-          Some((name, _)) => name.fmt(f)?,
-        }
+        self.meta_vars.fmt_sym(f, *sym, self.syms)?;
       }
       Ty::Fn(param, res) => {
         let needs_parens = self.prec > TyPrec::Arrow;
@@ -170,11 +168,17 @@ impl<'a> fmt::Display for TyDisplay<'a> {
   }
 }
 
+/// Names for the meta variables in some types being displayed together. Also tracks the type names
+/// in those types, so that types with the same name can be told apart.
 #[derive(Debug)]
 pub(crate) struct MetaVarNames<'a> {
   next_idx: usize,
   map: FxHashMap<MetaTyVar, MetaVarName>,
   info: &'a MetaVarInfo,
+  /// in the order they first appear in the types.
+  syms: Vec<Sym>,
+  /// the paths to write for the type names, if the types are displayed for some environment.
+  paths: Option<FxHashMap<Sym, Vec<hir::Name>>>,
 }
 
 impl<'a> MetaVarNames<'a> {
@@ -183,10 +187,32 @@ impl<'a> MetaVarNames<'a> {
       next_idx: 0,
       map: FxHashMap::default(),
       info,
+      syms: Vec::new(),
+      paths: None,
     }
   }
 
+  /// qualifies the type names in the types this is for by the shortest paths that refer to them in
+  /// `env`. call this after all the calls to [`Self::extend_for`].
+  pub(crate) fn set_env(&mut self, env: &EnvStack, syms: &Syms) {
+    let want: FxHashSet<_> = self
+      .syms
+      .iter()
+      .copied()
+      .filter(|&sym| sym != Sym::EXN)
+      .collect();
+    self.paths = Some(env.ty_name_paths(syms, &want));
+  }
+
   pub(crate) fn extend_for(&mut self, ty: &Ty) {
+    ty_syms(
+      &mut |sym| {
+        if !self.syms.contains(&sym) {
+          self.syms.push(sym);
+        }
+      },
+      ty,
+    );
     meta_vars(
       &Subst::default(),
       &mut |x, _| {
@@ -212,6 +238,62 @@ impl<'a> MetaVarNames<'a> {
       })
       .or_else(|| self.map.get(mv).copied())
   }
+
+  /// writes the name of the `sym`.
+  ///
+  /// if there is an environment, this is the shortest path to it there, or `?.` and its name if it
+  /// cannot be named there, as when it has been shadowed. if other types in these types with the
+  /// same name also cannot be named, they are numbered in the order they first appear in these
+  /// types, like `?.t/1` and `?.t/2`. else, this is as much of the path to where it was defined as
+  /// is needed to tell it apart from other types with the same name in these types.
+  fn fmt_sym(&self, f: &mut fmt::Formatter<'_>, sym: Sym, syms: &Syms) -> fmt::Result {
+    let path = match syms.get_path(&sym) {
+      Some(x) => x,
+      None => return f.write_str("exn"),
+    };
+    if let Some(paths) = &self.paths {
+      return match paths.get(&sym) {
+        Some(names) => sep_seq(f, ".", names.iter()),
+        None => {
+          let escaped: Vec<_> = self
+            .syms
+            .iter()
+            .copied()
+            .filter(|other| !paths.contains_key(other))
+            .filter(|other| {
+              syms
+                .get_path(other)
+                .map_or(false, |p| p.last() == path.last())
+            })
+            .collect();
+          if escaped.len() <= 1 {
+            write!(f, "?.{}", path.last())
+          } else {
+            let n = escaped
+              .iter()
+              .position(|&other| other == sym)
+              .map_or(0, |idx| idx + 1);
+            write!(f, "?.{}/{n}", path.last())
+          }
+        }
+      };
+    }
+    let others: Vec<_> = self
+      .syms
+      .iter()
+      .filter(|&&other| other != sym)
+      .filter_map(|other| {
+        let other_path = syms.get_path(other)?;
+        (other_path.last() == path.last()).then_some((*other, other_path))
+      })
+      .collect();
+    if others.is_empty() {
+      return path.last().fmt(f);
+    }
+    let other_paths: Vec<_> = others.iter().map(|&(_, p)| p).collect();
+    let names = unambiguous_suffix(path, &other_paths);
+    sep_seq(f, ".", names.into_iter())
+  }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -235,8 +317,8 @@ impl fmt::Display for MetaVarName {
   }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum TyPrec {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum TyPrec {
   Arrow,
   Star,
   App,
@@ -266,7 +348,7 @@ impl<'a> fmt::Display for RowDisplay<'a> {
 }
 
 /// Definition: TypeScheme, TypeFcn
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TyScheme {
   pub(crate) bound_vars: BoundTyVars,
   pub(crate) ty: Ty,
@@ -293,6 +375,19 @@ impl TyScheme {
     }
   }
 
+  /// Returns the sym iff this is the type scheme of a name for the type of that sym, as made by
+  /// [`Self::n_ary`].
+  pub(crate) fn as_ty_name(&self) -> Option<Sym> {
+    match &self.ty {
+      Ty::Con(args, sym) => args
+        .iter()
+        .enumerate()
+        .all(|(idx, arg)| matches!(arg, Ty::BoundVar(bv) if bv.0 == idx))
+        .then_some(*sym),
+      _ => None,
+    }
+  }
+
   pub(crate) fn n_ary<I>(iter: I, sym: Sym) -> Self
   where
     I: Iterator<Item = Option<TyVarKind>>,
@@ -320,9 +415,26 @@ impl TyScheme {
       prec: TyPrec::Arrow,
     }
   }
+
+  /// displays a part of the type of this, like a parameter type.
+  pub(crate) fn display_part<'a>(
+    &'a self,
+    ty: &'a Ty,
+    prec: TyPrec,
+    meta_vars: &'a MetaVarNames<'a>,
+    syms: &'a Syms,
+  ) -> impl fmt::Display + 'a {
+    TyDisplay {
+      ty,
+      bound_vars: Some(&self.bound_vars),
+      meta_vars,
+      syms,
+      prec,
+    }
+  }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub(crate) struct BoundTyVars(Vec<Option<TyVarKind>>);
 
 impl BoundTyVars {
@@ -339,14 +451,14 @@ impl BoundTyVars {
   }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum TyVarKind {
   Equality,
   Overloaded(Overload),
   Record(RecordTy),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) enum BasicOverload {
   Int,
   Real,
@@ -373,7 +485,7 @@ impl fmt::Display for BasicOverload {
   }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub(crate) enum CompositeOverload {
   WordInt,
   RealInt,
@@ -425,7 +537,7 @@ impl fmt::Display for CompositeOverload {
   }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub(crate) enum Overload {
   Basic(BasicOverload),
   Composite(CompositeOverload),
@@ -470,7 +582,7 @@ impl fmt::Display for Overload {
 }
 
 /// Information about overloads.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub(crate) struct Overloads {
   pub(crate) int: Vec<Sym>,
   pub(crate) real: Vec<Sym>,
@@ -514,7 +626,7 @@ impl std::ops::IndexMut<BasicOverload> for Overloads {
 /// - [`hir::TyVar`]
 ///
 /// Basically a de Bruijn index.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) struct BoundTyVar(usize);
 
 impl BoundTyVar {
@@ -530,13 +642,13 @@ impl BoundTyVar {
 ///
 /// Internally contains a "rank" to know when it should be generalizable; see "Efficient ML Type
 /// Inference Using Ranked Type Variables" (doi:10.1145/1292535.1292538)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub(crate) struct MetaTyVar {
   id: u32,
   rank: MetaTyVarRank,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub(crate) enum MetaTyVarRank {
   Finite(u16),
   Infinite,
@@ -611,9 +723,13 @@ impl MetaTyVarGeneralizer {
 }
 
 /// Corresponds to a user written type variable.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// The id is serialized too, so distinct fixed ty vars in a snapshot stay distinct when it is read
+/// back. The ids only need to be distinct within one file, since fixed ty vars are generalized away
+/// before they can get into a basis, so the ones read back never meet ones generated after.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub(crate) struct FixedTyVar {
-  id: Uniq,
+  id: u32,
   ty_var: hir::TyVar,
 }
 
@@ -630,28 +746,37 @@ impl fmt::Display for FixedTyVar {
 }
 
 #[derive(Debug, Default)]
-pub(crate) struct FixedTyVarGen(UniqGen);
+pub(crate) struct FixedTyVarGen(u32);
 
 impl FixedTyVarGen {
   pub(crate) fn gen(&mut self, ty_var: hir::TyVar) -> FixedTyVar {
-    FixedTyVar {
-      id: self.0.gen(),
-      ty_var,
-    }
+    let ret = FixedTyVar { id: self.0, ty_var };
+    self.0 += 1;
+    ret
   }
 }
 
 pub(crate) type RecordTy = BTreeMap<hir::Lab, Ty>;
 
 /// Definition: TyName
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) struct Sym(usize);
+///
+/// A `Sym` is in a segment of its [`Syms`]. See [`Syms::start_segment`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) struct Sym {
+  seg: u32,
+  idx: u32,
+}
 
 impl fmt::Debug for Sym {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     let mut dt = f.debug_tuple("Sym");
     match self.special() {
-      None => dt.field(&self.0),
+      None => {
+        if self.seg != 0 {
+          dt.field(&self.seg);
+        }
+        dt.field(&self.idx)
+      }
       Some(x) => dt.field(&x),
     };
     dt.finish()
@@ -662,7 +787,7 @@ macro_rules! mk_special_syms {
   ($( ($idx:expr, $mk_ty:ident, $name:ident, $str:literal), )*) => {
     impl Sym {
       $(
-        pub(crate) const $name: Self = Self($idx);
+        pub(crate) const $name: Self = Self { seg: 0, idx: $idx };
       )*
 
       pub(crate) fn special(&self) -> Option<&'static str> {
@@ -708,46 +833,139 @@ impl Sym {
 
   /// never call this on a weird sym.
   fn idx(&self) -> usize {
-    self.0 - Self::NUM_WEIRD
+    to_usize(self.idx) - Self::NUM_WEIRD
+  }
+
+  fn seg(&self) -> usize {
+    to_usize(self.seg)
   }
 
   /// Returns whether this `Sym` was generated by a [`Syms`] after that `Syms` generated the
   /// `marker`.
   pub(crate) fn generated_after(&self, marker: &SymsMarker) -> bool {
-    *self != Self::EXN && self.idx() >= marker.0
+    // a `Syms` only generates in its last segment, and that's where the marker is.
+    *self != Self::EXN && (self.seg(), self.idx()) >= (marker.seg, marker.idx)
   }
 }
 
+fn to_usize(n: u32) -> usize {
+  usize::try_from(n).expect("u32 should fit in usize")
+}
+
+fn to_u32(n: usize) -> u32 {
+  u32::try_from(n).expect("too many symbols")
+}
+
 /// Information about generated types, generated exceptions, and overload types.
 ///
 /// Note the `Default` impl is "fake", in that it returns a totally empty `Syms`, which will lack
 /// even built-in items like `type int` and `exception Bind`.
-#[derive(Debug, Default, Clone)]
+///
+/// The symbols are stored in segments. New symbols are always generated in the last segment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Syms {
-  /// remember: always use Sym::idx to index
-  store: Vec<(hir::Name, TyInfo)>,
-  exns: Vec<(hir::Name, Option<Ty>)>,
+  /// remember: always use Sym::seg and Sym::idx to index
+  store: Vec<Vec<(hir::Path, TyInfo)>>,
+  /// the segments of these are the same as the segments of `store`.
+  exns: Vec<Vec<(hir::Name, Option<Ty>)>>,
   overloads: Overloads,
+  /// how many bits the fixed-width integer and word types have, for checking literals.
+  lit_bits: FxHashMap<Sym, u16>,
+  /// the constructors of datatypes, in the order they were declared.
+  con_order: FxHashMap<Sym, Vec<hir::Name>>,
+}
+
+impl Default for Syms {
+  fn default() -> Self {
+    Self {
+      store: vec![Vec::new()],
+      exns: vec![Vec::new()],
+      overloads: Overloads::default(),
+      lit_bits: FxHashMap::default(),
+      con_order: FxHashMap::default(),
+    }
+  }
 }
 
 impl Syms {
-  pub(crate) fn start(&mut self, name: hir::Name) -> StartedSym {
+  /// Returns the number of segments.
+  pub fn num_segments(&self) -> usize {
+    self.store.len()
+  }
+
+  /// Makes this generate new symbols in segment `seg`, which must be empty, dropping any later
+  /// segments.
+  ///
+  /// Copies of a `Syms` that generate in different segments generate distinct symbols. This allows
+  /// generating symbols on many threads at once. The new symbols may be added back with
+  /// [`Self::join`].
+  pub fn start_segment(&mut self, seg: usize) {
+    self.store.resize_with(seg + 1, Vec::new);
+    self.exns.resize_with(seg + 1, Vec::new);
+    debug_assert!(self.store[seg].is_empty() && self.exns[seg].is_empty());
+  }
+
+  /// Adds the symbols generated in `other`, a copy of this that generated in other segments, to
+  /// this.
+  pub fn join(&mut self, other: Self) {
+    if self.store.len() < other.store.len() {
+      self.store.resize_with(other.store.len(), Vec::new);
+      self.exns.resize_with(other.exns.len(), Vec::new);
+    }
+    // a segment is generated in by only one copy, so take the segments this has none of.
+    for (seg, store) in other.store.into_iter().enumerate() {
+      if self.store[seg].is_empty() {
+        self.store[seg] = store;
+      }
+    }
+    for (seg, exns) in other.exns.into_iter().enumerate() {
+      if self.exns[seg].is_empty() {
+        self.exns[seg] = exns;
+      }
+    }
+    let ovs = [
+      BasicOverload::Int,
+      BasicOverload::Real,
+      BasicOverload::Word,
+      BasicOverload::String,
+      BasicOverload::Char,
+    ];
+    for ov in ovs {
+      let syms = &mut self.overloads[ov];
+      for &sym in other.overloads[ov].iter() {
+        if !syms.contains(&sym) {
+          syms.push(sym);
+        }
+      }
+    }
+    self.lit_bits.extend(other.lit_bits);
+    self.con_order.extend(other.con_order);
+  }
+
+  /// The `path` is the name of the type, qualified by the structures it was defined in.
+  pub(crate) fn start(&mut self, path: hir::Path) -> StartedSym {
     let ty_info = TyInfo {
       ty_scheme: TyScheme::zero(Ty::None),
       val_env: ValEnv::default(),
       def: None,
     };
-    self.store.push((name, ty_info));
+    let seg = self.store.len() - 1;
+    let store = &mut self.store[seg];
+    store.push((path, ty_info));
     StartedSym {
       bomb: DropBomb::new("must be passed to Syms::finish"),
       // calculate len after push, because we sub 1 in get, because of Sym::EXN.
-      sym: Sym(self.store.len()),
+      sym: Sym {
+        seg: to_u32(seg),
+        idx: to_u32(store.len()),
+      },
     }
   }
 
   pub(crate) fn finish(&mut self, mut started: StartedSym, ty_info: TyInfo) {
     started.bomb.defuse();
-    self.store[started.sym.idx()].1 = ty_info;
+    let sym = started.sym;
+    self.store[sym.seg()][sym.idx()].1 = ty_info;
   }
 
   /// Returns `None` iff passed `&Sym::EXN`.
@@ -755,39 +973,156 @@ impl Syms {
     if *sym == Sym::EXN {
       return None;
     }
-    let &(ref name, ref info) = self.store.get(sym.idx()).unwrap();
-    Some((name, info))
+    let &(ref path, ref info) = &self.store[sym.seg()][sym.idx()];
+    Some((path.last(), info))
+  }
+
+  /// Returns `None` iff passed `&Sym::EXN`.
+  pub(crate) fn get_path(&self, sym: &Sym) -> Option<&hir::Path> {
+    if *sym == Sym::EXN {
+      return None;
+    }
+    Some(&self.store[sym.seg()][sym.idx()].0)
   }
 
   pub(crate) fn insert_exn(&mut self, name: hir::Name, param: Option<Ty>) -> Exn {
-    let ret = Exn(self.exns.len());
-    self.exns.push((name, param));
+    let seg = self.exns.len() - 1;
+    let exns = &mut self.exns[seg];
+    let ret = Exn {
+      seg: to_u32(seg),
+      idx: to_u32(exns.len()),
+    };
+    exns.push((name, param));
     ret
   }
 
   pub(crate) fn get_exn(&self, exn: &Exn) -> (&hir::Name, Option<&Ty>) {
-    let &(ref name, ref param) = self.exns.get(exn.0).unwrap();
+    let &(ref name, ref param) = &self.exns[to_usize(exn.seg)][to_usize(exn.idx)];
     (name, param.as_ref())
   }
 
   pub(crate) fn mark(&self) -> SymsMarker {
-    SymsMarker(self.store.len())
+    let seg = self.store.len() - 1;
+    SymsMarker {
+      seg,
+      idx: self.store[seg].len(),
+    }
   }
 
   pub(crate) fn iter(&self) -> impl Iterator<Item = (&hir::Name, &TyInfo)> {
-    self.store.iter().map(|&(ref a, ref b)| (a, b))
+    self
+      .store
+      .iter()
+      .flatten()
+      .map(|&(ref a, ref b)| (a.last(), b))
   }
 
   pub(crate) fn overloads(&mut self) -> &mut Overloads {
     &mut self.overloads
   }
+
+  pub(crate) fn lit_bits(&self, sym: &Sym) -> Option<u16> {
+    self.lit_bits.get(sym).copied()
+  }
+
+  pub(crate) fn lit_bits_mut(&mut self) -> &mut FxHashMap<Sym, u16> {
+    &mut self.lit_bits
+  }
+
+  pub(crate) fn set_con_order(&mut self, sym: Sym, names: Vec<hir::Name>) {
+    self.con_order.insert(sym, names);
+  }
+
+  /// Returns the position of the constructor `name` in the declaration of the datatype `sym`, if
+  /// known.
+  pub(crate) fn con_idx(&self, sym: &Sym, name: &hir::Name) -> Option<usize> {
+    self.con_order.get(sym)?.iter().position(|x| x == name)
+  }
+
+  /// Returns a checkpoint, to later get the symbols generated after it with [`Self::since`].
+  pub fn checkpoint(&self) -> SymsCheckpoint {
+    let seg = self.store.len() - 1;
+    SymsCheckpoint {
+      seg,
+      store: self.store[seg].len(),
+      exns: self.exns[seg].len(),
+    }
+  }
+
+  /// Returns the symbols generated after the `checkpoint`, which must be in the current segment.
+  pub fn since(&self, checkpoint: SymsCheckpoint) -> SymsDelta {
+    let seg = checkpoint.seg;
+    assert_eq!(
+      seg,
+      self.store.len() - 1,
+      "checkpoint not in the current segment"
+    );
+    let is_new = |sym: &Sym| *sym != Sym::EXN && sym.seg() == seg && sym.idx() >= checkpoint.store;
+    SymsDelta {
+      checkpoint,
+      store: self.store[seg][checkpoint.store..].to_vec(),
+      exns: self.exns[seg][checkpoint.exns..].to_vec(),
+      lit_bits: self
+        .lit_bits
+        .iter()
+        .filter(|(sym, _)| is_new(sym))
+        .map(|(&sym, &bits)| (sym, bits))
+        .collect(),
+      con_order: self
+        .con_order
+        .iter()
+        .filter(|(sym, _)| is_new(sym))
+        .map(|(&sym, names)| (sym, names.clone()))
+        .collect(),
+    }
+  }
+
+  /// Generates the symbols in the `delta` again, as if by the same checking that generated them.
+  /// Returns whether that was possible, which is when this is at the checkpoint the `delta` started
+  /// from. If not, this is unchanged.
+  pub fn replay(&mut self, delta: SymsDelta) -> bool {
+    if self.checkpoint() != delta.checkpoint {
+      return false;
+    }
+    let seg = delta.checkpoint.seg;
+    self.store[seg].extend(delta.store);
+    self.exns[seg].extend(delta.exns);
+    self.lit_bits.extend(delta.lit_bits);
+    self.con_order.extend(delta.con_order);
+    true
+  }
+}
+
+/// A point in the generation of symbols by a [`Syms`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymsCheckpoint {
+  seg: usize,
+  store: usize,
+  exns: usize,
+}
+
+/// The symbols generated by a [`Syms`] after a [`SymsCheckpoint`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymsDelta {
+  checkpoint: SymsCheckpoint,
+  store: Vec<(hir::Path, TyInfo)>,
+  exns: Vec<(hir::Name, Option<Ty>)>,
+  lit_bits: Vec<(Sym, u16)>,
+  con_order: Vec<(Sym, Vec<hir::Name>)>,
 }
 
 /// A marker to determine when a `Sym` was generated.
-pub(crate) struct SymsMarker(usize);
+pub(crate) struct SymsMarker {
+  seg: usize,
+  idx: usize,
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) struct Exn(usize);
+/// Like [`Sym`], in a segment of its [`Syms`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub(crate) struct Exn {
+  seg: u32,
+  idx: u32,
+}
 
 /// A helper to construct information about [`Syms`]s.
 pub(crate) struct StartedSym {
@@ -802,7 +1137,7 @@ impl StartedSym {
 }
 
 /// Definition: TyStr
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TyInfo {
   pub(crate) ty_scheme: TyScheme,
   pub(crate) val_env: ValEnv,
@@ -818,7 +1153,7 @@ pub(crate) type TyEnv = FxHashMap<hir::Name, TyInfo>;
 /// Definition: ValEnv
 pub(crate) type ValEnv = FxHashMap<hir::Name, ValInfo>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct ValInfo {
   pub(crate) ty_scheme: TyScheme,
   pub(crate) id_status: IdStatus,
@@ -826,7 +1161,7 @@ pub(crate) struct ValInfo {
 }
 
 /// Definition: IdStatus
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub(crate) enum IdStatus {
   Con,
   Exn(Exn),
@@ -855,7 +1190,7 @@ pub(crate) trait EnvLike {
 }
 
 /// Definition: Env
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub(crate) struct Env {
   pub(crate) str_env: StrEnv,
   pub(crate) ty_env: TyEnv,
@@ -910,7 +1245,7 @@ impl EnvLike for Env {
 
 /// A wrapper around a stack of [`Env`]s. Is meant to act like an `Env` in most respects, but is
 /// faster to `Clone`.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub(crate) struct EnvStack(Vec<Arc<Env>>);
 
 impl EnvStack {
@@ -921,6 +1256,91 @@ impl EnvStack {
   pub(crate) fn push(&mut self, other: Env) {
     self.0.push(Arc::new(other));
   }
+
+  pub(crate) fn from_arcs(envs: Vec<Arc<Env>>) -> Self {
+    Self(envs)
+  }
+
+  pub(crate) fn arcs(&self) -> &[Arc<Env>] {
+    &self.0
+  }
+
+  /// returns the shortest paths that refer to the type names for the `want`ed syms in this, for those
+  /// that have one. only paths that end in the name the type was defined with count, so that e.g.
+  /// `type t = int` does not rename `int`.
+  pub(crate) fn ty_name_paths(
+    &self,
+    syms: &Syms,
+    want: &FxHashSet<Sym>,
+  ) -> FxHashMap<Sym, Vec<hir::Name>> {
+    let mut ret = FxHashMap::<Sym, Vec<hir::Name>>::default();
+    let mut names = FxHashSet::<&hir::Name>::default();
+    let mut tys: Vec<(Vec<&hir::Name>, &TyInfo)> = self
+      .0
+      .iter()
+      .rev()
+      .flat_map(|env| env.ty_env.iter())
+      .filter(|(name, _)| names.insert(name))
+      .map(|(name, ty_info)| (vec![name], ty_info))
+      .collect();
+    let mut names = FxHashSet::<&hir::Name>::default();
+    let mut strs: Vec<(Vec<&hir::Name>, &Env)> = self
+      .0
+      .iter()
+      .rev()
+      .flat_map(|env| env.str_env.iter())
+      .filter(|(name, _)| names.insert(name))
+      .map(|(name, env)| (vec![name], env))
+      .collect();
+    // breadth-first, so the first path found for a sym is a shortest one. sort each level so that
+    // among paths of the same length, we pick the same one every time.
+    loop {
+      tys.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+      for (path, ty_info) in tys {
+        let sym = match ty_info.ty_scheme.as_ty_name() {
+          Some(x) => x,
+          None => continue,
+        };
+        if !want.contains(&sym) || ret.contains_key(&sym) {
+          continue;
+        }
+        let defined_name = syms.get(&sym).map(|(name, _)| name);
+        if path.last().copied() == defined_name {
+          ret.insert(sym, path.into_iter().cloned().collect());
+        }
+      }
+      if ret.len() == want.len() || strs.is_empty() {
+        return ret;
+      }
+      tys = Vec::new();
+      let mut next = Vec::new();
+      for (path, env) in strs {
+        for (name, ty_info) in env.ty_env.iter() {
+          let mut path = path.clone();
+          path.push(name);
+          tys.push((path, ty_info));
+        }
+        for (name, env) in env.str_env.iter() {
+          let mut path = path.clone();
+          path.push(name);
+          next.push((path, env));
+        }
+      }
+      strs = next;
+    }
+  }
+
+  /// returns the values in scope with their names, innermost first.
+  pub(crate) fn vals(&self) -> Vec<(&hir::Name, &ValInfo)> {
+    let mut names = FxHashSet::<&hir::Name>::default();
+    self
+      .0
+      .iter()
+      .rev()
+      .flat_map(|env| env.val_env.iter())
+      .filter(|(name, _)| names.insert(name))
+      .collect()
+  }
 }
 
 impl EnvLike for EnvStack {
@@ -1001,28 +1421,54 @@ pub(crate) struct Cx {
 pub(crate) type TyNameSet = FxHashSet<Sym>;
 
 /// Definition: Sig
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Sig {
   pub(crate) ty_names: TyNameSet,
   pub(crate) env: Env,
 }
 
 /// Definition: FunSig
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct FunSig {
-  pub(crate) param: Sig,
+  /// Not in the Definition. Only used for display.
+  pub(crate) param_name: hir::Name,
+  pub(crate) param: FunParam,
   pub(crate) body_ty_names: TyNameSet,
   pub(crate) body_env: Env,
+  /// SML/NJ extension. For a curried functor, the functor returned by applying this one. Then
+  /// `body_ty_names` is empty, and `body_env` is empty except for its `def`.
+  pub(crate) result: Option<Box<FunSig>>,
+}
+
+impl FunSig {
+  /// Returns how many arguments to apply this to before getting a structure. This is more than one
+  /// for a curried functor.
+  pub(crate) fn num_params(&self) -> usize {
+    1 + self.result.as_ref().map_or(0, |x| x.num_params())
+  }
+}
+
+/// What a functor takes as its parameter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum FunParam {
+  /// A structure matching the signature. The only kind in the Definition.
+  Sig(Sig),
+  /// SML/NJ extension. A functor matching the functor signature with the name.
+  FunSig(hir::Name, Box<FunSig>),
 }
 
 pub(crate) type SigEnv = FxHashMap<hir::Name, Sig>;
 pub(crate) type FunEnv = FxHashMap<hir::Name, FunSig>;
+/// SML/NJ extension.
+pub(crate) type FunSigEnv = FxHashMap<hir::Name, FunSig>;
 
 /// Definition: Basis
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub(crate) struct Bs<E = EnvStack> {
   pub(crate) fun_env: Arc<FunEnv>,
   pub(crate) sig_env: Arc<SigEnv>,
+  /// SML/NJ extension.
+  pub(crate) fun_sig_env: Arc<FunSigEnv>,
   pub(crate) env: E,
 }
 
@@ -1044,25 +1490,38 @@ impl<E: EnvLike> Bs<E> {
     Arc::make_mut(&mut self.sig_env)
   }
 
+  pub(crate) fn as_mut_fun_sig_env(&mut self) -> &mut FunSigEnv {
+    Arc::make_mut(&mut self.fun_sig_env)
+  }
+
   pub(crate) fn append<E2: EnvLike>(&mut self, mut other: Bs<E2>) {
     self.as_mut_fun_env().extend(other.as_mut_fun_env().drain());
     self.as_mut_sig_env().extend(other.as_mut_sig_env().drain());
+    self
+      .as_mut_fun_sig_env()
+      .extend(other.as_mut_fun_sig_env().drain());
     self.env.append(&mut other.env.into_env());
   }
 }
 
 /// Information about meta type variables.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct MetaVarInfo(FxHashMap<MetaTyVar, TyVarKind>);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub(crate) struct Subst {
   mv_info: MetaVarInfo,
   entries: FxHashMap<MetaTyVar, SubstEntry>,
+  /// if logging changes, the old entry and info of each meta var inserted into, oldest first.
+  undo_log: Option<Vec<(MetaTyVar, Option<SubstEntry>, Option<TyVarKind>)>>,
 }
 
 impl Subst {
   pub(crate) fn insert(&mut self, mv: MetaTyVar, entry: SubstEntry) -> Option<SubstEntry> {
+    if let Some(undo_log) = &mut self.undo_log {
+      let old_info = self.mv_info.0.get(&mv).cloned();
+      undo_log.push((mv, self.entries.get(&mv).cloned(), old_info));
+    }
     match &entry {
       SubstEntry::Solved(_) => {}
       SubstEntry::Kind(kind) => {
@@ -1076,12 +1535,40 @@ impl Subst {
     self.entries.get(mv)
   }
 
+  /// starts logging changes, if not already, and returns a mark to undo the changes after now
+  /// with [`Self::undo_to`]. this is much cheaper than cloning the whole subst to restore later.
+  ///
+  /// call [`Self::stop_undo_log`] when done.
+  pub(crate) fn mark(&mut self) -> usize {
+    self.undo_log.get_or_insert_with(Vec::new).len()
+  }
+
+  /// undoes the changes since the `mark` was made.
+  pub(crate) fn undo_to(&mut self, mark: usize) {
+    let undo_log = self.undo_log.as_mut().expect("not logging changes");
+    for (mv, entry, info) in undo_log.drain(mark..).rev() {
+      match entry {
+        Some(entry) => self.entries.insert(mv, entry),
+        None => self.entries.remove(&mv),
+      };
+      match info {
+        Some(info) => self.mv_info.0.insert(mv, info),
+        None => self.mv_info.0.remove(&mv),
+      };
+    }
+  }
+
+  /// stops logging changes, keeping the changes not undone.
+  pub(crate) fn stop_undo_log(&mut self) {
+    self.undo_log = None;
+  }
+
   pub(crate) fn into_meta_var_info(self) -> MetaVarInfo {
     self.mv_info
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) enum SubstEntry {
   /// We solved this meta var to a `Ty`.
   Solved(Ty),
@@ -1314,7 +1801,7 @@ fn handle_bv(
 }
 
 /// A definition site.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Def {
   /// The path.
   pub path: DefPath,
@@ -1330,3 +1817,61 @@ pub enum DefPath {
   /// A std basis path.
   StdBasis(&'static str),
 }
+
+/// A regular path is serialized as its raw [`paths::PathId`], which means nothing without the
+/// [`paths::Root`] that made it. So it may only be read back where the same ids are given to the
+/// same paths.
+impl Serialize for DefPath {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let repr = match self {
+      DefPath::Regular(path) => DefPathRepr::Regular(path.to_raw()),
+      DefPath::StdBasis(name) => DefPathRepr::StdBasis(*name),
+    };
+    repr.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for DefPath {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let ret = match DefPathRepr::<String>::deserialize(deserializer)? {
+      DefPathRepr::Regular(raw) => DefPath::Regular(paths::PathId::from_raw(raw)),
+      DefPathRepr::StdBasis(name) => DefPath::StdBasis(static_str(name)),
+    };
+    Ok(ret)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+enum DefPathRepr<S> {
+  Regular(usize),
+  StdBasis(S),
+}
+
+/// Returns a `&'static str` for a deserialized string that is one of only a few, like the name of a
+/// std basis file. We leak each distinct one once.
+pub(crate) fn static_str(name: String) -> &'static str {
+  static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+  let mut names = NAMES
+    .lock()
+    .expect("static str names lock should not be poisoned");
+  if let Some(&ret) = names.get(name.as_str()) {
+    return ret;
+  }
+  let ret: &'static str = Box::leak(name.into_boxed_str());
+  names.insert(ret);
+  ret
+}
+
+/// For `#[serde(deserialize_with = ...)]` on `&'static str` fields. See [`static_str`].
+pub(crate) fn deserialize_static_str<'de, D>(deserializer: D) -> Result<&'static str, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  String::deserialize(deserializer).map(static_str)
+}
@@ -2,26 +2,100 @@ use fast_hash::FxHashMap;
 
 use crate::error::ErrorKind;
 use crate::st::St;
-use crate::types::{meta_vars, MetaTyVar, SubstEntry, Ty, TyVarKind};
+use crate::types::{meta_vars, EnvStack, MetaTyVar, SubstEntry, Ty, TyVarKind};
 use crate::util::apply;
 
 #[derive(Debug)]
 pub(crate) enum UnifyError {
   OccursCheck(MetaTyVar, Ty),
-  HeadMismatch,
+  /// The path is from the differing subterm out to the whole type, i.e. it is reversed.
+  HeadMismatch(Vec<TyPathStep>),
+}
+
+/// A step from a type into one of its immediate subterms.
+#[derive(Debug, Clone)]
+pub(crate) enum TyPathStep {
+  Row(hir::Lab),
+  Arg(usize),
+  Param,
+  Res,
 }
 
 pub(crate) type Result<T = (), E = UnifyError> = std::result::Result<T, E>;
 
-pub(crate) fn unify(st: &mut St, want: Ty, got: Ty, idx: hir::Idx) {
+/// emits an error iff `want` and `got` cannot be unified. the types in the error are displayed as
+/// they could be written in `env`.
+pub(crate) fn unify(st: &mut St, env: &EnvStack, want: Ty, got: Ty, idx: hir::Idx) {
+  unify_src(st, env, want, got, idx, None);
+}
+
+/// like [`unify`], but also takes the index of the thing that caused `want` to be wanted, if
+/// known.
+pub(crate) fn unify_src(
+  st: &mut St,
+  env: &EnvStack,
+  mut want: Ty,
+  mut got: Ty,
+  idx: hir::Idx,
+  want_src: Option<hir::Idx>,
+) {
   let e = match unify_(st, want.clone(), got.clone()) {
     Ok(()) => return,
     Err(e) => match e {
       UnifyError::OccursCheck(mv, ty) => ErrorKind::Circularity(mv, ty),
-      UnifyError::HeadMismatch => ErrorKind::MismatchedTypes(want, got),
+      UnifyError::HeadMismatch(mut path) => {
+        path.reverse();
+        // show the types as they are now, so they agree with the part.
+        apply(st.subst(), &mut want);
+        apply(st.subst(), &mut got);
+        let part = if path.is_empty() {
+          None
+        } else {
+          subterm(want.clone(), &path).zip(subterm(got.clone(), &path))
+        };
+        // the source of `want` is only interesting if it's not where we report the error.
+        let want_src = want_src.filter(|&x| x != idx);
+        ErrorKind::MismatchedTypes {
+          want,
+          got,
+          part,
+          want_src,
+        }
+      }
     },
   };
-  st.err(idx, e);
+  st.err_in(env, idx, e);
+}
+
+/// returns the subterm of `ty` at the `path`, if there is one.
+fn subterm(mut ty: Ty, path: &[TyPathStep]) -> Option<Ty> {
+  for step in path {
+    ty = match (ty, step) {
+      (Ty::Record(mut rows), TyPathStep::Row(lab)) => rows.remove(lab)?,
+      (Ty::Con(mut args, _), &TyPathStep::Arg(idx)) => {
+        if idx < args.len() {
+          args.swap_remove(idx)
+        } else {
+          return None;
+        }
+      }
+      (Ty::Fn(param, _), TyPathStep::Param) => *param,
+      (Ty::Fn(_, res), TyPathStep::Res) => *res,
+      _ => return None,
+    };
+  }
+  Some(ty)
+}
+
+/// adds `step` to the path of a head mismatch error.
+fn in_step<T>(res: Result<T>, step: TyPathStep) -> Result<T> {
+  res.map_err(|e| match e {
+    UnifyError::HeadMismatch(mut path) => {
+      path.push(step);
+      UnifyError::HeadMismatch(path)
+    }
+    UnifyError::OccursCheck(_, _) => e,
+  })
 }
 
 /// does not emit any errors to the `st`, instead returns an error (if any).
@@ -38,30 +112,30 @@ pub(crate) fn unify_(st: &mut St, mut want: Ty, mut got: Ty) -> Result {
     (Ty::Record(want_rows), Ty::Record(mut got_rows)) => {
       for (lab, want) in want_rows {
         match got_rows.remove(&lab) {
-          None => return Err(UnifyError::HeadMismatch),
-          Some(got) => unify_(st, want, got)?,
+          None => return Err(UnifyError::HeadMismatch(Vec::new())),
+          Some(got) => in_step(unify_(st, want, got), TyPathStep::Row(lab))?,
         }
       }
       if got_rows.is_empty() {
         Ok(())
       } else {
-        Err(UnifyError::HeadMismatch)
+        Err(UnifyError::HeadMismatch(Vec::new()))
       }
     }
     (Ty::Con(want_args, want_sym), Ty::Con(got_args, got_sym)) => {
       head_match(want_sym == got_sym)?;
       assert_eq!(want_args.len(), got_args.len());
-      for (want, got) in want_args.into_iter().zip(got_args) {
-        unify_(st, want, got)?;
+      for (idx, (want, got)) in want_args.into_iter().zip(got_args).enumerate() {
+        in_step(unify_(st, want, got), TyPathStep::Arg(idx))?;
       }
       Ok(())
     }
     (Ty::Fn(want_param, want_res), Ty::Fn(got_param, got_res)) => {
-      unify_(st, *want_param, *got_param)?;
-      unify_(st, *want_res, *got_res)
+      in_step(unify_(st, *want_param, *got_param), TyPathStep::Param)?;
+      in_step(unify_(st, *want_res, *got_res), TyPathStep::Res)
     }
     (Ty::BoundVar(_) | Ty::FixedVar(_) | Ty::Record(_) | Ty::Con(_, _) | Ty::Fn(_, _), _) => {
-      Err(UnifyError::HeadMismatch)
+      Err(UnifyError::HeadMismatch(Vec::new()))
     }
   }
 }
@@ -98,110 +172,118 @@ fn unify_mv(st: &mut St, mv: MetaTyVar, mut ty: Ty) -> Result {
     }
   }
   apply(st.subst(), &mut ty);
-  // solve mv to ty. however, mv may already have an entry.
-  match st.subst().insert(mv, SubstEntry::Solved(ty.clone())) {
-    // do nothing if no entry.
+  // solve mv to ty. however, mv may already have an entry, which ty must conform to first.
+  match st.subst().get(&mv) {
+    // nothing to check if no entry.
     None => {}
     // unreachable because we applied upon entry.
     Some(SubstEntry::Solved(ty)) => unreachable!("meta var already solved to {ty:?}"),
-    Some(SubstEntry::Kind(kind)) => match kind {
-      // TODO check ty is do more for equality checks
-      TyVarKind::Equality => {}
-      // mv was an overloaded ty var. ty must conform to that overload.
-      TyVarKind::Overloaded(ov) => match ty {
-        // don't emit more errors for None.
-        Ty::None => {}
-        // the simple case. check the sym is in the overload.
-        Ty::Con(args, s) => {
-          if ov
-            .as_basics()
-            .iter()
-            .any(|&ov| st.syms.overloads()[ov].contains(&s))
-          {
-            assert!(args.is_empty())
-          } else {
-            return Err(UnifyError::HeadMismatch);
-          }
+    Some(SubstEntry::Kind(kind)) => unify_kind(st, kind.clone(), ty.clone())?,
+  }
+  st.subst().insert(mv, SubstEntry::Solved(ty));
+  Ok(())
+}
+
+/// checks that `ty`, which a meta var with the `kind` is about to be solved to, conforms to that
+/// kind.
+fn unify_kind(st: &mut St, kind: TyVarKind, ty: Ty) -> Result {
+  match kind {
+    // TODO check ty is do more for equality checks
+    TyVarKind::Equality => {}
+    // mv was an overloaded ty var. ty must conform to that overload.
+    TyVarKind::Overloaded(ov) => match ty {
+      // don't emit more errors for None.
+      Ty::None => {}
+      // the simple case. check the sym is in the overload.
+      Ty::Con(args, s) => {
+        if ov
+          .as_basics()
+          .iter()
+          .any(|&ov| st.syms.overloads()[ov].contains(&s))
+        {
+          assert!(args.is_empty())
+        } else {
+          return Err(UnifyError::HeadMismatch(Vec::new()));
         }
-        // we solved mv = mv2. now we give mv2 mv's old entry, to make it an overloaded ty var.
-        // but mv2 itself may also have an entry.
-        Ty::MetaVar(mv2) => {
-          let ov = match st.subst().get(&mv2) {
-            // it didn't have an entry.
-            None => ov,
-            // unreachable because of apply.
-            Some(SubstEntry::Solved(ty)) => unreachable!("meta var already solved to {ty:?}"),
-            Some(SubstEntry::Kind(kind)) => match kind {
-              // all overload types are equality types.
-              TyVarKind::Equality => ov,
-              // it too was an overload. attempt to unify the two overloads.
-              TyVarKind::Overloaded(ov2) => match ov.unify(*ov2) {
-                Some(ov) => ov,
-                None => return Err(UnifyError::HeadMismatch),
-              },
-              // no overloaded type is a record.
-              TyVarKind::Record(_) => return Err(UnifyError::HeadMismatch),
+      }
+      // we are solving mv = mv2. so we give mv2 mv's entry, to make it an overloaded ty var.
+      // but mv2 itself may also have an entry.
+      Ty::MetaVar(mv2) => {
+        let ov = match st.subst().get(&mv2) {
+          // it didn't have an entry.
+          None => ov,
+          // unreachable because of apply.
+          Some(SubstEntry::Solved(ty)) => unreachable!("meta var already solved to {ty:?}"),
+          Some(SubstEntry::Kind(kind)) => match kind {
+            // all overload types are equality types.
+            TyVarKind::Equality => ov,
+            // it too was an overload. attempt to unify the two overloads.
+            TyVarKind::Overloaded(ov2) => match ov.unify(*ov2) {
+              Some(ov) => ov,
+              None => return Err(UnifyError::HeadMismatch(Vec::new())),
             },
-          };
-          let k = SubstEntry::Kind(TyVarKind::Overloaded(ov));
-          st.subst().insert(mv2, k);
-        }
-        // none of these are overloaded types.
-        Ty::BoundVar(_) | Ty::FixedVar(_) | Ty::Record(_) | Ty::Fn(_, _) => {
-          return Err(UnifyError::HeadMismatch)
-        }
-      },
-      // mv was a record ty var (i.e. from a `...` pattern). ty must have the rows gotten so
-      // far.
-      TyVarKind::Record(mut want_rows) => match ty {
-        // don't emit more errors for None.
-        Ty::None => {}
-        // ty was a record. it should have every label in the wanted rows, and the types should
-        // unify.
-        Ty::Record(mut got_rows) => {
-          for (lab, want) in want_rows {
-            match got_rows.remove(&lab) {
-              None => return Err(UnifyError::HeadMismatch),
-              Some(got) => unify_(st, want, got)?,
-            }
+            // no overloaded type is a record.
+            TyVarKind::Record(_) => return Err(UnifyError::HeadMismatch(Vec::new())),
+          },
+        };
+        let k = SubstEntry::Kind(TyVarKind::Overloaded(ov));
+        st.subst().insert(mv2, k);
+      }
+      // none of these are overloaded types.
+      Ty::BoundVar(_) | Ty::FixedVar(_) | Ty::Record(_) | Ty::Fn(_, _) => {
+        return Err(UnifyError::HeadMismatch(Vec::new()))
+      }
+    },
+    // mv was a record ty var (i.e. from a `...` pattern). ty must have the rows gotten so
+    // far.
+    TyVarKind::Record(mut want_rows) => match ty {
+      // don't emit more errors for None.
+      Ty::None => {}
+      // ty was a record. it should have every label in the wanted rows, and the types should
+      // unify.
+      Ty::Record(mut got_rows) => {
+        for (lab, want) in want_rows {
+          match got_rows.remove(&lab) {
+            None => return Err(UnifyError::HeadMismatch(Vec::new())),
+            Some(got) => in_step(unify_(st, want, got), TyPathStep::Row(lab))?,
           }
         }
-        // ty was a meta var, so we solved mv = mv2. check if mv2 has its own entry before
-        // setting mv2's entry to mv's old entry, which specifies the rows for this record ty
-        // var.
-        Ty::MetaVar(mv2) => {
-          match st.subst().get(&mv2) {
-            // there was no entry.
-            None => {}
-            // unreachable because of apply.
-            Some(SubstEntry::Solved(ty)) => unreachable!("meta var already solved to {ty:?}"),
-            Some(SubstEntry::Kind(kind)) => match kind {
-              // TODO check if the rows so far are equality.
-              TyVarKind::Equality => {}
-              // no overloaded type is a record type.
-              TyVarKind::Overloaded(_) => return Err(UnifyError::HeadMismatch),
-              // mv2 was another record ty var. merge the rows, and for those that appear in
-              // both, unify the types.
-              TyVarKind::Record(other_rows) => {
-                for (lab, mut want) in other_rows.clone() {
-                  if let Some(got) = want_rows.get(&lab) {
-                    unify_(st, want.clone(), got.clone())?;
-                    apply(st.subst(), &mut want);
-                  }
-                  want_rows.insert(lab, want);
+      }
+      // ty was a meta var, so we are solving mv = mv2. check if mv2 has its own entry before
+      // setting mv2's entry to mv's old entry, which specifies the rows for this record ty
+      // var.
+      Ty::MetaVar(mv2) => {
+        match st.subst().get(&mv2) {
+          // there was no entry.
+          None => {}
+          // unreachable because of apply.
+          Some(SubstEntry::Solved(ty)) => unreachable!("meta var already solved to {ty:?}"),
+          Some(SubstEntry::Kind(kind)) => match kind {
+            // TODO check if the rows so far are equality.
+            TyVarKind::Equality => {}
+            // no overloaded type is a record type.
+            TyVarKind::Overloaded(_) => return Err(UnifyError::HeadMismatch(Vec::new())),
+            // mv2 was another record ty var. merge the rows, and for those that appear in
+            // both, unify the types.
+            TyVarKind::Record(other_rows) => {
+              for (lab, mut want) in other_rows.clone() {
+                if let Some(got) = want_rows.get(&lab) {
+                  unify_(st, want.clone(), got.clone())?;
+                  apply(st.subst(), &mut want);
                 }
+                want_rows.insert(lab, want);
               }
-            },
-          }
-          // set the entry to make mv2 a record ty var.
-          let k = SubstEntry::Kind(TyVarKind::Record(want_rows));
-          st.subst().insert(mv2, k);
-        }
-        // none of these are record types.
-        Ty::BoundVar(_) | Ty::FixedVar(_) | Ty::Con(_, _) | Ty::Fn(_, _) => {
-          return Err(UnifyError::HeadMismatch)
+            }
+          },
         }
-      },
+        // set the entry to make mv2 a record ty var.
+        let k = SubstEntry::Kind(TyVarKind::Record(want_rows));
+        st.subst().insert(mv2, k);
+      }
+      // none of these are record types.
+      Ty::BoundVar(_) | Ty::FixedVar(_) | Ty::Con(_, _) | Ty::Fn(_, _) => {
+        return Err(UnifyError::HeadMismatch(Vec::new()))
+      }
     },
   }
   Ok(())
@@ -211,7 +293,7 @@ fn head_match(b: bool) -> Result {
   if b {
     Ok(())
   } else {
-    Err(UnifyError::HeadMismatch)
+    Err(UnifyError::HeadMismatch(Vec::new()))
   }
 }
 
@@ -1,20 +1,35 @@
 use crate::error::{ErrorKind, Item};
 use crate::st::St;
 use crate::types::{
-  BasicOverload, Generalizable, Overload, RecordTy, Subst, SubstEntry, Sym, Ty, TyScheme, TyVarKind,
+  BasicOverload, EnvStack, Generalizable, Overload, RecordTy, Subst, SubstEntry, Sym, Ty, TyScheme,
+  TyVarKind,
 };
 use fast_hash::FxHashMap;
 
-pub(crate) fn get_scon(st: &mut St, scon: &hir::SCon, g: Generalizable) -> Ty {
-  // we could have all of these return the basic overloads, but some of them will all only allow the
-  // default types anyway.
+pub(crate) fn get_scon(
+  st: &mut St,
+  env: &EnvStack,
+  idx: hir::Idx,
+  scon: &hir::SCon,
+  g: Generalizable,
+) -> Ty {
+  // each kind of literal may have any of the types in its overload class, e.g. `0w1` may be a
+  // `Word8.word`. these default to the usual types, like `word`, when generalizing.
+  let b = match scon {
+    hir::SCon::Int(_) => BasicOverload::Int,
+    hir::SCon::Real(_) => BasicOverload::Real,
+    hir::SCon::Word(_) => BasicOverload::Word,
+    hir::SCon::Char(_) => BasicOverload::Char,
+    hir::SCon::String(_) => BasicOverload::String,
+  };
+  let ret = basic_overload(st, b, g);
+  // we only know whether the literal is in range once we know its type.
   match scon {
-    hir::SCon::Int(_) => basic_overload(st, BasicOverload::Int, g),
-    hir::SCon::Real(_) => Ty::REAL,
-    hir::SCon::Word(_) => basic_overload(st, BasicOverload::Word, g),
-    hir::SCon::Char(_) => Ty::CHAR,
-    hir::SCon::String(_) => Ty::STRING,
+    hir::SCon::Int(value) => st.insert_lit(idx, env, ret.clone(), value.clone(), true),
+    hir::SCon::Word(value) => st.insert_lit(idx, env, ret.clone(), value.clone(), false),
+    hir::SCon::Real(_) | hir::SCon::Char(_) | hir::SCon::String(_) => {}
   }
+  ret
 }
 
 fn basic_overload(st: &mut St, b: BasicOverload, g: Generalizable) -> Ty {
@@ -2,13 +2,14 @@
 
 #![deny(missing_debug_implementations, missing_docs, rust_2018_idioms)]
 
+use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::fmt;
 
 pub use smol_str::SmolStr;
 
 /// An immutable, somewhat cheaply clone-able, non-empty string.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Name(SmolStr);
 
 impl Name {
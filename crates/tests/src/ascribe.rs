@@ -238,9 +238,8 @@ end
 val _ = Add.add Add.zero Add.zero
 val _ = Mul.add Mul.zero Mul.zero
 
-(* TODO improve error message with FQN *)
 val _ = Mul.add Mul.zero Add.zero
-(**                      ^^^^^^^^ expected t, found t *)
+(**                      ^^^^^^^^ expected Mul.t, found Add.t *)
 "#,
   );
 }
@@ -270,7 +269,7 @@ end
 val _ = A.bar A.foo = B.bar B.foo
 
 val _ = A.bar B.foo
-(**           ^^^^^ expected t, found t *)
+(**           ^^^^^ expected A.t, found B.t *)
 "#,
   );
 }
@@ -301,7 +300,7 @@ val _ = inc S.foo
 
 val _ = A.bar A.foo
 val _ = A.bar 123
-(**           ^^^ expected t, found int *)
+(**           ^^^ expected A.t, found int *)
 "#,
   );
 }
@@ -334,7 +333,7 @@ val _ = inc S.foo
 val _ = A.bar A.foo
 val _ = B.bar B.foo
 val _ = B.bar A.foo
-(**           ^^^^^ expected t, found t *)
+(**           ^^^^^ expected B.t, found A.t *)
 "#,
   );
 }
@@ -362,7 +361,7 @@ structure C:> SIG = Str
 structure D:> SIG = Str
 
 val _ = D.x: C.t
-(**     ^^^^^^^^ expected t, found t *)
+(**     ^^^^^^^^ expected C.t, found D.t *)
 "#,
   );
 }
@@ -383,7 +382,7 @@ end
 
 val _: S.t = S.x
 val _ = S.x: int
-(**     ^^^^^^^^ expected int, found t *)
+(**     ^^^^^^^^ expected int, found S.t *)
 "#,
   );
 }
@@ -0,0 +1,163 @@
+//! Tests for the call hierarchy of functions and functors.
+
+use crate::check::{analyze_project, find_pos};
+use analysis::CallHierarchyItemKind as Kind;
+
+/// Returns the ranges as `line:col`, one-based, and separated by commas.
+fn display_ranges(ranges: &[analysis::Range]) -> String {
+  let ranges: Vec<_> = ranges
+    .iter()
+    .map(|r| format!("{}:{}", r.start.line + 1, r.start.character + 1))
+    .collect();
+  ranges.join(", ")
+}
+
+/// Returns a display of the item, like `f (a.sml)`.
+fn display_item(root: &analysis::input::Root, item: &analysis::CallHierarchyItem) -> String {
+  let path = root.as_paths().get_rel_path(item.path).display();
+  format!("{} ({path})", item.name)
+}
+
+/// `files` are the source files, in order. Checks the item, incoming calls, and outgoing calls at
+/// the first occurrence of `needle` in `name`. The calls are like `f (a.sml) at 1:2`.
+fn check(
+  files: &[(&str, &str)],
+  name: &str,
+  needle: &str,
+  want_item: (&str, Kind),
+  want_incoming: &[&str],
+  want_outgoing: &[&str],
+) {
+  let mlb: Vec<_> = files.iter().map(|&(name, _)| name).collect();
+  let mlb = mlb.join("\n");
+  let mut all = vec![("sources.mlb", mlb.as_str())];
+  all.extend_from_slice(files);
+  let (root, input, an) = analyze_project(&all);
+  let pos = find_pos(&root, &input, name, needle);
+  let item = an.prepare_call_hierarchy(pos).expect("no item");
+  assert_eq!(want_item, (item.name.as_str(), item.kind));
+  let incoming: Vec<_> = an
+    .get_incoming_calls(pos)
+    .expect("no incoming calls")
+    .iter()
+    .map(|call| {
+      format!(
+        "{} at {}",
+        display_item(&root, &call.from),
+        display_ranges(&call.from_ranges)
+      )
+    })
+    .collect();
+  assert_eq!(want_incoming, incoming.as_slice());
+  let outgoing: Vec<_> = an
+    .get_outgoing_calls(pos)
+    .expect("no outgoing calls")
+    .iter()
+    .map(|call| {
+      format!(
+        "{} at {}",
+        display_item(&root, &call.to),
+        display_ranges(&call.from_ranges)
+      )
+    })
+    .collect();
+  assert_eq!(want_outgoing, outgoing.as_slice());
+}
+
+const TWO_FILES: [(&str, &str); 2] = [
+  ("a.sml", "fun f x = x + 1"),
+  ("b.sml", "fun g y = f y\nfun h z = f (g z)"),
+];
+
+#[test]
+fn incoming_across_files() {
+  check(
+    &TWO_FILES,
+    "a.sml",
+    "f x",
+    ("f", Kind::Fun),
+    &["g (b.sml) at 1:11", "h (b.sml) at 2:11"],
+    &[],
+  );
+}
+
+#[test]
+fn outgoing_across_files() {
+  check(
+    &TWO_FILES,
+    "b.sml",
+    "h z",
+    ("h", Kind::Fun),
+    &[],
+    &["f (a.sml) at 2:11", "g (b.sml) at 2:14"],
+  );
+}
+
+#[test]
+fn use_site() {
+  check(
+    &TWO_FILES,
+    "b.sml",
+    "f y",
+    ("f", Kind::Fun),
+    &["g (b.sml) at 1:11", "h (b.sml) at 2:11"],
+    &[],
+  );
+}
+
+#[test]
+fn val_fn() {
+  check(
+    &[("a.sml", "val f = fn x => x\nfun g y = f y")],
+    "a.sml",
+    "f =",
+    ("f", Kind::Fun),
+    &["g (a.sml) at 2:11"],
+    &[],
+  );
+}
+
+#[test]
+fn functor_app() {
+  check(
+    &[(
+      "a.sml",
+      "functor F (X : sig end) = struct end\nstructure S = F (struct end)",
+    )],
+    "a.sml",
+    "F (X",
+    ("F", Kind::Functor),
+    &["S (a.sml) at 2:15"],
+    &[],
+  );
+}
+
+#[test]
+fn functor_app_outgoing() {
+  check(
+    &[(
+      "a.sml",
+      "functor F (X : sig end) = struct end\nstructure S = F (struct end)",
+    )],
+    "a.sml",
+    "S =",
+    ("S", Kind::Structure),
+    &[],
+    &["F (a.sml) at 2:15"],
+  );
+}
+
+#[test]
+fn not_callable() {
+  let (root, input, an) =
+    analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", "val x = 3\nval y = x")]);
+  let lit = find_pos(&root, &input, "a.sml", "3");
+  assert!(an.prepare_call_hierarchy(lit).is_none());
+  assert!(an.get_incoming_calls(lit).is_none());
+  assert!(an.get_outgoing_calls(lit).is_none());
+  let use_site = lit.path.wrap(analysis::Position {
+    line: 1,
+    character: 8,
+  });
+  assert!(an.prepare_call_hierarchy(use_site).is_none());
+}
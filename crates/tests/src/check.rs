@@ -1,6 +1,6 @@
 //! Test infra.
 
-use fast_hash::FxHashMap;
+use fast_hash::{FxHashMap, FxHashSet};
 use once_cell::sync::Lazy;
 use paths::FileSystem as _;
 use std::fmt::{self, Write as _};
@@ -83,13 +83,63 @@ pub(crate) fn check_multi(ss: &[&str]) {
   go(ss, StdBasis::Minimal, Outcome::Pass)
 }
 
-/// ignores the Err if we already initialized logging, since that's fine.
+/// Like [`check`], but checks a whole project, given as the paths of its files relative to the root
+/// and their contents. The project is read and analyzed as the CLI does it, so it should have a root
+/// group, and errors in group files and the config file are checked too. Expectation comments may
+/// be in any file. Unlike [`check`], every error in every file is checked, so each must have an
+/// expectation comment, and each expected error must be emitted.
+///
+/// In a `millet.toml`, put expectation comments in TOML comments, like `# (** ^^^ message *)`. An
+/// error with no range, as for a config file that could not be parsed, is expected by a comment
+/// with a `+` arrow anywhere in its file.
+///
+/// ```ignore
+/// check_project(&[
+///   ("sources.cm", "Group is a.sml b.sml"),
+///   ("a.sml", "structure A = struct end"),
+///   ("b.sml", r#"
+/// structure B = C
+/// (**           ^ undefined structure: C *)
+/// "#),
+/// ]);
+/// ```
+#[track_caller]
+pub(crate) fn check_project(files: &[(&str, &str)]) {
+  go_with(StdBasis::Minimal, Outcome::Pass, |std_basis| {
+    Check::from_files(files, std_basis, Errors::All)
+  })
+}
+
+/// Like [`check_project`], but includes the full std basis.
+#[track_caller]
+pub(crate) fn check_project_with_std_basis(files: &[(&str, &str)]) {
+  go_with(StdBasis::Full, Outcome::Pass, |std_basis| {
+    Check::from_files(files, std_basis, Errors::All)
+  })
+}
+
+/// Like [`check_project_with_std_basis`], but the expectation comments should be not satisfied.
+#[track_caller]
+pub(crate) fn fail_project_with_std_basis(files: &[(&str, &str)]) {
+  go_with(StdBasis::Full, Outcome::Fail, |std_basis| {
+    Check::from_files(files, std_basis, Errors::All)
+  })
+}
+
 fn go(ss: &[&str], std_basis: StdBasis, want: Outcome) {
+  go_with(std_basis, want, |std_basis| Check::new(ss, std_basis))
+}
+
+/// ignores the Err if we already initialized logging, since that's fine.
+fn go_with<F>(std_basis: StdBasis, want: Outcome, f: F)
+where
+  F: FnOnce(analysis::StdBasis) -> Check,
+{
   let _ = env_logger::builder().is_test(true).try_init();
   if matches!(std_basis, StdBasis::Full) && env_var_eq_1("TEST_MINIMAL") {
     return;
   }
-  let c = Check::new(ss, std_basis.to_analysis());
+  let c = f(std_basis.to_analysis());
   match (want, c.reasons.is_empty()) {
     (Outcome::Pass, true) | (Outcome::Fail, false) => {}
     (Outcome::Pass, false) => panic!("UNEXPECTED FAIL: {c}"),
@@ -111,8 +161,8 @@ impl StdBasis {
   }
 }
 
-static MINIMAL: Lazy<analysis::StdBasis> = Lazy::new(analysis::StdBasis::minimal);
-static FULL: Lazy<analysis::StdBasis> = Lazy::new(analysis::StdBasis::full);
+pub(crate) static MINIMAL: Lazy<analysis::StdBasis> = Lazy::new(analysis::StdBasis::minimal);
+pub(crate) static FULL: Lazy<analysis::StdBasis> = Lazy::new(analysis::StdBasis::full);
 
 /// The real, canonical root file system path, aka `/`. Performs IO on first access. But this
 /// shouldn't fail because the root should be readable. (Otherwise, where are these tests being
@@ -123,6 +173,95 @@ pub(crate) static ROOT: Lazy<paths::CanonicalPathBuf> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Returns an in-memory file system with `files`, which are the names and contents of the files in
+/// the [`ROOT`] dir.
+pub(crate) fn memory_fs(files: &[(&str, &str)]) -> paths::MemoryFileSystem {
+  paths::MemoryFileSystem::new(
+    files
+      .iter()
+      .map(|&(name, contents)| (ROOT.as_path().join(name), contents.to_owned()))
+      .collect(),
+  )
+}
+
+/// The input and analysis of a whole project, after the analysis has checked the input.
+pub(crate) type Project = (
+  analysis::input::Root,
+  analysis::input::Input,
+  analysis::Analysis,
+);
+
+/// Analyzes `files`, which should make a project with one root group, with the minimal std basis.
+///
+/// Panics if getting the input fails.
+pub(crate) fn analyze_project(files: &[(&str, &str)]) -> Project {
+  analyze_project_with(files, MINIMAL.clone()).expect("in memory fs was not set up correctly")
+}
+
+/// Like [`analyze_project`], but uses `std_basis`, and returns the error if getting the input
+/// fails.
+pub(crate) fn analyze_project_with(
+  files: &[(&str, &str)],
+  std_basis: analysis::StdBasis,
+) -> Result<Project, analysis::input::GetInputError> {
+  let fs = memory_fs(files);
+  let mut root = analysis::input::get_root_dir(ROOT.to_owned());
+  let input = analysis::input::get(&fs, &mut root)?;
+  let mut an = analysis::Analysis::new(std_basis, config::ErrorLines::One);
+  an.get_many(&input);
+  Ok((root, input, an))
+}
+
+/// Returns the position of the start of the first occurrence of `needle` in the source file `name`
+/// of `input`.
+pub(crate) fn find_pos(
+  root: &analysis::input::Root,
+  input: &analysis::input::Input,
+  name: &str,
+  needle: &str,
+) -> paths::WithPath<analysis::Position> {
+  let file = input
+    .iter_sources()
+    .find(|s| root.as_paths().get_rel_path(s.path) == std::path::Path::new(name))
+    .expect("no such source file");
+  let idx = file.val.find(needle).expect("no such needle");
+  let before = &file.val[..idx];
+  let line = before.matches('\n').count();
+  let character = idx - before.rfind('\n').map_or(0, |nl| nl + 1);
+  file.path.wrap(analysis::Position {
+    line: line.try_into().unwrap(),
+    character: character.try_into().unwrap(),
+  })
+}
+
+/// Returns the message of the first error for `files`, if any, including an error getting the
+/// input. The files are ordered by path, and the errors in a file are in the order they were
+/// emitted.
+pub(crate) fn first_error(files: &[(&str, &str)]) -> Option<String> {
+  first_error_in(files, &MINIMAL)
+}
+
+/// Like [`first_error`], but includes the full std basis.
+pub(crate) fn first_error_with_std_basis(files: &[(&str, &str)]) -> Option<String> {
+  first_error_in(files, &FULL)
+}
+
+fn first_error_in(files: &[(&str, &str)], std_basis: &analysis::StdBasis) -> Option<String> {
+  let fs = memory_fs(files);
+  let mut root = analysis::input::get_root_dir(ROOT.to_owned());
+  match analysis::input::get(&fs, &mut root) {
+    Ok(input) => {
+      let mut an = analysis::Analysis::new(std_basis.clone(), config::ErrorLines::One);
+      an.get_many(&input)
+        .into_iter()
+        .flat_map(|(id, errors)| errors.into_iter().map(move |e| (id, e)))
+        .min_by_key(|(id, _)| root.as_paths().get_path(*id).as_path().to_owned())
+        .map(|(_, e)| e.message)
+    }
+    Err(e) => Some(e.to_string()),
+  }
+}
+
 struct Check {
   root: analysis::input::Root,
   files: paths::PathMap<ExpectFile>,
@@ -131,35 +270,45 @@ struct Check {
 
 impl Check {
   fn new(ss: &[&str], std_basis: analysis::StdBasis) -> Self {
-    let mut m = FxHashMap::<std::path::PathBuf, String>::default();
     let mut mlb_file = String::new();
-    for (idx, &s) in ss.iter().enumerate() {
+    let mut names = Vec::<String>::with_capacity(ss.len());
+    for idx in 0..ss.len() {
       let file_name = format!("f{idx}.sml");
       writeln!(mlb_file, "{file_name}").unwrap();
-      let file_name = std::path::PathBuf::from(file_name);
-      m.insert(ROOT.as_path().join(file_name), s.to_owned());
+      names.push(file_name);
     }
-    m.insert(ROOT.as_path().join("sources.mlb"), mlb_file);
-    let fs = paths::MemoryFileSystem::new(m);
+    let mut files: Vec<_> = names
+      .iter()
+      .map(String::as_str)
+      .zip(ss.iter().copied())
+      .collect();
+    files.push(("sources.mlb", mlb_file.as_str()));
+    Self::from_files(&files, std_basis, Errors::First)
+  }
+
+  fn from_files(files: &[(&str, &str)], std_basis: analysis::StdBasis, errors: Errors) -> Self {
+    let fs = memory_fs(files);
     let mut root = analysis::input::get_root_dir(ROOT.to_owned());
-    let input =
-      analysis::input::get(&fs, &mut root).expect("in memory fs was not set up correctly");
+    let expect_files: paths::PathMap<_> = files
+      .iter()
+      .map(|&(name, s)| {
+        let path = fs
+          .canonicalize(ROOT.as_path().join(name).as_path())
+          .expect("in memory fs was not set up correctly");
+        let path = root.as_mut_paths().get_id(&path).expect("path not in root");
+        let file = ExpectFile {
+          want: s
+            .lines()
+            .enumerate()
+            .filter_map(|(line_n, line_s)| get_expect_comment(line_n, line_s))
+            .collect(),
+        };
+        (path, file)
+      })
+      .collect();
     let mut ret = Self {
       root,
-      files: input
-        .iter_sources()
-        .map(|s| {
-          let file = ExpectFile {
-            want: s
-              .val
-              .lines()
-              .enumerate()
-              .filter_map(|(line_n, line_s)| get_expect_comment(line_n, line_s))
-              .collect(),
-          };
-          (s.path, file)
-        })
-        .collect(),
+      files: expect_files,
       reasons: Vec::new(),
     };
     let want_err_len: usize = ret
@@ -172,16 +321,59 @@ impl Check {
           .count()
       })
       .sum();
-    if !matches!(want_err_len, 0 | 1) {
+    if matches!(errors, Errors::First) && !matches!(want_err_len, 0 | 1) {
       ret.reasons.push(Reason::WantWrongNumError(want_err_len));
     }
     let mut an = analysis::Analysis::new(std_basis, config::ErrorLines::One);
-    let err = an
-      .get_many(&input)
-      .into_iter()
-      .flat_map(|(id, errors)| errors.into_iter().map(move |e| (id, e)))
-      .next();
-    for (&path, file) in ret.files.iter() {
+    let mut got_input = true;
+    let got: Vec<(paths::PathId, Option<analysis::Range>, String)> =
+      match analysis::input::get(&fs, &mut ret.root) {
+        Ok(input) => {
+          let mut got: Vec<_> = an
+            .get_many(&input)
+            .into_iter()
+            .flat_map(|(id, errors)| errors.into_iter().map(move |e| (id, e)))
+            .collect();
+          // the files come out of a hash map, so put them in a stable order. when checking only the
+          // first error, keep the errors in a file in the order they were emitted, since that's
+          // the one a user sees first.
+          got.sort_by_cached_key(|(id, e)| {
+            let path = ret.root.as_paths().get_path(*id).as_path().to_owned();
+            let range = match errors {
+              Errors::First => None,
+              Errors::All => {
+                let (start, end) = (e.range.start, e.range.end);
+                Some((start.line, start.character, end.line, end.character))
+              }
+            };
+            (path, range)
+          });
+          got
+            .into_iter()
+            .map(|(id, e)| (id, Some(e.range), e.message))
+            .collect()
+        }
+        Err(e) => {
+          got_input = false;
+          let got = e.to_string();
+          let path = fs
+            .canonicalize(e.path())
+            .ok()
+            .and_then(|path| ret.root.as_mut_paths().get_id(&path).ok())
+            .filter(|path| ret.files.contains_key(path));
+          match path {
+            Some(path) => vec![(path, e.range(), got)],
+            None => {
+              ret
+                .reasons
+                .push(Reason::NotInFiles(e.path().to_owned(), got));
+              return ret;
+            }
+          }
+        }
+      };
+    // hovers need the analysis, so there are none to check without the input.
+    for (&path, file) in ret.files.iter().filter(|_| got_input) {
       for (&region, expect) in file.want.iter() {
         if matches!(expect.kind, ExpectKind::Hover) {
           let pos = match region {
@@ -210,37 +402,90 @@ impl Check {
         }
       }
     }
-    let had_error = match err {
-      Some((id, e)) => {
-        match ret.get_err_reason(id, e.range, e.message) {
-          Ok(()) => {}
-          Err(r) => ret.reasons.push(r),
+    let got_len = match errors {
+      Errors::First => got.len().min(1),
+      Errors::All => got.len(),
+    };
+    let mut matched = FxHashSet::<paths::WithPath<Region>>::default();
+    for (path, range, msg) in got.into_iter().take(got_len) {
+      let r = match ret.get_err_reason(path, range, msg) {
+        Ok(region) => {
+          matched.insert(path.wrap(region));
+          continue;
         }
-        true
+        Err(r) => r,
+      };
+      // a mismatched error was still emitted where one was wanted, so don't also report it missing.
+      if let Reason::Mismatched(region, _, _) = &r {
+        matched.insert(*region);
+      }
+      ret.reasons.push(r);
+    }
+    match errors {
+      Errors::First => {
+        if got_len == 0 && want_err_len != 0 {
+          ret.reasons.push(Reason::NoErrorsEmitted(want_err_len));
+        }
+      }
+      Errors::All => {
+        let mut missing: Vec<_> = ret
+          .files
+          .iter()
+          .flat_map(|(&path, file)| {
+            file.want.iter().filter_map(move |(&region, expect)| {
+              matches!(expect.kind, ExpectKind::Error)
+                .then(|| (path.wrap(region), expect.msg.clone()))
+            })
+          })
+          .filter(|(region, _)| !matched.contains(region))
+          .collect();
+        missing.sort_by_cached_key(|(r, _)| {
+          let path = ret.root.as_paths().get_path(r.path).as_path().to_owned();
+          let (line, col) = match r.val {
+            Region::Exact {
+              line, col_start, ..
+            } => (line, col_start),
+            Region::Line(line) => (line, 0),
+          };
+          (path, line, col)
+        });
+        ret.reasons.extend(
+          missing
+            .into_iter()
+            .map(|(region, want)| Reason::WantedButNotGot(region, want)),
+        );
       }
-      None => false,
-    };
-    if !had_error && want_err_len != 0 {
-      ret.reasons.push(Reason::NoErrorsEmitted(want_err_len));
     }
     ret
   }
 
+  /// An error with no range is expected by an inexact expectation anywhere in its file.
   fn get_err_reason(
     &mut self,
     path: paths::PathId,
-    range: analysis::Range,
+    range: Option<analysis::Range>,
     got: String,
-  ) -> Result<(), Reason> {
+  ) -> Result<Region, Reason> {
     let file = &self.files[&path];
-    let region = if range.start.line == range.end.line {
-      Region::Exact {
-        line: range.start.line,
-        col_start: range.start.character,
-        col_end: range.end.character,
+    let region = match range {
+      Some(range) => {
+        if range.start.line == range.end.line {
+          Region::Exact {
+            line: range.start.line,
+            col_start: range.start.character,
+            col_end: range.end.character,
+          }
+        } else {
+          Region::Line(range.start.line)
+        }
       }
-    } else {
-      Region::Line(range.start.line)
+      None => file
+        .want
+        .iter()
+        .find_map(|(&region, expect)| {
+          matches!((region, &expect.kind), (Region::Line(_), ExpectKind::Error)).then_some(region)
+        })
+        .unwrap_or(Region::Line(0)),
     };
     let path_region = path.wrap(region);
     let want = match file.want.get(&region) {
@@ -251,7 +496,7 @@ impl Check {
       },
     };
     if want == got {
-      Ok(())
+      Ok(region)
     } else {
       Err(Reason::Mismatched(path_region, want, got))
     }
@@ -274,6 +519,12 @@ impl fmt::Display for Check {
           writeln!(f, "{path}:{range}: got an error, but wanted none")?;
           writeln!(f, "    - got:  {got}")?;
         }
+        Reason::WantedButNotGot(r, want) => {
+          let path = self.root.as_paths().get_path(r.path).as_path().display();
+          let range = r.val;
+          writeln!(f, "{path}:{range}: wanted an error, but got none")?;
+          writeln!(f, "    - want: {want}")?;
+        }
         Reason::Mismatched(r, want, got) => {
           let path = self.root.as_paths().get_path(r.path).as_path().display();
           let range = r.val;
@@ -281,6 +532,14 @@ impl fmt::Display for Check {
           writeln!(f, "    - want: {want}")?;
           writeln!(f, "    - got:  {got}")?;
         }
+        Reason::NotInFiles(path, got) => {
+          let path = path.display();
+          writeln!(
+            f,
+            "{path}: got an error for a path that is not one of the files"
+          )?;
+          writeln!(f, "    - got:  {got}")?;
+        }
         Reason::NoHover(r) => {
           let path = self.root.as_paths().get_path(r.path).as_path().display();
           let range = r.val;
@@ -314,6 +573,15 @@ enum Outcome {
   Fail,
 }
 
+/// Which of the errors that were emitted to check against the expectations.
+#[derive(Clone, Copy)]
+enum Errors {
+  /// Only the first, since later errors in a single file are often caused by the first.
+  First,
+  /// Every error in every file, in order of path and then range.
+  All,
+}
+
 struct ExpectFile {
   want: FxHashMap<Region, Expect>,
 }
@@ -347,7 +615,9 @@ enum Reason {
   WantWrongNumError(usize),
   NoErrorsEmitted(usize),
   GotButNotWanted(paths::WithPath<Region>, String),
+  WantedButNotGot(paths::WithPath<Region>, String),
   Mismatched(paths::WithPath<Region>, String, String),
+  NotInFiles(std::path::PathBuf, String),
   NoHover(paths::WithPath<Region>),
   InexactHover(paths::WithPath<u32>),
 }
@@ -434,6 +704,6 @@ fn get_expect_comment(line_n: usize, line_s: &str) -> Option<(Region, Expect)> {
   Some((region, expect))
 }
 
-fn env_var_eq_1(s: &str) -> bool {
+pub(crate) fn env_var_eq_1(s: &str) -> bool {
   std::env::var_os(s).map_or(false, |x| x == "1")
 }
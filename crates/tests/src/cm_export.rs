@@ -0,0 +1,125 @@
+//! Tests for SML/NJ CM export lists, which may use set expressions like `source(-)`.
+
+use crate::check::first_error;
+
+/// `files` are the names and contents of the files in the root dir, which should have one root
+/// group. `want` is the message of the first error, if there should be one.
+fn check(files: &[(&str, &str)], want: Option<&str>) {
+  assert_eq!(want, first_error(files).as_deref());
+}
+
+const LIB_SML: &str = r#"
+structure Shown = struct end
+local
+  structure Local = struct end
+in
+  structure Hidden = struct end
+  signature SIG = sig end
+end
+"#;
+
+#[test]
+fn source_minus() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm main.sml"),
+      ("lib.cm", "Library source(-) is lib.sml"),
+      ("lib.sml", LIB_SML),
+      (
+        "main.sml",
+        "structure A = Shown\nstructure B = Hidden\nsignature C = SIG",
+      ),
+    ],
+    None,
+  );
+}
+
+#[test]
+fn source_minus_local() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm main.sml"),
+      ("lib.cm", "Library source(-) is lib.sml"),
+      ("lib.sml", LIB_SML),
+      ("main.sml", "structure A = Local"),
+    ],
+    Some("undefined structure: Local"),
+  );
+}
+
+#[test]
+fn difference() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm main.sml"),
+      ("lib.cm", "Library source(-) - structure Hidden is lib.sml"),
+      ("lib.sml", LIB_SML),
+      ("main.sml", "structure A = Shown\nstructure B = Hidden"),
+    ],
+    Some("undefined structure: Hidden"),
+  );
+}
+
+#[test]
+fn intersection() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm main.sml"),
+      (
+        "lib.cm",
+        "Library source(lib.sml) * (structure Shown signature SIG) is lib.sml",
+      ),
+      ("lib.sml", LIB_SML),
+      ("main.sml", "signature C = SIG\nstructure B = Hidden"),
+    ],
+    Some("undefined structure: Hidden"),
+  );
+}
+
+#[test]
+fn group_minus() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm main.sml"),
+      (
+        "lib.cm",
+        "Library group(-) + structure Other is sub.cm other.sml",
+      ),
+      ("sub.cm", "Library structure Shown is lib.sml"),
+      ("lib.sml", LIB_SML),
+      ("other.sml", "structure Other = struct end"),
+      ("main.sml", "structure A = Shown\nstructure B = Other"),
+    ],
+    None,
+  );
+}
+
+#[test]
+fn source_not_member() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm"),
+      ("lib.cm", "Library source(other.sml) is lib.sml"),
+      ("lib.sml", LIB_SML),
+      ("other.sml", ""),
+    ],
+    Some("/other.sml: not a member of the group"),
+  );
+}
+
+#[test]
+fn source_minus_fixity() {
+  check(
+    &[
+      ("sources.cm", "Group is lib.cm main.sml"),
+      ("lib.cm", "Library source(-) is ops.sml lib.sml"),
+      ("ops.sml", "infix 5 ++\nfun a ++ b = a + b : int"),
+      (
+        "lib.sml",
+        "structure Lib = struct val x = 1 ++ 2 end\nsignature LIB = sig val x : int end",
+      ),
+      ("main.sml", "structure A : LIB = Lib"),
+    ],
+    None,
+  );
+}
@@ -0,0 +1,203 @@
+//! Tests for [`analysis::Analysis::get_dead_code`].
+
+use crate::check::{analyze_project, memory_fs};
+
+/// `files` are the names and contents of the files in the root dir, and `want` are the sorted
+/// names of files with the messages for each of their dead code warnings, in order.
+fn check(files: &[(&str, &str)], want: &[(&str, &str)]) {
+  let (mut root, input, an) = analyze_project(files);
+  let unlisted = analysis::input::get_unlisted_sources(&memory_fs(files), &mut root, &input);
+  let mut got: Vec<(String, analysis::Position, String)> = an
+    .get_dead_code(&input, &unlisted)
+    .into_iter()
+    .flat_map(|(path, errors)| {
+      let path = root.as_paths().get_rel_path(path).display().to_string();
+      errors
+        .into_iter()
+        .map(move |e| (path.clone(), e.range.start, e.message))
+    })
+    .collect();
+  got.sort_unstable_by(|(a_path, a_pos, _), (b_path, b_pos, _)| {
+    a_path
+      .cmp(b_path)
+      .then_with(|| (a_pos.line, a_pos.character).cmp(&(b_pos.line, b_pos.character)))
+  });
+  let got: Vec<_> = got
+    .iter()
+    .map(|(p, _, m)| (p.as_str(), m.as_str()))
+    .collect();
+  assert_eq!(want, got.as_slice());
+}
+
+#[test]
+fn used_across_files() {
+  check(
+    &[
+      ("sources.mlb", "a.sml b.sml"),
+      ("a.sml", "fun id x = x\nfun const x _ = x"),
+      ("b.sml", "val () = id ()"),
+    ],
+    &[("a.sml", "unused value: const")],
+  );
+}
+
+#[test]
+fn use_inside_own_dec() {
+  check(
+    &[
+      ("sources.mlb", "a.sml"),
+      ("a.sml", "fun loop x = loop x\nval used = ()\nval () = used"),
+    ],
+    &[("a.sml", "unused value: loop")],
+  );
+}
+
+#[test]
+fn constructor_exception() {
+  check(
+    &[
+      ("sources.mlb", "a.sml"),
+      (
+        "a.sml",
+        r#"
+datatype t = A | B
+exception E
+exception F
+val () = (case A of A => raise E | _ => ()) handle E => ()
+"#,
+      ),
+    ],
+    &[
+      ("a.sml", "unused constructor: B"),
+      ("a.sml", "unused exception: F"),
+    ],
+  );
+}
+
+#[test]
+fn structures() {
+  check(
+    &[
+      ("sources.mlb", "a.sml b.sml"),
+      (
+        "a.sml",
+        r#"
+signature SIG = sig val x : unit end
+structure S : SIG = struct val x = () end
+structure T = struct val y = () end
+functor F (X : SIG) = struct end
+"#,
+      ),
+      ("b.sml", "val () = S.x"),
+    ],
+    &[
+      ("a.sml", "unused structure: T"),
+      ("a.sml", "unused functor: F"),
+    ],
+  );
+}
+
+#[test]
+fn export() {
+  check(
+    &[
+      ("sources.cm", "Library structure S is a.sml"),
+      (
+        "a.sml",
+        "structure S = struct end\nstructure T = struct end",
+      ),
+    ],
+    &[("a.sml", "unused structure: T")],
+  );
+}
+
+#[test]
+fn files() {
+  check(
+    &[
+      ("sources.mlb", "a.sml b.sml"),
+      ("a.sml", "val x = 1\nval y = x"),
+      ("b.sml", "val () = ()"),
+      ("c.sml", "val z = 3"),
+    ],
+    &[
+      (
+        "a.sml",
+        "unused file: it defines nothing used elsewhere and has no effects",
+      ),
+      ("c.sml", "unused file: it is not listed in any group"),
+    ],
+  );
+}
+
+#[test]
+fn effect_in_structure() {
+  check(
+    &[
+      ("sources.mlb", "a.sml main.sml"),
+      ("a.sml", "fun main () = ()"),
+      ("main.sml", "structure Main = struct val () = main () end"),
+    ],
+    &[],
+  );
+}
+
+#[test]
+fn no_effect_in_structure() {
+  check(
+    &[
+      ("sources.mlb", "a.sml b.sml"),
+      ("a.sml", "structure A = struct val x = 1 end"),
+      ("b.sml", "val () = ()"),
+    ],
+    &[(
+      "a.sml",
+      "unused file: it defines nothing used elsewhere and has no effects",
+    )],
+  );
+}
+
+#[test]
+fn unlisted_only_in_group_dirs() {
+  check(
+    &[
+      ("sources.mlb", "src/a.sml"),
+      ("src/a.sml", "val () = ()"),
+      ("src/b.sml", "val () = ()"),
+      ("target/c.sml", "val () = ()"),
+    ],
+    &[("src/b.sml", "unused file: it is not listed in any group")],
+  );
+}
+
+#[test]
+fn structure_shadowed() {
+  check(
+    &[
+      ("sources.mlb", "sig.sml a.sml b.sml c.sml"),
+      ("sig.sml", "signature SIG = sig val x : unit end"),
+      ("a.sml", "structure S : SIG = struct val x = () end"),
+      ("b.sml", "structure S : SIG = struct val x = () end"),
+      ("c.sml", "val () = S.x"),
+    ],
+    &[(
+      "a.sml",
+      "unused file: it defines nothing used elsewhere and has no effects",
+    )],
+  );
+}
+
+#[test]
+fn export_shadowed() {
+  check(
+    &[
+      ("sources.cm", "Library structure S is a.sml b.sml"),
+      ("a.sml", "structure S = struct end"),
+      ("b.sml", "structure S = struct end"),
+    ],
+    &[(
+      "a.sml",
+      "unused file: it defines nothing used elsewhere and has no effects",
+    )],
+  );
+}
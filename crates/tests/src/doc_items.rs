@@ -0,0 +1,62 @@
+//! Tests for [`analysis::Analysis::get_doc_items`].
+
+use crate::check::analyze_project;
+
+fn check(s: &str, want: &[(&str, Option<&str>)]) {
+  let (_, _, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", s)]);
+  let got: Vec<_> = an
+    .get_doc_items()
+    .into_iter()
+    .map(|item| (format!("{} {}", item.kind.keyword(), item.name), item.doc))
+    .collect();
+  let got: Vec<_> = got
+    .iter()
+    .map(|(name, doc)| (name.as_str(), doc.as_deref()))
+    .collect();
+  assert_eq!(want, got.as_slice());
+}
+
+#[test]
+fn sorted() {
+  check(
+    r#"
+functor F () = struct end
+structure B = struct end
+signature S = sig end
+structure A = struct end
+"#,
+    &[
+      ("structure A", None),
+      ("structure B", None),
+      ("signature S", None),
+      ("functor F", None),
+    ],
+  );
+}
+
+#[test]
+fn doc() {
+  check(
+    r#"
+(*!
+ * Some numbers.
+ *)
+structure Nums = struct val one = 1 end
+"#,
+    &[("structure Nums", Some("Some numbers."))],
+  );
+}
+
+#[test]
+fn local() {
+  check(
+    r#"
+local
+  structure Hidden = struct end
+in
+  structure Shown = Hidden
+end
+"#,
+    &[("structure Shown", None)],
+  );
+}
@@ -1,10 +1,22 @@
 use std::path::{Path, PathBuf};
 
-use crate::check::{check_with_std_basis, fail_with_std_basis};
+use crate::check::{
+  check_project_with_std_basis, check_with_std_basis, fail_project_with_std_basis,
+  fail_with_std_basis,
+};
 use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
 
 const SML: &str = "sml";
 
+/// a line in an example that should be checked with the `smlnj` target.
+const SMLNJ_TARGET: &str = r#"(* with target = "smlnj" *)"#;
+
+const SMLNJ_CONFIG: &str = r#"
+version = 1
+[workspace]
+target = "smlnj"
+"#;
+
 fn check_all<F>(path: &Path, mut f: F)
 where
   F: FnMut(&str),
@@ -52,10 +64,24 @@ fn docs_dir() -> Option<PathBuf> {
 fn errors() {
   let path = docs_dir().unwrap().join("errors.md");
   check_all(path.as_path(), |s| {
+    let files = [
+      (config::FILE_NAME, SMLNJ_CONFIG),
+      ("sources.mlb", "a.sml"),
+      ("a.sml", s),
+    ];
+    let smlnj = s.lines().any(|line| line == SMLNJ_TARGET);
     if s.starts_with("(* ok *)") {
-      check_with_std_basis(s);
+      if smlnj {
+        check_project_with_std_basis(&files);
+      } else {
+        check_with_std_basis(s);
+      }
     } else if s.starts_with("(* error *)") {
-      fail_with_std_basis(s);
+      if smlnj {
+        fail_project_with_std_basis(&files);
+      } else {
+        fail_with_std_basis(s);
+      }
     }
   });
 }
@@ -0,0 +1,189 @@
+//! Tests that checking a project with the results of checking its files cached on disk gives the
+//! same result as checking it without.
+
+use crate::check::{memory_fs, MINIMAL, ROOT};
+use std::path::PathBuf;
+
+/// The errors, as strings like `a.sml 1:2 5011 message`, and the hover at every position of every
+/// source file, in order of path.
+type Output = (Vec<String>, Vec<Option<String>>);
+
+fn get(files: &[(&str, &str)], file_cache: Option<analysis::FileCache>) -> Output {
+  let fs = memory_fs(files);
+  let mut root = analysis::input::get_root_dir(ROOT.to_owned());
+  let input = analysis::input::get(&fs, &mut root).expect("in memory fs was not set up correctly");
+  let mut an = analysis::Analysis::new(MINIMAL.clone(), config::ErrorLines::One);
+  an.set_file_cache(file_cache);
+  let mut errors: Vec<_> = an
+    .get_many(&input)
+    .into_iter()
+    .map(|(path, errors)| (root.as_paths().get_rel_path(path).to_owned(), errors))
+    .collect();
+  errors.sort_by(|(a, _), (b, _)| a.cmp(b));
+  let errors: Vec<_> = errors
+    .into_iter()
+    .flat_map(|(path, errors)| {
+      errors.into_iter().map(move |e| {
+        format!(
+          "{} {}:{} {} {}",
+          path.display(),
+          e.range.start.line + 1,
+          e.range.start.character + 1,
+          e.code,
+          e.message
+        )
+      })
+    })
+    .collect();
+  let mut sources: Vec<_> = input
+    .iter_sources()
+    .map(|s| (root.as_paths().get_rel_path(s.path).to_owned(), s))
+    .collect();
+  sources.sort_by(|(a, _), (b, _)| a.cmp(b));
+  let mut hovers = Vec::new();
+  for (_, source) in sources {
+    for (line, s) in source.val.lines().enumerate() {
+      for character in 0..s.len() {
+        let pos = analysis::Position {
+          line: line.try_into().unwrap(),
+          character: character.try_into().unwrap(),
+        };
+        hovers.push(an.get_md(source.path.wrap(pos)).map(|(md, _)| md));
+      }
+    }
+  }
+  (errors, hovers)
+}
+
+/// A directory for a cache that is removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+  fn new(name: &str) -> Self {
+    let dir = std::env::temp_dir().join(format!("millet-test-{name}-{}", std::process::id()));
+    // in case it's left over from an earlier run that happened to have the same pid.
+    let _ = std::fs::remove_dir_all(&dir);
+    Self(dir)
+  }
+
+  fn cache(&self) -> analysis::FileCache {
+    analysis::FileCache::new(self.0.clone()).expect("couldn't make cache")
+  }
+
+  fn num_entries(&self) -> usize {
+    std::fs::read_dir(&self.0).map_or(0, Iterator::count)
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+/// Checks `files` without a cache, then with a cache twice, first filling it in and then reading
+/// from it, then again after replacing the file named by `changed`, and asserts every cached result
+/// is the same as the uncached one.
+fn check(name: &str, files: &[(&str, &str)], changed: (&str, &str)) {
+  let dir = TempDir::new(name);
+  let fresh = get(files, None);
+  assert!(!fresh.0.is_empty(), "no errors");
+  assert!(fresh.1.iter().any(Option::is_some), "no hovers");
+  let cold = get(files, Some(dir.cache()));
+  assert_eq!(fresh, cold);
+  let num_entries = dir.num_entries();
+  assert_ne!(num_entries, 0, "nothing was cached");
+  let warm = get(files, Some(dir.cache()));
+  assert_eq!(fresh, warm);
+  assert_eq!(num_entries, dir.num_entries());
+  let files: Vec<_> = files
+    .iter()
+    .map(|&(path, contents)| {
+      if path == changed.0 {
+        changed
+      } else {
+        (path, contents)
+      }
+    })
+    .collect();
+  let fresh = get(&files, None);
+  let cached = get(&files, Some(dir.cache()));
+  assert_eq!(fresh, cached);
+}
+
+const ONE_ROOT: &str = r#"
+version = 1
+[workspace]
+root = "a.mlb"
+"#;
+
+#[test]
+fn sequential_files() {
+  check(
+    "sequential_files",
+    &[
+      ("millet.toml", ONE_ROOT),
+      ("a.mlb", "a1.sml a2.sml a3.sml"),
+      (
+        "a1.sml",
+        r#"
+datatype 'a t = A | B of 'a * int
+exception E of int t
+structure S = struct val x = B (1, 2) fun f (_ : 'a t) = 3 end
+infix 5 +++
+fun a +++ b = a + b : int
+"#,
+      ),
+      (
+        "a2.sml",
+        r#"
+val y = S.f S.x +++ 4
+val _ : string = y
+val _ = raise E A
+fun g x = case x of A => 1
+"#,
+      ),
+      (
+        "a3.sml",
+        r#"
+datatype t = C
+val _ : int t = C
+val z = (S.x, y, C)
+"#,
+      ),
+    ],
+    (
+      "a2.sml",
+      r#"
+datatype u = U
+val y = S.f (B (U, 5))
+val _ : unit = y
+"#,
+    ),
+  );
+}
+
+#[test]
+fn groups_and_bases() {
+  check(
+    "groups_and_bases",
+    &[
+      ("millet.toml", ONE_ROOT),
+      (
+        "a.mlb",
+        r#"
+b.mlb
+basis B1 = bas b1.sml end
+basis B2 = bas b2.sml structure Nope = Nope end
+local open B1 B2 in a.sml end
+"#,
+      ),
+      ("b.mlb", "b.sml\nsignature SIG = NOPE"),
+      ("b.sml", "datatype d = D\nval _ : int = D"),
+      ("b1.sml", "datatype t = T of d\nval x = T D"),
+      ("b2.sml", "datatype t = T\nval y = T"),
+      ("a.sml", "val _ = x = y\nval _ : bool = (x, y)"),
+    ],
+    ("b.sml", "datatype d = D | E\nval _ : unit = E"),
+  );
+}
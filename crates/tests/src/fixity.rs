@@ -1,4 +1,4 @@
-use crate::check::check;
+use crate::check::{check, check_multi};
 
 #[test]
 fn smoke() {
@@ -86,3 +86,28 @@ val _ = 1 << 2 >> 3
 "#,
   );
 }
+
+#[test]
+fn infix_across_files() {
+  check_multi(&[
+    r#"
+infix 5 ++
+fun a ++ b = a + b
+"#,
+    r#"
+val _ : int = 1 ++ 2 ++ 3
+"#,
+  ]);
+}
+
+#[test]
+fn nonfix_across_files() {
+  check_multi(&[
+    r#"
+nonfix +
+"#,
+    r#"
+val _ : int = + (1, 2)
+"#,
+  ]);
+}
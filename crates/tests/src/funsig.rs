@@ -0,0 +1,262 @@
+//! Tests for SML/NJ functor signatures and functors that take functors, which are only allowed
+//! with the `smlnj` target.
+
+use crate::check::first_error;
+
+const SMLNJ: &str = r#"
+version = 1
+[workspace]
+target = "smlnj"
+"#;
+
+/// `config` is the contents of the config file, if any, `cm` is the contents of the root group,
+/// and `sml` is the contents of `a.sml`, which the group should contain. `want` is the message of
+/// the first error, if there should be one.
+fn check(config: Option<&str>, cm: &str, sml: &str, want: Option<&str>) {
+  let mut files = vec![("sources.cm", cm), ("a.sml", sml)];
+  if let Some(config) = config {
+    files.push((config::FILE_NAME, config));
+  }
+  let got = first_error(&files);
+  assert_eq!(want, got.as_deref());
+}
+
+const FUN_SIG: &str = r#"
+signature S = sig
+  type t
+  val x : t
+end
+
+funsig FS (X : S) = sig
+  type u
+  val y : X.t -> u
+end
+
+functor App (functor F : FS) = struct
+  structure A = F (struct type t = unit val x = () end)
+  val z : A.u = A.y ()
+end
+"#;
+
+#[test]
+fn functor_arg() {
+  let sml = format!(
+    "{FUN_SIG}{}",
+    r#"
+functor Id (X : S) = struct
+  type u = X.t
+  fun y x = x
+end
+
+structure B = App (Id)
+"#
+  );
+  check(Some(SMLNJ), "Group is a.sml", &sml, None);
+}
+
+#[test]
+fn functor_arg_not_match() {
+  let sml = format!(
+    "{FUN_SIG}{}",
+    r#"
+functor Bad (X : S) = struct
+  type u = X.t
+end
+
+structure B = App (Bad)
+"#
+  );
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("missing value required by signature: y"),
+  );
+}
+
+#[test]
+fn functor_arg_not_functor() {
+  let sml = format!("{FUN_SIG}{}", "structure B = App (struct end)\n");
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("expected the name of a functor as the argument"),
+  );
+}
+
+#[test]
+fn undefined_fun_sig() {
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    "functor App (functor F : FS) = struct end",
+    Some("undefined functor signature: FS"),
+  );
+}
+
+#[test]
+fn no_target() {
+  check(
+    None,
+    "Group is a.sml",
+    FUN_SIG,
+    Some("`funsig` requires the `smlnj` target"),
+  );
+}
+
+#[test]
+fn export() {
+  check(Some(SMLNJ), "Library funsig FS is a.sml", FUN_SIG, None);
+}
+
+#[test]
+fn export_no_target() {
+  check(
+    None,
+    "Library funsig FS is a.sml",
+    FUN_SIG,
+    Some("unsupported export kind"),
+  );
+}
+
+#[test]
+fn functor_param_mismatch() {
+  let sml = format!(
+    "{FUN_SIG}{}",
+    r#"
+funsig GS (functor G : FS) = S
+
+functor UseG (functor H : GS) = struct end
+
+functor Id (X : S) = X
+
+structure B = UseG (Id)
+"#
+  );
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("mismatched functor parameters: one takes a structure, the other a functor"),
+  );
+}
+
+const CURRIED: &str = r#"
+signature S = sig
+  type t
+  val x : t
+end
+
+functor Pair (X : S) (Y : sig type u val y : X.t -> u end) = struct
+  type t = X.t * Y.u
+  val x = (X.x, Y.y X.x)
+end
+
+structure A = struct type t = int val x = 1 end
+structure B = struct type u = bool fun y (x : int) = x > 0 end
+"#;
+
+#[test]
+fn curried() {
+  let sml = format!(
+    "{CURRIED}{}",
+    "structure P = Pair (A) (B)\nval _ : int * bool = P.x\n"
+  );
+  check(Some(SMLNJ), "Group is a.sml", &sml, None);
+}
+
+#[test]
+fn curried_realize() {
+  let sml = format!(
+    "{CURRIED}{}",
+    "structure P = Pair (A) (B)\nval _ : bool * int = P.x\n"
+  );
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("expected bool * int, found int * bool (in particular, expected bool, found int)"),
+  );
+}
+
+#[test]
+fn curried_too_few_args() {
+  let sml = format!("{CURRIED}{}", "structure P = Pair (A)\n");
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("expected 2 functor arguments, found 1"),
+  );
+}
+
+#[test]
+fn curried_too_many_args() {
+  let sml = format!("{CURRIED}{}", "structure P = Pair (A) (B) (A)\n");
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("expected 2 functor arguments, found 3"),
+  );
+}
+
+#[test]
+fn curried_no_target() {
+  check(
+    None,
+    "Group is a.sml",
+    CURRIED,
+    Some("a curried functor requires the `smlnj` target"),
+  );
+}
+
+const CURRIED_FUN_SIG: &str = r#"
+signature S = sig
+  type t
+  val x : t
+end
+
+funsig PS (X : S) (Y : S) = sig
+  val x : X.t * Y.t
+end
+
+functor UsePair (functor P : PS) = struct
+  structure Q = P (struct type t = int val x = 1 end) (struct type t = bool val x = true end)
+  val _ : int * bool = Q.x
+end
+"#;
+
+#[test]
+fn curried_functor_arg() {
+  let sml = format!(
+    "{CURRIED_FUN_SIG}{}",
+    r#"
+functor Pair (X : S) (Y : S) = struct
+  val x = (X.x, Y.x)
+end
+
+structure U = UsePair (Pair)
+"#
+  );
+  check(Some(SMLNJ), "Group is a.sml", &sml, None);
+}
+
+#[test]
+fn curried_functor_arg_wrong_num_params() {
+  let sml = format!(
+    "{CURRIED_FUN_SIG}{}",
+    r#"
+functor Id (X : S) = X
+
+structure U = UsePair (Id)
+"#
+  );
+  check(
+    Some(SMLNJ),
+    "Group is a.sml",
+    &sml,
+    Some("expected a functor with 2 parameters, found one with 1"),
+  );
+}
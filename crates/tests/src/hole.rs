@@ -0,0 +1,131 @@
+//! Tests for suggestions to fill expression holes.
+
+use crate::check::{analyze_project, find_pos};
+
+/// Returns the hover and the ways to fill the only `_` in `sml`.
+fn get(sml: &str) -> (String, Vec<String>) {
+  let (line, col) = {
+    let mut iter = sml
+      .lines()
+      .enumerate()
+      .flat_map(|(line, s)| s.match_indices('_').map(move |(col, _)| (line, col)));
+    let ret = iter.next().expect("no hole");
+    assert!(iter.next().is_none(), "more than one hole");
+    ret
+  };
+  let (_, input, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", sml)]);
+  let path = input
+    .iter_sources()
+    .next()
+    .expect("should have a source file")
+    .path;
+  let pos = path.wrap(analysis::Position {
+    line: line.try_into().unwrap(),
+    character: col.try_into().unwrap(),
+  });
+  let (md, _) = an.get_md(pos).expect("no hover for hole");
+  let fills = an.fill_hole(pos).map_or(Vec::new(), |(_, fills)| fills);
+  (md, fills)
+}
+
+#[test]
+fn direct() {
+  let (md, fills) = get(
+    r#"
+val x = 3
+val y = "hi"
+val z : int = _
+"#,
+  );
+  assert!(md.contains("`x : int`"), "{md}");
+  assert!(!md.contains("`y : "), "{md}");
+  assert_eq!(fills.first().map(String::as_str), Some("x"));
+}
+
+#[test]
+fn apply() {
+  let (_, fills) = get(
+    r#"
+val x = 3
+fun f (a : string) : int = 3
+val z : int = _
+"#,
+  );
+  let x = fills.iter().position(|s| s == "x").expect("no x");
+  let f = fills.iter().position(|s| s == "(f _)").expect("no f");
+  assert!(x < f, "{fills:?}");
+}
+
+#[test]
+fn apply_tuple() {
+  let (_, fills) = get(
+    r#"
+fun g (a : int, b : bool) : string = ""
+fun h (a : bool) (b : int, c : int, d : int) : string = ""
+val w : string = _
+"#,
+  );
+  assert!(fills.iter().any(|s| s == "(g (_, _))"), "{fills:?}");
+  assert!(fills.iter().any(|s| s == "(h _ (_, _, _))"), "{fills:?}");
+}
+
+#[test]
+fn constructor() {
+  let (md, fills) = get(
+    r#"
+datatype d = A | B of int
+val z : d = _
+"#,
+  );
+  assert!(md.contains("`B : int -> d`"), "{md}");
+  assert_eq!(fills[..2], ["A", "(B _)"]);
+}
+
+#[test]
+fn constructor_qualified() {
+  let (md, fills) = get(
+    r#"
+structure S = struct datatype d = A | B of int end
+val z : S.d = _
+"#,
+  );
+  assert!(md.contains("`S.B : int -> d`"), "{md}");
+  assert_eq!(fills[..2], ["S.A", "(S.B _)"]);
+}
+
+#[test]
+fn unconstrained() {
+  let (md, fills) = get(
+    r#"
+val x = 3
+val z = _
+"#,
+  );
+  assert!(!md.contains("`x : int`"), "{md}");
+  assert!(fills.is_empty(), "{fills:?}");
+}
+
+#[test]
+fn infix() {
+  let (_, fills) = get(
+    r#"
+infix <+>
+fun (a : int) <+> (b : int) : int = a
+val z : int = _
+"#,
+  );
+  assert!(fills.iter().any(|s| s == "(op <+> (_, _))"), "{fills:?}");
+}
+
+#[test]
+fn recovered() {
+  let (root, input, an) = analyze_project(&[
+    ("sources.mlb", "a.sml"),
+    ("a.sml", "val x = 3\nval z : int = andalso\nval y = 4"),
+  ]);
+  let pos = find_pos(&root, &input, "a.sml", "andalso");
+  if let Some((md, _)) = an.get_md(pos) {
+    assert!(!md.contains("`x : int`"), "{md}");
+  }
+  assert!(an.fill_hole(pos).is_none());
+}
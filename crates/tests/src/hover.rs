@@ -20,3 +20,46 @@ val _ = (   3, "hi", Uh false)
 "#,
   );
 }
+
+#[test]
+fn structure() {
+  check(
+    r#"
+structure S = struct val x = 1 datatype d = A | B of int end
+(**       ^ hover: datatype d = A | B of int *)
+"#,
+  );
+}
+
+#[test]
+fn open() {
+  check(
+    r#"
+structure S = struct type t = int end
+open S
+(**  ^ hover: type t = int *)
+"#,
+  );
+}
+
+#[test]
+fn signature() {
+  check(
+    r#"
+signature SIG = sig type t val f : t -> t end
+structure S :> SIG = struct type t = int fun f x = x end
+(**            ^ hover: val f : t -> t *)
+"#,
+  );
+}
+
+#[test]
+fn functor() {
+  check(
+    r#"
+functor F (X : sig type t end) = struct type u = X.t end
+structure S = F (struct type t = int end)
+(**           ^ hover: functor F (X : sig *)
+"#,
+  );
+}
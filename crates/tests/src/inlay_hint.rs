@@ -0,0 +1,99 @@
+//! Tests for inlay hints for types.
+
+use crate::check::analyze_project;
+
+const SML: &str = r#"val x = 3
+fun f (y : int) = y
+val z = f x
+"#;
+
+/// Returns the hints in `range`, which is zero-based `((line, col), (line, col))`, as strings like
+/// `1:2 : int`, one-based.
+fn get_hints(range: ((u32, u32), (u32, u32)), want: config::InlayHints) -> Vec<String> {
+  let (_, input, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", SML)]);
+  let path = input.iter_sources().next().expect("no source file").path;
+  let ((start_line, start_col), (end_line, end_col)) = range;
+  let range = analysis::Range {
+    start: analysis::Position {
+      line: start_line,
+      character: start_col,
+    },
+    end: analysis::Position {
+      line: end_line,
+      character: end_col,
+    },
+  };
+  an.get_inlay_hints(path.wrap(range), want)
+    .expect("no hints")
+    .into_iter()
+    .map(|hint| {
+      let pos = hint.position;
+      format!("{}:{} {}", pos.line + 1, pos.character + 1, hint.label)
+    })
+    .collect()
+}
+
+const WHOLE: ((u32, u32), (u32, u32)) = ((0, 0), (3, 0));
+
+#[test]
+fn bindings() {
+  let want = config::InlayHints::default();
+  assert_eq!(
+    get_hints(WHOLE, want),
+    ["1:6 : int", "2:6 : int -> int", "2:9 : int", "3:6 : int"]
+  );
+}
+
+#[test]
+fn instances() {
+  let want = config::InlayHints {
+    bindings: false,
+    instances: true,
+  };
+  assert_eq!(
+    get_hints(WHOLE, want),
+    ["2:20 : int", "3:10 : int -> int", "3:12 : int"]
+  );
+}
+
+#[test]
+fn all() {
+  let want = config::InlayHints {
+    bindings: true,
+    instances: true,
+  };
+  assert_eq!(
+    get_hints(WHOLE, want),
+    [
+      "1:6 : int",
+      "2:6 : int -> int",
+      "2:9 : int",
+      "2:20 : int",
+      "3:6 : int",
+      "3:10 : int -> int",
+      "3:12 : int",
+    ]
+  );
+}
+
+#[test]
+fn none() {
+  let want = config::InlayHints {
+    bindings: false,
+    instances: false,
+  };
+  assert!(get_hints(WHOLE, want).is_empty());
+}
+
+#[test]
+fn range() {
+  let want = config::InlayHints {
+    bindings: true,
+    instances: true,
+  };
+  // the end of the range is inclusive, so the hint right after `f` is included.
+  assert_eq!(
+    get_hints(((2, 0), (2, 9)), want),
+    ["3:6 : int", "3:10 : int -> int"]
+  );
+}
@@ -1,6 +1,7 @@
 //! Low-level tests for [`analysis::input::get`].
 
-use crate::check::ROOT;
+use crate::check::{analyze_project, ROOT};
+use std::collections::{BTreeMap, BTreeSet};
 
 #[test]
 fn arbitrary_root_group() {
@@ -30,6 +31,47 @@ root = "foo.cm"
   check_empty_cm(&["foo.cm", "bar.cm"], Some(config)).unwrap();
 }
 
+#[test]
+fn config_root_list() {
+  let config = r#"
+version = 1
+[workspace]
+root = ["foo.cm", "bar.mlb"]
+"#;
+  let groups = [
+    ("foo.cm", "Group is"),
+    ("bar.mlb", ""),
+    ("quz.cm", "Group is"),
+  ];
+  check_input(groups, Some(config)).unwrap();
+}
+
+#[test]
+fn config_root_glob() {
+  let config = r#"
+version = 1
+[workspace]
+root = "*.cm"
+"#;
+  let groups = [
+    ("foo.cm", "Group is"),
+    ("bar.cm", "Group is"),
+    ("quz.mlb", ""),
+  ];
+  check_input(groups, Some(config)).unwrap();
+}
+
+#[test]
+fn config_root_glob_no_match() {
+  let config = r#"
+version = 1
+[workspace]
+root = "*.cm"
+"#;
+  let e = check_input([("foo.mlb", "")], Some(config)).unwrap_err();
+  assert!(e.to_string().contains("not a group"));
+}
+
 #[test]
 fn no_root_group_in_config_ok() {
   check_empty_cm(&["sources.cm"], Some("version = 1")).unwrap();
@@ -57,6 +99,27 @@ fn config_parse_err() {
   assert!(e.to_string().contains("couldn't parse config"));
 }
 
+#[test]
+fn config_target() {
+  let config = r#"
+version = 1
+[workspace]
+target = "mlton"
+"#;
+  check_empty_cm(&["foo.cm"], Some(config)).unwrap();
+}
+
+#[test]
+fn config_target_invalid() {
+  let config = r#"
+version = 1
+[workspace]
+target = "nope"
+"#;
+  let e = check_empty_cm(&["foo.cm"], Some(config)).unwrap_err();
+  assert!(e.to_string().contains("couldn't parse config"));
+}
+
 #[test]
 fn cycle_1() {
   let e = check_input([("foo.cm", "Group is foo.cm")], None).unwrap_err();
@@ -141,3 +204,26 @@ where
   let mut root = analysis::input::get_root_dir(ROOT.to_owned());
   analysis::input::get(&fs, &mut root)
 }
+
+#[test]
+fn iter_groups() {
+  let (root, input, _) = analyze_project(&[
+    ("sources.mlb", "a.mlb b.sml"),
+    ("a.mlb", "a.sml"),
+    ("a.sml", "val a = 1"),
+    ("b.sml", "val b = a"),
+  ]);
+  let name = |path| root.as_paths().get_rel_path(path).display().to_string();
+  let got: BTreeMap<String, BTreeSet<String>> = input
+    .iter_groups()
+    .map(|group| (name(group.path), group.val.into_iter().map(name).collect()))
+    .collect();
+  let want = BTreeMap::from([
+    ("a.mlb".to_owned(), BTreeSet::from(["a.sml".to_owned()])),
+    (
+      "sources.mlb".to_owned(),
+      BTreeSet::from(["a.mlb".to_owned(), "b.sml".to_owned()]),
+    ),
+  ]);
+  assert_eq!(want, got);
+}
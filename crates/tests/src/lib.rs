@@ -7,30 +7,48 @@
 #![deny(rust_2018_idioms)]
 
 mod ascribe;
+mod call_hierarchy;
 mod check;
+mod cm_export;
 mod datatype_copy;
+mod dead_code;
 mod deviations;
+mod doc_items;
 mod docs;
 mod dupe;
 mod exn;
+mod file_cache;
 mod fixity;
 mod functor;
+mod funsig;
 mod generalize;
+mod hole;
 mod hover;
 mod incomplete;
 mod infix_without_op;
+mod inlay_hint;
 mod input;
 mod literal;
 mod local;
 mod matching;
 mod misc;
+mod mod_graph;
 mod num_record;
 mod overload;
+mod parallel;
 mod pat;
+mod project;
+mod recovery;
 mod rest_pat;
+mod robust;
 mod rust;
+mod semantic_tokens;
 mod shadow;
+mod signature_help;
 mod smoke;
+mod snapshot;
 mod std_basis;
+mod std_basis_cache;
+mod syntax_ranges;
 mod ty_name_escape;
 mod ty_var;
@@ -1,4 +1,4 @@
-use crate::check::check;
+use crate::check::{check, check_with_std_basis, first_error_with_std_basis};
 
 #[test]
 fn char_big() {
@@ -29,6 +29,82 @@ val _ = 123123123123123123123123132131
   );
 }
 
+#[test]
+fn word_too_large() {
+  check(
+    r#"
+val _ = 0w123456789123456789123456789
+(**     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ literal out of range for 63-bit type word *)
+"#,
+  );
+}
+
+#[test]
+fn sized_word() {
+  check_with_std_basis(
+    r#"
+val _ = 0w255 : Word8.word
+val _ = 0w300 : Word8.word
+(**     ^^^^^ literal out of range for 8-bit type Word8.word *)
+"#,
+  );
+}
+
+#[test]
+fn sized_int() {
+  check_with_std_basis(
+    r#"
+val _ = ~128 : Int8.int
+val _ = 127 : Int8.int
+val _ = ~129 : Int8.int
+(**     ^^^^ literal out of range for 8-bit type Int8.int *)
+"#,
+  );
+}
+
+#[test]
+fn sized_pat() {
+  check_with_std_basis(
+    r#"
+val _ = fn (0w256 : Word8.word) => ()
+(**         ^^^^^ literal out of range for 8-bit type Word8.word *)
+         | _ => ()
+"#,
+  );
+}
+
+/// Returns the message of the first error for `sml` as `a.sml` under the `target`, if any.
+fn first_error_with_target(target: &str, sml: &str) -> Option<String> {
+  let config = format!("version = 1\n[workspace]\ntarget = \"{target}\"\n");
+  first_error_with_std_basis(&[
+    ("sources.mlb", "a.sml"),
+    ("a.sml", sml),
+    (config::FILE_NAME, config.as_str()),
+  ])
+}
+
+#[test]
+fn int_target() {
+  let sml = "val _ = 2147483648 : int";
+  assert_eq!(
+    Some("literal out of range for 32-bit type int"),
+    first_error_with_target("mlton", sml).as_deref()
+  );
+  assert_eq!(None, first_error_with_target("smlnj", sml));
+}
+
+#[test]
+fn word32_target() {
+  let sml = "val _ = 0wx100000000 : Word32.word";
+  let want = Some("literal out of range for 32-bit type Word32.word");
+  assert_eq!(want, first_error_with_target("smlnj", sml).as_deref());
+  assert_eq!(want, first_error_with_target("mlton", sml).as_deref());
+  assert_eq!(
+    None,
+    first_error_with_target("smlnj", "val _ = 0wx100000000 : word")
+  );
+}
+
 #[test]
 fn real() {
   check(
@@ -40,7 +40,7 @@ fn big_datatype_missing() {
 datatype hmm = A | B of int | C of hmm | D of string
 val _ =
   case A of
-(**    + non-exhaustive case: missing C (C (C (C _))), C (C (C (B _))), and 6 others *)
+(**    + non-exhaustive case: missing B _, C (C A), and 6 more *)
     A => 0
   | B 1 => 1
   | B 3 => 2
@@ -443,7 +443,7 @@ fn rest_non_exhaustive() {
     r#"
 fun f x =
   case x of
-(**    + non-exhaustive case: missing {a = _, b = _} *)
+(**    + non-exhaustive case: missing _ *)
     {a = 3, ...} => 1
   | {b = 5, ...} => 2
   | {a = 1, b = 2} => 3
@@ -476,3 +476,28 @@ fun f x =
 "#,
   );
 }
+
+#[test]
+fn declaration_order() {
+  check(
+    r#"
+datatype d = Z | Y | X | W
+fun f x =
+  case x of
+(**    + non-exhaustive case: missing Z, X, and 1 more *)
+    Y => 1
+"#,
+  );
+}
+
+#[test]
+fn record_elide_any() {
+  check(
+    r#"
+fun f x =
+  case x of
+(**    + non-exhaustive case: missing {a = false, ...} *)
+    {a = true, b = _ : int} => 1
+"#,
+  );
+}
@@ -1,4 +1,4 @@
-use crate::check::{check, fail};
+use crate::check::{analyze_project, check, fail};
 
 #[test]
 fn apply() {
@@ -652,3 +652,78 @@ val _ = inc
 "#,
   );
 }
+
+#[test]
+fn qualified_ty_names() {
+  check(
+    r#"
+structure A = struct structure S = struct datatype t = T end end
+structure B = struct structure S = struct datatype t = T end end
+val _ = A.S.T : B.S.t
+(**     ^^^^^^^^^^^^^ expected B.S.t, found A.S.t *)
+"#,
+  );
+}
+
+#[test]
+fn mismatched_part() {
+  check(
+    r#"
+val f : (int * string) list -> bool = fn _ => true
+val _ = f [(1, 2)]
+(**       ^^^^^^^^ expected (int * string) list, found (int * int) list (in particular, expected string, found int) *)
+"#,
+  );
+}
+
+/// Returns the message and related locations, as `(line, col, message)`, zero-based, of the only
+/// error in the file `a.sml` containing `s`.
+fn only_error_related(s: &str) -> (String, Vec<(u32, u32, String)>) {
+  let (_, input, mut an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", s)]);
+  let mut errors: Vec<_> = an.get_many(&input).into_values().flatten().collect();
+  assert_eq!(errors.len(), 1, "want exactly one error");
+  let e = errors.pop().unwrap();
+  let related = e
+    .related
+    .into_iter()
+    .map(|(r, msg)| (r.start.line, r.start.character, msg))
+    .collect();
+  (e.message, related)
+}
+
+#[test]
+fn mismatched_want_src_fn() {
+  let (msg, related) = only_error_related("val f = fn (x : int) => x\nval _ = f \"hi\"");
+  assert_eq!("expected int, found string", msg);
+  assert_eq!(
+    vec![(1, 8, "the expected type comes from here".to_owned())],
+    related
+  );
+}
+
+#[test]
+fn mismatched_want_src_pat() {
+  let (msg, related) = only_error_related("val _ : int = \"hi\"");
+  assert_eq!("expected int, found string", msg);
+  assert_eq!(
+    vec![(0, 4, "the expected type comes from here".to_owned())],
+    related
+  );
+}
+
+#[test]
+fn mismatched_want_src_ty() {
+  let (msg, related) = only_error_related("val _ = \"hi\" : int");
+  assert_eq!("expected int, found string", msg);
+  assert_eq!(
+    vec![(0, 15, "the expected type comes from here".to_owned())],
+    related
+  );
+}
+
+#[test]
+fn mismatched_no_want_src() {
+  let (msg, related) = only_error_related("val _ = if 1 then () else ()");
+  assert_eq!("expected bool, found int", msg);
+  assert!(related.is_empty());
+}
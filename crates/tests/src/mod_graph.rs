@@ -0,0 +1,82 @@
+//! Tests for [`analysis::Analysis::get_mod_graph`].
+
+use crate::check::analyze_project;
+
+fn check(s: &str, want: &[(&str, &[&str])]) {
+  let (_, _, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", s)]);
+  let name = |x: &analysis::ModName| format!("{} {}", x.kind.keyword(), x.name);
+  let got: Vec<(String, Vec<String>)> = an
+    .get_mod_graph()
+    .iter()
+    .map(|(from, to)| (name(from), to.iter().map(name).collect()))
+    .collect();
+  let want: Vec<(String, Vec<String>)> = want
+    .iter()
+    .map(|&(from, to)| (from.to_owned(), to.iter().map(|&x| x.to_owned()).collect()))
+    .collect();
+  assert_eq!(want, got);
+}
+
+#[test]
+fn smoke() {
+  check(
+    r#"
+structure A = struct val x = 1 end
+structure B = struct val y = A.x end
+structure C = struct end
+"#,
+    &[
+      ("structure A", &[]),
+      ("structure B", &["structure A"]),
+      ("structure C", &[]),
+    ],
+  );
+}
+
+#[test]
+fn functor_sig() {
+  check(
+    r#"
+signature S = sig val x : int end
+functor F (X : S) = struct val y = X.x end
+structure A = F (struct val x = 1 end)
+"#,
+    &[
+      ("structure A", &["functor F"]),
+      ("signature S", &[]),
+      ("functor F", &["signature S"]),
+    ],
+  );
+}
+
+#[test]
+fn same_name_different_files() {
+  let (root, _, an) = analyze_project(&[
+    ("sources.mlb", "a.sml b.sml"),
+    ("a.sml", "structure A = struct val x = 1 end"),
+    (
+      "b.sml",
+      "structure A = struct val y = 2 end\nstructure B = struct val z = A.y end",
+    ),
+  ]);
+  let name = |x: &analysis::ModName| {
+    let path = root.as_paths().get_rel_path(x.path).display().to_string();
+    format!("{} {} {path}", x.kind.keyword(), x.name)
+  };
+  let mut got: Vec<(String, Vec<String>)> = an
+    .get_mod_graph()
+    .iter()
+    .map(|(from, to)| (name(from), to.iter().map(name).collect()))
+    .collect();
+  // path ids are not ordered by name.
+  got.sort();
+  let want = vec![
+    ("structure A a.sml".to_owned(), vec![]),
+    ("structure A b.sml".to_owned(), vec![]),
+    (
+      "structure B b.sml".to_owned(),
+      vec!["structure A b.sml".to_owned()],
+    ),
+  ];
+  assert_eq!(want, got);
+}
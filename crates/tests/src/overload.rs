@@ -1,4 +1,4 @@
-use crate::check::check;
+use crate::check::{check, check_with_std_basis};
 
 #[test]
 fn curry_add() {
@@ -27,7 +27,7 @@ fn op_add_err() {
     r#"
 val add = op+
 val _ = add (false, true)
-(**         ^^^^^^^^^^^^^ expected int * int, found bool * bool *)
+(**         ^^^^^^^^^^^^^ expected int * int, found bool * bool (in particular, expected int, found bool) *)
 "#,
   );
 }
@@ -38,7 +38,7 @@ fn immediately_solve_to_default() {
     r#"
 val add = op+
 val _ = add (1.1, 2.2)
-(**         ^^^^^^^^^^ expected int * int, found real * real *)
+(**         ^^^^^^^^^^ expected int * int, found real * real (in particular, expected int, found real) *)
 val _ = add (1, 2)
 "#,
   );
@@ -51,7 +51,7 @@ fn explicit_annotate() {
 val add = op+ : real * real -> real
 val _ = add (1.1, 2.2)
 val _ = add (1, 2)
-(**         ^^^^^^ expected real * real, found int * int *)
+(**         ^^^^^^ expected real * real, found int * int (in particular, expected real, found int) *)
 "#,
   );
 }
@@ -64,7 +64,7 @@ val add = op+
 (* make this a top-dec level seq *)
 signature S = sig end
 val _ = add (1.1, 2.2)
-(**         ^^^^^^^^^^ expected int * int, found real * real *)
+(**         ^^^^^^^^^^ expected int * int, found real * real (in particular, expected int, found real) *)
 "#,
   );
 }
@@ -132,7 +132,7 @@ fn must_solve_to_single_overloaded_type() {
   check(
     r#"
 val _ = 1.1 + 1
-(**     ^^^^^^^ expected <num> * <num>, found real * int *)
+(**     ^^^^^^^ expected real * real, found real * int (in particular, expected real, found int) *)
 "#,
   );
 }
@@ -142,7 +142,7 @@ fn overload_err() {
   check(
     r#"
 val  _ = false + true
-(**      ^^^^^^^^^^^^ expected <num> * <num>, found bool * bool *)
+(**      ^^^^^^^^^^^^ expected <num> * <num>, found bool * bool (in particular, expected <num>, found bool) *)
 "#,
   );
 }
@@ -186,3 +186,79 @@ fun hm a b = (abs (a - b), a + b)
 "#,
   );
 }
+
+#[test]
+fn sized_int() {
+  check_with_std_basis(
+    r#"
+val a : Int64.int = 3
+val _ : Int64.int = a + 4 * a
+val _ : Int8.int = 7 div 2
+val _ : Int16.int = abs (~ 5)
+val _ : Int32.int = 1 - 2
+val _ : bool = (1 : Int8.int) < 2
+val _ : IntInf.int = IntInf.pow (2, 100) - 1
+val _ : LargeInt.int = 123456789 mod 2
+"#,
+  );
+}
+
+#[test]
+fn sized_word() {
+  check_with_std_basis(
+    r#"
+val _ : Word8.word = 0w255
+val _ : Word32.word = 0wx1 + 0w2
+val _ : Word64.word = 0w3 * 0w4
+val _ : LargeWord.word = 0w1 mod 0w2
+val _ : bool = (0w1 : Word8.word) >= 0w2
+"#,
+  );
+}
+
+#[test]
+fn sized_real() {
+  check_with_std_basis(
+    r#"
+val _ : Real32.real = 1.5 / 2.0
+val _ : Real64.real = 1.5 * 2.0
+val _ : LargeReal.real = ~ 1.5 + abs 2.5
+val _ : bool = (1.5 : Real32.real) < 2.0
+"#,
+  );
+}
+
+#[test]
+fn wide_text() {
+  check_with_std_basis(
+    r#"
+val _ : WideChar.char = #"a"
+val _ : WideString.string = "hello"
+val _ : bool = (#"a" : WideChar.char) < #"b"
+val _ : bool = ("a" : WideString.string) <= "b"
+"#,
+  );
+}
+
+#[test]
+fn literal_default() {
+  check(
+    r#"
+val a = 1.5
+val b = #"a"
+val c = "hi"
+val _ = (a, b, c) : unit
+(**     ^^^^^^^^^^^^^^^^ expected unit, found real * char * string *)
+"#,
+  );
+}
+
+#[test]
+fn literal_not_in_class() {
+  check_with_std_basis(
+    r#"
+val _ = 3 : Word8.word
+(**     ^^^^^^^^^^^^^^ expected Word8.word, found int *)
+"#,
+  );
+}
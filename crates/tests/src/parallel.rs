@@ -0,0 +1,160 @@
+//! Tests that checking independent groups and basis bindings in parallel gives the same result as
+//! checking them one after another.
+
+use crate::check::{memory_fs, MINIMAL, ROOT};
+
+/// Returns the errors for `files`, sorted by path, as strings like `a.sml 1:2 5011 message`.
+fn get_errors(files: &[(&str, &str)], parallel: bool) -> Vec<String> {
+  let fs = memory_fs(files);
+  let mut root = analysis::input::get_root_dir(ROOT.to_owned());
+  let input = analysis::input::get(&fs, &mut root).expect("in memory fs was not set up correctly");
+  let mut an = analysis::Analysis::new(MINIMAL.clone(), config::ErrorLines::One);
+  an.set_parallel(parallel);
+  let mut errors: Vec<_> = an
+    .get_many(&input)
+    .into_iter()
+    .map(|(path, errors)| (root.as_paths().get_rel_path(path).to_owned(), errors))
+    .collect();
+  errors.sort_by(|(a, _), (b, _)| a.cmp(b));
+  errors
+    .into_iter()
+    .flat_map(|(path, errors)| {
+      errors.into_iter().map(move |e| {
+        format!(
+          "{} {}:{} {} {}",
+          path.display(),
+          e.range.start.line + 1,
+          e.range.start.character + 1,
+          e.code,
+          e.message
+        )
+      })
+    })
+    .collect()
+}
+
+fn check(files: &[(&str, &str)]) {
+  let sequential = get_errors(files, false);
+  assert!(!sequential.is_empty(), "no errors");
+  let parallel = get_errors(files, true);
+  assert_eq!(sequential, parallel);
+}
+
+const CONFIG: &str = r#"
+version = 1
+[workspace]
+root = ["a.mlb", "b.mlb", "c.mlb"]
+"#;
+
+#[test]
+fn independent_roots() {
+  check(&[
+    ("millet.toml", CONFIG),
+    ("a.mlb", "a1.sml a2.sml"),
+    ("a1.sml", "infix 5 +++\nfun a +++ b = a + b : int"),
+    ("a2.sml", "val x = 1 +++ 2\nval _ : string = x"),
+    ("b.mlb", "b.sml\nstructure S = Nope"),
+    ("b.sml", "datatype t = T\nval _ : int = T\nval _ = 1 +++ 2"),
+    ("c.mlb", "c.sml\nsignature SIG = NOPE"),
+    (
+      "c.sml",
+      "datatype u = U\nval _ : int = U\nexception E of u\nval _ = E 3",
+    ),
+  ]);
+}
+
+#[test]
+fn shared_group() {
+  check(&[
+    ("millet.toml", CONFIG),
+    ("a.mlb", "a1.sml a2.sml"),
+    (
+      "a1.sml",
+      "infix 5 +++\nfun a +++ b = a + b : int\ndatatype t = T",
+    ),
+    ("a2.sml", "val x = 1 +++ 2\nval _ : string = x"),
+    ("b.mlb", "b.sml"),
+    ("b.sml", "datatype t = T\nval _ : int = T"),
+    ("c.mlb", "a.mlb c.sml\nstructure S = Nope"),
+    (
+      "c.sml",
+      "val _ : int = T\nval _ = 1 +++ 2\nval _ : bool = x",
+    ),
+  ]);
+}
+
+const ONE_ROOT: &str = r#"
+version = 1
+[workspace]
+root = "a.mlb"
+"#;
+
+#[test]
+fn basis_bindings() {
+  check(&[
+    ("millet.toml", ONE_ROOT),
+    (
+      "a.mlb",
+      r#"
+basis B1 = bas b1.sml end
+basis B2 = bas b2.sml structure Nope = Nope end
+basis B3 = let open B1 in b3.sml end
+local open B1 B2 B3 in a.sml end
+"#,
+    ),
+    (
+      "b1.sml",
+      "datatype t = T\nstructure S = struct val x = T end",
+    ),
+    ("b2.sml", "datatype t = T\nval _ : int = T"),
+    ("b3.sml", "val y = S.x\nval _ : bool = y"),
+    ("a.sml", "val _ : t = S.x\nval _ : unit = y"),
+  ]);
+}
+
+#[test]
+fn nested_shared_groups() {
+  check(&[
+    ("millet.toml", ONE_ROOT),
+    ("a.mlb", "b.mlb c.mlb a.sml"),
+    ("b.mlb", "d.mlb b.sml"),
+    ("c.mlb", "d.mlb c.sml"),
+    ("d.mlb", "d.sml\nsignature SIG = NOPE"),
+    ("d.sml", "datatype d = D\nval _ : int = D"),
+    ("b.sml", "datatype t = T of d\nval _ : unit = T D"),
+    ("c.sml", "datatype t = T of d\nval _ : bool = T D"),
+    ("a.sml", "val _ : int = T D"),
+  ]);
+}
+
+#[test]
+fn infix_file() {
+  check(&[
+    ("millet.toml", ONE_ROOT),
+    ("a.mlb", "a1.sml a2.sml a3.sml a4.sml"),
+    ("a1.sml", "infix 5 +++\nfun a +++ b = a + b : int\nnonfix +"),
+    ("a2.sml", "val x = 1 +++ 2\nval _ : string = x"),
+    ("a3.sml", "val y = + (1, 2)\nval _ : unit = y"),
+    ("a4.sml", "val _ : bool = 3"),
+  ]);
+}
+
+#[test]
+fn escaped_names() {
+  check(&[
+    ("millet.toml", ONE_ROOT),
+    (
+      "a.mlb",
+      r#"
+basis B1 = bas b1.sml end
+basis B2 = bas b2.sml end
+local open B1 in a1.sml end
+local open B2 in a2.sml end
+"#,
+    ),
+    ("b1.sml", "datatype t = T\nval x = T"),
+    ("b2.sml", "datatype t = T\nval y = T"),
+    ("a1.sml", "datatype t = U\nval z = x"),
+    ("a2.sml", "datatype t = U\nval _ = y = z"),
+  ]);
+}
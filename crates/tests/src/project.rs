@@ -0,0 +1,198 @@
+//! Tests for whole projects with group files, and maybe config files, in the root dir.
+
+use crate::check::check_project;
+
+#[test]
+fn cm_ok() {
+  check_project(&[
+    ("sources.cm", "Group is a.sml b.sml c.sml"),
+    ("a.sml", "structure A = struct val x = 1 end"),
+    ("b.sml", "structure B = struct val y = A.x + 1 end"),
+    ("c.sml", "val _ : int = B.y"),
+  ]);
+}
+
+#[test]
+fn cm_err_in_last_file() {
+  check_project(&[
+    ("sources.cm", "Group is a.sml b.sml c.sml"),
+    ("a.sml", "structure A = struct val x = 1 end"),
+    ("b.sml", "structure B = struct val y = A.x + 1 end"),
+    (
+      "c.sml",
+      r#"
+val _ = C.z
+(**     ^^^ undefined structure: C *)
+"#,
+    ),
+  ]);
+}
+
+#[test]
+fn cm_order() {
+  check_project(&[
+    ("sources.cm", "Group is b.sml a.sml"),
+    ("a.sml", "structure A = struct val x = 1 end"),
+    (
+      "b.sml",
+      r#"
+structure B = struct val y = A.x end
+(**                          ^^^ undefined structure: A *)
+"#,
+    ),
+  ]);
+}
+
+#[test]
+fn cm_syntax_err() {
+  check_project(&[
+    (
+      "sources.cm",
+      r#"
+Group structure A a.sml
+(**             ^ couldn't process SML/NJ CM file: expected `is` *)
+"#,
+    ),
+    ("a.sml", "structure A = struct end"),
+  ]);
+}
+
+#[test]
+fn mlb_err() {
+  check_project(&[
+    (
+      "sources.mlb",
+      r#"
+a.sml
+structure B = Nope
+(**           ^^^^ undefined structure: Nope *)
+"#,
+    ),
+    ("a.sml", "structure A = struct end"),
+  ]);
+}
+
+#[test]
+fn subdir() {
+  check_project(&[
+    ("sources.cm", "Group is lib/sources.cm main.sml"),
+    ("lib/sources.cm", "Group is a.sml"),
+    ("lib/a.sml", "structure A = struct val x = 1 end"),
+    (
+      "main.sml",
+      r#"
+val _ : string = A.x
+(**              ^^^ expected string, found int *)
+"#,
+    ),
+  ]);
+}
+
+#[test]
+fn config_root() {
+  check_project(&[
+    (
+      "millet.toml",
+      r#"
+version = 1
+[workspace]
+root = "b.cm"
+"#,
+    ),
+    ("a.cm", "Group is a.sml"),
+    ("a.sml", "val _ = nope"),
+    ("b.cm", "Group is b.sml"),
+    ("b.sml", "val _ = 1"),
+  ]);
+}
+
+#[test]
+fn config_invalid_version() {
+  check_project(&[
+    (
+      "millet.toml",
+      r#"
+version = 2
+# (** + invalid config version: expected 1, found 2 *)
+"#,
+    ),
+    ("sources.cm", "Group is a.sml"),
+    ("a.sml", "val _ = 1"),
+  ]);
+}
+
+#[test]
+fn config_roots_separate() {
+  check_project(&[
+    (
+      "millet.toml",
+      r#"
+version = 1
+[workspace]
+root = ["a.mlb", "b.mlb"]
+"#,
+    ),
+    ("a.mlb", "a.sml"),
+    ("a.sml", "structure S = struct val x = 1 end"),
+    ("b.mlb", "b.sml"),
+    (
+      "b.sml",
+      r#"
+val _ = S.x
+(**     ^^^ undefined structure: S *)
+"#,
+    ),
+  ]);
+}
+
+/// the shared file is fine in the root with `S`, but not in the other. the error should be reported
+/// no matter the order of the roots.
+fn config_roots_shared(roots: &str) {
+  let config = format!("version = 1\n[workspace]\nroot = {roots}\n");
+  check_project(&[
+    ("millet.toml", config.as_str()),
+    ("a.mlb", "a.sml shared.sml"),
+    ("a.sml", "structure S = struct val x = 1 end"),
+    ("b.mlb", "shared.sml"),
+    (
+      "shared.sml",
+      r#"
+val _ = S.x
+(**     ^^^ undefined structure: S *)
+"#,
+    ),
+  ]);
+}
+
+#[test]
+fn config_roots_shared_a_first() {
+  config_roots_shared(r#"["a.mlb", "b.mlb"]"#);
+}
+
+#[test]
+fn config_roots_shared_b_first() {
+  config_roots_shared(r#"["b.mlb", "a.mlb"]"#);
+}
+
+#[test]
+fn errors_in_many_files() {
+  check_project(&[
+    ("sources.cm", "Group is a.sml b.sml"),
+    (
+      "a.sml",
+      r#"
+val _ = A.x
+(**     ^^^ undefined structure: A *)
+val _ : string = 3
+(**              ^ expected string, found int *)
+"#,
+    ),
+    (
+      "b.sml",
+      r#"
+val _ = B.y
+(**     ^^^ undefined structure: B *)
+"#,
+    ),
+  ]);
+}
@@ -0,0 +1,168 @@
+//! Tests that after a syntax error, the parser recovers, so the rest of the file is still checked.
+//!
+//! These check all of the errors, not just the first like most other tests do.
+
+use syntax::ast::{self, AstNode as _};
+
+/// Returns the zero-based line and message of every error in `sml`.
+fn errors(sml: &str) -> Vec<(u32, String)> {
+  let an = analysis::Analysis::new(analysis::StdBasis::minimal(), config::ErrorLines::One);
+  an.get_one(sml)
+    .into_iter()
+    .map(|e| (e.range.start.line, e.message))
+    .collect()
+}
+
+fn check(sml: &str, want: &[(u32, &str)]) {
+  let got = errors(sml);
+  let want: Vec<_> = want
+    .iter()
+    .map(|&(line, msg)| (line, msg.to_owned()))
+    .collect();
+  assert_eq!(want, got);
+}
+
+#[test]
+fn struct_missing_end() {
+  check(
+    r#"
+structure A = struct
+  val x = 1
+structure B = struct
+  val y : string = A.x
+end
+"#,
+    &[(3, "expected `end`"), (4, "expected string, found int")],
+  );
+}
+
+#[test]
+fn sig_missing_end() {
+  check(
+    r#"
+signature S = sig
+  val x : int
+structure A = struct val y = 1 end
+val _ : string = A.y
+"#,
+    &[(3, "expected `end`"), (4, "expected string, found int")],
+  );
+}
+
+#[test]
+fn junk_in_struct() {
+  check(
+    r#"
+structure S = struct
+  val x = 1
+  then 3
+  val y = "hi"
+end
+val _ : int = S.y
+"#,
+    &[
+      (3, "expected a declaration"),
+      (6, "expected int, found string"),
+    ],
+  );
+}
+
+#[test]
+fn junk_in_let() {
+  check(
+    r#"
+val a = let
+  val x = 1
+  => 2
+  val y = x
+in
+  y
+end
+val _ : string = a
+"#,
+    &[
+      (3, "expected a declaration"),
+      (8, "expected string, found int"),
+    ],
+  );
+}
+
+#[test]
+fn junk_exp() {
+  check(
+    r#"
+val x = andalso true
+val y : string = 4
+"#,
+    &[
+      (1, "expected an expression"),
+      (2, "expected string, found int"),
+    ],
+  );
+}
+
+#[test]
+fn junk_exp_nested() {
+  check(
+    r#"
+val x = (andalso (1, 2), 3)
+val _ : string = #2 x
+"#,
+    &[
+      (1, "expected an expression"),
+      (2, "expected string, found int"),
+    ],
+  );
+}
+
+#[test]
+fn top_level_close() {
+  check(
+    r#"
+val x = 1
+)
+val y : string = x
+"#,
+    &[
+      (2, "expected a top-level item"),
+      (3, "expected string, found int"),
+    ],
+  );
+}
+
+#[test]
+fn top_level_junk() {
+  check(
+    r#"
+val x = 1
+of x => x
+val y : string = x
+"#,
+    &[
+      (2, "expected a top-level item"),
+      (3, "expected string, found int"),
+    ],
+  );
+}
+
+#[test]
+fn junk_in_struct_is_hole_dec() {
+  let lexed = lex::get("structure S = struct val x = 1 then 3 val y = x end");
+  let mut fix_env = parse::parser::STD_BASIS.clone();
+  let parsed = parse::get(&lexed.tokens, &mut fix_env);
+  let lowered = lower::get(&parsed.root);
+  let holes: Vec<_> = parsed
+    .root
+    .syntax()
+    .descendants()
+    .filter_map(ast::DecStrDec::cast)
+    .filter_map(|dec| match dec.dec_one()? {
+      ast::DecOne::HoleDec(hole) => Some(hole),
+      _ => None,
+    })
+    .collect();
+  assert_eq!(holes.len(), 1);
+  assert_eq!(holes[0].syntax().text().to_string().trim(), "then 3");
+  let ptr = ast::SyntaxNodePtr::new(holes[0].syntax());
+  assert!(lowered.ptrs.ast_to_hir(ptr).is_some());
+}
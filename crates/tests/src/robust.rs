@@ -0,0 +1,101 @@
+//! Tests that the early passes handle any input without panicking, and that the syntax tree keeps
+//! all of the input.
+//!
+//! These are deterministic stand-ins for the fuzz targets in the `fuzz` dir, so that the basic
+//! properties are checked on every test run.
+
+use syntax::ast::AstNode as _;
+
+/// How many ways to mutate each file.
+const MUTATIONS_PER_FILE: usize = 16;
+
+/// Inputs known to be tricky, like unclosed things and trivia outside of any item.
+const TRICKY: [&str; 14] = [
+  "",
+  " ",
+  "\n\n",
+  "(* hi *)",
+  "(*",
+  "\"",
+  "#\"",
+  "'",
+  ")",
+  ") ",
+  "val",
+  "val x = (* unclosed",
+  "fun f x = case x of",
+  "structure S = struct val _ = 1 (* end *)",
+];
+
+/// Lexes, parses, and lowers `s`, and asserts that neither the tokens nor the syntax tree lose any
+/// of `s`.
+fn roundtrip(s: &str) {
+  let lexed = lex::get(s);
+  let lexed_text: String = lexed.tokens.iter().map(|tok| tok.text).collect();
+  assert_eq!(s, lexed_text, "tokens do not make the input");
+  let mut fix_env = parse::parser::STD_BASIS.clone();
+  let parsed = parse::get(&lexed.tokens, &mut fix_env);
+  let parsed_text = parsed.root.syntax().text().to_string();
+  assert_eq!(s, parsed_text, "syntax tree does not make the input");
+  lower::get(&parsed.root);
+}
+
+/// Returns some mutations of `s`: truncations of it, and it with a char removed, at evenly spaced
+/// char boundaries.
+fn mutations(s: &str) -> Vec<String> {
+  let boundaries: Vec<_> = s.char_indices().map(|(idx, _)| idx).collect();
+  let step = (boundaries.len() / MUTATIONS_PER_FILE).max(1);
+  let mut ret = Vec::new();
+  for &idx in boundaries.iter().step_by(step) {
+    ret.push(s[..idx].to_owned());
+    let mut removed = s.to_owned();
+    removed.remove(idx);
+    ret.push(removed);
+  }
+  ret
+}
+
+fn lib_files() -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+  std::iter::empty()
+    .chain(sml_libs::std_basis::FILES)
+    .chain(sml_libs::std_basis_extra::FILES)
+    .chain(sml_libs::sml_nj::FILES)
+}
+
+#[test]
+fn tricky() {
+  let an = analysis::Analysis::new(analysis::StdBasis::minimal(), config::ErrorLines::One);
+  for s in TRICKY {
+    roundtrip(s);
+    an.get_one(s);
+  }
+}
+
+#[test]
+fn libs() {
+  for &(name, contents) in lib_files() {
+    let r = std::panic::catch_unwind(|| roundtrip(contents));
+    assert!(r.is_ok(), "{name}");
+  }
+}
+
+#[test]
+fn libs_mutated() {
+  for &(name, contents) in lib_files() {
+    for (idx, s) in mutations(contents).iter().enumerate() {
+      let r = std::panic::catch_unwind(|| roundtrip(s));
+      assert!(r.is_ok(), "{name}: mutation {idx}");
+    }
+  }
+}
+
+#[test]
+fn analysis_mutated() {
+  let an = analysis::Analysis::new(analysis::StdBasis::minimal(), config::ErrorLines::One);
+  for &(name, contents) in sml_libs::std_basis_extra::FILES {
+    for (idx, s) in mutations(contents).iter().enumerate() {
+      let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| an.get_one(s)));
+      assert!(r.is_ok(), "{name}: mutation {idx}");
+    }
+  }
+}
@@ -0,0 +1,62 @@
+//! Tests for classifying names for semantic highlighting.
+
+use crate::check::{analyze_project_with, env_var_eq_1, find_pos, FULL};
+use analysis::SemanticTokenKind as Kind;
+
+const SML: &str = r#"
+val xs = List.rev [1] @ [2]
+datatype t = A | B of int
+fun f A = 0
+  | f (B y) = y
+infix 5 ++
+fun a ++ b = a - b
+val z = 1 ++ 2
+"#;
+
+/// Returns the kind, whether it is a definition, and whether it is from the std basis, of the
+/// token at the start of `needle`.
+fn get(needle: &str) -> (Kind, bool, bool) {
+  let (root, input, an) =
+    analyze_project_with(&[("sources.mlb", "a.sml"), ("a.sml", SML)], FULL.clone())
+      .expect("in memory fs was not set up correctly");
+  let pos = find_pos(&root, &input, "a.sml", needle);
+  let tok = an
+    .get_semantic_tokens(pos.path, None)
+    .expect("no tokens")
+    .into_iter()
+    .find(|tok| {
+      tok.range.start.line == pos.val.line && tok.range.start.character == pos.val.character
+    })
+    .expect("no token at position");
+  (tok.kind, tok.definition, tok.std_basis)
+}
+
+#[test]
+fn std_basis_path() {
+  if env_var_eq_1("TEST_MINIMAL") {
+    return;
+  }
+  assert_eq!(get("List"), (Kind::Structure, false, true));
+  assert_eq!(get("rev"), (Kind::Val, false, true));
+  assert_eq!(get("xs"), (Kind::Val, true, false));
+}
+
+#[test]
+fn infix() {
+  if env_var_eq_1("TEST_MINIMAL") {
+    return;
+  }
+  assert_eq!(get("@"), (Kind::Infix, false, true));
+  assert_eq!(get("++ b"), (Kind::Infix, true, false));
+  assert_eq!(get("++ 2"), (Kind::Infix, false, false));
+}
+
+#[test]
+fn con_and_var_pat() {
+  if env_var_eq_1("TEST_MINIMAL") {
+    return;
+  }
+  assert_eq!(get("A = 0"), (Kind::Con, false, false));
+  assert_eq!(get("B y"), (Kind::Con, false, false));
+  assert_eq!(get("y)"), (Kind::Val, true, false));
+}
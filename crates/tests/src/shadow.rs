@@ -81,3 +81,60 @@ val _ = 3 : S.t
 "#,
   );
 }
+
+#[test]
+fn datatype_escaped() {
+  check(
+    r#"
+datatype t = A
+val x = A
+datatype t = B
+val _ = x : t
+(**     ^^^^^ expected t, found ?.t *)
+"#,
+  );
+}
+
+#[test]
+fn datatypes_escaped() {
+  check(
+    r#"
+datatype t = A
+fun f (_ : t) = ()
+datatype t = B
+val y = B
+datatype t = C
+val _ = f y
+(**       ^ expected ?.t/1, found ?.t/2 *)
+"#,
+  );
+}
+
+#[test]
+fn local_datatype_shadows() {
+  check(
+    r#"
+datatype t = A
+structure S = struct
+  datatype t = B
+  val _ = A : t
+(**       ^^^^^ expected t, found ?.t *)
+end
+"#,
+  );
+}
+
+#[test]
+fn local_datatype_shadows_reachable() {
+  check(
+    r#"
+structure S = struct datatype t = A end
+structure T = struct
+  open S
+  datatype t = B
+  val _ = A : t
+(**       ^^^^^ expected t, found S.t *)
+end
+"#,
+  );
+}
@@ -0,0 +1,66 @@
+//! Tests for signature help for applications of functions.
+
+use crate::check::{analyze_project, find_pos};
+
+const SML: &str = r#"
+fun curry (a : int) (b : bool) (c : string) = a
+(*!
+ * Adds the numbers.
+ *)
+fun add (a : int, b : int) = a + b
+val _ = add (10, 20)
+val _ = curry 10 true "s"
+"#;
+
+/// The label, the parameters as slices of the label, the active parameter, and the doc.
+type Help = (String, Vec<String>, Option<usize>, Option<String>);
+
+/// Returns the help at the start of `needle`.
+fn get(needle: &str) -> Option<Help> {
+  let (root, input, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", SML)]);
+  let pos = find_pos(&root, &input, "a.sml", needle);
+  let help = an.get_signature_help(pos)?;
+  let params: Vec<_> = help
+    .params
+    .iter()
+    .map(|range| help.label[range.clone()].to_owned())
+    .collect();
+  Some((help.label, params, help.active_param, help.doc))
+}
+
+#[test]
+fn tuple_first() {
+  let (label, params, active, doc) = get("0, 20").expect("no help");
+  assert_eq!(label, "val add : int * int -> int");
+  assert_eq!(params, ["int", "int"]);
+  assert_eq!(active, Some(0));
+  assert_eq!(doc.as_deref(), Some("Adds the numbers."));
+}
+
+#[test]
+fn tuple_second() {
+  let (_, _, active, _) = get("0)").expect("no help");
+  assert_eq!(active, Some(1));
+}
+
+#[test]
+fn curried() {
+  let (label, params, active, doc) = get("0 true").expect("no help");
+  assert_eq!(label, "val curry : int -> bool -> string -> int");
+  assert_eq!(params, ["int", "bool", "string"]);
+  assert_eq!(active, Some(0));
+  assert!(doc.is_none());
+}
+
+#[test]
+fn curried_later() {
+  let (_, _, active, _) = get("rue").expect("no help");
+  assert_eq!(active, Some(1));
+  let (_, _, active, _) = get("s\"").expect("no help");
+  assert_eq!(active, Some(2));
+}
+
+#[test]
+fn not_in_arg() {
+  assert!(get("urry 10").is_none());
+}
@@ -0,0 +1,170 @@
+//! Snapshot tests, which pin the full output of some of the APIs of [`analysis::Analysis`].
+//!
+//! Each snapshot is a file in the `snapshots` dir of this crate. When the output changes on
+//! purpose, run the tests with the environment variable `UPDATE_SNAPSHOTS=1` to overwrite the
+//! snapshots with the new output, and review the diff.
+
+use crate::check::{analyze_project_with, env_var_eq_1, find_pos};
+use std::fmt::Write as _;
+
+/// Panics iff `got` is not the contents of the snapshot called `name`, unless updating snapshots,
+/// in which case writes `got` as the new contents of that snapshot.
+#[track_caller]
+fn expect(name: &str, got: &str) {
+  let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+  let path = dir.join(format!("{name}.txt"));
+  if env_var_eq_1("UPDATE_SNAPSHOTS") {
+    std::fs::create_dir_all(&dir).expect("couldn't create snapshot dir");
+    std::fs::write(&path, got).expect("couldn't write snapshot");
+    return;
+  }
+  let want = match std::fs::read_to_string(&path) {
+    Ok(x) => x,
+    Err(e) => panic!(
+      "couldn't read snapshot {}: {e}. run with UPDATE_SNAPSHOTS=1 to create it",
+      path.display()
+    ),
+  };
+  if want != got {
+    panic!(
+      "snapshot {} did not match. run with UPDATE_SNAPSHOTS=1 to update it\n\n  want:\n{want}\n  got:\n{got}",
+      path.display()
+    );
+  }
+}
+
+/// A project that has been analyzed.
+struct Project {
+  root: analysis::input::Root,
+  input: analysis::input::Input,
+  an: analysis::Analysis,
+}
+
+impl Project {
+  /// `files` are the names and contents of the files in the root dir, which should have one root
+  /// group.
+  fn new(files: &[(&str, &str)], std_basis: analysis::StdBasis) -> Self {
+    let (root, input, an) =
+      analyze_project_with(files, std_basis).expect("in memory fs was not set up correctly");
+    Self { root, input, an }
+  }
+
+  /// Returns the position of the start of the first occurrence of `needle` in the source file
+  /// `name`.
+  fn pos(&self, name: &str, needle: &str) -> paths::WithPath<analysis::Position> {
+    find_pos(&self.root, &self.input, name, needle)
+  }
+
+  fn display(&self, range: paths::WithPath<analysis::Range>) -> String {
+    let path = self.root.as_paths().get_rel_path(range.path).display();
+    format!("{path}:{}", display_range(range.val))
+  }
+}
+
+/// Lines and columns start at 1, as in most editors.
+fn display_range(range: analysis::Range) -> String {
+  format!(
+    "{}:{}..{}:{}",
+    range.start.line + 1,
+    range.start.character + 1,
+    range.end.line + 1,
+    range.end.character + 1
+  )
+}
+
+fn hover(p: &Project, name: &str, needle: &str) -> String {
+  let pos = p.pos(name, needle);
+  let path = pos.path;
+  let (md, range) = p.an.get_md(pos).expect("no hover");
+  let range = p.display(path.wrap(range));
+  format!("{range}\n\n{md}\n")
+}
+
+#[test]
+fn hover_poly_doc() {
+  let p = Project::new(
+    &[
+      ("sources.mlb", "a.sml"),
+      (
+        "a.sml",
+        "(*!\n * Returns its argument.\n *)\nfun id x = x\nval _ = id 3",
+      ),
+    ],
+    analysis::StdBasis::minimal(),
+  );
+  expect("hover_poly_doc", &hover(&p, "a.sml", "id 3"));
+}
+
+#[test]
+fn hover_std_basis() {
+  if env_var_eq_1("TEST_MINIMAL") {
+    return;
+  }
+  let p = Project::new(
+    &[
+      ("sources.mlb", "a.sml"),
+      ("a.sml", "val _ = TextIO.print \"hi\""),
+    ],
+    analysis::StdBasis::full(),
+  );
+  expect("hover_std_basis", &hover(&p, "a.sml", "print"));
+}
+
+#[test]
+fn def_and_ty_defs() {
+  let p = Project::new(
+    &[
+      ("sources.mlb", "a.sml\nb.sml"),
+      ("a.sml", "datatype d = A | B of int"),
+      ("b.sml", "val x = B 3"),
+    ],
+    analysis::StdBasis::minimal(),
+  );
+  let mut got = String::new();
+  let def = p.an.get_def(p.pos("b.sml", "B 3")).expect("no def");
+  writeln!(got, "def: {}", p.display(def)).unwrap();
+  writeln!(got, "ty defs:").unwrap();
+  let ty_defs = p.an.get_ty_defs(p.pos("b.sml", "x")).expect("no ty defs");
+  for ty_def in ty_defs {
+    writeln!(got, "- {}", p.display(ty_def)).unwrap();
+  }
+  expect("def_and_ty_defs", &got);
+}
+
+/// Returns the range and text to fill the missing arms of the `case` in `a.sml`, which is `sml`.
+fn fill_missing_arms_in(sml: &str) -> String {
+  let p = Project::new(
+    &[("sources.mlb", "a.sml"), ("a.sml", sml)],
+    analysis::StdBasis::minimal(),
+  );
+  let (range, text) = p
+    .an
+    .fill_missing_arms(p.pos("a.sml", "case"))
+    .expect("no fill");
+  format!("{}\n{text}\n", display_range(range))
+}
+
+#[test]
+fn fill_missing_arms() {
+  let got = fill_missing_arms_in("datatype d = A | B of int\nfun f (x : d) = case x of A => 1");
+  expect("fill_missing_arms", &got);
+}
+
+#[test]
+fn fill_missing_arms_outside_structure() {
+  let got = fill_missing_arms_in(
+    "structure M = struct datatype d = A | B of int end\nfun f (x : M.d) = case x of M.A => 1",
+  );
+  expect("fill_missing_arms_outside_structure", &got);
+}
+
+#[test]
+fn fill_missing_arms_inside_structure() {
+  let got = fill_missing_arms_in(
+    r#"structure M = struct
+  datatype d = A | B of int
+  fun f (x : d) = case x of A => 1
+end"#,
+  );
+  expect("fill_missing_arms_inside_structure", &got);
+}
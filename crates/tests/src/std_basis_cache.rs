@@ -0,0 +1,85 @@
+//! Tests for snapshots of the std basis, which are cached on disk.
+
+use crate::check::{analyze_project_with, env_var_eq_1};
+
+const SML: &str = r#"
+val xs = List.map (fn x => x + 1) [1, 2, 3]
+val n : int = List.length xs
+datatype 'a t = A | B of 'a * int
+fun f (x : 'a) (y : 'a t) = case y of A => x | B (z, _) => z
+structure S = struct val y = Option.valOf (SOME "hi") end
+val _ : string = S.y ^ Int.toString n
+val _ = f 3 (B (4, 5)) andalso true
+"#;
+
+/// Returns the errors and the hover at every position of [`SML`], when analyzed with `std_basis`.
+fn get(std_basis: analysis::StdBasis) -> (Vec<String>, Vec<Option<String>>) {
+  let files = [("sources.mlb", "a.sml"), ("a.sml", SML)];
+  let (_, input, mut an) =
+    analyze_project_with(&files, std_basis).expect("in memory fs was not set up correctly");
+  let errors: Vec<_> = an
+    .get_many(&input)
+    .into_iter()
+    .flat_map(|(_, errors)| errors)
+    .map(|e| format!("{}: error[{}]: {}", e.range.start, e.code, e.message))
+    .collect();
+  let path = input
+    .iter_sources()
+    .next()
+    .expect("should have a source file")
+    .path;
+  let mut hovers = Vec::new();
+  for (line, s) in SML.lines().enumerate() {
+    for character in 0..s.len() {
+      let pos = analysis::Position {
+        line: line.try_into().unwrap(),
+        character: character.try_into().unwrap(),
+      };
+      hovers.push(an.get_md(path.wrap(pos)).map(|(md, _)| md));
+    }
+  }
+  (errors, hovers)
+}
+
+fn check(std_basis: analysis::StdBasis) {
+  let mut snapshot = Vec::<u8>::new();
+  std_basis
+    .write(&mut snapshot)
+    .expect("couldn't write snapshot");
+  let cached = analysis::StdBasis::read(snapshot.as_slice()).expect("couldn't read snapshot");
+  let (want_errors, want_hovers) = get(std_basis);
+  let (got_errors, got_hovers) = get(cached);
+  assert_eq!(want_errors, got_errors);
+  assert!(want_hovers.iter().any(Option::is_some));
+  assert_eq!(want_hovers, got_hovers);
+}
+
+#[test]
+fn minimal() {
+  check(analysis::StdBasis::minimal());
+}
+
+#[test]
+fn full() {
+  if env_var_eq_1("TEST_MINIMAL") {
+    return;
+  }
+  check(analysis::StdBasis::full());
+}
+
+#[test]
+fn truncated() {
+  let mut snapshot = Vec::<u8>::new();
+  analysis::StdBasis::minimal()
+    .write(&mut snapshot)
+    .expect("couldn't write snapshot");
+  snapshot.truncate(snapshot.len() / 2);
+  assert!(analysis::StdBasis::read(snapshot.as_slice()).is_err());
+}
+
+#[test]
+fn huge_len() {
+  // a varint tag for a `u64`, then the max `u64`, as the length of the first sequence.
+  let snapshot = [0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+  assert!(analysis::StdBasis::read(snapshot.as_slice()).is_err());
+}
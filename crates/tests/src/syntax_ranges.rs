@@ -0,0 +1,111 @@
+//! Tests for folding and selection ranges.
+
+use crate::check::{analyze_project, find_pos, MINIMAL};
+
+const SML: &str = r#"structure S = struct
+  val x =
+    let
+      val y = 1
+    in
+      y
+    end
+end
+(* a
+   comment *)
+open A
+open B
+"#;
+
+/// Returns the range like `1:2-3:4`, one-based.
+fn display_range(range: analysis::Range) -> String {
+  format!(
+    "{}:{}-{}:{}",
+    range.start.line + 1,
+    range.start.character + 1,
+    range.end.line + 1,
+    range.end.character + 1
+  )
+}
+
+fn display_folding(ranges: Vec<analysis::FoldingRange>) -> Vec<String> {
+  ranges
+    .into_iter()
+    .map(|r| format!("{:?} {}", r.kind, display_range(r.range)))
+    .collect()
+}
+
+fn one() -> analysis::Analysis {
+  analysis::Analysis::new(MINIMAL.clone(), config::ErrorLines::One)
+}
+
+const WANT_FOLDING: [&str; 4] = [
+  "Region 1:15-8:4",
+  "Region 3:5-7:8",
+  "Comment 9:1-10:14",
+  "Imports 11:1-12:7",
+];
+
+#[test]
+fn folding() {
+  let (root, input, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", SML)]);
+  let path = find_pos(&root, &input, "a.sml", "structure").path;
+  let got = an.get_folding_ranges(path).expect("no folding ranges");
+  assert_eq!(display_folding(got), WANT_FOLDING);
+}
+
+#[test]
+fn folding_one() {
+  let got = one().get_folding_ranges_one(SML);
+  assert_eq!(display_folding(got), WANT_FOLDING);
+}
+
+#[test]
+fn folding_one_line() {
+  let got = one().get_folding_ranges_one("structure S = struct val x = let in 1 end end");
+  assert!(got.is_empty());
+}
+
+/// Asserts the ranges start at the token `y` on line 6, include the `let` and the `struct`, and end
+/// at the whole file.
+fn check_selection(ranges: &[analysis::Range]) {
+  let ranges: Vec<_> = ranges.iter().copied().map(display_range).collect();
+  assert_eq!(ranges.first().map(String::as_str), Some("6:7-6:8"));
+  assert!(ranges.iter().any(|r| r == "3:5-7:8"), "no let: {ranges:?}");
+  assert!(
+    ranges.iter().any(|r| r == "1:15-8:4"),
+    "no struct: {ranges:?}"
+  );
+  assert!(ranges.last().expect("no ranges").starts_with("1:1-"));
+}
+
+fn contains(outer: analysis::Range, inner: analysis::Range) -> bool {
+  let start = |r: analysis::Range| (r.start.line, r.start.character);
+  let end = |r: analysis::Range| (r.end.line, r.end.character);
+  start(outer) <= start(inner) && end(inner) <= end(outer)
+}
+
+#[test]
+fn selection() {
+  let (root, input, an) = analyze_project(&[("sources.mlb", "a.sml"), ("a.sml", SML)]);
+  let pos = find_pos(&root, &input, "a.sml", "y\n    end");
+  let got = an.get_selection_ranges(pos).expect("no selection ranges");
+  check_selection(&got);
+  for pair in got.windows(2) {
+    assert!(contains(pair[1], pair[0]), "not nested: {pair:?}");
+    assert!(!contains(pair[0], pair[1]), "not strictly nested: {pair:?}");
+  }
+  let got_one = one()
+    .get_selection_ranges_one(SML, pos.val)
+    .expect("no selection ranges");
+  assert_eq!(got.len(), got_one.len());
+  check_selection(&got_one);
+}
+
+#[test]
+fn selection_one_out_of_range() {
+  let pos = analysis::Position {
+    line: 100,
+    character: 0,
+  };
+  assert!(one().get_selection_ranges_one(SML, pos).is_none());
+}
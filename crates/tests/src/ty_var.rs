@@ -26,7 +26,7 @@ fn bound_at_fun_2() {
   check(
     r#"
 fun 'a f (id: 'a -> 'a) x = id x + 1
-(**                         ^^^^^^^^ expected <num> * <num>, found 'a * int *)
+(**                         ^^^^^^^^ expected <num> * <num>, found 'a * int (in particular, expected <num>, found 'a) *)
 "#,
   );
 }
@@ -60,7 +60,7 @@ fn apply() {
     r#"
 fun ('t, 'u) apply (f: 't -> 'u) (x: 't): 'u = f x
 val _ = apply op+ (1, false)
-(**               ^^^^^^^^^^ expected <num> * <num>, found int * bool *)
+(**               ^^^^^^^^^^ expected int * int, found int * bool (in particular, expected int, found bool) *)
 "#,
   );
 }
@@ -71,7 +71,7 @@ fn different_vars() {
     r#"
 fun ('a, 'b) f (xs: 'a list) (x: 'b) =
     x :: xs
-(** ^^^^^^^ expected ?a * ?a list, found 'b * 'a list *)
+(** ^^^^^^^ expected 'b * 'b list, found 'b * 'a list (in particular, expected 'b, found 'a) *)
 "#,
   );
 }
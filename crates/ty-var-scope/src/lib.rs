@@ -96,10 +96,27 @@ fn get_str_dec(cx: &mut Cx, ars: &hir::Arenas, str_dec: hir::StrDecIdx) {
     }
     hir::StrDec::Functor(fun_binds) => {
       for fun_bind in fun_binds {
-        get_sig_exp(cx, ars, fun_bind.param_sig);
+        for (_, param) in &fun_bind.params {
+          get_functor_param(cx, ars, param);
+        }
         get_str_exp(cx, ars, fun_bind.body);
       }
     }
+    hir::StrDec::FunSig(fun_sig_binds) => {
+      for fun_sig_bind in fun_sig_binds {
+        for (_, param) in &fun_sig_bind.params {
+          get_functor_param(cx, ars, param);
+        }
+        get_sig_exp(cx, ars, fun_sig_bind.body);
+      }
+    }
+  }
+}
+
+fn get_functor_param(cx: &mut Cx, ars: &hir::Arenas, param: &hir::FunctorParam) {
+  match param {
+    hir::FunctorParam::Structure(sig_exp) => get_sig_exp(cx, ars, *sig_exp),
+    hir::FunctorParam::Functor(_) => {}
   }
 }
 
@@ -115,7 +132,11 @@ fn get_str_exp(cx: &mut Cx, ars: &hir::Arenas, str_exp: hir::StrExpIdx) {
       get_str_exp(cx, ars, *str_exp);
       get_sig_exp(cx, ars, *sig_exp);
     }
-    hir::StrExp::App(_, str_exp) => get_str_exp(cx, ars, *str_exp),
+    hir::StrExp::App(_, args) => {
+      for &arg in args {
+        get_str_exp(cx, ars, arg);
+      }
+    }
     hir::StrExp::Let(str_dec, str_exp) => {
       get_str_dec(cx, ars, *str_dec);
       get_str_exp(cx, ars, *str_exp);
@@ -26,6 +26,8 @@ export async function activate(cx: vscode.ExtensionContext) {
   };
   const clientOpts: LanguageClientOptions = {
     documentSelector: [{ scheme: "file", language: "sml" }],
+    initializationOptions: config.get("server"),
+    synchronize: { configurationSection: "millet" },
   };
   client = new LanguageClient("millet", serverOpts, clientOpts);
   client.start();
@@ -0,0 +1,19 @@
+//! Runs all of the analysis for a single file, including statics, on any input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+  // the analysis is not Sync, and making the std basis for every input would be slow.
+  static AN: analysis::Analysis =
+    analysis::Analysis::new(analysis::StdBasis::minimal(), config::ErrorLines::One);
+}
+
+fuzz_target!(|data: &[u8]| {
+  let s = match std::str::from_utf8(data) {
+    Ok(x) => x,
+    Err(_) => return,
+  };
+  AN.with(|an| an.get_one(s));
+});
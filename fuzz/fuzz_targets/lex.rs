@@ -0,0 +1,15 @@
+//! Lexes any input, and checks the tokens make up the input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  let s = match std::str::from_utf8(data) {
+    Ok(x) => x,
+    Err(_) => return,
+  };
+  let lexed = lex::get(s);
+  let text: String = lexed.tokens.iter().map(|tok| tok.text).collect();
+  assert_eq!(s, text);
+});
@@ -0,0 +1,16 @@
+//! Lowers any input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  let s = match std::str::from_utf8(data) {
+    Ok(x) => x,
+    Err(_) => return,
+  };
+  let lexed = lex::get(s);
+  let mut fix_env = parse::parser::STD_BASIS.clone();
+  let parsed = parse::get(&lexed.tokens, &mut fix_env);
+  lower::get(&parsed.root);
+});
@@ -0,0 +1,17 @@
+//! Parses any input, and checks the text of the syntax tree is the input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use syntax::ast::AstNode as _;
+
+fuzz_target!(|data: &[u8]| {
+  let s = match std::str::from_utf8(data) {
+    Ok(x) => x,
+    Err(_) => return,
+  };
+  let lexed = lex::get(s);
+  let mut fix_env = parse::parser::STD_BASIS.clone();
+  let parsed = parse::get(&lexed.tokens, &mut fix_env);
+  assert_eq!(s, parsed.root.syntax().text().to_string());
+});
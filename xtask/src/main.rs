@@ -12,6 +12,7 @@ enum Cmd {
   Ci,
   Dist,
   Tag,
+  FuzzCorpus,
 }
 
 struct CmdSpec {
@@ -22,7 +23,7 @@ struct CmdSpec {
 }
 
 impl Cmd {
-  const VALUES: [Cmd; 4] = [Cmd::Help, Cmd::Ci, Cmd::Dist, Cmd::Tag];
+  const VALUES: [Cmd; 5] = [Cmd::Help, Cmd::Ci, Cmd::Dist, Cmd::Tag, Cmd::FuzzCorpus];
 
   fn spec(&self) -> CmdSpec {
     match self {
@@ -53,6 +54,12 @@ impl Cmd {
         options: &[],
         args: &[("<tag>", "the name of the tag")],
       },
+      Cmd::FuzzCorpus => CmdSpec {
+        name: "fuzz-corpus",
+        desc: "seed the corpus of each fuzz target with the sml libraries and the tests",
+        options: &[],
+        args: &[],
+      },
     }
   }
 }
@@ -275,6 +282,44 @@ fn dist(sh: &Shell, release: bool, target: Option<&str>) -> Result<()> {
   Ok(())
 }
 
+/// The fuzz targets in the `fuzz` dir.
+const FUZZ_TARGETS: [&str; 4] = ["lex", "parse", "lower", "analysis"];
+
+fn fuzz_corpus(sh: &Shell) -> Result<()> {
+  let out = cmd!(sh, "git ls-files crates/sml-libs/src crates/tests/src").output()?;
+  let mut seeds = Vec::<String>::new();
+  for path in String::from_utf8(out.stdout)?.lines() {
+    let path = Path::new(path);
+    match path.extension().and_then(|x| x.to_str()) {
+      Some("sml") => seeds.push(sh.read_file(path)?),
+      Some("rs") => {
+        // the test cases are usually in raw strings.
+        let contents = sh.read_file(path)?;
+        let mut rest = contents.as_str();
+        while let Some((_, after_start)) = rest.split_once("r#\"") {
+          match after_start.split_once("\"#") {
+            Some((seed, after_end)) => {
+              seeds.push(seed.to_owned());
+              rest = after_end;
+            }
+            None => break,
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+  for target in FUZZ_TARGETS {
+    let dir: PathBuf = ["fuzz", "corpus", target].iter().collect();
+    sh.create_dir(&dir)?;
+    for (idx, seed) in seeds.iter().enumerate() {
+      sh.write_file(dir.join(format!("seed-{idx}.sml")), seed)?;
+    }
+  }
+  println!("wrote {} seeds for each fuzz target", seeds.len());
+  Ok(())
+}
+
 fn run_ci(sh: &Shell) -> Result<()> {
   cmd!(sh, "cargo build --locked").run()?;
   cmd!(sh, "cargo fmt -- --check").run()?;
@@ -317,6 +362,10 @@ fn main() -> Result<()> {
       finish_args(args)?;
       dist(&sh, release, target.as_deref())?;
     }
+    Cmd::FuzzCorpus => {
+      finish_args(args)?;
+      fuzz_corpus(&sh)?;
+    }
     Cmd::Tag => {
       let tag: String = args.free_from_str()?;
       finish_args(args)?;